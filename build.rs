@@ -1,6 +1,7 @@
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
+use sha2::{Digest, Sha256};
 
 fn main() {
     // Check if bundled feature is enabled
@@ -9,6 +10,26 @@ fn main() {
     let target_arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
     let target_env = env::var("CARGO_CFG_TARGET_ENV").unwrap_or_default();
 
+    // Check if static linking is requested; iOS apps can't load shared
+    // libraries, so the native image is always linked statically there
+    let static_link = env::var("CARGO_FEATURE_STATIC").is_ok() || target_os == "ios";
+    if bundled && static_link {
+        panic!("The `bundled` feature can't be combined with static linking (the `static` feature or iOS targets)");
+    }
+
+    // Check if the native library is replaced by the in-memory mock
+    if env::var("CARGO_FEATURE_MOCK").is_ok() {
+        if bundled || env::var("CARGO_FEATURE_STATIC").is_ok() {
+            panic!("The `mock` feature replaces the native library and can't be combined with `bundled` or `static`");
+        }
+        // Neither the native library nor its header is needed: every function
+        // is routed to src/mock.rs, and the bindings are read from there
+        let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        generate_mock_bindings(&manifest_dir);
+        println!("cargo:rerun-if-changed=src/mock.rs");
+        return;
+    }
+
     // Determine the platform-specific directory
     let platform_dir = get_platform_dir(&target_os, &target_arch, &target_env);
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
@@ -36,7 +57,12 @@ fn main() {
 
     let mut found_lib_dir = None;
     for path in &native_lib_paths {
-        if path.exists() && has_native_library(path, &target_os) {
+        let has_library = if static_link {
+            has_static_library(path, &target_os)
+        } else {
+            has_native_library(path, &target_os)
+        };
+        if path.exists() && has_library {
             println!("cargo:warning=Found native libraries at: {}", path.display());
             found_lib_dir = Some(path.clone());
             break;
@@ -51,7 +77,10 @@ fn main() {
 
     // Only set up linking if bundled feature is NOT enabled
     // When bundled is enabled, we use embedded libraries with dynamic loading instead
-    if !bundled {
+    if static_link {
+        // Link the native image into the final binary, nothing to copy for runtime
+        setup_static_linking(&lib_dir, &target_os);
+    } else if !bundled {
         // Set up linking based on target OS
         setup_linking(&lib_dir, &target_os);
 
@@ -84,10 +113,14 @@ fn get_platform_dir(target_os: &str, target_arch: &str, target_env: &str) -> Str
         ("windows", "aarch64", _) => "windows-aarch64".to_string(),
         ("macos", "x86_64", _) => "darwin-x86_64".to_string(),
         ("macos", "aarch64", _) => "darwin-aarch64".to_string(),
+        ("ios", "aarch64", "sim") => "ios-aarch64-sim".to_string(),
+        ("ios", "aarch64", _) => "ios-aarch64".to_string(),
         ("linux", "x86_64", "musl") => "linux-x86_64-musl".to_string(),
         ("linux", "aarch64", "musl") => "linux-aarch64-musl".to_string(),
         ("linux", "x86_64", _) => "linux-x86_64".to_string(),
         ("linux", "aarch64", _) => "linux-aarch64".to_string(),
+        ("freebsd", "x86_64", _) => "freebsd-x86_64".to_string(),
+        ("freebsd", "aarch64", _) => "freebsd-aarch64".to_string(),
         _ => format!("{}-{}", target_os, target_arch),
     }
 }
@@ -100,14 +133,19 @@ fn has_native_library(path: &PathBuf, target_os: &str) -> bool {
         "macos" => {
             path.join("libzip4j-abi.dylib").exists() || path.join("zip4j-abi.dylib").exists()
         }
-        "linux" => {
+        "linux" | "freebsd" => {
             path.join("libzip4j-abi.so").exists() || path.join("zip4j-abi.so").exists()
         }
         _ => false,
     }
 }
 
-
+fn has_static_library(path: &Path, target_os: &str) -> bool {
+    match target_os {
+        "linux" | "macos" | "ios" => path.join("libzip4j-abi.a").exists(),
+        _ => false,
+    }
+}
 
 fn setup_linking(lib_dir: &PathBuf, target_os: &str) {
     println!("cargo:rustc-link-search=native={}", lib_dir.display());
@@ -136,8 +174,8 @@ fn setup_linking(lib_dir: &PathBuf, target_os: &str) {
             // Add rpath for runtime library loading
             println!("cargo:rustc-link-arg=-Wl,-rpath,@loader_path");
         }
-        "linux" => {
-            // On Linux, check which naming convention is used
+        "linux" | "freebsd" => {
+            // On Linux and FreeBSD, check which naming convention is used
             if lib_dir.join("libzip4j-abi.so").exists() {
                 println!("cargo:rustc-link-lib=dylib=zip4j-abi");
             } else if lib_dir.join("zip4j-abi.so").exists() {
@@ -161,6 +199,25 @@ fn setup_linking(lib_dir: &PathBuf, target_os: &str) {
     }
 }
 
+fn setup_static_linking(lib_dir: &Path, target_os: &str) {
+    println!("cargo:rustc-link-search=native={}", lib_dir.display());
+    println!("cargo:rustc-link-lib=static=zip4j-abi");
+
+    // System libraries the native image itself depends on
+    match target_os {
+        "linux" => {
+            for lib in ["z", "dl", "pthread", "rt", "m"] {
+                println!("cargo:rustc-link-lib={}", lib);
+            }
+        }
+        "macos" | "ios" => {
+            println!("cargo:rustc-link-lib=z");
+            println!("cargo:rustc-link-lib=framework=Foundation");
+        }
+        _ => {}
+    }
+}
+
 fn copy_runtime_libraries(lib_dir: &PathBuf, target_os: &str) {
     let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
     let target_dir = out_dir.parent().unwrap().parent().unwrap().parent().unwrap();
@@ -208,7 +265,7 @@ fn copy_runtime_libraries(lib_dir: &PathBuf, target_os: &str) {
                 }
             }
         }
-        "linux" => {
+        "linux" | "freebsd" => {
             for so_name in &["libzip4j-abi.so", "zip4j-abi.so"] {
                 let so_path = lib_dir.join(so_name);
                 if so_path.exists() {
@@ -345,7 +402,8 @@ fn generate_dynamic_bindings(bindings_str: &str) -> String {
     result
 }
 
-fn generate_dynamic_wrapper(func_signature: &str) -> String {
+/// Split a function signature into its name, parameter names and types, and return type
+fn parse_signature(func_signature: &str) -> Option<(String, Vec<String>, Vec<String>, String)> {
     // Parse function signature: "pub fn name(args...) -> return_type;"
     let func_signature = func_signature.trim_end_matches(';');
 
@@ -354,7 +412,7 @@ fn generate_dynamic_wrapper(func_signature: &str) -> String {
         Some(pos) => pos + 7,
         None => {
             eprintln!("Warning: Invalid function signature (no 'pub fn'): {}", func_signature);
-            return String::new();
+            return None;
         }
     };
 
@@ -362,7 +420,7 @@ fn generate_dynamic_wrapper(func_signature: &str) -> String {
         Some(pos) => pos,
         None => {
             eprintln!("Warning: Invalid function signature (no opening paren): {}", func_signature);
-            return String::new();
+            return None;
         }
     };
     let fn_name = &func_signature[fn_start..fn_start + fn_name_end];
@@ -372,7 +430,7 @@ fn generate_dynamic_wrapper(func_signature: &str) -> String {
         Some(pos) => pos + 1,
         None => {
             eprintln!("Warning: Invalid function signature (no opening paren): {}", func_signature);
-            return String::new();
+            return None;
         }
     };
 
@@ -380,7 +438,7 @@ fn generate_dynamic_wrapper(func_signature: &str) -> String {
         Some(pos) => pos,
         None => {
             eprintln!("Warning: Invalid function signature (no closing paren): {}", func_signature);
-            return String::new();
+            return None;
         }
     };
     let params_str = &func_signature[params_start..params_end];
@@ -408,6 +466,15 @@ fn generate_dynamic_wrapper(func_signature: &str) -> String {
         }
     }
 
+    Some((fn_name.to_string(), param_names, param_types, return_type.to_string()))
+}
+
+fn generate_dynamic_wrapper(func_signature: &str) -> String {
+    let (fn_name, param_names, param_types, return_type) = match parse_signature(func_signature) {
+        Some(signature) => signature,
+        None => return String::new(),
+    };
+
     // Generate the dynamic wrapper
     let mut wrapper = String::new();
     wrapper.push_str("#[cfg(feature = \"bundled\")]\n");
@@ -452,6 +519,87 @@ fn generate_dynamic_wrapper(func_signature: &str) -> String {
     wrapper
 }
 
+/// Types of the GraalVM isolate API, as bindgen generates them from graal_isolate.h
+const GRAAL_TYPES: &str = "\
+pub type __graal_uword = ::std::os::raw::c_ulong;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct __graal_isolate_t {
+    _unused: [u8; 0],
+}
+pub type graal_isolate_t = __graal_isolate_t;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct __graal_isolatethread_t {
+    _unused: [u8; 0],
+}
+pub type graal_isolatethread_t = __graal_isolatethread_t;
+pub const __graal_create_isolate_params_version: _bindgen_ty_1 = 4;
+pub type _bindgen_ty_1 = ::std::os::raw::c_uint;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct __graal_create_isolate_params_t {
+    pub version: ::std::os::raw::c_int,
+    pub reserved_address_space_size: __graal_uword,
+    pub auxiliary_image_path: *const ::std::os::raw::c_char,
+    pub auxiliary_image_reserved_space_size: __graal_uword,
+    pub _reserved_1: ::std::os::raw::c_int,
+    pub _reserved_2: *mut *mut ::std::os::raw::c_char,
+    pub pkey: ::std::os::raw::c_int,
+    pub _reserved_3: ::std::os::raw::c_char,
+    pub _reserved_4: ::std::os::raw::c_char,
+    pub _reserved_5: ::std::os::raw::c_char,
+}
+pub type graal_create_isolate_params_t = __graal_create_isolate_params_t;
+";
+
+/// Generate bindings that forward every function of src/mock.rs, so the mock
+/// builds without the native library, its header or libclang
+fn generate_mock_bindings(manifest_dir: &str) {
+    let mock_source = fs::read_to_string(PathBuf::from(manifest_dir).join("src").join("mock.rs"))
+        .expect("Couldn't read src/mock.rs");
+
+    let mut result = String::new();
+    result.push_str("// Auto-generated mock bindings, calling into src/mock.rs\n");
+    result.push_str(GRAAL_TYPES);
+
+    // Every `pub(crate) unsafe fn` of the mock stands in for the C function of the same name
+    for (start, _) in mock_source.match_indices("pub(crate) unsafe fn ") {
+        let declaration = &mock_source[start..];
+        let body_start = declaration.find('{').expect("Mock function without a body");
+        let signature = declaration[..body_start]
+            .replace("pub(crate) unsafe fn ", "pub fn ")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .replace("( ", "(")
+            .replace(", )", ")");
+
+        let (fn_name, param_names, param_types, return_type) = match parse_signature(&signature) {
+            Some(signature) => signature,
+            None => continue,
+        };
+        // Underscores only mark parameters the mock ignores
+        let param_names: Vec<&str> = param_names.iter().map(|name| name.trim_start_matches('_')).collect();
+        let params: Vec<String> = param_names.iter().zip(param_types.iter())
+            .map(|(name, type_str)| format!("{}: {}", name, type_str))
+            .collect();
+
+        result.push_str(&format!(
+            "pub unsafe fn {}({}) -> {} {{\n    crate::mock::{}({})\n}}\n",
+            fn_name,
+            params.join(", "),
+            return_type,
+            fn_name,
+            param_names.join(", ")
+        ));
+    }
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    fs::write(out_dir.join("bindings.rs"), result)
+        .expect("Couldn't write mock bindings!");
+}
+
 fn generate_embedded_libraries(manifest_dir: &str) {
     let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
     let native_dir = PathBuf::from(manifest_dir).join("zip4j-abi").join("build").join("native").join("nativeCompile");
@@ -459,19 +607,26 @@ fn generate_embedded_libraries(manifest_dir: &str) {
     // Platform-specific library mappings
     let platforms = vec![
         ("windows-x86_64", "zip4j-abi.dll"),
+        ("windows-aarch64", "zip4j-abi.dll"),
         ("linux-x86_64", "libzip4j-abi.so"),
         ("linux-x86_64-musl", "libzip4j-abi.so"),
         ("linux-aarch64", "libzip4j-abi.so"),
+        ("freebsd-x86_64", "libzip4j-abi.so"),
+        ("freebsd-aarch64", "libzip4j-abi.so"),
         ("darwin-x86_64", "libzip4j-abi.dylib"),
         ("darwin-aarch64", "libzip4j-abi.dylib"),
     ];
 
+    // Libraries are embedded uncompressed with include_bytes!, so the loader
+    // writes (or memfd-loads) the static data directly: there is no
+    // decompression step and no second in-memory copy of the library.
     let mut embedded_code = String::new();
     embedded_code.push_str("// Auto-generated embedded libraries\n");
     embedded_code.push_str("use std::collections::HashMap;\n\n");
     embedded_code.push_str("pub struct EmbeddedLibrary {\n");
     embedded_code.push_str("    pub data: &'static [u8],\n");
     embedded_code.push_str("    pub filename: &'static str,\n");
+    embedded_code.push_str("    pub sha256: [u8; 32],\n");
     embedded_code.push_str("}\n\n");
     embedded_code.push_str("pub fn get_embedded_libraries() -> HashMap<&'static str, EmbeddedLibrary> {\n");
     embedded_code.push_str("    let mut libs = HashMap::new();\n\n");
@@ -491,6 +646,11 @@ fn generate_embedded_libraries(manifest_dir: &str) {
                 "        filename: \"{}\",\n",
                 filename
             ));
+            let data = fs::read(&lib_path).expect("Failed to read library for hashing");
+            embedded_code.push_str(&format!(
+                "        sha256: {:?},\n",
+                <[u8; 32]>::from(Sha256::digest(&data))
+            ));
             embedded_code.push_str("    });\n\n");
 
             println!("cargo:warning=Embedding library for {}: {}", platform, lib_path.display());
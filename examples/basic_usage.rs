@@ -100,7 +100,7 @@ fn create_encrypted_zip() -> Result<(), ZipError> {
 fn read_zip_contents() -> Result<(), ZipError> {
     let zip = ZipFile::new("simple_archive.zip")?;
     
-    println!("   Archive: {}", zip.file_path()?);
+    println!("   Archive: {}", zip.file_path()?.display());
     println!("   Valid: {}", zip.is_valid()?);
     println!("   Encrypted: {}", zip.is_encrypted()?);
     println!("   Entries: {}", zip.entry_count()?);
@@ -136,7 +136,7 @@ fn demo_archive_inspection() -> Result<()> {
     let zip = ZipFile::new("memory_demo.zip")?;
     
     println!("Archive properties:");
-    println!("  File path: {}", zip.file_path()?);
+    println!("  File path: {}", zip.file_path()?.display());
     println!("  Is valid: {}", zip.is_valid()?);
     println!("  Is encrypted: {}", zip.is_encrypted()?);
     println!("  Is split archive: {}", zip.is_split_archive()?);
@@ -190,7 +190,7 @@ fn demo_advanced_operations() -> Result<()> {
     // Demonstrate error handling
     match zip.get_entry_by_name("nonexistent.txt") {
         Ok(_) => println!("This shouldn't happen!"),
-        Err(ZipError::EntryNotFound) => println!("✓ Correctly handled missing entry"),
+        Err(e) if matches!(e.root(), ZipError::EntryNotFound) => println!("✓ Correctly handled missing entry: {}", e),
         Err(e) => println!("Unexpected error: {}", e),
     }
     
@@ -1,11 +1,12 @@
 //! Embedded library loading for bundled mode
 //!
 //! This module handles extracting and loading native libraries that are embedded
-//! directly into the Rust binary when the "bundled" feature is enabled.
+//! directly into the Rust binary when the "bundled" feature is enabled. On Linux
+//! the library is loaded from an in-memory file without touching the disk.
 
 
 #[cfg(feature = "bundled")]
-use once_cell::sync::Lazy;
+use once_cell::sync::OnceCell;
 #[cfg(feature = "bundled")]
 use libloading::Library;
 #[cfg(feature = "bundled")]
@@ -14,31 +15,80 @@ use tempfile::TempDir;
 #[cfg(feature = "bundled")]
 include!(concat!(env!("OUT_DIR"), "/embedded_libs.rs"));
 
+/// The loaded native library, set on first use or by [`use_library`]
 #[cfg(feature = "bundled")]
-static LIBRARY_LOADER: Lazy<LibraryLoader> = Lazy::new(|| {
-    LibraryLoader::new().expect("Failed to initialize embedded library loader")
-});
+static LIBRARY_LOADER: OnceCell<LibraryLoader> = OnceCell::new();
+
+/// Environment variable naming a native library to load instead of the embedded one
+#[cfg(feature = "bundled")]
+const LIBRARY_PATH_ENV: &str = "ZIP2RS_LIBRARY_PATH";
+
+/// Environment variable naming a directory to extract the embedded library into
+#[cfg(feature = "bundled")]
+const EXTRACT_DIR_ENV: &str = "ZIP2RS_EXTRACT_DIR";
+
+/// Directory set by [`set_extraction_dir`], taking precedence over [`EXTRACT_DIR_ENV`]
+#[cfg(feature = "bundled")]
+static EXTRACTION_DIR: OnceCell<std::path::PathBuf> = OnceCell::new();
 
 #[cfg(feature = "bundled")]
 struct LibraryLoader {
-    _temp_dir: TempDir,
-    _library_path: std::path::PathBuf,
+    _temp_dir: Option<TempDir>,
+    /// Where the library was loaded from, unknown for libraries supplied by the host
+    _library_path: Option<std::path::PathBuf>,
     library: Library,
 }
 
 #[cfg(feature = "bundled")]
 impl LibraryLoader {
     fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        // A library supplied by the operator (e.g. a patched build) takes precedence
+        if let Some(lib_path) = std::env::var_os(LIBRARY_PATH_ENV) {
+            let lib_path = std::path::PathBuf::from(lib_path);
+            let library = unsafe { Library::new(&lib_path) }
+                .map_err(|e| format!("Failed to load {} from {}: {}", LIBRARY_PATH_ENV, lib_path.display(), e))?;
+
+            return Ok(LibraryLoader {
+                _temp_dir: None,
+                _library_path: Some(lib_path),
+                library,
+            });
+        }
+
         let platform = get_current_platform();
 
         let embedded_libs = get_embedded_libraries();
         let embedded_lib = embedded_libs.get(platform)
             .ok_or_else(|| format!("No embedded library found for platform: {}", platform))?;
 
-        // Always extract to a temporary directory for clean isolation
+        // An explicitly configured directory acts as a persistent cache
+        if let Some(dir) = extraction_dir() {
+            let lib_path = extract_cached(&dir, embedded_lib)?;
+            let (library, temp_dir) = load_verified(&lib_path, &embedded_lib.sha256)?;
+
+            return Ok(LibraryLoader {
+                _temp_dir: temp_dir,
+                _library_path: Some(lib_path),
+                library,
+            });
+        }
+
+        // Load straight from memory where the platform allows it, so nothing
+        // is written to disk; fall back to extracting the library otherwise
+        #[cfg(target_os = "linux")]
+        if let Ok(library) = load_from_memory(embedded_lib.filename, embedded_lib.data) {
+            return Ok(LibraryLoader {
+                _temp_dir: None,
+                _library_path: None,
+                library,
+            });
+        }
+
+        // Extract to a private temporary directory for clean isolation
         let temp_dir = tempfile::tempdir()?;
         let lib_path = temp_dir.path().join(embedded_lib.filename);
         std::fs::write(&lib_path, embedded_lib.data)?;
+        verify_file(&lib_path, &embedded_lib.sha256)?;
 
         // Set up environment so the dynamic linker can find the library
         #[cfg(target_os = "windows")]
@@ -53,11 +103,11 @@ impl LibraryLoader {
             std::env::set_var("PATH", new_path);
         }
 
-        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "macos"))]
         {
             let temp_dir_str = temp_dir.path().to_string_lossy();
 
-            #[cfg(target_os = "linux")]
+            #[cfg(any(target_os = "linux", target_os = "freebsd"))]
             {
                 let current_path = std::env::var("LD_LIBRARY_PATH").unwrap_or_default();
                 let new_path = if current_path.is_empty() {
@@ -84,14 +134,14 @@ impl LibraryLoader {
         let library = unsafe { Library::new(&lib_path)? };
 
         Ok(LibraryLoader {
-            _temp_dir: temp_dir,
-            _library_path: lib_path,
+            _temp_dir: Some(temp_dir),
+            _library_path: Some(lib_path),
             library,
         })
     }
 
-    fn library_path(&self) -> &std::path::Path {
-        &self._library_path
+    fn library_path(&self) -> Option<&std::path::Path> {
+        self._library_path.as_deref()
     }
 
     fn get_symbol<T>(&self, symbol: &[u8]) -> Result<libloading::Symbol<T>, libloading::Error> {
@@ -99,11 +149,122 @@ impl LibraryLoader {
     }
 }
 
+/// Configured extraction directory, if any
+#[cfg(feature = "bundled")]
+fn extraction_dir() -> Option<std::path::PathBuf> {
+    EXTRACTION_DIR.get().cloned().or_else(|| std::env::var_os(EXTRACT_DIR_ENV).map(Into::into))
+}
+
+/// Extract the library into `dir` under a name unique to this crate version and
+/// library contents, reusing the file if an earlier run already extracted it intact
+#[cfg(feature = "bundled")]
+fn extract_cached(dir: &std::path::Path, embedded_lib: &EmbeddedLibrary) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+    use std::io::Write;
+
+    let hash: String = embedded_lib.sha256[..8].iter().map(|byte| format!("{:02x}", byte)).collect();
+    let lib_path = dir.join(format!("zip2rs-{}-{}-{}", env!("CARGO_PKG_VERSION"), hash, embedded_lib.filename));
+    if verify_file(&lib_path, &embedded_lib.sha256).is_ok() {
+        return Ok(lib_path);
+    }
+
+    // Write under a temporary name first so concurrent processes never load a partial file
+    std::fs::create_dir_all(dir)?;
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(embedded_lib.data)?;
+    file.persist(&lib_path)?;
+    Ok(lib_path)
+}
+
+/// Check an extracted library against the SHA-256 embedded at build time, so a
+/// file replaced on disk is never loaded
+#[cfg(feature = "bundled")]
+fn verify_file(path: &std::path::Path, expected: &[u8; 32]) -> Result<(), Box<dyn std::error::Error>> {
+    verify_contents(&mut std::fs::File::open(path)?, path, expected)
+}
+
+/// Check the contents of a library read from `path` against the SHA-256 embedded at build time
+#[cfg(feature = "bundled")]
+fn verify_contents(contents: &mut impl std::io::Read, path: &std::path::Path, expected: &[u8; 32]) -> Result<(), Box<dyn std::error::Error>> {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    std::io::copy(contents, &mut hasher)?;
+    if hasher.finalize().as_slice() != expected {
+        return Err(format!("Integrity check failed for {}: it does not match the embedded library", path.display()).into());
+    }
+    Ok(())
+}
+
+/// Load a library from a shared directory, but only the bytes that were verified
+///
+/// Checking the path and then loading it again by name would leave a window
+/// to swap the file in between. The file is opened once instead, and the
+/// same descriptor is hashed and then loaded through `/proc/self/fd`.
+#[cfg(all(feature = "bundled", target_os = "linux"))]
+fn load_verified(path: &std::path::Path, expected: &[u8; 32]) -> Result<(Library, Option<TempDir>), Box<dyn std::error::Error>> {
+    use std::os::fd::AsRawFd;
+
+    let mut file = std::fs::File::open(path)?;
+    verify_contents(&mut file, path, expected)?;
+    let library = unsafe { Library::new(format!("/proc/self/fd/{}", file.as_raw_fd()))? };
+    Ok((library, None))
+}
+
+/// Load a library from a shared directory, but only the bytes that were verified
+///
+/// Checking the path and then loading it again by name would leave a window
+/// to swap the file in between. The file is copied into a private directory
+/// instead, which nobody else can write to, and the copy is checked and loaded.
+#[cfg(all(feature = "bundled", not(target_os = "linux")))]
+fn load_verified(path: &std::path::Path, expected: &[u8; 32]) -> Result<(Library, Option<TempDir>), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let copy = temp_dir.path().join(path.file_name().ok_or("Library path has no file name")?);
+    std::fs::copy(path, &copy)?;
+    verify_file(&copy, expected)?;
+    let library = unsafe { Library::new(&copy)? };
+    Ok((library, Some(temp_dir)))
+}
+
+/// Set the directory the embedded library is extracted into
+///
+/// Fails if the library has already been loaded.
+#[cfg(feature = "bundled")]
+pub fn set_extraction_dir(dir: std::path::PathBuf) -> crate::error::Result<()> {
+    if LIBRARY_LOADER.get().is_some() {
+        return Err(crate::error::ZipError::Unknown("The native library has already been loaded".to_string()));
+    }
+    EXTRACTION_DIR
+        .set(dir)
+        .map_err(|_| crate::error::ZipError::Unknown("The extraction directory has already been set".to_string()))
+}
+
+/// Load a library from an anonymous in-memory file (Linux `memfd_create`)
+#[cfg(all(feature = "bundled", target_os = "linux"))]
+fn load_from_memory(filename: &str, data: &[u8]) -> Result<Library, Box<dyn std::error::Error>> {
+    use std::io::Write;
+    use std::os::fd::{AsRawFd, FromRawFd};
+
+    let name = std::ffi::CString::new(filename)?;
+    let fd = unsafe { libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC) };
+    if fd < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    // The library stays mapped after the file is closed
+    let mut file = unsafe { std::fs::File::from_raw_fd(fd) };
+    file.write_all(data)?;
+    let library = unsafe { Library::new(format!("/proc/self/fd/{}", file.as_raw_fd()))? };
+    Ok(library)
+}
+
 #[cfg(feature = "bundled")]
 fn get_current_platform() -> &'static str {
     #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
     return "windows-x86_64";
 
+    #[cfg(all(target_os = "windows", target_arch = "aarch64"))]
+    return "windows-aarch64";
+
     #[cfg(all(target_os = "linux", target_arch = "x86_64", target_env = "gnu"))]
     return "linux-x86_64";
 
@@ -119,24 +280,45 @@ fn get_current_platform() -> &'static str {
     #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
     return "darwin-aarch64";
 
+    #[cfg(all(target_os = "freebsd", target_arch = "x86_64"))]
+    return "freebsd-x86_64";
+
+    #[cfg(all(target_os = "freebsd", target_arch = "aarch64"))]
+    return "freebsd-aarch64";
+
     // Fallback - this will cause an error at runtime if the platform isn't supported
     "unknown"
 }
 
-/// Get the path to the extracted library
+/// Get the path to the extracted library, if it was loaded from a file
 #[cfg(feature = "bundled")]
-pub fn get_library_path() -> &'static std::path::Path {
-    LIBRARY_LOADER.library_path()
+pub fn get_library_path() -> Result<Option<&'static std::path::Path>, Box<dyn std::error::Error>> {
+    Ok(loader()?.library_path())
+}
+
+/// Get the library loader, loading the library on first use
+#[cfg(feature = "bundled")]
+fn loader() -> Result<&'static LibraryLoader, Box<dyn std::error::Error>> {
+    LIBRARY_LOADER.get_or_try_init(LibraryLoader::new)
 }
 
 /// Initialize the embedded library loader (called automatically on first use)
 #[cfg(feature = "bundled")]
 pub fn initialize() -> Result<(), Box<dyn std::error::Error>> {
-    // This will trigger the lazy initialization
-    Lazy::force(&LIBRARY_LOADER);
+    loader()?;
     Ok(())
 }
 
+/// Use a native library the host has already loaded instead of the embedded one
+///
+/// Fails if a library has already been loaded.
+#[cfg(feature = "bundled")]
+pub fn use_library(library: Library) -> crate::error::Result<()> {
+    LIBRARY_LOADER
+        .set(LibraryLoader { _temp_dir: None, _library_path: None, library })
+        .map_err(|_| crate::error::ZipError::Unknown("The native library has already been loaded".to_string()))
+}
+
 /// Ensure the embedded library is initialized, converting errors to ZipError
 #[cfg(feature = "bundled")]
 pub fn ensure_initialized() -> crate::error::Result<()> {
@@ -146,7 +328,7 @@ pub fn ensure_initialized() -> crate::error::Result<()> {
 /// Get a function pointer from the embedded library
 #[cfg(feature = "bundled")]
 pub fn get_function<T>(symbol: &[u8]) -> Result<libloading::Symbol<T>, Box<dyn std::error::Error>> {
-    Ok(LIBRARY_LOADER.get_symbol(symbol)?)
+    Ok(loader()?.get_symbol(symbol)?)
 }
 
 // Stub implementations for when bundled feature is not enabled
@@ -6,12 +6,19 @@ use std::str::Utf8Error;
 pub type Result<T> = std::result::Result<T, ZipError>;
 
 /// Errors that can occur during zip operations
+///
+/// Errors reported by the native library come wrapped in [`ZipError::Native`]
+/// and errors from operations on a file or entry in [`ZipError::WithContext`];
+/// match on [`ZipError::root`] to find out what went wrong.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ZipError {
     /// Invalid handle provided
     InvalidHandle,
     /// File not found
     FileNotFound,
+    /// File already exists
+    FileExists,
     /// Zip-specific error
     ZipException(String),
     /// I/O error
@@ -22,6 +29,8 @@ pub enum ZipError {
     OutOfMemory,
     /// Entry not found in archive
     EntryNotFound,
+    /// Several entries share the name that was looked up
+    DuplicateEntry(String),
     /// Buffer too small for operation
     BufferTooSmall,
     /// Operation was cancelled
@@ -34,10 +43,58 @@ pub enum ZipError {
     PermissionDenied,
     /// Disk full
     DiskFull,
+    /// Not enough free space at the destination, checked before starting
+    InsufficientSpace {
+        /// Bytes the operation needs
+        required: u64,
+        /// Bytes available
+        available: u64,
+    },
     /// Unknown error
     Unknown(String),
     /// String conversion error
     StringConversion(String),
+    /// Archive has been sealed and can no longer be modified
+    Sealed,
+    /// Entry was obtained before the archive was modified and must be looked up again
+    StaleEntry,
+    /// I/O error raised on the Rust side, kept as the error's source
+    Io(std::io::Error),
+    /// Text that isn't valid UTF-8, kept as the error's source
+    Utf8(Utf8Error),
+    /// Error reported by the native library, see [`ZipError::root`]
+    Native {
+        /// Raw error code returned by the native call
+        code: i32,
+        /// Class and message of the Java exception behind the error, if there was one
+        message: Option<String>,
+        /// What the code means
+        error: Box<ZipError>,
+    },
+    /// Error raised by an operation on a file or entry, see [`ZipError::root`]
+    WithContext {
+        /// Operation and the file or entry it was working on
+        context: ErrorContext,
+        /// The error itself
+        error: Box<ZipError>,
+    },
+}
+
+/// Operation an error happened in, and the file or entry it was working on
+///
+/// Displayed like a call, e.g. `add_file(/tmp/a.txt)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// Name of the operation, e.g. `add_file`
+    pub operation: &'static str,
+    /// Path or entry name the operation was working on
+    pub target: String,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.operation, self.target)
+    }
 }
 
 impl fmt::Display for ZipError {
@@ -45,24 +102,45 @@ impl fmt::Display for ZipError {
         match self {
             ZipError::InvalidHandle => write!(f, "Invalid handle"),
             ZipError::FileNotFound => write!(f, "File not found"),
+            ZipError::FileExists => write!(f, "File already exists"),
             ZipError::ZipException(msg) => write!(f, "Zip error: {}", msg),
             ZipError::IoError(msg) => write!(f, "I/O error: {}", msg),
             ZipError::InvalidParameter(msg) => write!(f, "Invalid parameter: {}", msg),
             ZipError::OutOfMemory => write!(f, "Out of memory"),
             ZipError::EntryNotFound => write!(f, "Entry not found in archive"),
+            ZipError::DuplicateEntry(name) => write!(f, "Duplicate entry: {}", name),
             ZipError::BufferTooSmall => write!(f, "Buffer too small"),
             ZipError::OperationCancelled => write!(f, "Operation was cancelled"),
             ZipError::UnsupportedOperation => write!(f, "Unsupported operation"),
             ZipError::NullPointer => write!(f, "Null pointer error"),
             ZipError::PermissionDenied => write!(f, "Permission denied"),
             ZipError::DiskFull => write!(f, "Disk full"),
+            ZipError::InsufficientSpace { required, available } => {
+                write!(f, "Insufficient space: {} bytes required, {} bytes available", required, available)
+            }
             ZipError::Unknown(msg) => write!(f, "Unknown error: {}", msg),
             ZipError::StringConversion(msg) => write!(f, "String conversion error: {}", msg),
+            ZipError::Sealed => write!(f, "Archive is sealed and cannot be modified"),
+            ZipError::StaleEntry => write!(f, "Entry refers to an outdated state of the archive"),
+            ZipError::Io(err) => write!(f, "I/O error: {}", err),
+            ZipError::Utf8(err) => write!(f, "String conversion error: {}", err),
+            ZipError::Native { error, .. } => write!(f, "{}", error),
+            ZipError::WithContext { context, error } => write!(f, "{}: {}", context, error),
         }
     }
 }
 
-impl std::error::Error for ZipError {}
+impl std::error::Error for ZipError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ZipError::Io(err) => Some(err),
+            ZipError::Utf8(err) => Some(err),
+            // The wrapped error is already part of the message, so skip to its source
+            ZipError::Native { error, .. } | ZipError::WithContext { error, .. } => error.source(),
+            _ => None,
+        }
+    }
+}
 
 impl From<NulError> for ZipError {
     fn from(err: NulError) -> Self {
@@ -72,25 +150,42 @@ impl From<NulError> for ZipError {
 
 impl From<Utf8Error> for ZipError {
     fn from(err: Utf8Error) -> Self {
-        ZipError::StringConversion(err.to_string())
+        ZipError::Utf8(err)
     }
 }
 
 impl From<std::io::Error> for ZipError {
     fn from(err: std::io::Error) -> Self {
-        ZipError::IoError(err.to_string())
+        ZipError::Io(err)
     }
 }
 
 impl ZipError {
     /// Convert an error code from the native library to a ZipError
     pub fn from_code(code: i32) -> Self {
-        match code {
+        Self::native(code, None)
+    }
+
+    /// Convert an error code returned by a native call to a ZipError carrying the
+    /// class and message of the Java exception behind it, when there is one
+    ///
+    /// Must be called on the thread that made the failing call.
+    pub(crate) fn from_native(code: i32) -> Self {
+        let message = crate::ffi::helpers::take_last_exception(code);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(code, message = message.as_deref().unwrap_or(""), "native call failed");
+        crate::telemetry::record_error(code);
+        Self::native(code, message)
+    }
+
+    fn native(code: i32, message: Option<String>) -> Self {
+        let describe = |fallback: &str| message.clone().unwrap_or_else(|| fallback.to_string());
+        let error = match code {
             -1 => ZipError::InvalidHandle,
             -2 => ZipError::FileNotFound,
-            -3 => ZipError::ZipException("Zip operation failed".to_string()),
-            -4 => ZipError::IoError("I/O operation failed".to_string()),
-            -5 => ZipError::InvalidParameter("Invalid parameter provided".to_string()),
+            -3 => ZipError::ZipException(describe("Zip operation failed")),
+            -4 => ZipError::IoError(describe("I/O operation failed")),
+            -5 => ZipError::InvalidParameter(describe("Invalid parameter provided")),
             -6 => ZipError::OutOfMemory,
             -7 => ZipError::EntryNotFound,
             -8 => ZipError::BufferTooSmall,
@@ -99,7 +194,67 @@ impl ZipError {
             -11 => ZipError::NullPointer,
             -12 => ZipError::PermissionDenied,
             -13 => ZipError::DiskFull,
-            _ => ZipError::Unknown(format!("Error code: {}", code)),
+            _ => ZipError::Unknown(match &message {
+                Some(message) => format!("{} (error code {})", message, code),
+                None => format!("Error code: {}", code),
+            }),
+        };
+
+        ZipError::Native { code, message, error: Box::new(error) }
+    }
+
+    /// The error without the code or context it was wrapped in, for matching on what went wrong
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{ZipError, ZipFile};
+    ///
+    /// let zip = ZipFile::new("archive.zip")?;
+    /// match zip.extract_file("missing.txt", "out") {
+    ///     Err(e) if matches!(e.root(), ZipError::FileNotFound) => println!("not there: {}", e),
+    ///     other => other?,
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    pub fn root(&self) -> &ZipError {
+        match self {
+            ZipError::Native { error, .. } | ZipError::WithContext { error, .. } => error.root(),
+            error => error,
+        }
+    }
+
+    /// Raw code returned by the native library, if the error came from a native call
+    pub fn code(&self) -> Option<i32> {
+        match self {
+            ZipError::Native { code, .. } => Some(*code),
+            ZipError::WithContext { error, .. } => error.code(),
+            _ => None,
+        }
+    }
+
+    /// Class and message of the Java exception behind the error, if there was one
+    pub fn native_message(&self) -> Option<&str> {
+        match self {
+            ZipError::Native { message, .. } => message.as_deref(),
+            ZipError::WithContext { error, .. } => error.native_message(),
+            _ => None,
+        }
+    }
+
+    /// The operation and file or entry the error happened on, if known
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            ZipError::WithContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Wrap the error with the operation that failed and the file or entry involved
+    pub(crate) fn with_context(self, operation: &'static str, target: impl fmt::Display) -> Self {
+        ZipError::WithContext {
+            context: ErrorContext { operation, target: target.to_string() },
+            error: Box::new(self),
         }
     }
 }
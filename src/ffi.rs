@@ -1,7 +1,10 @@
+use std::cell::{Cell, RefCell};
 use std::ffi::{CStr, CString};
 use std::os::raw::{c_char, c_int, c_longlong};
-use std::sync::Once;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
 use crate::error::{Result, ZipError};
+use crate::types::{Diagnostics, InitOptions, VersionInfo};
 
 // Include the generated bindings (either static or dynamic based on bundled feature)
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
@@ -13,84 +16,455 @@ pub use graal_isolate_t as GraalIsolate;
 /// Global isolate and thread for GraalVM operations
 static mut GRAAL_ISOLATE: *mut GraalIsolate = std::ptr::null_mut();
 static mut GRAAL_THREAD: *mut GraalIsolateThread = std::ptr::null_mut();
-static INIT_ONCE: Once = Once::new();
+
+/// Lifecycle of the shared isolate; transitions happen under this lock
+static STATE: Mutex<RuntimeState> = Mutex::new(RuntimeState::Uninitialized);
+
+/// Incremented whenever a new shared isolate is created, so threads attached
+/// to an isolate that has since been torn down attach again
+static EPOCH: AtomicU64 = AtomicU64::new(0);
+
+/// Threads attached to the shared isolate besides the one that created it
+static ATTACHED_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RuntimeState {
+    /// No isolate exists, either not created yet or torn down by `cleanup`
+    Uninitialized,
+    /// The isolate is up and zip4j is initialized, with the options it was created with
+    Running(InitOptions),
+}
+
+fn lock_state() -> MutexGuard<'static, RuntimeState> {
+    STATE.lock().unwrap_or_else(PoisonError::into_inner)
+}
 
 /// Initialize the zip4j library with proper GraalVM isolate setup
+///
+/// Does nothing if the library is already initialized. After [`cleanup`] a
+/// fresh isolate is created.
 pub fn init() -> Result<()> {
+    let mut state = lock_state();
+    if matches!(*state, RuntimeState::Running(_)) {
+        return Ok(());
+    }
+    start(&mut state, &InitOptions::default())
+}
+
+/// Initialize the zip4j library in an isolate sized according to `options`
+///
+/// Does nothing if the library is already initialized with the same
+/// options. Fails with `ZipError::UnsupportedOperation` if it was initialized
+/// with different ones, since the limits of a running isolate can't be changed.
+pub fn init_with_options(options: &InitOptions) -> Result<()> {
+    let mut state = lock_state();
+    if let RuntimeState::Running(running) = &*state {
+        return if running == options {
+            Ok(())
+        } else {
+            Err(ZipError::UnsupportedOperation)
+        };
+    }
+    start(&mut state, options)
+}
+
+/// Create the shared isolate with `options` and mark the runtime as running
+fn start(state: &mut RuntimeState, options: &InitOptions) -> Result<()> {
+    let (isolate, thread) = create_isolate(options)?;
+
+    // Store the isolate and thread globally
+    unsafe {
+        GRAAL_ISOLATE = isolate;
+        GRAAL_THREAD = thread;
+    }
+
+    EPOCH.fetch_add(1, Ordering::SeqCst);
+    *state = RuntimeState::Running(options.clone());
+    Ok(())
+}
+
+/// Create a new isolate sized according to `options` and initialize zip4j in it
+pub(crate) fn create_isolate(options: &InitOptions) -> Result<(*mut GraalIsolate, *mut GraalIsolateThread)> {
+    let max_heap_bytes = options.max_heap_mb
+        .map(|mb| {
+            mb.checked_mul(1024 * 1024)
+                .and_then(|bytes| c_longlong::try_from(bytes).ok())
+                .ok_or_else(|| ZipError::InvalidParameter(format!("max_heap_mb {} is too large", mb)))
+        })
+        .transpose()?;
+
     // Ensure embedded library is initialized first
     #[cfg(feature = "bundled")]
     {
         embedded::ensure_initialized()?;
     }
 
-    let mut init_result = Ok(());
-
-    INIT_ONCE.call_once(|| {
-        unsafe {
-            let mut isolate: *mut GraalIsolate = std::ptr::null_mut();
-            let mut thread: *mut GraalIsolateThread = std::ptr::null_mut();
-
-            // Create a new GraalVM isolate
-            let create_result = graal_create_isolate(
-                std::ptr::null_mut(), // No special parameters
-                &mut isolate,
-                &mut thread
-            );
-
-            if create_result != 0 {
-                init_result = Err(ZipError::Unknown(format!("Failed to create GraalVM isolate: {}", create_result)));
-                return;
+    unsafe {
+        let mut isolate: *mut GraalIsolate = std::ptr::null_mut();
+        let mut thread: *mut GraalIsolateThread = std::ptr::null_mut();
+
+        let mut params: graal_create_isolate_params_t = std::mem::zeroed();
+        params.version = __graal_create_isolate_params_version as c_int;
+        let params_ptr = match options.reserved_address_space {
+            Some(bytes) => {
+                params.reserved_address_space_size = bytes as _;
+                &mut params as *mut graal_create_isolate_params_t
             }
+            None => std::ptr::null_mut(), // No special parameters
+        };
+
+        // Create a new GraalVM isolate
+        let create_result = graal_create_isolate(
+            params_ptr,
+            &mut isolate,
+            &mut thread
+        );
 
-            // Store the isolate and thread globally
-            GRAAL_ISOLATE = isolate;
-            GRAAL_THREAD = thread;
+        if create_result != 0 {
+            return Err(ZipError::Unknown(format!("Failed to create GraalVM isolate: {}", create_result)));
+        }
 
-            // Now initialize the zip4j library
-            let zip_init_result = zip4j_init(thread);
-            if zip_init_result != 0 {
-                init_result = Err(ZipError::from_code(zip_init_result));
-                return;
+        // Now initialize the zip4j library, leaving nothing behind if that fails
+        let mut zip_init_result = zip4j_init(thread);
+        if zip_init_result == 0 {
+            if let Some(max_heap_bytes) = max_heap_bytes {
+                zip_init_result = zip4j_set_max_heap_size(thread, max_heap_bytes);
             }
         }
-    });
+        if zip_init_result != 0 {
+            graal_tear_down_isolate(thread);
+            return Err(ZipError::from_code(zip_init_result));
+        }
 
-    init_result
+        Ok((isolate, thread))
+    }
 }
 
 /// Cleanup the zip4j library and tear down the GraalVM isolate
+///
+/// Waits for calls still running on other threads to finish first. Threads
+/// attached to the isolate are detached along with it, and archives and
+/// entries opened in it fail with `ZipError::InvalidHandle` afterwards. The
+/// library can be initialized again.
 pub fn cleanup() -> Result<()> {
+    let mut state = lock_state();
+    if *state == RuntimeState::Uninitialized {
+        return Ok(());
+    }
+
+    // Waiting on a call this thread is itself making would never return
+    if HELD_CALLS.with(Cell::get) > 0 {
+        return Err(ZipError::InvalidParameter("cleanup called from inside a library call".to_string()));
+    }
+
+    // Hold back new calls and let the ones in flight finish
+    let mut calls = lock_calls();
+    calls.closing = true;
+    while calls.in_flight > 0 {
+        calls = CALLS_DONE.wait(calls).unwrap_or_else(PoisonError::into_inner);
+    }
+    drop(calls);
+
+    // The shared isolate, even when called inside `Isolate::run`
+    let thread = match shared_thread() {
+        Ok(thread) => thread,
+        Err(e) => {
+            lock_calls().closing = false;
+            return Err(e);
+        }
+    };
+
     unsafe {
-        if !GRAAL_THREAD.is_null() {
-            // Cleanup zip4j first
-            let cleanup_result = zip4j_cleanup(GRAAL_THREAD);
-            if cleanup_result != 0 {
-                // Continue with teardown even if cleanup failed
-                eprintln!("Warning: zip4j cleanup failed with code: {}", cleanup_result);
+        // Cleanup zip4j first
+        let cleanup_result = zip4j_cleanup(thread);
+        if cleanup_result != 0 {
+            // Continue with teardown even if cleanup failed
+            eprintln!("Warning: zip4j cleanup failed with code: {}", cleanup_result);
+        }
+
+        // Reset global pointers; the isolate is gone even if teardown reports an error
+        GRAAL_ISOLATE = std::ptr::null_mut();
+        GRAAL_THREAD = std::ptr::null_mut();
+        ATTACHED_THREADS.store(0, Ordering::SeqCst);
+        lock_calls().closing = false;
+        *state = RuntimeState::Uninitialized;
+
+        // Tear down the GraalVM isolate
+        let teardown_result = graal_detach_all_threads_and_tear_down_isolate(thread);
+        if teardown_result != 0 {
+            return Err(ZipError::Unknown(format!("Failed to tear down GraalVM isolate: {}", teardown_result)));
+        }
+    }
+    Ok(())
+}
+
+/// Query the versions baked into the native library
+pub fn version_info() -> Result<VersionInfo> {
+    let mut abi: c_int = 0;
+    let mut buffer = vec![0u8; 256];
+    let mut length: c_int = 0;
+
+    loop {
+        let result = unsafe {
+            zip4j_get_version_info(
+                *get_thread()?,
+                &mut abi,
+                buffer.as_mut_ptr() as *mut c_char,
+                buffer.len() as c_int,
+                &mut length
+            )
+        };
+
+        if result == constants::ERROR_BUFFER_TOO_SMALL {
+            buffer.resize(length as usize, 0);
+            continue;
+        }
+        if helpers::is_error(result) {
+            return Err(ZipError::from_native(result));
+        }
+
+        buffer.truncate(length as usize);
+        let mut versions = helpers::read_string_list(&buffer)?.into_iter();
+        return Ok(VersionInfo {
+            zip4j: versions.next().unwrap_or_default(),
+            abi: abi as u32,
+            graalvm: versions.next().unwrap_or_default(),
+        });
+    }
+}
+
+/// Query heap usage and open handles of the isolate the current thread calls into
+pub fn diagnostics() -> Result<Diagnostics> {
+    let mut values = [0 as c_longlong; 7];
+    let result = unsafe {
+        zip4j_get_diagnostics(*get_thread()?, values.as_mut_ptr(), values.len() as c_int)
+    };
+
+    if helpers::is_error(result) {
+        return Err(ZipError::from_native(result));
+    }
+
+    let [heap_used, heap_committed, heap_max, open_archives, open_entries, open_streams, open_parameter_sets] =
+        values.map(|value| value.max(0) as u64);
+    Ok(Diagnostics {
+        heap_used,
+        heap_committed,
+        heap_max,
+        attached_threads: usize::from(is_initialized()) + ATTACHED_THREADS.load(Ordering::SeqCst),
+        open_archives: open_archives as usize,
+        open_entries: open_entries as usize,
+        open_streams: open_streams as usize,
+        open_parameter_sets: open_parameter_sets as usize,
+    })
+}
+
+thread_local! {
+    /// Isolate thread for an OS thread other than the one that created the isolate
+    static ATTACHED_THREAD: RefCell<AttachedThread> = const {
+        RefCell::new(AttachedThread { thread: std::ptr::null_mut(), epoch: 0 })
+    };
+
+    /// Number of [`ThreadGuard`]s for the shared isolate the current OS thread holds
+    static HELD_CALLS: Cell<usize> = const { Cell::new(0) };
+
+    /// Thread and id of the dedicated isolate the current OS thread is running in, if any
+    static ENTERED_THREAD: Cell<(*mut GraalIsolateThread, usize)> = const { Cell::new((std::ptr::null_mut(), 0)) };
+}
+
+/// Isolate thread attached on first use, detached again when the OS thread exits
+struct AttachedThread {
+    thread: *mut GraalIsolateThread,
+    /// [`EPOCH`] of the isolate the thread was attached to
+    epoch: u64,
+}
+
+impl AttachedThread {
+    fn get(&mut self) -> Result<*mut GraalIsolateThread> {
+        let epoch = EPOCH.load(Ordering::SeqCst);
+        if self.epoch != epoch || self.thread.is_null() {
+            // Never attached, or attached to an isolate that `cleanup` tore down
+            self.thread = std::ptr::null_mut();
+            self.epoch = epoch;
+            let attach_result = unsafe { graal_attach_thread(GRAAL_ISOLATE, &mut self.thread) };
+            if attach_result != 0 || self.thread.is_null() {
+                self.thread = std::ptr::null_mut();
+                return Err(ZipError::Unknown(format!("Failed to attach thread to GraalVM isolate: {}", attach_result)));
             }
+            ATTACHED_THREADS.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(self.thread)
+    }
+}
 
-            // Tear down the GraalVM isolate
-            let teardown_result = graal_tear_down_isolate(GRAAL_THREAD);
-            if teardown_result != 0 {
-                return Err(ZipError::Unknown(format!("Failed to tear down GraalVM isolate: {}", teardown_result)));
+impl Drop for AttachedThread {
+    fn drop(&mut self) {
+        unsafe {
+            // The isolate may already have been torn down by `cleanup`
+            if !self.thread.is_null() && !GRAAL_ISOLATE.is_null() && self.epoch == EPOCH.load(Ordering::SeqCst) {
+                graal_detach_thread(self.thread);
+                ATTACHED_THREADS.fetch_sub(1, Ordering::SeqCst);
             }
+        }
+    }
+}
+
+/// Isolate thread to pass to native calls, see [`get_thread`]
+///
+/// While a guard for the shared isolate is alive, [`cleanup`] waits instead
+/// of tearing the isolate down underneath the call.
+pub(crate) struct ThreadGuard {
+    thread: *mut GraalIsolateThread,
+    /// Whether the guard is counted in [`CALLS`]
+    counted: bool,
+}
+
+impl std::ops::Deref for ThreadGuard {
+    type Target = *mut GraalIsolateThread;
+
+    fn deref(&self) -> &Self::Target {
+        &self.thread
+    }
+}
 
-            // Reset global pointers
-            GRAAL_ISOLATE = std::ptr::null_mut();
-            GRAAL_THREAD = std::ptr::null_mut();
+impl Drop for ThreadGuard {
+    fn drop(&mut self) {
+        if self.counted {
+            HELD_CALLS.with(|held| held.set(held.get() - 1));
+            let mut calls = lock_calls();
+            calls.in_flight -= 1;
+            if calls.in_flight == 0 {
+                CALLS_DONE.notify_all();
+            }
         }
     }
-    Ok(())
 }
 
-/// Get the current GraalVM isolate thread
+/// Calls into the shared isolate that are in flight
+struct Calls {
+    in_flight: usize,
+    /// Set while `cleanup` waits for the calls in flight, holding back new ones
+    closing: bool,
+}
+
+static CALLS: Mutex<Calls> = Mutex::new(Calls { in_flight: 0, closing: false });
+
+/// Signalled when the last call in flight finishes
+static CALLS_DONE: Condvar = Condvar::new();
+
+fn lock_calls() -> MutexGuard<'static, Calls> {
+    CALLS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Start a call into the shared isolate, unless it isn't running or is being torn down
+fn begin_call() -> Option<ThreadGuard> {
+    let mut calls = lock_calls();
+    // A thread already in a call keeps the isolate alive, so nested calls may always proceed
+    let nested = HELD_CALLS.with(Cell::get) > 0;
+    if !is_initialized() || (calls.closing && !nested) {
+        return None;
+    }
+    calls.in_flight += 1;
+    HELD_CALLS.with(|held| held.set(held.get() + 1));
+    Some(ThreadGuard { thread: std::ptr::null_mut(), counted: true })
+}
+
+/// Get the GraalVM isolate thread for the calling OS thread
 ///
-/// # Safety
+/// The thread that initialized the library uses the isolate thread created
+/// with the isolate; any other thread is attached to the isolate the first
+/// time it calls in, so archives can be read from several threads at once.
+///
+/// The library is initialized on demand, so [`init`] is optional. Fails if
+/// initialization or attaching the thread fails.
+pub(crate) fn get_thread() -> Result<ThreadGuard> {
+    let (entered, _) = ENTERED_THREAD.with(Cell::get);
+    if !entered.is_null() {
+        return Ok(ThreadGuard { thread: entered, counted: false });
+    }
+
+    loop {
+        ensure_initialized()?;
+        if let Some(mut guard) = begin_call() {
+            guard.thread = shared_thread()?;
+            return Ok(guard);
+        }
+        // `cleanup` is tearing the isolate down; wait for it, then start a new one
+        drop(lock_state());
+    }
+}
+
+/// Get the isolate thread for a call on a handle created in isolate `id` at `epoch`
+///
+/// Fails with `ZipError::InvalidHandle` outside that isolate, or once the
+/// shared isolate the handle was created in has been cleaned up, where the
+/// handle would refer to something else.
+pub(crate) fn handle_thread(id: usize, epoch: u64) -> Result<ThreadGuard> {
+    if id != isolate_id() || epoch != self::epoch() {
+        return Err(ZipError::InvalidHandle);
+    }
+    let thread = get_thread()?;
+    // Checked again now that `cleanup` can't run anymore
+    if epoch != self::epoch() {
+        return Err(ZipError::InvalidHandle);
+    }
+    Ok(thread)
+}
+
+/// Get the calling OS thread's isolate thread in the shared isolate, even inside
+/// [`Isolate::run`](crate::Isolate::run), without initializing the library
+fn shared_thread() -> Result<*mut GraalIsolateThread> {
+    if !is_initialized() {
+        return Err(ZipError::InvalidHandle);
+    }
+
+    unsafe {
+        let thread = graal_get_current_thread(GRAAL_ISOLATE);
+        if !thread.is_null() {
+            return Ok(thread);
+        }
+    }
+    ATTACHED_THREAD.with(|attached| attached.borrow_mut().get())
+}
+
+/// Route calls from the current OS thread to the dedicated isolate `id` through
+/// `thread`, or back to the shared isolate when null, returning the previously
+/// entered thread and id
+pub(crate) fn enter_thread(thread: *mut GraalIsolateThread, id: usize) -> (*mut GraalIsolateThread, usize) {
+    ENTERED_THREAD.with(|entered| entered.replace((thread, id)))
+}
+
+/// Identity of the isolate calls from the current OS thread go to, 0 for the shared one
+pub(crate) fn isolate_id() -> usize {
+    ENTERED_THREAD.with(Cell::get).1
+}
+
+/// Generation of the isolate calls from the current OS thread go to: the
+/// [`EPOCH`] of the shared isolate, 0 for a dedicated one
+pub(crate) fn epoch() -> u64 {
+    if isolate_id() == 0 {
+        EPOCH.load(Ordering::SeqCst)
+    } else {
+        0
+    }
+}
+
+/// Isolate thread to release a handle created in isolate `id` at `epoch` with,
+/// if that isolate still exists and can be reached from here without
+/// initializing the library again
 ///
-/// This function should only be called after successful initialization.
-/// Returns null if not initialized.
-pub(crate) fn get_thread() -> *mut GraalIsolateThread {
-    unsafe { GRAAL_THREAD }
+/// Handles of the shared isolate can be released from inside a dedicated
+/// isolate's `run`; those of a dedicated isolate only from inside its own.
+pub(crate) fn release_thread(id: usize, epoch: u64) -> Option<ThreadGuard> {
+    if id != 0 {
+        return (id == isolate_id()).then(|| get_thread().ok()).flatten();
+    }
+
+    let mut guard = begin_call()?;
+    if epoch != EPOCH.load(Ordering::SeqCst) {
+        return None;
+    }
+    guard.thread = shared_thread().ok()?;
+    Some(guard)
 }
 
 /// Check if the library is initialized
@@ -100,7 +474,7 @@ pub fn is_initialized() -> bool {
 
 /// Ensure the library is initialized, initializing it automatically if needed
 pub(crate) fn ensure_initialized() -> Result<()> {
-    if !is_initialized() {
+    if !is_initialized() && isolate_id() == 0 {
         init()?;
     }
     Ok(())
@@ -146,6 +520,32 @@ pub mod constants {
     pub const AES_KEY_STRENGTH_128: c_int = 1;
     pub const AES_KEY_STRENGTH_192: c_int = 2;
     pub const AES_KEY_STRENGTH_256: c_int = 3;
+
+    // Entry health
+    pub const HEALTH_INTACT: c_int = 0;
+    pub const HEALTH_TRUNCATED: c_int = 1;
+    pub const HEALTH_CRC_MISMATCH: c_int = 2;
+    pub const HEALTH_WRONG_PASSWORD: c_int = 3;
+    pub const HEALTH_CORRUPT: c_int = 4;
+
+    // Change detection
+    pub const CHANGE_DETECTION_CRC: c_int = 0;
+    pub const CHANGE_DETECTION_MTIME: c_int = 1;
+
+    // Entry differences
+    pub const DIFF_UNCHANGED: c_int = 0;
+    pub const DIFF_MISSING: c_int = 1;
+    pub const DIFF_MODIFIED: c_int = 2;
+
+    // Name normalization
+    pub const NAME_NORMALIZATION_NONE: c_int = 0;
+    pub const NAME_NORMALIZATION_NFC: c_int = 1;
+    pub const NAME_NORMALIZATION_NFD: c_int = 2;
+
+    // Output streams
+    pub const OUTPUT_NONE: c_int = 0;
+    pub const OUTPUT_STDOUT: c_int = 1;
+    pub const OUTPUT_STDERR: c_int = 2;
 }
 
 /// Helper functions for FFI operations
@@ -157,6 +557,29 @@ pub mod helpers {
         CString::new(s).map_err(ZipError::from)
     }
 
+    /// Encode strings as a buffer of consecutive null-terminated C strings
+    pub fn to_c_string_list<I, S>(strings: I) -> Result<Vec<u8>>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut buffer = Vec::new();
+        for s in strings {
+            buffer.extend_from_slice(to_c_string(s.as_ref())?.as_bytes_with_nul());
+        }
+        Ok(buffer)
+    }
+
+    /// Split a buffer of null-terminated strings, as written by the native library
+    pub fn read_string_list(buffer: &[u8]) -> Result<Vec<String>> {
+        match buffer.strip_suffix(&[0]) {
+            Some(strings) => strings.split(|&byte| byte == 0)
+                .map(|bytes| Ok(std::str::from_utf8(bytes)?.to_string()))
+                .collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
     /// Check if a return code indicates success
     pub fn is_success(code: c_int) -> bool {
         code == constants::SUCCESS
@@ -186,7 +609,7 @@ pub mod helpers {
 
         let result = unsafe {
             zip4j_get_last_error(
-                get_thread(),
+                *get_thread()?,
                 handle,
                 buffer.as_mut_ptr() as *mut c_char,
                 BUFFER_SIZE as c_int,
@@ -201,6 +624,38 @@ pub mod helpers {
         read_string_from_buffer_u8(&buffer, error_length)
     }
 
+    /// Take the description of the Java exception behind the error `code` just returned on this thread
+    ///
+    /// Returns `None` when the error didn't come from an exception or its description can't be read.
+    pub fn take_last_exception(code: c_int) -> Option<String> {
+        let mut buffer = vec![0u8; 1024];
+        let mut details_length: c_int = 0;
+
+        loop {
+            let result = unsafe {
+                zip4j_take_last_exception(
+                    *get_thread().ok()?,
+                    code,
+                    buffer.as_mut_ptr() as *mut c_char,
+                    buffer.len() as c_int,
+                    &mut details_length
+                )
+            };
+
+            if result == constants::ERROR_BUFFER_TOO_SMALL && details_length as usize >= buffer.len() {
+                buffer.resize(details_length as usize + 1, 0);
+                continue;
+            }
+            if is_error(result) {
+                return None;
+            }
+
+            return read_string_from_buffer_u8(&buffer, details_length)
+                .ok()
+                .filter(|details| !details.is_empty());
+        }
+    }
+
     /// Read a string from a u8 buffer with length (for cross-platform compatibility)
     pub fn read_string_from_buffer_u8(buffer: &[u8], length: c_int) -> Result<String> {
         if length <= 0 {
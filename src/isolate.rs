@@ -0,0 +1,94 @@
+//! Dedicated GraalVM isolates
+//!
+//! By default every archive lives in one shared isolate created by
+//! [`init`](crate::init). An [`Isolate`] is a separate Java heap with its own
+//! garbage collector, so untrusted or memory-hungry archive processing can be
+//! kept apart from the rest of the application.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::error::Result;
+use crate::ffi::{self, GraalIsolateThread};
+use crate::types::InitOptions;
+
+/// Id of the next dedicated isolate; 0 stands for the shared one
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// A dedicated GraalVM isolate with its own heap
+///
+/// Archives opened inside [`Isolate::run`] are created in this isolate and
+/// can only be used inside `run` calls on the same isolate: anywhere else
+/// their methods fail with `ZipError::InvalidHandle`, and one dropped outside
+/// is only released when the isolate itself is torn down. Likewise, archives
+/// of the shared isolate can't be used inside `run`. Dropping the
+/// `Isolate` tears it down and frees everything that was created in it.
+///
+/// An isolate is bound to the OS thread that created it, so it is neither
+/// `Send` nor `Sync`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use zip2rs::{Isolate, ZipFile};
+///
+/// let sandbox = Isolate::new()?;
+/// let names = sandbox.run(|| ZipFile::open_existing("upload.zip")?.entry_names())?;
+/// println!("{:?}", names);
+/// # Ok::<(), zip2rs::ZipError>(())
+/// ```
+#[derive(Debug)]
+pub struct Isolate {
+    thread: *mut GraalIsolateThread,
+    /// Identifies handles created in this isolate, see [`ffi::isolate_id`]
+    id: usize,
+}
+
+impl Isolate {
+    /// Create a new isolate
+    pub fn new() -> Result<Self> {
+        Self::with_options(&InitOptions::default())
+    }
+
+    /// Create a new isolate sized according to `options`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{InitOptions, Isolate};
+    ///
+    /// // A 64 MiB heap for a plugin's archives, separate from the host's
+    /// let sandbox = Isolate::with_options(&InitOptions::new().with_max_heap_mb(64))?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    pub fn with_options(options: &InitOptions) -> Result<Self> {
+        let (_, thread) = ffi::create_isolate(options)?;
+        Ok(Self { thread, id: NEXT_ID.fetch_add(1, Ordering::Relaxed) })
+    }
+
+    /// Run `f` with every call from this thread going to this isolate
+    ///
+    /// Calls can be nested, including across different isolates; the
+    /// previously active isolate is restored when `f` returns or panics.
+    pub fn run<R, F: FnOnce() -> R>(&self, f: F) -> R {
+        /// Restores the previously entered isolate, even when unwinding
+        struct Restore(*mut GraalIsolateThread, usize);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                ffi::enter_thread(self.0, self.1);
+            }
+        }
+
+        let (thread, id) = ffi::enter_thread(self.thread, self.id);
+        let _restore = Restore(thread, id);
+        f()
+    }
+}
+
+impl Drop for Isolate {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::zip4j_cleanup(self.thread);
+            ffi::graal_tear_down_isolate(self.thread);
+        }
+    }
+}
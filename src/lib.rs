@@ -17,13 +17,15 @@
 //! - **Comprehensive Metadata**: Access detailed entry information
 //! - **Iterator Support**: Iterate over entries with Rust iterators
 //! - **Safe API**: Memory-safe operations with comprehensive error handling
+//! - **Thread Safety**: `ZipFile` is `Send + Sync`, with native threads attached on demand
+//! - **Isolation**: Process untrusted archives in a dedicated GraalVM isolate with its own heap
 //!
 //! ## Quick Start
 //!
 //! ```rust,no_run
 //! use zip2rs::{ZipFile, ZipParameters, CompressionLevel, EncryptionMethod};
 //!
-//! // Initialize the library (call once at startup)
+//! // Initialize the library (optional, it also happens on first use)
 //! zip2rs::init()?;
 //!
 //! // Create a new ZIP file
@@ -84,24 +86,48 @@
 
 pub mod error;
 pub mod ffi;
+pub mod isolate;
+pub mod output;
+pub mod raw;
+pub mod runtime;
 pub mod types;
 pub mod zip_entry;
 pub mod zip_file;
+pub mod zip_reader;
+pub mod zip_writer;
 
 #[cfg(feature = "bundled")]
 mod embedded;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod telemetry;
 
 // Re-export main types for convenience
-pub use error::{Result, ZipError};
+pub use error::{ErrorContext, Result, ZipError};
+pub use isolate::Isolate;
+pub use output::{capture_native_output, drain_native_output, NativeOutputLine, NativeStream};
+pub use runtime::{RuntimeGuard, Zip4jRuntime};
 pub use types::{
-    AesKeyStrength, CompressionLevel, CompressionMethod, EncryptionMethod, ZipParameters,
+    AddDirOptions, AesKeyStrength, ChangeDetection, CompressionLevel, CompressionMethod,
+    ConflictResolution, Diagnostics, DiffKind, DirEntry, DuplicatePolicy, EncryptionMethod,
+    EntryCondition, EntryDiff, EntryDigest, EntryHealth, EntrySource, EntryTestResult,
+    ExcludeFilter, ExtractOptions, ExtractReport, ExtractedEntry, FileConflict, InitOptions,
+    NameNormalization, OpenMode, Password, Progress, SalvageReport, SaveMode, SealOptions,
+    SymlinkPolicy, SyncOptions, SyncReport, TestReport, VersionInfo, ZipEntryBuilder, ZipEntryInfo,
+    ZipEvent, ZipParameters,
 };
+#[cfg(feature = "serde")]
+pub use types::{ArchiveManifest, ManifestEntry};
 pub use zip_entry::ZipEntry;
-pub use zip_file::{ZipFile, ZipEntryIterator};
+pub use zip_file::{Batch, Entries, IntoEntries, Walk, ZipFile, ZipEntryIterator};
+pub use zip_reader::ZipReader;
+pub use zip_writer::ZipWriter;
 
 /// Initialize the zip4j library
 /// 
-/// This must be called once before using any other functions.
+/// Calling this is optional: the library initializes itself the first
+/// time it is used. Calling it at startup moves that cost out of the
+/// first archive operation and surfaces initialization errors early.
 /// It's safe to call this multiple times.
 /// 
 /// # Examples
@@ -118,11 +144,117 @@ pub fn init() -> Result<()> {
     ffi::init()
 }
 
+/// Initialize the zip4j library with resource limits for the GraalVM isolate
+///
+/// Must be called before anything else uses the library, since a running
+/// isolate can't be resized: once the library is initialized, calling it
+/// again with the same options does nothing and with different ones fails
+/// with `ZipError::UnsupportedOperation`. A `max_heap_mb` too large to
+/// express in bytes fails with `ZipError::InvalidParameter`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use zip2rs::InitOptions;
+///
+/// // Keep the Java heap small on an embedded device
+/// zip2rs::init_with_options(&InitOptions::new().with_max_heap_mb(48))?;
+/// # Ok::<(), zip2rs::ZipError>(())
+/// ```
+pub fn init_with_options(options: &InitOptions) -> Result<()> {
+    ffi::init_with_options(options)
+}
+
+/// Initialize the zip4j library using a native library the host has already loaded
+///
+/// For applications that load `zip4j-abi` themselves (or link it into the
+/// executable), this skips extracting and loading the embedded copy. Must be
+/// called before anything else uses the library. A raw `dlopen`/`LoadLibrary`
+/// handle can be wrapped with `libloading::os::unix::Library::from_raw` or
+/// `libloading::os::windows::Library::from_raw` and converted with `.into()`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// // The host application ships its own build of the native library
+/// let library = unsafe { libloading::Library::new("/opt/app/lib/libzip4j-abi.so") }
+///     .expect("native library missing");
+/// zip2rs::init_with_library(library)?;
+/// # Ok::<(), zip2rs::ZipError>(())
+/// ```
+#[cfg(feature = "bundled")]
+pub fn init_with_library(library: libloading::Library) -> Result<()> {
+    embedded::use_library(library)?;
+    ffi::init()
+}
+
+/// Set the directory the bundled native library is extracted into
+///
+/// By default the library is loaded from memory on Linux and extracted to a
+/// fresh temporary directory elsewhere. A configured directory, e.g. a
+/// per-user cache, is used instead on every platform: the library is written
+/// there once under a name that includes the crate version and a hash of its
+/// contents, and later runs load the existing file. The `ZIP2RS_EXTRACT_DIR`
+/// environment variable has the same effect. Must be called before anything
+/// else uses the library.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// let cache = std::env::var_os("HOME").map(std::path::PathBuf::from).unwrap().join(".cache/myapp");
+/// zip2rs::set_extraction_dir(cache)?;
+/// # Ok::<(), zip2rs::ZipError>(())
+/// ```
+#[cfg(feature = "bundled")]
+pub fn set_extraction_dir<P: Into<std::path::PathBuf>>(dir: P) -> Result<()> {
+    embedded::set_extraction_dir(dir.into())
+}
+
+/// Report the zip4j, ABI and GraalVM versions baked into the native library
+///
+/// Useful in bug reports and for checking at startup that a separately
+/// shipped library is the one the application was built against.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// let version = zip2rs::native_version()?;
+/// println!("{}", version); // e.g. "zip4j 2.11.5, ABI 1, GraalVM 24.0.1+9.1"
+/// # Ok::<(), zip2rs::ZipError>(())
+/// ```
+pub fn native_version() -> Result<VersionInfo> {
+    ffi::version_info()
+}
+
+/// Report heap usage and open handles of the embedded Java runtime
+///
+/// Inside [`Isolate::run`] this describes that isolate's heap and handles,
+/// while `attached_threads` always counts threads of the shared isolate.
+/// Long-running hosts can sample it periodically: open handle counts that keep
+/// growing point to archives or entries that are never dropped.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// let diagnostics = zip2rs::diagnostics()?;
+/// println!(
+///     "heap {}/{} MiB, {} archives open",
+///     diagnostics.heap_used >> 20,
+///     diagnostics.heap_max >> 20,
+///     diagnostics.open_archives
+/// );
+/// # Ok::<(), zip2rs::ZipError>(())
+/// ```
+pub fn diagnostics() -> Result<Diagnostics> {
+    ffi::diagnostics()
+}
+
 /// Cleanup the zip4j library
 /// 
 /// This should be called when you're done using the library,
 /// typically at application shutdown. It's safe to call this
-/// multiple times.
+/// multiple times, and [`init`] can bring the library back up
+/// afterwards, e.g. between test cases.
 /// 
 /// # Examples
 /// 
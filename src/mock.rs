@@ -0,0 +1,1621 @@
+//! In-memory stand-in for the native library, enabled with the `mock` feature
+//!
+//! With `mock` enabled nothing is loaded or linked: every call that would go
+//! to zip4j is answered by a simple in-process fake instead, so code using
+//! [`ZipFile`](crate::ZipFile) can be unit-tested where the GraalVM library
+//! isn't available. Archives are kept in a map keyed by their path and never
+//! touch the disk, but files added from disk are read and extraction writes
+//! real files, so round trips behave as they would with zip4j.
+//!
+//! The fake covers creating and opening archives, adding files, folders and
+//! data, listing and reading entries, extracting, and removing and renaming
+//! entries. Data is stored as is: compression and encryption settings are
+//! recorded on the entries but not applied, and passwords are never checked.
+//! Anything else (split archives, progress monitors, staging, locking, ...)
+//! fails with `ZipError::UnsupportedOperation`. Since archives only exist in
+//! memory, [`ZipFile::open_existing`](crate::ZipFile::open_existing) can't
+//! find them; open them with [`ZipFile::new`](crate::ZipFile::new).
+//!
+//! # Examples
+//!
+//! ```rust,ignore
+//! #[test]
+//! fn writes_report() {
+//!     zip2rs::mock::reset();
+//!     export_report("report.zip").unwrap();
+//!
+//!     let zip = zip2rs::ZipFile::new("report.zip").unwrap();
+//!     assert_eq!(zip.entry_names().unwrap(), ["summary.csv"]);
+//! }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_longlong};
+use std::path::{Path, PathBuf};
+use std::ptr::NonNull;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::ffi::constants::{
+    COMPRESSION_DEFLATE, ENCRYPTION_NONE, ERROR_BUFFER_TOO_SMALL, ERROR_ENTRY_NOT_FOUND,
+    ERROR_FILE_NOT_FOUND, ERROR_INVALID_HANDLE, ERROR_INVALID_PARAMETER, ERROR_IO_EXCEPTION,
+    ERROR_NULL_POINTER, ERROR_UNSUPPORTED_OPERATION, HEALTH_INTACT, OUTPUT_NONE, SUCCESS,
+};
+use crate::ffi::{graal_create_isolate_params_t, graal_isolate_t, graal_isolatethread_t};
+
+/// Forget every archive and handle, so each test starts from an empty state
+pub fn reset() {
+    *state() = State::default();
+}
+
+/// Everything the fake library holds
+#[derive(Default)]
+struct State {
+    last_handle: c_longlong,
+    /// Archive contents by path, kept after the archive is closed
+    archives: HashMap<PathBuf, Archive>,
+    /// Open archive handles and the path they refer to
+    open: HashMap<c_longlong, PathBuf>,
+    /// Entry handles, each a snapshot of the entry when it was looked up
+    entries: HashMap<c_longlong, Entry>,
+    parameters: HashMap<c_longlong, Parameters>,
+    writers: HashMap<c_longlong, Writer>,
+}
+
+impl State {
+    fn next_handle(&mut self) -> c_longlong {
+        self.last_handle += 1;
+        self.last_handle
+    }
+
+    fn archive(&mut self, handle: c_longlong) -> Option<&mut Archive> {
+        let path = self.open.get(&handle)?;
+        self.archives.get_mut(path)
+    }
+}
+
+fn state() -> MutexGuard<'static, State> {
+    static STATE: OnceLock<Mutex<State>> = OnceLock::new();
+    STATE.get_or_init(Mutex::default).lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[derive(Default)]
+struct Archive {
+    entries: Vec<Entry>,
+    comment: String,
+}
+
+impl Archive {
+    /// Add an entry, replacing any entry with the same name like zip4j does
+    fn put(&mut self, entry: Entry) {
+        match self.entries.iter_mut().find(|existing| existing.name == entry.name) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    fn find(&self, name: &str) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+}
+
+#[derive(Clone)]
+struct Entry {
+    name: String,
+    data: Arc<[u8]>,
+    is_directory: bool,
+    crc: u32,
+    /// Modification time in DOS format
+    last_modified: u32,
+    compression_method: c_int,
+    encryption_method: c_int,
+}
+
+impl Entry {
+    fn file(name: String, data: Vec<u8>, parameters: &Parameters) -> Self {
+        Self {
+            crc: crc32(&data),
+            data: data.into(),
+            ..Self::directory(name, parameters)
+        }
+    }
+
+    fn directory(name: String, parameters: &Parameters) -> Self {
+        let millis = parameters.last_modified.unwrap_or_else(|| {
+            SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_millis() as c_longlong)
+        });
+        Self {
+            is_directory: name.ends_with('/'),
+            name,
+            data: Arc::from([]),
+            crc: 0,
+            last_modified: dos_time(millis),
+            compression_method: parameters.compression_method,
+            encryption_method: parameters.encryption_method,
+        }
+    }
+
+    fn general_purpose_flags(&self) -> c_int {
+        // Encrypted, and names stored as UTF-8
+        (self.encryption_method != ENCRYPTION_NONE) as c_int | 0x800
+    }
+}
+
+/// Settings of a parameter set that affect how entries are named and described
+#[derive(Clone)]
+struct Parameters {
+    compression_method: c_int,
+    encryption_method: c_int,
+    file_name_in_zip: Option<String>,
+    root_folder_name: Option<String>,
+    default_folder_path: Option<PathBuf>,
+    include_root_folder: bool,
+    /// Modification time in milliseconds since the epoch
+    last_modified: Option<c_longlong>,
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Self {
+            compression_method: COMPRESSION_DEFLATE,
+            encryption_method: ENCRYPTION_NONE,
+            file_name_in_zip: None,
+            root_folder_name: None,
+            default_folder_path: None,
+            include_root_folder: true,
+            last_modified: None,
+        }
+    }
+}
+
+impl Parameters {
+    /// Name in the archive of `path`, which is below `base` if given
+    fn entry_name(&self, path: &Path, base: Option<&Path>) -> String {
+        let relative = base.or(self.default_folder_path.as_deref())
+            .and_then(|base| path.strip_prefix(base).ok())
+            .filter(|relative| !relative.as_os_str().is_empty())
+            .unwrap_or_else(|| path.file_name().map_or(path, Path::new));
+        let name = relative.to_string_lossy().replace('\\', "/");
+        match &self.root_folder_name {
+            Some(root) => format!("{}/{}", root.trim_end_matches('/'), name),
+            None => name,
+        }
+    }
+}
+
+/// Entry being written in chunks
+struct Writer {
+    archive: c_longlong,
+    entry_name: String,
+    parameters: Parameters,
+    data: Vec<u8>,
+}
+
+/// Read a C string argument
+unsafe fn read_str(ptr: *mut c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+}
+
+/// Read a non-empty C string argument
+unsafe fn read_required(ptr: *mut c_char) -> std::result::Result<String, c_int> {
+    match read_str(ptr) {
+        None => Err(ERROR_NULL_POINTER),
+        Some(s) if s.trim().is_empty() => Err(ERROR_INVALID_PARAMETER),
+        Some(s) => Ok(s),
+    }
+}
+
+/// Write a value through an out pointer
+unsafe fn write<T>(out: *mut T, value: T) -> c_int {
+    if out.is_null() {
+        return ERROR_NULL_POINTER;
+    }
+    *out = value;
+    SUCCESS
+}
+
+/// Copy a string and its null terminator into a buffer, like the native library does
+unsafe fn write_string(s: &str, buffer: *mut c_char, buffer_size: c_int, length: *mut c_int) -> c_int {
+    if buffer.is_null() || length.is_null() {
+        return ERROR_NULL_POINTER;
+    }
+    *length = s.len() as c_int;
+    if s.len() >= buffer_size.max(0) as usize {
+        return ERROR_BUFFER_TOO_SMALL;
+    }
+    std::ptr::copy_nonoverlapping(s.as_ptr() as *const c_char, buffer, s.len());
+    *buffer.add(s.len()) = 0;
+    SUCCESS
+}
+
+/// Copy raw bytes into a buffer, writing the required size if it is too small
+unsafe fn write_bytes(bytes: &[u8], buffer: *mut c_char, buffer_size: c_int, length: *mut c_int) -> c_int {
+    if buffer.is_null() || length.is_null() {
+        return ERROR_NULL_POINTER;
+    }
+    *length = bytes.len() as c_int;
+    if bytes.len() > buffer_size.max(0) as usize {
+        return ERROR_BUFFER_TOO_SMALL;
+    }
+    std::ptr::copy_nonoverlapping(bytes.as_ptr() as *const c_char, buffer, bytes.len());
+    SUCCESS
+}
+
+/// Read a buffer of consecutive null-terminated strings, keeping empty ones like the native library
+unsafe fn read_str_list(ptr: *mut c_char, length: c_int) -> Vec<String> {
+    if ptr.is_null() || length <= 0 {
+        return Vec::new();
+    }
+    let bytes = std::slice::from_raw_parts(ptr as *const u8, length as usize);
+    let terminated = match bytes.iter().rposition(|&byte| byte == 0) {
+        Some(end) => &bytes[..end],
+        None => return Vec::new(),
+    };
+    terminated.split(|&byte| byte == 0)
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect()
+}
+
+/// Read a buffer of (old name, new name) pairs, failing if a name is unpaired
+unsafe fn read_str_pairs(ptr: *mut c_char, length: c_int) -> std::result::Result<Vec<(String, String)>, c_int> {
+    if ptr.is_null() {
+        return Err(ERROR_NULL_POINTER);
+    }
+    let names = read_str_list(ptr, length);
+    let pairs = names.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return Err(ERROR_INVALID_PARAMETER);
+    }
+    Ok(pairs.map(|pair| (pair[0].clone(), pair[1].clone())).collect())
+}
+
+/// Turn the outcome of a file system operation into a return code
+fn io_result(result: std::io::Result<()>) -> c_int {
+    match result {
+        Ok(()) => SUCCESS,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => ERROR_FILE_NOT_FOUND,
+        Err(_) => ERROR_IO_EXCEPTION,
+    }
+}
+
+/// Add a file or, recursively, a folder from disk
+fn add_path(archive: &mut Archive, path: &Path, parameters: &Parameters, base: Option<&Path>) -> std::io::Result<()> {
+    if path.is_dir() {
+        let name = format!("{}/", parameters.entry_name(path, base));
+        archive.put(Entry::directory(name, parameters));
+        let mut children = std::fs::read_dir(path)?.collect::<std::io::Result<Vec<_>>>()?;
+        children.sort_by_key(|child| child.file_name());
+        for child in children {
+            add_path(archive, &child.path(), parameters, base)?;
+        }
+        Ok(())
+    } else {
+        let data = std::fs::read(path)?;
+        let name = match &parameters.file_name_in_zip {
+            Some(name) => name.clone(),
+            None => parameters.entry_name(path, base),
+        };
+        archive.put(Entry::file(name, data, parameters));
+        Ok(())
+    }
+}
+
+/// Add a folder the way zip4j's `addFolder` does, below its own name unless told otherwise
+fn add_folder(archive: &mut Archive, folder: &Path, parameters: &Parameters) -> std::io::Result<()> {
+    if !folder.is_dir() {
+        return Err(std::io::ErrorKind::NotFound.into());
+    }
+    let base = if parameters.include_root_folder { folder.parent() } else { Some(folder) };
+    let mut children = std::fs::read_dir(folder)?.collect::<std::io::Result<Vec<_>>>()?;
+    children.sort_by_key(|child| child.file_name());
+    if parameters.include_root_folder {
+        let name = format!("{}/", parameters.entry_name(folder, base));
+        archive.put(Entry::directory(name, parameters));
+    }
+    for child in children {
+        add_path(archive, &child.path(), parameters, base)?;
+    }
+    Ok(())
+}
+
+/// Write an entry below `dest`, under `name` instead of its own if given
+fn extract_to(entry: &Entry, dest: &Path, name: Option<&str>) -> std::io::Result<()> {
+    let path = crate::zip_entry::extraction_path(dest, name.unwrap_or(&entry.name))
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?;
+    if entry.is_directory {
+        return std::fs::create_dir_all(path);
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, &entry.data)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// Convert milliseconds since the epoch to a DOS date and time (in UTC)
+fn dos_time(millis: c_longlong) -> u32 {
+    let seconds = millis.div_euclid(1000);
+    let (days, time) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+
+    // Civil date from days since the epoch, after Howard Hinnant's `civil_from_days`
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as c_longlong;
+
+    if year < 1980 {
+        return (1 << 21) | (1 << 16);
+    }
+    ((year - 1980).min(127) << 25 | month << 21 | day << 16 | (time / 3600) << 11 | (time % 3600 / 60) << 5 | (time % 60 / 2)) as u32
+}
+
+// ========== Isolate ==========
+
+pub(crate) unsafe fn graal_create_isolate(
+    _params: *mut graal_create_isolate_params_t,
+    isolate: *mut *mut graal_isolate_t,
+    thread: *mut *mut graal_isolatethread_t,
+) -> c_int {
+    write(isolate, NonNull::dangling().as_ptr());
+    write(thread, NonNull::dangling().as_ptr())
+}
+
+pub(crate) unsafe fn graal_attach_thread(_isolate: *mut graal_isolate_t, thread: *mut *mut graal_isolatethread_t) -> c_int {
+    write(thread, NonNull::dangling().as_ptr())
+}
+
+pub(crate) unsafe fn graal_get_current_thread(_isolate: *mut graal_isolate_t) -> *mut graal_isolatethread_t {
+    NonNull::dangling().as_ptr()
+}
+
+pub(crate) unsafe fn graal_get_isolate(_thread: *mut graal_isolatethread_t) -> *mut graal_isolate_t {
+    NonNull::dangling().as_ptr()
+}
+
+pub(crate) unsafe fn graal_detach_thread(_thread: *mut graal_isolatethread_t) -> c_int {
+    SUCCESS
+}
+
+pub(crate) unsafe fn graal_tear_down_isolate(_thread: *mut graal_isolatethread_t) -> c_int {
+    SUCCESS
+}
+
+pub(crate) unsafe fn graal_detach_all_threads_and_tear_down_isolate(_thread: *mut graal_isolatethread_t) -> c_int {
+    SUCCESS
+}
+
+// ========== Lifecycle ==========
+
+pub(crate) unsafe fn zip4j_init(_thread: *mut graal_isolatethread_t) -> c_int {
+    SUCCESS
+}
+
+pub(crate) unsafe fn zip4j_cleanup(_thread: *mut graal_isolatethread_t) -> c_int {
+    // Handles die with the isolate, archives live on like files would
+    let mut state = state();
+    state.open.clear();
+    state.entries.clear();
+    state.parameters.clear();
+    state.writers.clear();
+    SUCCESS
+}
+
+pub(crate) unsafe fn zip4j_set_max_heap_size(_thread: *mut graal_isolatethread_t, _bytes: c_longlong) -> c_int {
+    SUCCESS
+}
+
+pub(crate) unsafe fn zip4j_get_version_info(
+    _thread: *mut graal_isolatethread_t,
+    abi_version: *mut c_int,
+    buffer: *mut c_char,
+    buffer_size: c_int,
+    length: *mut c_int,
+) -> c_int {
+    if write(abi_version, 1) != SUCCESS {
+        return ERROR_NULL_POINTER;
+    }
+    write_bytes(b"mock\0mock\0", buffer, buffer_size, length)
+}
+
+pub(crate) unsafe fn zip4j_get_diagnostics(_thread: *mut graal_isolatethread_t, values: *mut c_longlong, count: c_int) -> c_int {
+    if values.is_null() {
+        return ERROR_NULL_POINTER;
+    }
+    let state = state();
+    let diagnostics = [
+        0,
+        0,
+        0,
+        state.open.len() as c_longlong,
+        state.entries.len() as c_longlong,
+        state.writers.len() as c_longlong,
+        state.parameters.len() as c_longlong,
+    ];
+    for (i, value) in diagnostics.iter().take(count.max(0) as usize).enumerate() {
+        *values.add(i) = *value;
+    }
+    SUCCESS
+}
+
+pub(crate) unsafe fn zip4j_take_last_exception(
+    _thread: *mut graal_isolatethread_t,
+    _code: c_int,
+    buffer: *mut c_char,
+    buffer_size: c_int,
+    length: *mut c_int,
+) -> c_int {
+    write_string("", buffer, buffer_size, length)
+}
+
+pub(crate) unsafe fn zip4j_get_last_error(
+    _thread: *mut graal_isolatethread_t,
+    _handle: c_longlong,
+    buffer: *mut c_char,
+    buffer_size: c_int,
+    length: *mut c_int,
+) -> c_int {
+    write_string("", buffer, buffer_size, length)
+}
+
+pub(crate) unsafe fn zip4j_capture_output(_thread: *mut graal_isolatethread_t, _enabled: c_int) -> c_int {
+    SUCCESS
+}
+
+pub(crate) unsafe fn zip4j_next_output_line(
+    _thread: *mut graal_isolatethread_t,
+    stream: *mut c_int,
+    _buffer: *mut c_char,
+    _buffer_size: c_int,
+    length: *mut c_int,
+) -> c_int {
+    // Nothing is ever printed
+    write(length, 0);
+    write(stream, OUTPUT_NONE)
+}
+
+// ========== Parameters ==========
+
+pub(crate) unsafe fn zip4j_create_parameters(
+    _thread: *mut graal_isolatethread_t,
+    _compression_level: c_int,
+    compression_method: c_int,
+    encryption_method: c_int,
+    _aes_key_strength: c_int,
+    _password: *mut c_char,
+    handle: *mut c_longlong,
+) -> c_int {
+    let mut state = state();
+    let parameters_handle = state.next_handle();
+    state.parameters.insert(parameters_handle, Parameters {
+        compression_method,
+        encryption_method,
+        ..Parameters::default()
+    });
+    write(handle, parameters_handle)
+}
+
+pub(crate) unsafe fn zip4j_release_parameters(_thread: *mut graal_isolatethread_t, handle: c_longlong) -> c_int {
+    match state().parameters.remove(&handle) {
+        Some(_) => SUCCESS,
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+/// Change a parameter set
+fn update_parameters<F: FnOnce(&mut Parameters)>(handle: c_longlong, update: F) -> c_int {
+    match state().parameters.get_mut(&handle) {
+        Some(parameters) => {
+            update(parameters);
+            SUCCESS
+        }
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_parameters_set_include_root_folder(_thread: *mut graal_isolatethread_t, handle: c_longlong, include: c_int) -> c_int {
+    update_parameters(handle, |parameters| parameters.include_root_folder = include != 0)
+}
+
+pub(crate) unsafe fn zip4j_parameters_set_read_hidden(_thread: *mut graal_isolatethread_t, handle: c_longlong, _files: c_int, _folders: c_int) -> c_int {
+    update_parameters(handle, |_| {})
+}
+
+pub(crate) unsafe fn zip4j_parameters_set_symbolic_link(_thread: *mut graal_isolatethread_t, handle: c_longlong, _action: c_int) -> c_int {
+    update_parameters(handle, |_| {})
+}
+
+pub(crate) unsafe fn zip4j_parameters_set_root_folder_name(_thread: *mut graal_isolatethread_t, handle: c_longlong, name: *mut c_char) -> c_int {
+    let name = read_str(name);
+    update_parameters(handle, |parameters| parameters.root_folder_name = name)
+}
+
+pub(crate) unsafe fn zip4j_parameters_set_file_name_in_zip(_thread: *mut graal_isolatethread_t, handle: c_longlong, name: *mut c_char) -> c_int {
+    let name = read_str(name);
+    update_parameters(handle, |parameters| parameters.file_name_in_zip = name)
+}
+
+pub(crate) unsafe fn zip4j_parameters_set_default_folder_path(_thread: *mut graal_isolatethread_t, handle: c_longlong, path: *mut c_char) -> c_int {
+    let path = read_str(path).map(PathBuf::from);
+    update_parameters(handle, |parameters| parameters.default_folder_path = path)
+}
+
+pub(crate) unsafe fn zip4j_parameters_set_last_modified_time(_thread: *mut graal_isolatethread_t, handle: c_longlong, millis: c_longlong) -> c_int {
+    update_parameters(handle, |parameters| parameters.last_modified = Some(millis))
+}
+
+pub(crate) unsafe fn zip4j_parameters_set_entry_crc(_thread: *mut graal_isolatethread_t, handle: c_longlong, _crc: c_longlong) -> c_int {
+    update_parameters(handle, |_| {})
+}
+
+pub(crate) unsafe fn zip4j_parameters_set_file_comment(_thread: *mut graal_isolatethread_t, handle: c_longlong, _comment: *mut c_char) -> c_int {
+    update_parameters(handle, |_| {})
+}
+
+// ========== Archives ==========
+
+pub(crate) unsafe fn zip4j_create(_thread: *mut graal_isolatethread_t, path: *mut c_char, handle: *mut c_longlong) -> c_int {
+    let path = match read_required(path) {
+        Ok(path) => PathBuf::from(path),
+        Err(code) => return code,
+    };
+    let mut state = state();
+    state.archives.entry(path.clone()).or_default();
+    let zip_handle = state.next_handle();
+    state.open.insert(zip_handle, path);
+    write(handle, zip_handle)
+}
+
+pub(crate) unsafe fn zip4j_create_with_password(
+    thread: *mut graal_isolatethread_t,
+    path: *mut c_char,
+    _password: *mut c_char,
+    handle: *mut c_longlong,
+) -> c_int {
+    zip4j_create(thread, path, handle)
+}
+
+pub(crate) unsafe fn zip4j_set_password(_thread: *mut graal_isolatethread_t, handle: c_longlong, _password: *mut c_char) -> c_int {
+    match state().open.contains_key(&handle) {
+        true => SUCCESS,
+        false => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_close(_thread: *mut graal_isolatethread_t, handle: c_longlong) -> c_int {
+    match state().open.remove(&handle) {
+        Some(_) => SUCCESS,
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_set_name_normalization(_thread: *mut graal_isolatethread_t, handle: c_longlong, _form: c_int) -> c_int {
+    zip4j_set_password(_thread, handle, std::ptr::null_mut())
+}
+
+/// Names are compared as stored, so normalizing returns them unchanged
+pub(crate) unsafe fn zip4j_normalize_names(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    names: *mut c_char,
+    names_length: c_int,
+    buffer: *mut c_char,
+    buffer_size: c_int,
+    length: *mut c_int,
+) -> c_int {
+    if names.is_null() {
+        return ERROR_NULL_POINTER;
+    }
+    if !state().open.contains_key(&handle) {
+        return ERROR_INVALID_HANDLE;
+    }
+    let names = read_str_list(names, names_length).iter().flat_map(|name| name.bytes().chain([0])).collect::<Vec<u8>>();
+    write_bytes(&names, buffer, buffer_size, length)
+}
+
+pub(crate) unsafe fn zip4j_is_valid(_thread: *mut graal_isolatethread_t, handle: c_longlong, valid: *mut c_int) -> c_int {
+    match state().archive(handle) {
+        Some(_) => write(valid, 1),
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_is_encrypted(_thread: *mut graal_isolatethread_t, handle: c_longlong, encrypted: *mut c_int) -> c_int {
+    match state().archive(handle) {
+        Some(archive) => write(encrypted, archive.entries.iter().any(|entry| entry.encryption_method != ENCRYPTION_NONE) as c_int),
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_is_split_archive(_thread: *mut graal_isolatethread_t, handle: c_longlong, split: *mut c_int) -> c_int {
+    match state().archive(handle) {
+        Some(_) => write(split, 0),
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_get_archive_size(_thread: *mut graal_isolatethread_t, handle: c_longlong, size: *mut c_longlong) -> c_int {
+    match state().archive(handle) {
+        Some(archive) => write(size, archive.entries.iter().map(|entry| entry.data.len() as c_longlong).sum()),
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_get_file_path(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    buffer: *mut c_char,
+    buffer_size: c_int,
+    length: *mut c_int,
+) -> c_int {
+    match state().open.get(&handle) {
+        Some(path) => write_string(&path.to_string_lossy(), buffer, buffer_size, length),
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_get_comment(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    buffer: *mut c_char,
+    buffer_size: c_int,
+    length: *mut c_int,
+) -> c_int {
+    match state().archive(handle) {
+        Some(archive) => write_string(&archive.comment, buffer, buffer_size, length),
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_set_comment(_thread: *mut graal_isolatethread_t, handle: c_longlong, comment: *mut c_char) -> c_int {
+    let comment = match read_str(comment) {
+        Some(comment) => comment,
+        None => return ERROR_NULL_POINTER,
+    };
+    match state().archive(handle) {
+        Some(archive) => {
+            archive.comment = comment;
+            SUCCESS
+        }
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+// ========== Entries ==========
+
+pub(crate) unsafe fn zip4j_get_entry_count(_thread: *mut graal_isolatethread_t, handle: c_longlong, count: *mut c_longlong) -> c_int {
+    match state().archive(handle) {
+        Some(archive) => write(count, archive.entries.len() as c_longlong),
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+/// Hand out a handle for the entry `find` picks from the archive
+fn entry_handle<F: FnOnce(&Archive) -> Option<Entry>>(handle: c_longlong, find: F, missing: c_int) -> std::result::Result<c_longlong, c_int> {
+    let mut state = state();
+    let entry = find(state.archive(handle).ok_or(ERROR_INVALID_HANDLE)?).ok_or(missing)?;
+    let entry_handle = state.next_handle();
+    state.entries.insert(entry_handle, entry);
+    Ok(entry_handle)
+}
+
+pub(crate) unsafe fn zip4j_get_entry_by_index(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    index: c_longlong,
+    out: *mut c_longlong,
+) -> c_int {
+    if out.is_null() {
+        return ERROR_NULL_POINTER;
+    }
+    let found = entry_handle(handle, |archive| {
+        usize::try_from(index).ok().and_then(|index| archive.entries.get(index).cloned())
+    }, ERROR_INVALID_PARAMETER);
+    match found {
+        Ok(entry_handle) => write(out, entry_handle),
+        Err(code) => code,
+    }
+}
+
+pub(crate) unsafe fn zip4j_get_entry_by_name(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    name: *mut c_char,
+    out: *mut c_longlong,
+) -> c_int {
+    let name = match read_str(name) {
+        Some(name) if !out.is_null() => name,
+        _ => return ERROR_NULL_POINTER,
+    };
+    match entry_handle(handle, |archive| archive.find(&name).cloned(), ERROR_ENTRY_NOT_FOUND) {
+        Ok(entry_handle) => write(out, entry_handle),
+        Err(code) => code,
+    }
+}
+
+pub(crate) unsafe fn zip4j_contains_entry(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    name: *mut c_char,
+    contains: *mut c_int,
+) -> c_int {
+    let name = match read_str(name) {
+        Some(name) => name,
+        None => return ERROR_NULL_POINTER,
+    };
+    match state().archive(handle) {
+        Some(archive) => write(contains, archive.find(&name).is_some() as c_int),
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_list_entry_names(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    buffer: *mut c_char,
+    buffer_size: c_int,
+    length: *mut c_int,
+) -> c_int {
+    let names = match state().archive(handle) {
+        Some(archive) => archive.entries.iter().flat_map(|entry| entry.name.bytes().chain([0])).collect::<Vec<u8>>(),
+        None => return ERROR_INVALID_HANDLE,
+    };
+    write_bytes(&names, buffer, buffer_size, length)
+}
+
+pub(crate) unsafe fn zip4j_list_entries(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    buffer: *mut c_char,
+    buffer_size: c_int,
+    length: *mut c_int,
+) -> c_int {
+    let mut records = Vec::new();
+    match state().archive(handle) {
+        Some(archive) => {
+            for entry in &archive.entries {
+                records.extend_from_slice(&(entry.name.len() as u32).to_le_bytes());
+                records.extend_from_slice(entry.name.as_bytes());
+                records.extend_from_slice(&(entry.data.len() as u64).to_le_bytes());
+                records.extend_from_slice(&(entry.data.len() as u64).to_le_bytes());
+                records.extend_from_slice(&entry.crc.to_le_bytes());
+                records.extend_from_slice(&entry.compression_method.to_le_bytes());
+                records.extend_from_slice(&entry.encryption_method.to_le_bytes());
+                records.push(entry.is_directory as u8);
+                records.push((entry.encryption_method != ENCRYPTION_NONE) as u8);
+                records.extend_from_slice(&entry.last_modified.to_le_bytes());
+                records.extend_from_slice(&(entry.general_purpose_flags() as u16).to_le_bytes());
+            }
+        }
+        None => return ERROR_INVALID_HANDLE,
+    }
+    write_bytes(&records, buffer, buffer_size, length)
+}
+
+pub(crate) unsafe fn zip4j_release_entry(_thread: *mut graal_isolatethread_t, handle: c_longlong) -> c_int {
+    match state().entries.remove(&handle) {
+        Some(_) => SUCCESS,
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+/// Read a property of the entry behind `handle` into `out`
+unsafe fn entry_property<T, F: FnOnce(&Entry) -> T>(handle: c_longlong, out: *mut T, property: F) -> c_int {
+    match state().entries.get(&handle) {
+        Some(entry) => write(out, property(entry)),
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_entry_get_name(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    buffer: *mut c_char,
+    buffer_size: c_int,
+    length: *mut c_int,
+) -> c_int {
+    match state().entries.get(&handle) {
+        Some(entry) => write_string(&entry.name, buffer, buffer_size, length),
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_entry_get_size(_thread: *mut graal_isolatethread_t, handle: c_longlong, size: *mut c_longlong) -> c_int {
+    entry_property(handle, size, |entry| entry.data.len() as c_longlong)
+}
+
+pub(crate) unsafe fn zip4j_entry_get_compressed_size(_thread: *mut graal_isolatethread_t, handle: c_longlong, size: *mut c_longlong) -> c_int {
+    entry_property(handle, size, |entry| entry.data.len() as c_longlong)
+}
+
+pub(crate) unsafe fn zip4j_entry_is_directory(_thread: *mut graal_isolatethread_t, handle: c_longlong, directory: *mut c_int) -> c_int {
+    entry_property(handle, directory, |entry| entry.is_directory as c_int)
+}
+
+pub(crate) unsafe fn zip4j_entry_is_encrypted(_thread: *mut graal_isolatethread_t, handle: c_longlong, encrypted: *mut c_int) -> c_int {
+    entry_property(handle, encrypted, |entry| (entry.encryption_method != ENCRYPTION_NONE) as c_int)
+}
+
+pub(crate) unsafe fn zip4j_entry_get_crc(_thread: *mut graal_isolatethread_t, handle: c_longlong, crc: *mut c_longlong) -> c_int {
+    entry_property(handle, crc, |entry| entry.crc as c_longlong)
+}
+
+pub(crate) unsafe fn zip4j_entry_get_version_needed(_thread: *mut graal_isolatethread_t, handle: c_longlong, version: *mut c_int) -> c_int {
+    entry_property(handle, version, |_| 20)
+}
+
+pub(crate) unsafe fn zip4j_entry_get_version_made_by(_thread: *mut graal_isolatethread_t, handle: c_longlong, version: *mut c_int) -> c_int {
+    entry_property(handle, version, |_| 51)
+}
+
+pub(crate) unsafe fn zip4j_entry_get_offsets(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    header_offset: *mut c_longlong,
+    data_offset: *mut c_longlong,
+) -> c_int {
+    // Entries aren't laid out in a file, so there are no meaningful offsets
+    write(header_offset, 0);
+    entry_property(handle, data_offset, |_| 0)
+}
+
+pub(crate) unsafe fn zip4j_entry_get_general_purpose_flags(_thread: *mut graal_isolatethread_t, handle: c_longlong, flags: *mut c_int) -> c_int {
+    entry_property(handle, flags, Entry::general_purpose_flags)
+}
+
+pub(crate) unsafe fn zip4j_entry_get_last_modified_time(_thread: *mut graal_isolatethread_t, handle: c_longlong, time: *mut c_longlong) -> c_int {
+    entry_property(handle, time, |entry| entry.last_modified as c_longlong)
+}
+
+pub(crate) unsafe fn zip4j_entry_get_compression_method(_thread: *mut graal_isolatethread_t, handle: c_longlong, method: *mut c_int) -> c_int {
+    entry_property(handle, method, |entry| entry.compression_method)
+}
+
+pub(crate) unsafe fn zip4j_entry_get_encryption_method(_thread: *mut graal_isolatethread_t, handle: c_longlong, method: *mut c_int) -> c_int {
+    entry_property(handle, method, |entry| entry.encryption_method)
+}
+
+pub(crate) unsafe fn zip4j_set_entry_unix_mode(_thread: *mut graal_isolatethread_t, handle: c_longlong, name: *mut c_char, _mode: c_int) -> c_int {
+    // Permissions aren't recorded, but the entry has to exist
+    let name = match read_required(name) {
+        Ok(name) => name,
+        Err(code) => return code,
+    };
+    match state().archive(handle) {
+        Some(archive) if archive.find(&name).is_some() => SUCCESS,
+        Some(_) => ERROR_ENTRY_NOT_FOUND,
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+// ========== Adding ==========
+
+/// Change the archive behind `handle` with the parameter set `parameters_handle`, or the defaults if 0
+fn modify_archive<F>(handle: c_longlong, parameters_handle: c_longlong, modify: F) -> c_int
+where
+    F: FnOnce(&mut Archive, &Parameters) -> std::io::Result<()>,
+{
+    let mut state = state();
+    let parameters = match parameters_handle {
+        0 => Parameters::default(),
+        handle => match state.parameters.get(&handle) {
+            Some(parameters) => parameters.clone(),
+            None => return ERROR_INVALID_HANDLE,
+        },
+    };
+    match state.archive(handle) {
+        Some(archive) => io_result(modify(archive, &parameters)),
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_add_file(_thread: *mut graal_isolatethread_t, handle: c_longlong, path: *mut c_char) -> c_int {
+    let path = match read_required(path) {
+        Ok(path) => PathBuf::from(path),
+        Err(code) => return code,
+    };
+    modify_archive(handle, 0, |archive, parameters| add_path(archive, &path, parameters, None))
+}
+
+pub(crate) unsafe fn zip4j_add_file_with_parameters(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    path: *mut c_char,
+    parameters_handle: c_longlong,
+) -> c_int {
+    let path = match read_required(path) {
+        Ok(path) => PathBuf::from(path),
+        Err(code) => return code,
+    };
+    modify_archive(handle, parameters_handle, |archive, parameters| add_path(archive, &path, parameters, None))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) unsafe fn zip4j_add_file_with_params(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    path: *mut c_char,
+    _compression_level: c_int,
+    compression_method: c_int,
+    encryption_method: c_int,
+    _aes_key_strength: c_int,
+    _password: *mut c_char,
+) -> c_int {
+    let path = match read_required(path) {
+        Ok(path) => PathBuf::from(path),
+        Err(code) => return code,
+    };
+    let parameters = Parameters { compression_method, encryption_method, ..Parameters::default() };
+    match state().archive(handle) {
+        Some(archive) => io_result(add_path(archive, &path, &parameters, None)),
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_add_files(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    paths: *mut c_char,
+    paths_length: c_int,
+    base_path: *mut c_char,
+    parameters_handle: c_longlong,
+) -> c_int {
+    let paths = read_str_list(paths, paths_length);
+    let base = read_str(base_path).filter(|base| !base.trim().is_empty()).map(PathBuf::from);
+    modify_archive(handle, parameters_handle, |archive, parameters| {
+        paths.iter().try_for_each(|path| add_path(archive, Path::new(path), parameters, base.as_deref()))
+    })
+}
+
+pub(crate) unsafe fn zip4j_add_directory(_thread: *mut graal_isolatethread_t, handle: c_longlong, path: *mut c_char) -> c_int {
+    let path = match read_required(path) {
+        Ok(path) => PathBuf::from(path),
+        Err(code) => return code,
+    };
+    modify_archive(handle, 0, |archive, parameters| add_folder(archive, &path, parameters))
+}
+
+pub(crate) unsafe fn zip4j_add_directory_with_parameters(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    path: *mut c_char,
+    parameters_handle: c_longlong,
+) -> c_int {
+    let path = match read_required(path) {
+        Ok(path) => PathBuf::from(path),
+        Err(code) => return code,
+    };
+    modify_archive(handle, parameters_handle, |archive, parameters| add_folder(archive, &path, parameters))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) unsafe fn zip4j_add_directory_with_params(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    path: *mut c_char,
+    _compression_level: c_int,
+    compression_method: c_int,
+    encryption_method: c_int,
+    _aes_key_strength: c_int,
+    _password: *mut c_char,
+) -> c_int {
+    let path = match read_required(path) {
+        Ok(path) => PathBuf::from(path),
+        Err(code) => return code,
+    };
+    let parameters = Parameters { compression_method, encryption_method, ..Parameters::default() };
+    match state().archive(handle) {
+        Some(archive) => io_result(add_folder(archive, &path, &parameters)),
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) unsafe fn zip4j_add_data(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    name: *mut c_char,
+    data: *mut c_char,
+    data_length: c_int,
+    _compression_level: c_int,
+    compression_method: c_int,
+    encryption_method: c_int,
+    _aes_key_strength: c_int,
+    _password: *mut c_char,
+) -> c_int {
+    let name = match read_required(name) {
+        Ok(name) => name,
+        Err(code) => return code,
+    };
+    if data.is_null() {
+        return ERROR_NULL_POINTER;
+    }
+    let data = std::slice::from_raw_parts(data as *const u8, data_length.max(0) as usize).to_vec();
+    let parameters = Parameters { compression_method, encryption_method, ..Parameters::default() };
+    match state().archive(handle) {
+        Some(archive) => {
+            archive.put(Entry::file(name, data, &parameters));
+            SUCCESS
+        }
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_begin_data(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    name: *mut c_char,
+    parameters_handle: c_longlong,
+    writer: *mut c_longlong,
+) -> c_int {
+    let entry_name = match read_required(name) {
+        Ok(name) if !writer.is_null() => name,
+        Ok(_) => return ERROR_NULL_POINTER,
+        Err(code) => return code,
+    };
+    let mut state = state();
+    let parameters = match state.parameters.get(&parameters_handle) {
+        Some(parameters) => parameters.clone(),
+        None => return ERROR_INVALID_HANDLE,
+    };
+    if state.archive(handle).is_none() {
+        return ERROR_INVALID_HANDLE;
+    }
+    let writer_handle = state.next_handle();
+    state.writers.insert(writer_handle, Writer { archive: handle, entry_name, parameters, data: Vec::new() });
+    write(writer, writer_handle)
+}
+
+pub(crate) unsafe fn zip4j_write_data(_thread: *mut graal_isolatethread_t, handle: c_longlong, data: *mut c_char, data_length: c_int) -> c_int {
+    if data.is_null() {
+        return ERROR_NULL_POINTER;
+    }
+    match state().writers.get_mut(&handle) {
+        Some(writer) => {
+            writer.data.extend_from_slice(std::slice::from_raw_parts(data as *const u8, data_length.max(0) as usize));
+            SUCCESS
+        }
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_finish_data(_thread: *mut graal_isolatethread_t, handle: c_longlong) -> c_int {
+    let mut state = state();
+    let writer = match state.writers.remove(&handle) {
+        Some(writer) => writer,
+        None => return ERROR_INVALID_HANDLE,
+    };
+    match state.archive(writer.archive) {
+        Some(archive) => {
+            archive.put(Entry::file(writer.entry_name, writer.data, &writer.parameters));
+            SUCCESS
+        }
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_abort_data(_thread: *mut graal_isolatethread_t, handle: c_longlong) -> c_int {
+    match state().writers.remove(&handle) {
+        Some(_) => SUCCESS,
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+// ========== Reading and extracting ==========
+
+/// Look up an open archive and one of its entry handles
+fn archive_entry(handle: c_longlong, entry_handle: c_longlong) -> std::result::Result<Entry, c_int> {
+    let mut state = state();
+    if state.archive(handle).is_none() {
+        return Err(ERROR_INVALID_HANDLE);
+    }
+    state.entries.get(&entry_handle).cloned().ok_or(ERROR_INVALID_HANDLE)
+}
+
+pub(crate) unsafe fn zip4j_extract_all(_thread: *mut graal_isolatethread_t, handle: c_longlong, dest: *mut c_char) -> c_int {
+    let dest = match read_required(dest) {
+        Ok(dest) => PathBuf::from(dest),
+        Err(code) => return code,
+    };
+    let entries = match state().archive(handle) {
+        Some(archive) => archive.entries.clone(),
+        None => return ERROR_INVALID_HANDLE,
+    };
+    io_result(entries.iter().try_for_each(|entry| extract_to(entry, &dest, None)))
+}
+
+pub(crate) unsafe fn zip4j_extract_all_parallel(thread: *mut graal_isolatethread_t, handle: c_longlong, dest: *mut c_char, _threads: c_int) -> c_int {
+    zip4j_extract_all(thread, handle, dest)
+}
+
+pub(crate) unsafe fn zip4j_extract_file(_thread: *mut graal_isolatethread_t, handle: c_longlong, name: *mut c_char, dest: *mut c_char) -> c_int {
+    let (name, dest) = match (read_required(name), read_required(dest)) {
+        (Ok(name), Ok(dest)) => (name, PathBuf::from(dest)),
+        (Err(code), _) | (_, Err(code)) => return code,
+    };
+    let entry = match state().archive(handle) {
+        Some(archive) => archive.find(&name).cloned(),
+        None => return ERROR_INVALID_HANDLE,
+    };
+    match entry {
+        Some(entry) => io_result(extract_to(&entry, &dest, None)),
+        None => ERROR_ENTRY_NOT_FOUND,
+    }
+}
+
+pub(crate) unsafe fn zip4j_extract_entry(_thread: *mut graal_isolatethread_t, handle: c_longlong, entry_handle: c_longlong, dest: *mut c_char) -> c_int {
+    let dest = match read_required(dest) {
+        Ok(dest) => PathBuf::from(dest),
+        Err(code) => return code,
+    };
+    match archive_entry(handle, entry_handle) {
+        Ok(entry) => io_result(extract_to(&entry, &dest, None)),
+        Err(code) => code,
+    }
+}
+
+pub(crate) unsafe fn zip4j_extract_entry_as(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    entry_handle: c_longlong,
+    dest: *mut c_char,
+    name: *mut c_char,
+) -> c_int {
+    let (dest, name) = match (read_required(dest), read_required(name)) {
+        (Ok(dest), Ok(name)) => (PathBuf::from(dest), name),
+        (Err(code), _) | (_, Err(code)) => return code,
+    };
+    match archive_entry(handle, entry_handle) {
+        Ok(entry) => io_result(extract_to(&entry, &dest, Some(&name))),
+        Err(code) => code,
+    }
+}
+
+pub(crate) unsafe fn zip4j_extract_data(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    entry_handle: c_longlong,
+    buffer: *mut c_char,
+    buffer_size: c_int,
+    length: *mut c_int,
+) -> c_int {
+    match archive_entry(handle, entry_handle) {
+        Ok(entry) => write_bytes(&entry.data, buffer, buffer_size, length),
+        Err(code) => code,
+    }
+}
+
+pub(crate) unsafe fn zip4j_extract_range(
+    _thread: *mut graal_isolatethread_t,
+    handle: c_longlong,
+    entry_handle: c_longlong,
+    offset: c_longlong,
+    buffer: *mut c_char,
+    buffer_size: c_int,
+    length: *mut c_int,
+) -> c_int {
+    if offset < 0 || buffer_size < 0 {
+        return ERROR_INVALID_PARAMETER;
+    }
+    match archive_entry(handle, entry_handle) {
+        Ok(entry) => {
+            let start = (offset as usize).min(entry.data.len());
+            let end = start.saturating_add(buffer_size as usize).min(entry.data.len());
+            write_bytes(&entry.data[start..end], buffer, buffer_size, length)
+        }
+        Err(code) => code,
+    }
+}
+
+pub(crate) unsafe fn zip4j_test_entry(_thread: *mut graal_isolatethread_t, handle: c_longlong, entry_handle: c_longlong) -> c_int {
+    match archive_entry(handle, entry_handle) {
+        Ok(_) => SUCCESS,
+        Err(code) => code,
+    }
+}
+
+pub(crate) unsafe fn zip4j_scan_entry(_thread: *mut graal_isolatethread_t, handle: c_longlong, entry_handle: c_longlong, health: *mut c_int) -> c_int {
+    match archive_entry(handle, entry_handle) {
+        Ok(_) => write(health, HEALTH_INTACT),
+        Err(code) => code,
+    }
+}
+
+pub(crate) unsafe fn zip4j_get_usable_space(_thread: *mut graal_isolatethread_t, handle: c_longlong, _dest: *mut c_char, space: *mut c_longlong) -> c_int {
+    // Disk space is never the reason an extraction fails here
+    match state().archive(handle) {
+        Some(_) => write(space, c_longlong::MAX),
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+// ========== Removing ==========
+
+/// Remove the entries named in `names` from the archive, failing if one is missing
+fn remove_names(handle: c_longlong, names: &[String]) -> c_int {
+    match state().archive(handle) {
+        Some(archive) => {
+            if names.iter().any(|name| archive.find(name).is_none()) {
+                return ERROR_ENTRY_NOT_FOUND;
+            }
+            archive.entries.retain(|entry| !names.contains(&entry.name));
+            SUCCESS
+        }
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_remove_file(_thread: *mut graal_isolatethread_t, handle: c_longlong, name: *mut c_char) -> c_int {
+    match read_required(name) {
+        Ok(name) => remove_names(handle, &[name]),
+        Err(code) => code,
+    }
+}
+
+pub(crate) unsafe fn zip4j_remove_files(_thread: *mut graal_isolatethread_t, handle: c_longlong, names: *mut c_char, names_length: c_int) -> c_int {
+    remove_names(handle, &read_str_list(names, names_length))
+}
+
+pub(crate) unsafe fn zip4j_remove_entry(_thread: *mut graal_isolatethread_t, handle: c_longlong, entry_handle: c_longlong) -> c_int {
+    match archive_entry(handle, entry_handle) {
+        Ok(entry) => remove_names(handle, &[entry.name]),
+        Err(code) => code,
+    }
+}
+
+// ========== Renaming ==========
+
+/// What `changes` does to the entry `name`: `None` if it is left alone, `Some(None)` if it is removed.
+/// Names match exactly; with `move_children`, entries below a renamed folder move along with it
+fn changed_name(name: &str, changes: &[(String, Option<String>)], move_children: bool) -> Option<Option<String>> {
+    if let Some((_, new_name)) = changes.iter().find(|(old_name, _)| old_name == name) {
+        return Some(new_name.clone());
+    }
+    if !move_children {
+        return None;
+    }
+    changes.iter()
+        .filter_map(|(old_name, new_name)| Some((old_name, new_name.as_ref()?)))
+        .filter(|(old_name, _)| old_name.ends_with('/') && name.starts_with(old_name.as_str()))
+        .max_by_key(|(old_name, _)| old_name.len())
+        .map(|(old_name, new_name)| Some(format!("{}{}", new_name, &name[old_name.len()..])))
+}
+
+/// Rename and remove entries of the archive in one go
+fn rename_names(handle: c_longlong, changes: &[(String, Option<String>)], move_children: bool) -> c_int {
+    match state().archive(handle) {
+        Some(archive) => {
+            archive.entries = std::mem::take(&mut archive.entries).into_iter()
+                .filter_map(|mut entry| match changed_name(&entry.name, changes, move_children) {
+                    None => Some(entry),
+                    Some(None) => None,
+                    Some(Some(name)) => {
+                        entry.name = name;
+                        Some(entry)
+                    }
+                })
+                .collect();
+            SUCCESS
+        }
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn zip4j_rename_entry(_thread: *mut graal_isolatethread_t, handle: c_longlong, entry_handle: c_longlong, new_name: *mut c_char) -> c_int {
+    let new_name = match read_required(new_name) {
+        Ok(new_name) => new_name,
+        Err(code) => return code,
+    };
+    match archive_entry(handle, entry_handle) {
+        Ok(entry) => rename_names(handle, &[(entry.name, Some(new_name))], true),
+        Err(code) => code,
+    }
+}
+
+pub(crate) unsafe fn zip4j_rename_entries(_thread: *mut graal_isolatethread_t, handle: c_longlong, names: *mut c_char, names_length: c_int) -> c_int {
+    let pairs = match read_str_pairs(names, names_length) {
+        Ok(pairs) => pairs,
+        Err(code) => return code,
+    };
+    let mut old_names = HashSet::new();
+    if pairs.iter().any(|(old_name, new_name)| old_name.is_empty() || new_name.trim().is_empty() || !old_names.insert(old_name)) {
+        return ERROR_INVALID_PARAMETER;
+    }
+    let changes: Vec<_> = pairs.into_iter().map(|(old_name, new_name)| (old_name, Some(new_name))).collect();
+    rename_names(handle, &changes, true)
+}
+
+pub(crate) unsafe fn zip4j_rewrite_entries(_thread: *mut graal_isolatethread_t, handle: c_longlong, names: *mut c_char, names_length: c_int) -> c_int {
+    // An empty new name removes the entry
+    let changes: Vec<_> = match read_str_pairs(names, names_length) {
+        Ok(pairs) => pairs.into_iter()
+            .map(|(old_name, new_name)| (old_name, Some(new_name).filter(|new_name| !new_name.is_empty())))
+            .collect(),
+        Err(code) => return code,
+    };
+
+    // Each entry is changed at most once, and no two entries end up with the same name
+    let existing: HashSet<String> = match state().archive(handle) {
+        Some(archive) => archive.entries.iter().map(|entry| entry.name.clone()).collect(),
+        None => return ERROR_INVALID_HANDLE,
+    };
+    let mut old_names = HashSet::new();
+    let mut targets = HashSet::new();
+    for (old_name, new_name) in &changes {
+        if old_name.is_empty() || !old_names.insert(old_name.as_str()) {
+            return ERROR_INVALID_PARAMETER;
+        }
+        if let Some(new_name) = new_name {
+            if !targets.insert(new_name.as_str()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+        }
+    }
+    if targets.iter().any(|target| existing.contains(*target) && !old_names.contains(target)) {
+        return ERROR_INVALID_PARAMETER;
+    }
+    rename_names(handle, &changes, false)
+}
+
+// ========== Not simulated ==========
+//
+// Split archives, progress monitors, staging, locking, passwords and
+// streaming reads fail like an operation the library doesn't support.
+
+pub(crate) unsafe fn zip4j_create_container(
+    _thread: *mut graal_isolatethread_t,
+    _path: *mut c_char,
+    _entry_name: *mut c_char,
+    _data: *mut c_char,
+    _data_length: c_int,
+    _handle: *mut c_longlong,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_encode_password(
+    _thread: *mut graal_isolatethread_t,
+    _password: *mut c_char,
+    _charset: *mut c_char,
+    _buffer: *mut c_char,
+    _buffer_size: c_int,
+    _length: *mut c_int,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) unsafe fn zip4j_sync_files(
+    _thread: *mut graal_isolatethread_t,
+    _handle: c_longlong,
+    _remove_names: *mut c_char,
+    _remove_names_length: c_int,
+    _paths: *mut c_char,
+    _paths_length: c_int,
+    _base_path: *mut c_char,
+    _parameters_handle: c_longlong,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_extract_entry_with_digest(
+    _thread: *mut graal_isolatethread_t,
+    _handle: c_longlong,
+    _entry_handle: c_longlong,
+    _dest: *mut c_char,
+    _digest: *mut c_char,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_verify_extracted_entry(
+    _thread: *mut graal_isolatethread_t,
+    _handle: c_longlong,
+    _entry_handle: c_longlong,
+    _dest: *mut c_char,
+    _matches: *mut c_int,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_compare_entry_with_file(
+    _thread: *mut graal_isolatethread_t,
+    _handle: c_longlong,
+    _entry_handle: c_longlong,
+    _path: *mut c_char,
+    _detection: c_int,
+    _status: *mut c_int,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_create_input_stream(_thread: *mut graal_isolatethread_t, _handle: c_longlong, _entry_handle: c_longlong, _stream: *mut c_longlong) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_stream_read(
+    _thread: *mut graal_isolatethread_t,
+    _stream: c_longlong,
+    _buffer: *mut c_char,
+    _buffer_size: c_int,
+    _bytes_read: *mut c_int,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_close_input_stream(_thread: *mut graal_isolatethread_t, _stream: c_longlong) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_get_progress_monitor(_thread: *mut graal_isolatethread_t, _handle: c_longlong, _monitor: *mut c_longlong) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_get_progress_percentage(_thread: *mut graal_isolatethread_t, _monitor: c_longlong, _percentage: *mut c_int) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_get_progress_work(
+    _thread: *mut graal_isolatethread_t,
+    _monitor: c_longlong,
+    _work_completed: *mut c_longlong,
+    _total_work: *mut c_longlong,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_is_operation_finished(_thread: *mut graal_isolatethread_t, _monitor: c_longlong, _finished: *mut c_int) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_get_operation_result(_thread: *mut graal_isolatethread_t, _monitor: c_longlong) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_cancel_operation(_thread: *mut graal_isolatethread_t, _monitor: c_longlong) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_release_progress_monitor(_thread: *mut graal_isolatethread_t, _monitor: c_longlong) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_align_archive(_thread: *mut graal_isolatethread_t, _handle: c_longlong, _alignment: c_int) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_compact(_thread: *mut graal_isolatethread_t, _handle: c_longlong, _reclaimed: *mut c_longlong) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_save_as(_thread: *mut graal_isolatethread_t, _handle: c_longlong, _path: *mut c_char, _compact: c_int) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_begin_staging(_thread: *mut graal_isolatethread_t, _handle: c_longlong) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_end_staging(_thread: *mut graal_isolatethread_t, _handle: c_longlong, _commit: c_int) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_lock(_thread: *mut graal_isolatethread_t, _handle: c_longlong, _wait: c_int, _acquired: *mut c_int) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_unlock(_thread: *mut graal_isolatethread_t, _handle: c_longlong) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_create_split_zip(_thread: *mut graal_isolatethread_t, _path: *mut c_char, _split_size: c_longlong, _handle: *mut c_longlong) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_create_split_zip_from_files(
+    _thread: *mut graal_isolatethread_t,
+    _path: *mut c_char,
+    _paths: *mut c_char,
+    _paths_length: c_int,
+    _parameters_handle: c_longlong,
+    _split_size: c_longlong,
+    _handle: *mut c_longlong,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_create_split_zip_from_folder(
+    _thread: *mut graal_isolatethread_t,
+    _path: *mut c_char,
+    _folder: *mut c_char,
+    _parameters_handle: c_longlong,
+    _split_size: c_longlong,
+    _handle: *mut c_longlong,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_split_archive(
+    _thread: *mut graal_isolatethread_t,
+    _handle: c_longlong,
+    _output_path: *mut c_char,
+    _split_size: c_longlong,
+    _split_handle: *mut c_longlong,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_split_archive_async(
+    _thread: *mut graal_isolatethread_t,
+    _handle: c_longlong,
+    _output_path: *mut c_char,
+    _split_size: c_longlong,
+    _split_handle: *mut c_longlong,
+    _monitor: *mut c_longlong,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_extract_entry_async(
+    _thread: *mut graal_isolatethread_t,
+    _handle: c_longlong,
+    _entry_handle: c_longlong,
+    _dest: *mut c_char,
+    _monitor: *mut c_longlong,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_merge_split_files(_thread: *mut graal_isolatethread_t, _handle: c_longlong, _output_path: *mut c_char) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_merge_split_files_async(
+    _thread: *mut graal_isolatethread_t,
+    _handle: c_longlong,
+    _output_path: *mut c_char,
+    _monitor: *mut c_longlong,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_verify_password(_thread: *mut graal_isolatethread_t, _handle: c_longlong, _password: *mut c_char, _valid: *mut c_int) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_find_password(
+    _thread: *mut graal_isolatethread_t,
+    _handle: c_longlong,
+    _candidates: *mut c_char,
+    _candidates_length: c_int,
+    _index: *mut c_int,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
+
+pub(crate) unsafe fn zip4j_change_password(
+    _thread: *mut graal_isolatethread_t,
+    _handle: c_longlong,
+    _old_password: *mut c_char,
+    _new_password: *mut c_char,
+    _encryption_method: c_int,
+) -> c_int {
+    ERROR_UNSUPPORTED_OPERATION
+}
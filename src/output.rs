@@ -0,0 +1,126 @@
+//! Capturing what the native library prints
+//!
+//! zip4j and the Java runtime occasionally print warnings to `System.out` and
+//! `System.err`, which end up on the process's stdout and stderr. Once
+//! [`capture_native_output`] is enabled those lines are queued instead, and
+//! can be taken with [`drain_native_output`]. With the `log` or `tracing`
+//! feature, captured lines are also forwarded automatically after each archive
+//! operation, under the `zip2rs::native` target: stdout lines at info level,
+//! stderr lines at warn level.
+//!
+//! Messages printed by the GraalVM runtime itself rather than by Java code
+//! can't be captured.
+
+use std::os::raw::{c_char, c_int};
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::error::Result;
+use crate::ffi::{self, constants, helpers};
+
+/// Whether lines are being captured, so operations only look for output to forward when they are
+static CAPTURING: AtomicBool = AtomicBool::new(false);
+
+/// Stream a captured line was printed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NativeStream {
+    /// `System.out`
+    Stdout,
+    /// `System.err`
+    Stderr,
+}
+
+/// A line printed by the native library while output was captured
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeOutputLine {
+    /// Stream the line was printed to
+    pub stream: NativeStream,
+    /// The line, without its line terminator
+    pub text: String,
+}
+
+/// Start or stop capturing what the native library prints
+///
+/// While capturing, at most the 1024 most recent lines are kept until they
+/// are drained or forwarded.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// zip2rs::capture_native_output(true)?;
+/// let zip = zip2rs::ZipFile::new("archive.zip")?;
+/// zip.extract_all("out")?;
+/// for line in zip2rs::drain_native_output()? {
+///     eprintln!("[zip4j {:?}] {}", line.stream, line.text);
+/// }
+/// # Ok::<(), zip2rs::ZipError>(())
+/// ```
+pub fn capture_native_output(enabled: bool) -> Result<()> {
+    let result = unsafe {
+        ffi::zip4j_capture_output(*ffi::get_thread()?, enabled as c_int)
+    };
+
+    if helpers::is_error(result) {
+        return Err(crate::error::ZipError::from_native(result));
+    }
+
+    CAPTURING.store(enabled, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Take every line captured so far, oldest first
+pub fn drain_native_output() -> Result<Vec<NativeOutputLine>> {
+    let mut lines = Vec::new();
+    let mut buffer = vec![0u8; 1024];
+
+    loop {
+        let mut stream: c_int = constants::OUTPUT_NONE;
+        let mut line_length: c_int = 0;
+
+        let result = unsafe {
+            ffi::zip4j_next_output_line(
+                *ffi::get_thread()?,
+                &mut stream,
+                buffer.as_mut_ptr() as *mut c_char,
+                buffer.len() as c_int,
+                &mut line_length
+            )
+        };
+
+        if result == constants::ERROR_BUFFER_TOO_SMALL {
+            buffer.resize(line_length as usize + 1, 0);
+            continue;
+        }
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
+        let stream = match stream {
+            constants::OUTPUT_STDOUT => NativeStream::Stdout,
+            constants::OUTPUT_STDERR => NativeStream::Stderr,
+            _ => return Ok(lines),
+        };
+        let text = helpers::read_string_from_buffer_u8(&buffer, line_length)?;
+        lines.push(NativeOutputLine { stream, text });
+    }
+}
+
+/// Forward captured lines to `log` or `tracing`, if either is enabled and output is being captured
+pub(crate) fn forward() {
+    if !cfg!(any(feature = "log", feature = "tracing")) || !CAPTURING.load(Ordering::Relaxed) {
+        return;
+    }
+
+    for line in drain_native_output().unwrap_or_default() {
+        #[cfg(feature = "tracing")]
+        match line.stream {
+            NativeStream::Stdout => tracing::info!(target: "zip2rs::native", "{}", line.text),
+            NativeStream::Stderr => tracing::warn!(target: "zip2rs::native", "{}", line.text),
+        }
+        #[cfg(all(feature = "log", not(feature = "tracing")))]
+        match line.stream {
+            NativeStream::Stdout => log::info!(target: "zip2rs::native", "{}", line.text),
+            NativeStream::Stderr => log::warn!(target: "zip2rs::native", "{}", line.text),
+        }
+        #[cfg(not(any(feature = "log", feature = "tracing")))]
+        let _ = line;
+    }
+}
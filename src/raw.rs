@@ -0,0 +1,921 @@
+//! Thin wrappers around the zip4j-abi C API
+//!
+//! For native capabilities the rest of the crate doesn't surface (yet), the
+//! functions here call the C functions directly. There is one per function of
+//! the ABI, named after it without the `zip4j_` prefix, and each one:
+//!
+//! - passes the isolate thread of the calling OS thread, initializing the
+//!   library on first use like the rest of the crate
+//! - takes strings as `&CStr`, and optional ones (passwords, base paths, ...)
+//!   as `Option<&CStr>`, which is passed as a null pointer when `None`
+//! - takes data and lists of consecutive null-terminated strings as `&[u8]`
+//! - returns scalar out parameters, with flags as `bool`
+//! - fills output buffers given as `&mut [u8]` and writes the length of the
+//!   result to a `&mut c_int`, also when failing with
+//!   `ZipError::BufferTooSmall`, so the buffer can be grown to fit
+//! - turns error codes into a [`ZipError`] with the Java exception details
+//!
+//! Handles are plain `c_longlong`s, as in the C API. The native library
+//! checks every handle it is given, so a wrong one fails with
+//! `ZipError::InvalidHandle` instead of misbehaving. Archives and entries
+//! can be moved between this module and [`ZipFile`](crate::ZipFile) or
+//! [`ZipEntry`](crate::ZipEntry) with their `into_raw_handle` and
+//! `from_raw_handle` functions. Values such as compression methods are the
+//! constants in [`ffi::constants`].
+//!
+//! Initialization and cleanup are left to [`Zip4jRuntime`](crate::Zip4jRuntime),
+//! and the `graal_*` isolate functions to [`Isolate`](crate::Isolate).
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use zip2rs::raw;
+//!
+//! let zip = raw::create(c"archive.zip")?;
+//! raw::set_entry_unix_mode(zip, c"bin/run.sh", 0o755)?;
+//! let mut buffer = vec![0u8; 256];
+//! let mut length = 0;
+//! raw::get_comment(zip, &mut buffer, &mut length)?;
+//! raw::close(zip)?;
+//! # Ok::<(), zip2rs::ZipError>(())
+//! ```
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_longlong};
+use crate::error::{Result, ZipError};
+use crate::ffi::{self, helpers, GraalIsolateThread};
+
+/// Call into the native library with the isolate thread of the calling OS thread,
+/// turning the returned error code into a `ZipError`
+fn call<F: FnOnce(*mut GraalIsolateThread) -> c_int>(f: F) -> Result<()> {
+    let result = f(*ffi::get_thread()?);
+    if helpers::is_error(result) {
+        return Err(ZipError::from_native(result));
+    }
+    Ok(())
+}
+
+/// Pointer to a string argument
+fn ptr(s: &CStr) -> *mut c_char {
+    s.as_ptr() as *mut c_char
+}
+
+/// Pointer to an optional string argument, null when `None`
+fn opt_ptr(s: Option<&CStr>) -> *mut c_char {
+    s.map_or(std::ptr::null_mut(), ptr)
+}
+
+/// Pointer to a data or string list argument
+fn data_ptr(data: &[u8]) -> *mut c_char {
+    data.as_ptr() as *mut c_char
+}
+
+/// Length of a data or string list argument, which the C API limits to `c_int`
+fn data_len(data: &[u8]) -> Result<c_int> {
+    c_int::try_from(data.len())
+        .map_err(|_| ZipError::InvalidParameter(format!("{} bytes don't fit in a single native call", data.len())))
+}
+
+/// Pointer to an output buffer
+fn buffer_ptr(buffer: &mut [u8]) -> *mut c_char {
+    buffer.as_mut_ptr() as *mut c_char
+}
+
+/// Size of an output buffer; anything beyond `c_int::MAX` bytes is left unused
+fn capacity(buffer: &[u8]) -> c_int {
+    buffer.len().min(c_int::MAX as usize) as c_int
+}
+
+// Parameters
+
+/// Create a reusable parameter set for add operations, returning its handle
+pub fn create_parameters(
+    compression_level: c_int,
+    compression_method: c_int,
+    encryption_method: c_int,
+    aes_key_strength: c_int,
+    password: Option<&CStr>,
+) -> Result<c_longlong> {
+    let mut params: c_longlong = 0;
+    call(|thread| unsafe {
+        ffi::zip4j_create_parameters(
+            thread,
+            compression_level,
+            compression_method,
+            encryption_method,
+            aes_key_strength,
+            opt_ptr(password),
+            &mut params
+        )
+    })?;
+    Ok(params)
+}
+
+/// Release a parameter set
+pub fn release_parameters(params: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_release_parameters(thread, params) })
+}
+
+/// Set whether adding a folder stores the folder itself, not just its contents
+pub fn parameters_set_include_root_folder(params: c_longlong, include: bool) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_parameters_set_include_root_folder(thread, params, include as c_int) })
+}
+
+/// Set whether hidden files and folders are added
+pub fn parameters_set_read_hidden(params: c_longlong, files: bool, folders: bool) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_parameters_set_read_hidden(thread, params, files as c_int, folders as c_int) })
+}
+
+/// Set whether symbolic links are stored as links instead of the files they point to
+pub fn parameters_set_symbolic_link(params: c_longlong, store_link: bool) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_parameters_set_symbolic_link(thread, params, store_link as c_int) })
+}
+
+/// Set the folder added entries are placed in
+pub fn parameters_set_root_folder_name(params: c_longlong, name: Option<&CStr>) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_parameters_set_root_folder_name(thread, params, opt_ptr(name)) })
+}
+
+/// Set the name an added file is stored under
+pub fn parameters_set_file_name_in_zip(params: c_longlong, name: Option<&CStr>) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_parameters_set_file_name_in_zip(thread, params, opt_ptr(name)) })
+}
+
+/// Set the folder entry names of added files are relative to
+pub fn parameters_set_default_folder_path(params: c_longlong, path: Option<&CStr>) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_parameters_set_default_folder_path(thread, params, opt_ptr(path)) })
+}
+
+/// Set the modification time of added entries, in milliseconds since the epoch
+pub fn parameters_set_last_modified_time(params: c_longlong, millis: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_parameters_set_last_modified_time(thread, params, millis) })
+}
+
+/// Set the CRC-32 of an added entry up front
+pub fn parameters_set_entry_crc(params: c_longlong, crc: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_parameters_set_entry_crc(thread, params, crc) })
+}
+
+/// Set the comment of added entries
+pub fn parameters_set_file_comment(params: c_longlong, comment: Option<&CStr>) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_parameters_set_file_comment(thread, params, opt_ptr(comment)) })
+}
+
+// Archives
+
+/// Create a new zip file or open an existing one, returning its handle
+pub fn create(path: &CStr) -> Result<c_longlong> {
+    let mut zip: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_create(thread, ptr(path), &mut zip) })?;
+    Ok(zip)
+}
+
+/// Create a new zip file or open an existing one with a password, returning its handle
+pub fn create_with_password(path: &CStr, password: Option<&CStr>) -> Result<c_longlong> {
+    let mut zip: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_create_with_password(thread, ptr(path), opt_ptr(password), &mut zip) })?;
+    Ok(zip)
+}
+
+/// Create a new archive whose first entry is stored uncompressed and unencrypted, like EPUB's mimetype
+pub fn create_container(path: &CStr, entry_name: &CStr, data: &[u8]) -> Result<c_longlong> {
+    let length = data_len(data)?;
+    let mut zip: c_longlong = 0;
+    call(|thread| unsafe {
+        ffi::zip4j_create_container(thread, ptr(path), ptr(entry_name), data_ptr(data), length, &mut zip)
+    })?;
+    Ok(zip)
+}
+
+/// Set or, with `None`, clear the password of an archive
+pub fn set_password(zip: c_longlong, password: Option<&CStr>) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_set_password(thread, zip, opt_ptr(password)) })
+}
+
+/// Encode a UTF-8 password in another charset, for archives with legacy-encoded passwords
+///
+/// The encoded bytes are not null-terminated.
+pub fn encode_password(password: &CStr, charset: &CStr, buffer: &mut [u8], length: &mut c_int) -> Result<()> {
+    call(|thread| unsafe {
+        ffi::zip4j_encode_password(thread, ptr(password), ptr(charset), buffer_ptr(buffer), capacity(buffer), length)
+    })
+}
+
+/// Close an archive
+pub fn close(zip: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_close(thread, zip) })
+}
+
+/// Set the Unicode normalization form used to match and extract entry names
+pub fn set_name_normalization(zip: c_longlong, form: c_int) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_set_name_normalization(thread, zip, form) })
+}
+
+/// Normalize consecutive null-terminated names the way lookups in an archive do,
+/// writing them back the same way
+pub fn normalize_names(zip: c_longlong, names: &[u8], buffer: &mut [u8], length: &mut c_int) -> Result<()> {
+    let names_length = data_len(names)?;
+    call(|thread| unsafe {
+        ffi::zip4j_normalize_names(thread, zip, data_ptr(names), names_length, buffer_ptr(buffer), capacity(buffer), length)
+    })
+}
+
+/// Check whether an archive is a valid zip file
+pub fn is_valid(zip: c_longlong) -> Result<bool> {
+    let mut valid: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_is_valid(thread, zip, &mut valid) })?;
+    Ok(valid != 0)
+}
+
+/// Check whether an archive has encrypted entries
+pub fn is_encrypted(zip: c_longlong) -> Result<bool> {
+    let mut encrypted: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_is_encrypted(thread, zip, &mut encrypted) })?;
+    Ok(encrypted != 0)
+}
+
+/// Check whether an archive is split into parts
+pub fn is_split_archive(zip: c_longlong) -> Result<bool> {
+    let mut split: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_is_split_archive(thread, zip, &mut split) })?;
+    Ok(split != 0)
+}
+
+/// Get the size of an archive in bytes
+pub fn get_archive_size(zip: c_longlong) -> Result<c_longlong> {
+    let mut size: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_get_archive_size(thread, zip, &mut size) })?;
+    Ok(size)
+}
+
+/// Get the absolute path of an archive, null-terminated
+pub fn get_file_path(zip: c_longlong, buffer: &mut [u8], length: &mut c_int) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_get_file_path(thread, zip, buffer_ptr(buffer), capacity(buffer), length) })
+}
+
+/// Get the comment of an archive, null-terminated
+pub fn get_comment(zip: c_longlong, buffer: &mut [u8], length: &mut c_int) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_get_comment(thread, zip, buffer_ptr(buffer), capacity(buffer), length) })
+}
+
+/// Set the comment of an archive
+pub fn set_comment(zip: c_longlong, comment: &CStr) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_set_comment(thread, zip, ptr(comment)) })
+}
+
+// Entries
+
+/// Get the number of entries in an archive
+pub fn get_entry_count(zip: c_longlong) -> Result<c_longlong> {
+    let mut count: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_get_entry_count(thread, zip, &mut count) })?;
+    Ok(count)
+}
+
+/// Get a handle for the entry at `index`
+pub fn get_entry_by_index(zip: c_longlong, index: c_longlong) -> Result<c_longlong> {
+    let mut entry: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_get_entry_by_index(thread, zip, index, &mut entry) })?;
+    Ok(entry)
+}
+
+/// Get a handle for the entry called `name`
+pub fn get_entry_by_name(zip: c_longlong, name: &CStr) -> Result<c_longlong> {
+    let mut entry: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_get_entry_by_name(thread, zip, ptr(name), &mut entry) })?;
+    Ok(entry)
+}
+
+/// Check whether an archive has an entry called `name`
+pub fn contains_entry(zip: c_longlong, name: &CStr) -> Result<bool> {
+    let mut found: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_contains_entry(thread, zip, ptr(name), &mut found) })?;
+    Ok(found != 0)
+}
+
+/// List the metadata of every entry as packed little-endian records, the way
+/// [`ZipFile::entry_infos`](crate::ZipFile::entry_infos) reads them
+pub fn list_entries(zip: c_longlong, buffer: &mut [u8], length: &mut c_int) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_list_entries(thread, zip, buffer_ptr(buffer), capacity(buffer), length) })
+}
+
+/// List the names of every entry as consecutive null-terminated strings
+pub fn list_entry_names(zip: c_longlong, buffer: &mut [u8], length: &mut c_int) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_list_entry_names(thread, zip, buffer_ptr(buffer), capacity(buffer), length) })
+}
+
+/// Release an entry handle
+pub fn release_entry(entry: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_release_entry(thread, entry) })
+}
+
+/// Get the name of an entry, null-terminated
+pub fn entry_get_name(entry: c_longlong, buffer: &mut [u8], length: &mut c_int) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_entry_get_name(thread, entry, buffer_ptr(buffer), capacity(buffer), length) })
+}
+
+/// Get the uncompressed size of an entry
+pub fn entry_get_size(entry: c_longlong) -> Result<c_longlong> {
+    let mut size: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_entry_get_size(thread, entry, &mut size) })?;
+    Ok(size)
+}
+
+/// Get the compressed size of an entry
+pub fn entry_get_compressed_size(entry: c_longlong) -> Result<c_longlong> {
+    let mut size: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_entry_get_compressed_size(thread, entry, &mut size) })?;
+    Ok(size)
+}
+
+/// Check whether an entry is a directory
+pub fn entry_is_directory(entry: c_longlong) -> Result<bool> {
+    let mut directory: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_entry_is_directory(thread, entry, &mut directory) })?;
+    Ok(directory != 0)
+}
+
+/// Check whether an entry is encrypted
+pub fn entry_is_encrypted(entry: c_longlong) -> Result<bool> {
+    let mut encrypted: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_entry_is_encrypted(thread, entry, &mut encrypted) })?;
+    Ok(encrypted != 0)
+}
+
+/// Get the CRC-32 of an entry
+pub fn entry_get_crc(entry: c_longlong) -> Result<c_longlong> {
+    let mut crc: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_entry_get_crc(thread, entry, &mut crc) })?;
+    Ok(crc)
+}
+
+/// Get the version needed to extract an entry
+pub fn entry_get_version_needed(entry: c_longlong) -> Result<c_int> {
+    let mut version: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_entry_get_version_needed(thread, entry, &mut version) })?;
+    Ok(version)
+}
+
+/// Get the version an entry was made by
+pub fn entry_get_version_made_by(entry: c_longlong) -> Result<c_int> {
+    let mut version: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_entry_get_version_made_by(thread, entry, &mut version) })?;
+    Ok(version)
+}
+
+/// Get the offsets of an entry's local header and of its data in the archive
+pub fn entry_get_offsets(entry: c_longlong) -> Result<(c_longlong, c_longlong)> {
+    let mut header_offset: c_longlong = 0;
+    let mut data_offset: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_entry_get_offsets(thread, entry, &mut header_offset, &mut data_offset) })?;
+    Ok((header_offset, data_offset))
+}
+
+/// Get the general purpose bit flags of an entry
+pub fn entry_get_general_purpose_flags(entry: c_longlong) -> Result<c_int> {
+    let mut flags: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_entry_get_general_purpose_flags(thread, entry, &mut flags) })?;
+    Ok(flags)
+}
+
+/// Get the modification time of an entry, in DOS format
+pub fn entry_get_last_modified_time(entry: c_longlong) -> Result<c_longlong> {
+    let mut time: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_entry_get_last_modified_time(thread, entry, &mut time) })?;
+    Ok(time)
+}
+
+/// Get the compression method of an entry
+pub fn entry_get_compression_method(entry: c_longlong) -> Result<c_int> {
+    let mut method: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_entry_get_compression_method(thread, entry, &mut method) })?;
+    Ok(method)
+}
+
+/// Get the encryption method of an entry
+pub fn entry_get_encryption_method(entry: c_longlong) -> Result<c_int> {
+    let mut method: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_entry_get_encryption_method(thread, entry, &mut method) })?;
+    Ok(method)
+}
+
+// Adding
+
+/// Add a file with the default parameters
+pub fn add_file(zip: c_longlong, path: &CStr) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_add_file(thread, zip, ptr(path)) })
+}
+
+/// Add a file with the given compression and encryption
+pub fn add_file_with_params(
+    zip: c_longlong,
+    path: &CStr,
+    compression_level: c_int,
+    compression_method: c_int,
+    encryption_method: c_int,
+    aes_key_strength: c_int,
+    password: Option<&CStr>,
+) -> Result<()> {
+    call(|thread| unsafe {
+        ffi::zip4j_add_file_with_params(
+            thread,
+            zip,
+            ptr(path),
+            compression_level,
+            compression_method,
+            encryption_method,
+            aes_key_strength,
+            opt_ptr(password)
+        )
+    })
+}
+
+/// Add a file with a parameter set
+pub fn add_file_with_parameters(zip: c_longlong, path: &CStr, params: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_add_file_with_parameters(thread, zip, ptr(path), params) })
+}
+
+/// Add files given as consecutive null-terminated paths in one operation, named relative to `base_path` if given
+pub fn add_files(zip: c_longlong, paths: &[u8], base_path: Option<&CStr>, params: c_longlong) -> Result<()> {
+    let paths_length = data_len(paths)?;
+    call(|thread| unsafe {
+        ffi::zip4j_add_files(thread, zip, data_ptr(paths), paths_length, opt_ptr(base_path), params)
+    })
+}
+
+/// Add files and remove entries with a single rewrite, with paths and names as consecutive
+/// null-terminated strings and entry names relative to `base_path`
+///
+/// Files are added first and replace entries of the same name, so only entries that are not
+/// being replaced need to be among the names to remove.
+pub fn sync_files(zip: c_longlong, remove_names: &[u8], paths: &[u8], base_path: &CStr, params: c_longlong) -> Result<()> {
+    let remove_names_length = data_len(remove_names)?;
+    let paths_length = data_len(paths)?;
+    call(|thread| unsafe {
+        ffi::zip4j_sync_files(
+            thread,
+            zip,
+            data_ptr(remove_names),
+            remove_names_length,
+            data_ptr(paths),
+            paths_length,
+            ptr(base_path),
+            params
+        )
+    })
+}
+
+/// Add a folder and its contents with the default parameters
+pub fn add_directory(zip: c_longlong, path: &CStr) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_add_directory(thread, zip, ptr(path)) })
+}
+
+/// Add a folder and its contents with the given compression and encryption
+pub fn add_directory_with_params(
+    zip: c_longlong,
+    path: &CStr,
+    compression_level: c_int,
+    compression_method: c_int,
+    encryption_method: c_int,
+    aes_key_strength: c_int,
+    password: Option<&CStr>,
+) -> Result<()> {
+    call(|thread| unsafe {
+        ffi::zip4j_add_directory_with_params(
+            thread,
+            zip,
+            ptr(path),
+            compression_level,
+            compression_method,
+            encryption_method,
+            aes_key_strength,
+            opt_ptr(password)
+        )
+    })
+}
+
+/// Add a folder and its contents with a parameter set
+pub fn add_directory_with_parameters(zip: c_longlong, path: &CStr, params: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_add_directory_with_parameters(thread, zip, ptr(path), params) })
+}
+
+/// Add an entry with `data` as its contents, replacing any entry with the same name
+#[allow(clippy::too_many_arguments)]
+pub fn add_data(
+    zip: c_longlong,
+    name: &CStr,
+    data: &[u8],
+    compression_level: c_int,
+    compression_method: c_int,
+    encryption_method: c_int,
+    aes_key_strength: c_int,
+    password: Option<&CStr>,
+) -> Result<()> {
+    let length = data_len(data)?;
+    call(|thread| unsafe {
+        ffi::zip4j_add_data(
+            thread,
+            zip,
+            ptr(name),
+            data_ptr(data),
+            length,
+            compression_level,
+            compression_method,
+            encryption_method,
+            aes_key_strength,
+            opt_ptr(password)
+        )
+    })
+}
+
+/// Start writing an entry in chunks, returning the writer's handle
+pub fn begin_data(zip: c_longlong, name: &CStr, params: c_longlong) -> Result<c_longlong> {
+    let mut writer: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_begin_data(thread, zip, ptr(name), params, &mut writer) })?;
+    Ok(writer)
+}
+
+/// Write the next chunk of an entry started with [`begin_data`]
+pub fn write_data(writer: c_longlong, data: &[u8]) -> Result<()> {
+    let length = data_len(data)?;
+    call(|thread| unsafe { ffi::zip4j_write_data(thread, writer, data_ptr(data), length) })
+}
+
+/// Finish an entry started with [`begin_data`], adding it to the archive
+pub fn finish_data(writer: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_finish_data(thread, writer) })
+}
+
+/// Abandon an entry started with [`begin_data`], leaving the archive unchanged
+pub fn abort_data(writer: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_abort_data(thread, writer) })
+}
+
+/// Set the Unix permissions an entry is extracted with
+pub fn set_entry_unix_mode(zip: c_longlong, name: &CStr, mode: c_int) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_set_entry_unix_mode(thread, zip, ptr(name), mode) })
+}
+
+// Extracting
+
+/// Extract every entry below `dest_path`
+pub fn extract_all(zip: c_longlong, dest_path: &CStr) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_extract_all(thread, zip, ptr(dest_path)) })
+}
+
+/// Extract every entry below `dest_path` on `threads` worker threads (at least 1)
+pub fn extract_all_parallel(zip: c_longlong, dest_path: &CStr, threads: c_int) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_extract_all_parallel(thread, zip, ptr(dest_path), threads) })
+}
+
+/// Get the bytes available on the file system that holds `path`
+pub fn get_usable_space(zip: c_longlong, path: &CStr) -> Result<c_longlong> {
+    let mut space: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_get_usable_space(thread, zip, ptr(path), &mut space) })?;
+    Ok(space)
+}
+
+/// Extract an entry below `dest_path`, returning the SHA-256 of its contents
+pub fn extract_entry_with_digest(zip: c_longlong, entry: c_longlong, dest_path: &CStr) -> Result<[u8; 32]> {
+    let mut digest = [0u8; 32];
+    call(|thread| unsafe {
+        ffi::zip4j_extract_entry_with_digest(thread, zip, entry, ptr(dest_path), buffer_ptr(&mut digest))
+    })?;
+    Ok(digest)
+}
+
+/// Check whether the file an entry was extracted to below `dest_path` still matches it
+pub fn verify_extracted_entry(zip: c_longlong, entry: c_longlong, dest_path: &CStr) -> Result<bool> {
+    let mut matches: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_verify_extracted_entry(thread, zip, entry, ptr(dest_path), &mut matches) })?;
+    Ok(matches != 0)
+}
+
+/// Compare an entry with a file on disk, returning one of the `DIFF_*` constants
+pub fn compare_entry_with_file(zip: c_longlong, entry: c_longlong, path: &CStr, detection: c_int) -> Result<c_int> {
+    let mut status: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_compare_entry_with_file(thread, zip, entry, ptr(path), detection, &mut status) })?;
+    Ok(status)
+}
+
+/// Extract the entry called `name` below `dest_path`
+pub fn extract_file(zip: c_longlong, name: &CStr, dest_path: &CStr) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_extract_file(thread, zip, ptr(name), ptr(dest_path)) })
+}
+
+/// Extract an entry below `dest_path`
+pub fn extract_entry(zip: c_longlong, entry: c_longlong, dest_path: &CStr) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_extract_entry(thread, zip, entry, ptr(dest_path)) })
+}
+
+/// Start extracting a file entry below `dest_path` in the background, returning the handle
+/// of a progress monitor
+pub fn extract_entry_async(zip: c_longlong, entry: c_longlong, dest_path: &CStr) -> Result<c_longlong> {
+    let mut monitor: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_extract_entry_async(thread, zip, entry, ptr(dest_path), &mut monitor) })?;
+    Ok(monitor)
+}
+
+/// Extract an entry below `dest_path` under another name
+pub fn extract_entry_as(zip: c_longlong, entry: c_longlong, dest_path: &CStr, name: &CStr) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_extract_entry_as(thread, zip, entry, ptr(dest_path), ptr(name)) })
+}
+
+/// Extract the contents of an entry into memory
+pub fn extract_data(zip: c_longlong, entry: c_longlong, buffer: &mut [u8], length: &mut c_int) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_extract_data(thread, zip, entry, buffer_ptr(buffer), capacity(buffer), length) })
+}
+
+/// Extract up to `buffer.len()` bytes of an entry's contents, starting at `offset`
+pub fn extract_range(zip: c_longlong, entry: c_longlong, offset: c_longlong, buffer: &mut [u8], length: &mut c_int) -> Result<()> {
+    call(|thread| unsafe {
+        ffi::zip4j_extract_range(thread, zip, entry, offset, buffer_ptr(buffer), capacity(buffer), length)
+    })
+}
+
+/// Open a stream over the contents of an entry, returning its handle
+pub fn create_input_stream(zip: c_longlong, entry: c_longlong) -> Result<c_longlong> {
+    let mut stream: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_create_input_stream(thread, zip, entry, &mut stream) })?;
+    Ok(stream)
+}
+
+/// Read the next bytes from a stream, returning how many were read, or -1 at the end
+pub fn stream_read(stream: c_longlong, buffer: &mut [u8]) -> Result<c_int> {
+    let mut read: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_stream_read(thread, stream, buffer_ptr(buffer), capacity(buffer), &mut read) })?;
+    Ok(read)
+}
+
+/// Close a stream opened with [`create_input_stream`]
+pub fn close_input_stream(stream: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_close_input_stream(thread, stream) })
+}
+
+/// Decompress an entry and check its CRC
+pub fn test_entry(zip: c_longlong, entry: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_test_entry(thread, zip, entry) })
+}
+
+/// Classify the condition of an entry, returning one of the `HEALTH_*` constants
+pub fn scan_entry(zip: c_longlong, entry: c_longlong) -> Result<c_int> {
+    let mut health: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_scan_entry(thread, zip, entry, &mut health) })?;
+    Ok(health)
+}
+
+// Modifying
+
+/// Remove the entry called `name`
+pub fn remove_file(zip: c_longlong, name: &CStr) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_remove_file(thread, zip, ptr(name)) })
+}
+
+/// Remove entries given as consecutive null-terminated names in one operation
+pub fn remove_files(zip: c_longlong, names: &[u8]) -> Result<()> {
+    let length = data_len(names)?;
+    call(|thread| unsafe { ffi::zip4j_remove_files(thread, zip, data_ptr(names), length) })
+}
+
+/// Remove an entry
+pub fn remove_entry(zip: c_longlong, entry: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_remove_entry(thread, zip, entry) })
+}
+
+/// Rename an entry
+pub fn rename_entry(zip: c_longlong, entry: c_longlong, name: &CStr) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_rename_entry(thread, zip, entry, ptr(name)) })
+}
+
+/// Rename entries given as consecutive null-terminated (old name, new name) pairs in one operation
+pub fn rename_entries(zip: c_longlong, names: &[u8]) -> Result<()> {
+    let length = data_len(names)?;
+    call(|thread| unsafe { ffi::zip4j_rename_entries(thread, zip, data_ptr(names), length) })
+}
+
+/// Rename and remove entries with a single rewrite, given as consecutive null-terminated
+/// (old name, new name) pairs; an empty new name removes the entry
+pub fn rewrite_entries(zip: c_longlong, names: &[u8]) -> Result<()> {
+    let length = data_len(names)?;
+    call(|thread| unsafe { ffi::zip4j_rewrite_entries(thread, zip, data_ptr(names), length) })
+}
+
+/// Pad stored entries so their data starts at a multiple of `alignment` bytes
+pub fn align_archive(zip: c_longlong, alignment: c_int) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_align_archive(thread, zip, alignment) })
+}
+
+/// Rewrite an archive without unused space, returning the number of bytes reclaimed
+pub fn compact(zip: c_longlong) -> Result<c_longlong> {
+    let mut reclaimed: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_compact(thread, zip, &mut reclaimed) })?;
+    Ok(reclaimed)
+}
+
+/// Apply the following modifications to a copy of the archive
+pub fn begin_staging(zip: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_begin_staging(thread, zip) })
+}
+
+/// Replace the archive with the staged copy if `commit`, or discard the copy
+pub fn end_staging(zip: c_longlong, commit: bool) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_end_staging(thread, zip, commit as c_int) })
+}
+
+/// Take an exclusive advisory lock on an archive, returning whether it was acquired
+///
+/// Without `wait`, returns `false` instead of blocking while someone else holds the lock.
+pub fn lock(zip: c_longlong, wait: bool) -> Result<bool> {
+    let mut acquired: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_lock(thread, zip, wait as c_int, &mut acquired) })?;
+    Ok(acquired != 0)
+}
+
+/// Release the lock taken with [`lock`]
+pub fn unlock(zip: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_unlock(thread, zip) })
+}
+
+/// Write a copy of an archive to `output_path`, without unused space if `compact`
+pub fn save_as(zip: c_longlong, output_path: &CStr, compact: bool) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_save_as(thread, zip, ptr(output_path), compact as c_int) })
+}
+
+// Passwords
+
+/// Check whether `password` opens an archive
+pub fn verify_password(zip: c_longlong, password: &CStr) -> Result<bool> {
+    let mut valid: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_verify_password(thread, zip, ptr(password), &mut valid) })?;
+    Ok(valid != 0)
+}
+
+/// Find the first of the consecutive null-terminated `candidates` that opens an archive,
+/// returning its index, or -1 if none do
+pub fn find_password(zip: c_longlong, candidates: &[u8]) -> Result<c_int> {
+    let length = data_len(candidates)?;
+    let mut index: c_int = -1;
+    call(|thread| unsafe { ffi::zip4j_find_password(thread, zip, data_ptr(candidates), length, &mut index) })?;
+    Ok(index)
+}
+
+/// Re-encrypt every encrypted entry with a new password, and with `encryption_method`
+/// unless it is -1
+pub fn change_password(zip: c_longlong, old_password: &CStr, new_password: &CStr, encryption_method: c_int) -> Result<()> {
+    call(|thread| unsafe {
+        ffi::zip4j_change_password(thread, zip, ptr(old_password), ptr(new_password), encryption_method)
+    })
+}
+
+// Split archives
+
+/// Create a split archive with parts of `split_size` bytes, returning its handle
+pub fn create_split_zip(path: &CStr, split_size: c_longlong) -> Result<c_longlong> {
+    let mut zip: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_create_split_zip(thread, ptr(path), split_size, &mut zip) })?;
+    Ok(zip)
+}
+
+/// Create a split archive of the files given as consecutive null-terminated paths, returning its handle
+pub fn create_split_zip_from_files(path: &CStr, paths: &[u8], params: c_longlong, split_size: c_longlong) -> Result<c_longlong> {
+    let paths_length = data_len(paths)?;
+    let mut zip: c_longlong = 0;
+    call(|thread| unsafe {
+        ffi::zip4j_create_split_zip_from_files(thread, ptr(path), data_ptr(paths), paths_length, params, split_size, &mut zip)
+    })?;
+    Ok(zip)
+}
+
+/// Create a split archive of a folder, returning its handle
+pub fn create_split_zip_from_folder(path: &CStr, folder: &CStr, params: c_longlong, split_size: c_longlong) -> Result<c_longlong> {
+    let mut zip: c_longlong = 0;
+    call(|thread| unsafe {
+        ffi::zip4j_create_split_zip_from_folder(thread, ptr(path), ptr(folder), params, split_size, &mut zip)
+    })?;
+    Ok(zip)
+}
+
+/// Write a copy of an archive split into parts, returning the handle of the split archive
+pub fn split_archive(zip: c_longlong, output_path: &CStr, split_size: c_longlong) -> Result<c_longlong> {
+    let mut split: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_split_archive(thread, zip, ptr(output_path), split_size, &mut split) })?;
+    Ok(split)
+}
+
+/// Start splitting an archive in the background, returning the handles of the split
+/// archive and of a progress monitor
+pub fn split_archive_async(zip: c_longlong, output_path: &CStr, split_size: c_longlong) -> Result<(c_longlong, c_longlong)> {
+    let mut split: c_longlong = 0;
+    let mut monitor: c_longlong = 0;
+    call(|thread| unsafe {
+        ffi::zip4j_split_archive_async(thread, zip, ptr(output_path), split_size, &mut split, &mut monitor)
+    })?;
+    Ok((split, monitor))
+}
+
+/// Merge the parts of a split archive into one file
+pub fn merge_split_files(zip: c_longlong, output_path: &CStr) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_merge_split_files(thread, zip, ptr(output_path)) })
+}
+
+/// Start merging a split archive in the background, returning the handle of a progress monitor
+pub fn merge_split_files_async(zip: c_longlong, output_path: &CStr) -> Result<c_longlong> {
+    let mut monitor: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_merge_split_files_async(thread, zip, ptr(output_path), &mut monitor) })?;
+    Ok(monitor)
+}
+
+// Progress
+
+/// Get a progress monitor for operations on an archive, returning its handle
+pub fn get_progress_monitor(zip: c_longlong) -> Result<c_longlong> {
+    let mut monitor: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_get_progress_monitor(thread, zip, &mut monitor) })?;
+    Ok(monitor)
+}
+
+/// Get how far the monitored operation is, in percent
+pub fn get_progress_percentage(monitor: c_longlong) -> Result<c_int> {
+    let mut percentage: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_get_progress_percentage(thread, monitor, &mut percentage) })?;
+    Ok(percentage)
+}
+
+/// Check whether the monitored operation has finished
+pub fn is_operation_finished(monitor: c_longlong) -> Result<bool> {
+    let mut finished: c_int = 0;
+    call(|thread| unsafe { ffi::zip4j_is_operation_finished(thread, monitor, &mut finished) })?;
+    Ok(finished != 0)
+}
+
+/// Ask the monitored operation to stop
+pub fn cancel_operation(monitor: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_cancel_operation(thread, monitor) })
+}
+
+/// Get the work done and the total work of the monitored operation
+pub fn get_progress_work(monitor: c_longlong) -> Result<(c_longlong, c_longlong)> {
+    let mut completed: c_longlong = 0;
+    let mut total: c_longlong = 0;
+    call(|thread| unsafe { ffi::zip4j_get_progress_work(thread, monitor, &mut completed, &mut total) })?;
+    Ok((completed, total))
+}
+
+/// Get the outcome of the finished monitored operation
+pub fn get_operation_result(monitor: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_get_operation_result(thread, monitor) })
+}
+
+/// Release a progress monitor
+pub fn release_progress_monitor(monitor: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_release_progress_monitor(thread, monitor) })
+}
+
+// Runtime
+
+/// Limit the heap of the native library to `bytes`
+pub fn set_max_heap_size(bytes: c_longlong) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_set_max_heap_size(thread, bytes) })
+}
+
+/// Get the last error message recorded for a handle, null-terminated
+pub fn get_last_error(handle: c_longlong, buffer: &mut [u8], length: &mut c_int) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_get_last_error(thread, handle, buffer_ptr(buffer), capacity(buffer), length) })
+}
+
+/// Take the details of the Java exception behind error `code`, null-terminated
+///
+/// The functions in this module already take them for the [`ZipError`] they return.
+pub fn take_last_exception(code: c_int, buffer: &mut [u8], length: &mut c_int) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_take_last_exception(thread, code, buffer_ptr(buffer), capacity(buffer), length) })
+}
+
+/// Get the ABI version, and the zip4j and GraalVM versions as consecutive null-terminated strings
+pub fn get_version_info(buffer: &mut [u8], length: &mut c_int) -> Result<c_int> {
+    let mut abi_version: c_int = 0;
+    call(|thread| unsafe {
+        ffi::zip4j_get_version_info(thread, &mut abi_version, buffer_ptr(buffer), capacity(buffer), length)
+    })?;
+    Ok(abi_version)
+}
+
+/// Fill `values` with the runtime counters read by [`ffi::diagnostics`]
+pub fn get_diagnostics(values: &mut [c_longlong]) -> Result<()> {
+    let count = values.len().min(c_int::MAX as usize) as c_int;
+    call(|thread| unsafe { ffi::zip4j_get_diagnostics(thread, values.as_mut_ptr(), count) })
+}
+
+/// Start or stop capturing what the native library prints
+pub fn capture_output(enabled: bool) -> Result<()> {
+    call(|thread| unsafe { ffi::zip4j_capture_output(thread, enabled as c_int) })
+}
+
+/// Take the oldest captured line, returning the `OUTPUT_*` stream it was printed to,
+/// or `OUTPUT_NONE` if no line is waiting
+pub fn next_output_line(buffer: &mut [u8], length: &mut c_int) -> Result<c_int> {
+    let mut stream: c_int = 0;
+    call(|thread| unsafe {
+        ffi::zip4j_next_output_line(thread, &mut stream, buffer_ptr(buffer), capacity(buffer), length)
+    })?;
+    Ok(stream)
+}
@@ -0,0 +1,83 @@
+//! Reference-counted runtime lifetime
+//!
+//! Applications and libraries that embed this crate can each hold a
+//! [`RuntimeGuard`] instead of coordinating calls to [`init`](crate::init)
+//! and [`cleanup`](crate::cleanup): the runtime starts with the first guard
+//! and is torn down when the last one is dropped. A runtime that was already
+//! initialized when the first guard was acquired is left running.
+
+use std::sync::{Mutex, PoisonError};
+use crate::error::Result;
+use crate::ffi;
+
+/// Live guards
+static GUARDS: Mutex<Guards> = Mutex::new(Guards { count: 0, owned: false });
+
+struct Guards {
+    count: usize,
+    /// Whether the first guard initialized the runtime, and the last one should tear it down
+    owned: bool,
+}
+
+/// Entry point for acquiring the shared runtime
+#[derive(Debug)]
+pub struct Zip4jRuntime;
+
+impl Zip4jRuntime {
+    /// Initialize the runtime if needed and keep it alive until the returned guard is dropped
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{Zip4jRuntime, ZipFile};
+    ///
+    /// let _runtime = Zip4jRuntime::acquire()?;
+    /// let zip = ZipFile::new("archive.zip")?;
+    /// println!("{} entries", zip.entry_count()?);
+    /// // The runtime is cleaned up once every guard has been dropped
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    pub fn acquire() -> Result<RuntimeGuard> {
+        let mut guards = GUARDS.lock().unwrap_or_else(PoisonError::into_inner);
+        if guards.count == 0 {
+            guards.owned = !ffi::is_initialized();
+        }
+        ffi::init()?;
+        guards.count += 1;
+        Ok(RuntimeGuard { _private: () })
+    }
+
+    /// Number of guards currently keeping the runtime alive
+    pub fn guard_count() -> usize {
+        GUARDS.lock().unwrap_or_else(PoisonError::into_inner).count
+    }
+}
+
+/// Keeps the runtime initialized while alive, see [`Zip4jRuntime::acquire`]
+///
+/// Archives still open when the last guard is dropped become unusable, so
+/// drop them first. The runtime is only cleaned up if the first guard
+/// initialized it.
+#[derive(Debug)]
+#[must_use = "the runtime is cleaned up as soon as the guard is dropped"]
+pub struct RuntimeGuard {
+    _private: (),
+}
+
+impl Drop for RuntimeGuard {
+    fn drop(&mut self) {
+        let mut guards = GUARDS.lock().unwrap_or_else(PoisonError::into_inner);
+        guards.count -= 1;
+        if guards.count == 0 && guards.owned {
+            guards.owned = false;
+            if let Err(e) = ffi::cleanup() {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("runtime cleanup failed: {}", e);
+                #[cfg(all(feature = "log", not(feature = "tracing")))]
+                log::warn!("runtime cleanup failed: {}", e);
+                #[cfg(not(any(feature = "log", feature = "tracing")))]
+                let _ = e;
+            }
+        }
+    }
+}
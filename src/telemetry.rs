@@ -0,0 +1,141 @@
+//! Operation metrics, reported through the [`metrics`](https://docs.rs/metrics)
+//! facade when the `metrics` feature is enabled
+//!
+//! Install any `metrics` recorder (a Prometheus exporter, StatsD, ...) to
+//! receive:
+//!
+//! - `zip2rs_operations_total`: counter of archive operations, labelled `operation`
+//! - `zip2rs_operation_duration_seconds`: histogram of how long each operation
+//!   took, nearly all of it spent in the native library, labelled `operation`
+//! - `zip2rs_errors_total`: counter of failed native calls, labelled with the raw `code`
+//! - `zip2rs_bytes_compressed_total`: counter of uncompressed bytes added to archives
+//! - `zip2rs_bytes_extracted_total`: counter of uncompressed bytes extracted,
+//!   to disk or into memory
+//!
+//! Public methods built on other public methods count as one operation.
+//! Without the feature every function here does nothing.
+
+use std::path::Path;
+#[cfg(feature = "metrics")]
+use std::cell::Cell;
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+#[cfg(feature = "metrics")]
+thread_local! {
+    /// Operations running on this thread, so only the outermost one is counted
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts an operation and records its duration when dropped, then forwards
+/// whatever the native library printed meanwhile (see [`crate::output`])
+pub(crate) struct OperationTimer {
+    #[cfg(feature = "metrics")]
+    operation: &'static str,
+    #[cfg(feature = "metrics")]
+    start: Instant,
+    /// Whether no other operation was running on this thread when this one started
+    #[cfg(feature = "metrics")]
+    outermost: bool,
+}
+
+impl OperationTimer {
+    /// Start timing `operation`
+    pub(crate) fn start(operation: &'static str) -> Self {
+        #[cfg(not(feature = "metrics"))]
+        let _ = operation;
+        Self {
+            #[cfg(feature = "metrics")]
+            operation,
+            #[cfg(feature = "metrics")]
+            start: Instant::now(),
+            #[cfg(feature = "metrics")]
+            outermost: DEPTH.with(|depth| depth.replace(depth.get() + 1)) == 0,
+        }
+    }
+}
+
+impl Drop for OperationTimer {
+    fn drop(&mut self) {
+        #[cfg(feature = "metrics")]
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+        #[cfg(feature = "metrics")]
+        if self.outermost {
+            metrics::counter!("zip2rs_operations_total", "operation" => self.operation).increment(1);
+            metrics::histogram!("zip2rs_operation_duration_seconds", "operation" => self.operation)
+                .record(self.start.elapsed().as_secs_f64());
+        }
+        crate::output::forward();
+    }
+}
+
+/// Count a native call that failed with `code`
+pub(crate) fn record_error(code: i32) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!("zip2rs_errors_total", "code" => code.to_string()).increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = code;
+}
+
+/// Count bytes handed to the archive for compression
+pub(crate) fn record_compressed(bytes: u64) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!("zip2rs_bytes_compressed_total").increment(bytes);
+    #[cfg(not(feature = "metrics"))]
+    let _ = bytes;
+}
+
+/// Count the size of a file that was added to the archive; folders count nothing
+pub(crate) fn record_compressed_file(path: &Path) {
+    #[cfg(feature = "metrics")]
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() => record_compressed(metadata.len()),
+        _ => {}
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = path;
+}
+
+/// Count the sizes of all files below a folder that was added to the archive
+pub(crate) fn record_compressed_dir(path: &Path) {
+    #[cfg(feature = "metrics")]
+    record_compressed(dir_size(path));
+    #[cfg(not(feature = "metrics"))]
+    let _ = path;
+}
+
+/// Add up the sizes of the files below `path`, skipping anything unreadable
+#[cfg(feature = "metrics")]
+fn dir_size(path: &Path) -> u64 {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(file_type) if file_type.is_file() => entry.metadata().map_or(0, |metadata| metadata.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Count bytes extracted, to disk or into memory
+pub(crate) fn record_extracted(bytes: u64) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!("zip2rs_bytes_extracted_total").increment(bytes);
+    #[cfg(not(feature = "metrics"))]
+    let _ = bytes;
+}
+
+/// Count bytes extracted to disk, computed by `bytes` from the uncompressed
+/// sizes of the entries only when metrics are enabled, as that may take native calls
+pub(crate) fn record_extracted_with<F: FnOnce() -> crate::error::Result<u64>>(bytes: F) {
+    #[cfg(feature = "metrics")]
+    if let Ok(bytes) = bytes() {
+        record_extracted(bytes);
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = bytes;
+}
@@ -1,8 +1,16 @@
-use std::os::raw::c_int;
-use crate::ffi::constants;
+use std::fmt;
+use std::os::raw::{c_char, c_int, c_longlong};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zeroize::Zeroizing;
+use crate::error::{Result, ZipError};
+use crate::ffi::{self, constants, helpers};
 
 /// Compression levels for zip entries
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum CompressionLevel {
     /// No compression
     None,
@@ -27,6 +35,8 @@ impl From<CompressionLevel> for c_int {
 
 /// Compression methods for zip entries
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum CompressionMethod {
     /// Store without compression
     Store,
@@ -55,6 +65,8 @@ impl From<c_int> for CompressionMethod {
 
 /// Encryption methods for zip entries
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum EncryptionMethod {
     /// No encryption
     None,
@@ -91,6 +103,8 @@ impl From<c_int> for EncryptionMethod {
 
 /// AES key strength for AES encryption
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum AesKeyStrength {
     /// 128-bit key
     Aes128,
@@ -110,8 +124,157 @@ impl From<AesKeyStrength> for c_int {
     }
 }
 
+/// A password that is wiped from memory when dropped
+///
+/// Built from `&str`, `String`, byte slices or byte vectors, and from
+/// `secrecy::SecretString` with the `secrecy` feature. The bytes are used verbatim
+/// as key material, so text passwords are UTF-8 encoded; use [`Password::encoded`]
+/// or raw bytes for archives written by tools that used a legacy codepage. The
+/// copies handed to the native library are wiped as well once they are no longer
+/// needed.
+#[derive(Clone)]
+pub struct Password(Zeroizing<Vec<u8>>);
+
+impl Password {
+    /// Create a password from the exact bytes used as key material
+    pub fn new<B: Into<Vec<u8>>>(bytes: B) -> Self {
+        Self(Zeroizing::new(bytes.into()))
+    }
+
+    /// Create a password from text encoded in the given charset
+    ///
+    /// Legacy ZipCrypto archives often encode the password in the codepage of the
+    /// system that created them, e.g. `"IBM437"` for old DOS tools or `"windows-1252"`.
+    /// Fails with [`ZipError::InvalidParameter`](crate::ZipError::InvalidParameter) if
+    /// the charset is unknown or cannot represent the password.
+    ///
+    /// ```no_run
+    /// use zip2rs::{Password, ZipFile};
+    ///
+    /// let password = Password::encoded("pässwört", "IBM437")?;
+    /// let zip = ZipFile::with_password("legacy.zip", password)?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    pub fn encoded<S: AsRef<str>>(password: S, charset: &str) -> Result<Self> {
+        ffi::ensure_initialized()?;
+
+        let c_password = Password::from(password.as_ref()).to_c_string()?;
+        let c_charset = helpers::to_c_string(charset)?;
+        let mut buffer = Zeroizing::new(vec![0u8; password.as_ref().len().max(16)]);
+        let mut encoded_length: c_int = 0;
+
+        let mut result = unsafe {
+            ffi::zip4j_encode_password(
+                *ffi::get_thread()?,
+                c_password.as_ptr() as *mut c_char,
+                c_charset.as_ptr() as *mut c_char,
+                buffer.as_mut_ptr() as *mut c_char,
+                buffer.len() as c_int,
+                &mut encoded_length,
+            )
+        };
+
+        if result == constants::ERROR_BUFFER_TOO_SMALL {
+            buffer = Zeroizing::new(vec![0u8; encoded_length as usize]);
+            result = unsafe {
+                ffi::zip4j_encode_password(
+                    *ffi::get_thread()?,
+                    c_password.as_ptr() as *mut c_char,
+                    c_charset.as_ptr() as *mut c_char,
+                    buffer.as_mut_ptr() as *mut c_char,
+                    buffer.len() as c_int,
+                    &mut encoded_length,
+                )
+            };
+        }
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
+        buffer.truncate(encoded_length as usize);
+        Ok(Self(buffer))
+    }
+
+    /// Get the password bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Copy the password into a null-terminated buffer that is wiped when dropped
+    pub(crate) fn to_c_string(&self) -> Result<Zeroizing<Vec<u8>>> {
+        if self.0.contains(&0) {
+            return Err(crate::error::ZipError::StringConversion("Password contains a nul byte".to_string()));
+        }
+
+        let mut buffer = Zeroizing::new(Vec::with_capacity(self.0.len() + 1));
+        buffer.extend_from_slice(&self.0);
+        buffer.push(0);
+        Ok(buffer)
+    }
+}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Password(***)")
+    }
+}
+
+impl From<&str> for Password {
+    fn from(password: &str) -> Self {
+        Self::new(password)
+    }
+}
+
+impl From<String> for Password {
+    fn from(password: String) -> Self {
+        Self::new(password)
+    }
+}
+
+impl From<&String> for Password {
+    fn from(password: &String) -> Self {
+        Self::new(password.as_str())
+    }
+}
+
+impl From<&[u8]> for Password {
+    fn from(password: &[u8]) -> Self {
+        Self::new(password)
+    }
+}
+
+impl From<Vec<u8>> for Password {
+    fn from(password: Vec<u8>) -> Self {
+        Self::new(password)
+    }
+}
+
+impl From<&Password> for Password {
+    fn from(password: &Password) -> Self {
+        password.clone()
+    }
+}
+
+#[cfg(feature = "secrecy")]
+impl From<&secrecy::SecretString> for Password {
+    fn from(password: &secrecy::SecretString) -> Self {
+        use secrecy::ExposeSecret;
+        Self::new(password.expose_secret())
+    }
+}
+
+#[cfg(feature = "secrecy")]
+impl From<secrecy::SecretString> for Password {
+    fn from(password: secrecy::SecretString) -> Self {
+        Self::from(&password)
+    }
+}
+
 /// Parameters for adding files to a zip archive
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ZipParameters {
     /// Compression level
     pub compression_level: CompressionLevel,
@@ -121,8 +284,19 @@ pub struct ZipParameters {
     pub encryption_method: EncryptionMethod,
     /// AES key strength (only used with AES encryption)
     pub aes_key_strength: AesKeyStrength,
-    /// Password for encryption (if any)
-    pub password: Option<String>,
+    /// Password for encryption (if any), never serialized
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub password: Option<Password>,
+    /// Virtual top-level folder for files added from disk (if any)
+    pub root_folder_name: Option<String>,
+    /// Name to store a file added from disk under, instead of its file name (if any)
+    pub file_name_in_zip: Option<String>,
+    /// Folder that names of files added from disk are relative to (if any)
+    pub default_folder_path: Option<PathBuf>,
+    /// Store symbolic links as link entries instead of the files they point to
+    pub symlink: bool,
+    /// Byte boundary that stored entries' data is padded to (if any)
+    pub alignment: Option<u32>,
 }
 
 impl Default for ZipParameters {
@@ -133,6 +307,11 @@ impl Default for ZipParameters {
             encryption_method: EncryptionMethod::None,
             aes_key_strength: AesKeyStrength::Aes256,
             password: None,
+            root_folder_name: None,
+            file_name_in_zip: None,
+            default_folder_path: None,
+            symlink: false,
+            alignment: None,
         }
     }
 }
@@ -168,13 +347,54 @@ impl ZipParameters {
     }
     
     /// Set password for encryption
-    pub fn with_password<S: Into<String>>(mut self, password: S) -> Self {
+    pub fn with_password<S: Into<Password>>(mut self, password: S) -> Self {
         self.password = Some(password.into());
         self
     }
     
+    /// Place files added from disk under a virtual top-level folder
+    pub fn with_root_folder_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.root_folder_name = Some(name.into());
+        self
+    }
+    
+    /// Store a file added from disk under the given archive path
+    pub fn with_file_name_in_zip<S: Into<String>>(mut self, name: S) -> Self {
+        self.file_name_in_zip = Some(name.into());
+        self
+    }
+    
+    /// Compute names of files added from disk relative to the given folder
+    ///
+    /// For example, adding `/home/me/project/src/main.rs` with a default folder
+    /// path of `/home/me/project` stores the entry as `src/main.rs`.
+    pub fn with_default_folder_path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.default_folder_path = Some(path.into());
+        self
+    }
+    
+    /// Store symbolic links as link entries instead of following them
+    ///
+    /// When adding a directory, links inside it are detected and stored as
+    /// links too, without descending into linked directories.
+    pub fn with_symlink(mut self, symlink: bool) -> Self {
+        self.symlink = symlink;
+        self
+    }
+    
+    /// Align the data of stored entries to `alignment` bytes, like `zipalign`
+    ///
+    /// After entries are added with these parameters the archive is re-aligned
+    /// with [`ZipFile::align_archive`](crate::ZipFile::align_archive), which
+    /// rewrites it; when adding many entries it is cheaper to call that once at
+    /// the end. Removing entries later can shift data off its boundary again.
+    pub fn with_alignment(mut self, alignment: u32) -> Self {
+        self.alignment = Some(alignment);
+        self
+    }
+    
     /// Enable AES 256-bit encryption with password
-    pub fn with_aes256_encryption<S: Into<String>>(mut self, password: S) -> Self {
+    pub fn with_aes256_encryption<S: Into<Password>>(mut self, password: S) -> Self {
         self.encryption_method = EncryptionMethod::Aes256;
         self.aes_key_strength = AesKeyStrength::Aes256;
         self.password = Some(password.into());
@@ -182,7 +402,7 @@ impl ZipParameters {
     }
     
     /// Enable AES 128-bit encryption with password
-    pub fn with_aes128_encryption<S: Into<String>>(mut self, password: S) -> Self {
+    pub fn with_aes128_encryption<S: Into<Password>>(mut self, password: S) -> Self {
         self.encryption_method = EncryptionMethod::Aes128;
         self.aes_key_strength = AesKeyStrength::Aes128;
         self.password = Some(password.into());
@@ -190,9 +410,1137 @@ impl ZipParameters {
     }
     
     /// Enable standard ZIP encryption with password
-    pub fn with_standard_encryption<S: Into<String>>(mut self, password: S) -> Self {
+    pub fn with_standard_encryption<S: Into<Password>>(mut self, password: S) -> Self {
         self.encryption_method = EncryptionMethod::Standard;
         self.password = Some(password.into());
         self
     }
 }
+
+impl ZipParameters {
+    /// Register these parameters with the native library
+    pub(crate) fn to_native(&self) -> Result<NativeParameters> {
+        let c_password = self.password.as_ref().map(Password::to_c_string).transpose()?;
+        let mut handle: c_longlong = 0;
+
+        let result = unsafe {
+            ffi::zip4j_create_parameters(
+                *ffi::get_thread()?,
+                self.compression_level.into(),
+                self.compression_method.into(),
+                self.encryption_method.into(),
+                self.aes_key_strength.into(),
+                c_password.as_ref().map_or(std::ptr::null_mut(), |pwd| pwd.as_ptr() as *mut c_char),
+                &mut handle
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
+        let native_params = NativeParameters { handle };
+        let thread = ffi::get_thread()?;
+
+        set_native_string(self.root_folder_name.as_deref(), |name| unsafe {
+            ffi::zip4j_parameters_set_root_folder_name(*thread, handle, name)
+        })?;
+        set_native_string(self.file_name_in_zip.as_deref(), |name| unsafe {
+            ffi::zip4j_parameters_set_file_name_in_zip(*thread, handle, name)
+        })?;
+        let default_folder_path = self.default_folder_path.as_ref().map(|path| path.to_string_lossy());
+        set_native_string(default_folder_path.as_deref(), |path| unsafe {
+            ffi::zip4j_parameters_set_default_folder_path(*thread, handle, path)
+        })?;
+
+        if self.symlink {
+            let result = unsafe {
+                ffi::zip4j_parameters_set_symbolic_link(*thread, handle, 1)
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+        }
+
+        Ok(native_params)
+    }
+}
+
+/// Pass an optional string setting to a native setter
+fn set_native_string<F>(value: Option<&str>, setter: F) -> Result<()>
+where
+    F: FnOnce(*mut c_char) -> c_int,
+{
+    if let Some(value) = value {
+        let c_value = helpers::to_c_string(value)?;
+        let result = setter(c_value.as_ptr() as *mut c_char);
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+    }
+    Ok(())
+}
+
+/// Native copy of a `ZipParameters`, released when dropped
+#[derive(Debug)]
+pub(crate) struct NativeParameters {
+    handle: c_longlong,
+}
+
+impl NativeParameters {
+    /// Get the internal handle
+    pub(crate) fn handle(&self) -> c_longlong {
+        self.handle
+    }
+}
+
+impl Drop for NativeParameters {
+    fn drop(&mut self) {
+        // Release the parameters handle
+        if let Ok(thread) = ffi::get_thread() {
+            unsafe {
+                ffi::zip4j_release_parameters(*thread, self.handle);
+            }
+        }
+    }
+}
+
+/// Predicate deciding whether a file or folder is skipped when adding a directory
+///
+/// Mirrors zip4j's `ExcludeFileFilter`: it receives the path on disk and returns
+/// `true` to exclude it. Excluding a folder skips everything below it.
+#[derive(Clone)]
+pub struct ExcludeFilter(Arc<dyn Fn(&Path) -> bool + Send + Sync>);
+
+impl ExcludeFilter {
+    /// Create a filter from a closure
+    pub fn new<F>(filter: F) -> Self
+    where
+        F: Fn(&Path) -> bool + Send + Sync + 'static,
+    {
+        Self(Arc::new(filter))
+    }
+
+    /// Check if a path is excluded
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        (self.0)(path)
+    }
+}
+
+impl fmt::Debug for ExcludeFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ExcludeFilter(..)")
+    }
+}
+
+/// How symbolic links are handled when adding a directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SymlinkPolicy {
+    /// Follow links and store the files they point to
+    #[default]
+    Follow,
+    /// Leave links out of the archive
+    Skip,
+    /// Store links as link entries
+    Link,
+}
+
+/// Options for adding a directory to a zip archive
+#[derive(Debug, Clone)]
+pub struct AddDirOptions {
+    /// Compression and encryption parameters
+    pub params: ZipParameters,
+    /// Store the directory itself as the top-level folder (otherwise only its contents are added)
+    pub include_root: bool,
+    /// Predicate for files and folders to skip
+    pub exclude: Option<ExcludeFilter>,
+    /// Glob patterns for files and folders to skip, matched against paths relative to the directory
+    pub exclude_patterns: Vec<String>,
+    /// Include hidden files and folders
+    pub include_hidden: bool,
+    /// How symbolic links inside the directory are handled
+    pub symlinks: SymlinkPolicy,
+    /// File name suffixes (e.g. `.png`) of files that are always stored uncompressed
+    pub stored_extensions: Vec<String>,
+    /// Skip files whose entries already exist with the same size and CRC32
+    pub skip_unchanged: bool,
+    /// Limit on the average rate at which files are read, in bytes per second
+    pub max_bytes_per_second: Option<u64>,
+}
+
+impl Default for AddDirOptions {
+    fn default() -> Self {
+        Self {
+            params: ZipParameters::default(),
+            include_root: true,
+            exclude: None,
+            exclude_patterns: Vec::new(),
+            include_hidden: true,
+            symlinks: SymlinkPolicy::Follow,
+            stored_extensions: Vec::new(),
+            skip_unchanged: false,
+            max_bytes_per_second: None,
+        }
+    }
+}
+
+impl AddDirOptions {
+    /// Create new default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set compression and encryption parameters
+    pub fn with_params(mut self, params: ZipParameters) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Set whether the directory itself appears as the top-level folder
+    pub fn with_include_root(mut self, include_root: bool) -> Self {
+        self.include_root = include_root;
+        self
+    }
+
+    /// Skip files and folders for which the closure returns `true`
+    pub fn with_exclude<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&Path) -> bool + Send + Sync + 'static,
+    {
+        self.exclude = Some(ExcludeFilter::new(filter));
+        self
+    }
+
+    /// Skip files and folders matching a glob pattern (e.g. `.git`, `**/node_modules`)
+    pub fn with_exclude_pattern<S: Into<String>>(mut self, pattern: S) -> Self {
+        self.exclude_patterns.push(pattern.into());
+        self
+    }
+
+    /// Set whether hidden files and folders are included
+    pub fn with_hidden(mut self, include_hidden: bool) -> Self {
+        self.include_hidden = include_hidden;
+        self
+    }
+
+    /// Set how symbolic links inside the directory are handled
+    pub fn with_symlinks(mut self, symlinks: SymlinkPolicy) -> Self {
+        self.symlinks = symlinks;
+        self
+    }
+
+    /// Always store files ending in `extension` uncompressed, like aapt does for `.png` or `.so`
+    ///
+    /// Matching is case-insensitive and a leading dot is optional; multi-part
+    /// suffixes such as `.resources.arsc` also match a file with exactly that name.
+    pub fn with_stored_extension<S: Into<String>>(mut self, extension: S) -> Self {
+        self.stored_extensions.push(extension.into());
+        self
+    }
+
+    /// Only add files that are new or differ from their existing entry in size or CRC32
+    ///
+    /// Repackaging a mostly unchanged tree then only reads the files instead
+    /// of compressing and rewriting all of them.
+    pub fn with_skip_unchanged(mut self, skip_unchanged: bool) -> Self {
+        self.skip_unchanged = skip_unchanged;
+        self
+    }
+
+    /// Add files one by one, pausing in between so they are read at no more
+    /// than `bytes_per_second` on average
+    ///
+    /// Keeps a background backup from saturating the disk. The rate is an
+    /// average over the whole directory; each file is still read at full speed.
+    pub fn with_max_bytes_per_second(mut self, bytes_per_second: u64) -> Self {
+        self.max_bytes_per_second = Some(bytes_per_second);
+        self
+    }
+
+    /// Check if the directory has to be walked on the Rust side
+    ///
+    /// zip4j can't evaluate closures or glob patterns, has no way to skip links
+    /// or unchanged files, and applies a single compression method to the whole
+    /// directory.
+    pub(crate) fn needs_walk(&self) -> bool {
+        self.exclude.is_some()
+            || !self.exclude_patterns.is_empty()
+            || self.symlinks == SymlinkPolicy::Skip
+            || !self.stored_extensions.is_empty()
+            || self.skip_unchanged
+            || self.max_bytes_per_second.is_some()
+    }
+
+    /// Check if a file has to be stored uncompressed because of its extension
+    pub(crate) fn is_stored(&self, path: &Path) -> bool {
+        let name = match path.file_name() {
+            Some(name) if path.is_file() => format!(".{}", name.to_string_lossy().to_lowercase()),
+            _ => return false,
+        };
+        self.stored_extensions.iter().any(|extension| {
+            let extension = extension.to_lowercase();
+            name.ends_with(&format!(".{}", extension.trim_start_matches('.')))
+        })
+    }
+
+    /// Parameters used for the files in the directory
+    pub(crate) fn zip_params(&self) -> ZipParameters {
+        let store_links = self.params.symlink || self.symlinks == SymlinkPolicy::Link;
+        self.params.clone().with_symlink(store_links)
+    }
+
+    /// Register these options with the native library
+    pub(crate) fn to_native(&self) -> Result<NativeParameters> {
+        let native_params = self.zip_params().to_native()?;
+
+        let result = unsafe {
+            ffi::zip4j_parameters_set_include_root_folder(
+                *ffi::get_thread()?,
+                native_params.handle(),
+                self.include_root as c_int
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
+        let result = unsafe {
+            ffi::zip4j_parameters_set_read_hidden(
+                *ffi::get_thread()?,
+                native_params.handle(),
+                self.include_hidden as c_int,
+                self.include_hidden as c_int
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
+        Ok(native_params)
+    }
+}
+
+/// Where the data of an entry added with `ZipFile::add` comes from
+#[derive(Debug, Clone)]
+pub enum EntrySource {
+    /// Bytes held in memory
+    Data(Vec<u8>),
+    /// A file on disk
+    File(PathBuf),
+}
+
+/// Description of a single entry to add, bundling its data with its metadata
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::time::SystemTime;
+/// use zip2rs::{ZipEntryBuilder, ZipFile, ZipParameters};
+///
+/// let mut zip = ZipFile::new("release.zip")?;
+/// zip.add(
+///     ZipEntryBuilder::from_file("bin/tool", "target/release/tool")
+///         .with_unix_mode(0o755)
+///         .with_last_modified(SystemTime::UNIX_EPOCH)
+///         .with_comment("release build"),
+/// )?;
+/// zip.add(ZipEntryBuilder::from_data("VERSION", "1.2.3").with_params(ZipParameters::new()))?;
+/// # Ok::<(), zip2rs::ZipError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ZipEntryBuilder {
+    /// Name of the entry in the archive
+    pub name: String,
+    /// Data of the entry
+    pub source: EntrySource,
+    /// Compression and encryption parameters
+    pub params: ZipParameters,
+    /// Last modification time (defaults to now, or the file's time for files on disk)
+    pub last_modified: Option<SystemTime>,
+    /// Unix permission bits (e.g. `0o644`)
+    pub unix_mode: Option<u32>,
+    /// Entry comment
+    pub comment: Option<String>,
+    /// Precomputed CRC32 of the data, for encryption modes that need it before the data is written
+    pub crc: Option<u32>,
+}
+
+impl ZipEntryBuilder {
+    /// Create an entry from bytes held in memory
+    pub fn from_data<S: Into<String>, B: Into<Vec<u8>>>(name: S, data: B) -> Self {
+        Self::with_source(name.into(), EntrySource::Data(data.into()))
+    }
+
+    /// Create an entry from a file on disk, stored under the given name
+    pub fn from_file<S: Into<String>, P: Into<PathBuf>>(name: S, path: P) -> Self {
+        Self::with_source(name.into(), EntrySource::File(path.into()))
+    }
+
+    fn with_source(name: String, source: EntrySource) -> Self {
+        Self {
+            name,
+            source,
+            params: ZipParameters::default(),
+            last_modified: None,
+            unix_mode: None,
+            comment: None,
+            crc: None,
+        }
+    }
+
+    /// Set compression and encryption parameters
+    pub fn with_params(mut self, params: ZipParameters) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Set the last modification time
+    pub fn with_last_modified(mut self, time: SystemTime) -> Self {
+        self.last_modified = Some(time);
+        self
+    }
+
+    /// Set the Unix permission bits
+    pub fn with_unix_mode(mut self, mode: u32) -> Self {
+        self.unix_mode = Some(mode);
+        self
+    }
+
+    /// Set the entry comment
+    pub fn with_comment<S: Into<String>>(mut self, comment: S) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    /// Set the precomputed CRC32 of the data
+    pub fn with_crc(mut self, crc: u32) -> Self {
+        self.crc = Some(crc);
+        self
+    }
+
+    /// Register the entry's parameters and metadata with the native library
+    pub(crate) fn to_native(&self) -> Result<NativeParameters> {
+        let native_params = self.params.clone().with_file_name_in_zip(self.name.as_str()).to_native()?;
+        let handle = native_params.handle();
+
+        if let Some(time) = self.last_modified {
+            let millis = time.duration_since(UNIX_EPOCH)
+                .map_err(|_| crate::error::ZipError::InvalidParameter("Modification time is before 1970".to_string()))?
+                .as_millis();
+
+            let result = unsafe {
+                ffi::zip4j_parameters_set_last_modified_time(*ffi::get_thread()?, handle, millis as c_longlong)
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+        }
+
+        if let Some(crc) = self.crc {
+            let result = unsafe {
+                ffi::zip4j_parameters_set_entry_crc(*ffi::get_thread()?, handle, crc as c_longlong)
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+        }
+
+        let thread = ffi::get_thread()?;
+        set_native_string(self.comment.as_deref(), |comment| unsafe {
+            ffi::zip4j_parameters_set_file_comment(*thread, handle, comment)
+        })?;
+
+        Ok(native_params)
+    }
+}
+
+/// Progress of a long-running operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Progress {
+    /// Percentage done (0-100)
+    pub percent: u8,
+    /// Bytes processed so far
+    pub bytes_done: u64,
+    /// Total bytes to process
+    pub bytes_total: u64,
+}
+
+/// Owned snapshot of an entry's metadata, from [`ZipEntry::info`](crate::ZipEntry::info) or
+/// [`ZipFile::entry_infos`](crate::ZipFile::entry_infos)
+///
+/// Unlike [`ZipEntry`](crate::ZipEntry) it holds no native handle, so it can be
+/// sent to other threads, stored, and used after the archive is closed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ZipEntryInfo {
+    /// Name of the entry
+    pub name: String,
+    /// Uncompressed size in bytes
+    pub size: u64,
+    /// Compressed size in bytes
+    pub compressed_size: u64,
+    /// CRC32 of the uncompressed data
+    pub crc32: u32,
+    /// Compression method
+    pub compression_method: CompressionMethod,
+    /// Encryption method
+    pub encryption_method: EncryptionMethod,
+    /// Whether the entry is a directory
+    pub is_directory: bool,
+    /// Whether the entry is encrypted
+    pub is_encrypted: bool,
+    /// Last modification time (DOS time format)
+    pub last_modified_time: u32,
+    /// Raw general purpose bit flags
+    pub general_purpose_flags: u16,
+}
+
+/// Immediate child of a folder in the archive, from [`ZipFile::list_dir`](crate::ZipFile::list_dir)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DirEntry {
+    /// Name of the child within its folder, without a trailing `/`
+    pub name: String,
+    /// Full path in the archive; folders end with `/`
+    pub path: String,
+    /// Whether the child is a folder
+    pub is_dir: bool,
+    /// Metadata of the entry, or `None` for folders that only exist as part of other entries' paths
+    pub info: Option<ZipEntryInfo>,
+}
+
+/// Condition of an entry, as reported by [`ZipFile::scan`](crate::ZipFile::scan)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum EntryCondition {
+    /// Headers and data read cleanly and the CRC matched
+    Intact,
+    /// The entry's data is cut off before its recorded size
+    Truncated,
+    /// The data decompressed but its CRC doesn't match the recorded one
+    CrcMismatch,
+    /// The entry is encrypted and the archive password is missing or wrong
+    WrongPassword,
+    /// The local header or compressed data is damaged
+    Corrupt,
+}
+
+impl From<c_int> for EntryCondition {
+    fn from(value: c_int) -> Self {
+        match value {
+            constants::HEALTH_INTACT => EntryCondition::Intact,
+            constants::HEALTH_TRUNCATED => EntryCondition::Truncated,
+            constants::HEALTH_CRC_MISMATCH => EntryCondition::CrcMismatch,
+            constants::HEALTH_WRONG_PASSWORD => EntryCondition::WrongPassword,
+            _ => EntryCondition::Corrupt,
+        }
+    }
+}
+
+/// Health of a single entry, from [`ZipFile::scan`](crate::ZipFile::scan)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntryHealth {
+    /// Name of the entry
+    pub name: String,
+    /// What state the entry is in
+    pub condition: EntryCondition,
+    /// Description of the problem, for entries that aren't intact
+    pub detail: Option<String>,
+}
+
+impl EntryHealth {
+    /// Whether the entry is intact
+    pub fn is_intact(&self) -> bool {
+        self.condition == EntryCondition::Intact
+    }
+}
+
+/// Summary of a best-effort extraction with [`ZipFile::salvage`](crate::ZipFile::salvage)
+#[derive(Debug, Default)]
+pub struct SalvageReport {
+    /// Names of the entries that were extracted intact
+    pub recovered: Vec<String>,
+    /// Names of the entries that were skipped, with the reason
+    pub failed: Vec<(String, ZipError)>,
+}
+
+/// Summary of an extraction with
+/// [`ZipFile::extract_all_with_report`](crate::ZipFile::extract_all_with_report)
+#[derive(Debug, Default)]
+pub struct ExtractReport {
+    /// Number of files written
+    pub files_written: usize,
+    /// Number of directory entries created
+    pub directories_created: usize,
+    /// Total uncompressed size of the files written, in bytes
+    pub bytes_written: u64,
+    /// Names of the entries that were skipped because nothing was left of them
+    /// after sanitizing (e.g. `/` or `..`)
+    pub skipped: Vec<String>,
+    /// Entries whose names would have escaped the destination, with the path
+    /// they were written to instead
+    pub sanitized: Vec<(String, PathBuf)>,
+    /// Names of the entries that failed to extract, with the reason
+    pub failed: Vec<(String, ZipError)>,
+}
+
+impl ExtractReport {
+    /// Whether every entry was extracted under its own name
+    pub fn is_ok(&self) -> bool {
+        self.skipped.is_empty() && self.sanitized.is_empty() && self.failed.is_empty()
+    }
+}
+
+/// Whether [`ZipFile::open`](crate::ZipFile::open) creates the archive, opens an existing one, or both
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum OpenMode {
+    /// Open the archive if it exists, otherwise start a new one
+    #[default]
+    CreateOrOpen,
+    /// Start a new archive, failing with `ZipError::FileExists` if the file exists
+    CreateNew,
+    /// Open an existing archive, failing with `ZipError::FileNotFound` if it is missing
+    OpenExisting,
+}
+
+/// Unicode normalization applied to entry names when looking them up and extracting them
+///
+/// macOS stores file names decomposed (NFD) while most other systems use the
+/// composed form (NFC), so the same `café.txt` can be spelled with different
+/// bytes depending on where the archive was made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum NameNormalization {
+    /// Use names exactly as stored
+    #[default]
+    Unchanged,
+    /// Match names in composed form and extract files under composed names
+    Nfc,
+    /// Match names in decomposed form and extract files under decomposed names
+    Nfd,
+}
+
+impl From<NameNormalization> for c_int {
+    fn from(normalization: NameNormalization) -> Self {
+        match normalization {
+            NameNormalization::Unchanged => constants::NAME_NORMALIZATION_NONE,
+            NameNormalization::Nfc => constants::NAME_NORMALIZATION_NFC,
+            NameNormalization::Nfd => constants::NAME_NORMALIZATION_NFD,
+        }
+    }
+}
+
+/// Which entry a lookup by name returns when several entries share the name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DuplicatePolicy {
+    /// The first entry in archive order
+    #[default]
+    First,
+    /// The last entry in archive order, which is what most extractors end up writing
+    Last,
+    /// None; fail with `ZipError::DuplicateEntry`
+    Error,
+}
+
+/// How a [`ZipFile`](crate::ZipFile) writes modifications to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SaveMode {
+    /// Rewrite the archive in place
+    #[default]
+    InPlace,
+    /// Apply each modification to a temporary copy next to the archive and
+    /// atomically rename it over the original once the modification succeeded
+    Atomic,
+}
+
+/// How [`ZipFile::diff_with_dir`](crate::ZipFile::diff_with_dir) decides whether a file of the
+/// same size as its entry has changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ChangeDetection {
+    /// Compare the file's CRC32 with the entry's (reads every file)
+    #[default]
+    Crc,
+    /// Compare modification times, at the 2 second resolution of the ZIP format
+    Mtime,
+}
+
+impl From<ChangeDetection> for c_int {
+    fn from(detection: ChangeDetection) -> Self {
+        match detection {
+            ChangeDetection::Crc => constants::CHANGE_DETECTION_CRC,
+            ChangeDetection::Mtime => constants::CHANGE_DETECTION_MTIME,
+        }
+    }
+}
+
+/// Kind of difference between an archive and a directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DiffKind {
+    /// The entry has no matching file on disk
+    OnlyInArchive,
+    /// The file on disk has no matching entry
+    OnlyOnDisk,
+    /// Both exist but the contents differ
+    Modified,
+}
+
+/// A difference found by [`ZipFile::diff_with_dir`](crate::ZipFile::diff_with_dir)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntryDiff {
+    /// Entry name, relative to the directory and using `/` separators
+    pub name: String,
+    /// How the entry and the file differ
+    pub kind: DiffKind,
+}
+
+/// Options for [`ZipFile::sync_directory`](crate::ZipFile::sync_directory)
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SyncOptions {
+    /// Parameters used for added and replaced files
+    pub params: ZipParameters,
+    /// How changed files are detected
+    pub detection: ChangeDetection,
+    /// Remove entries whose files no longer exist in the directory
+    pub remove_missing: bool,
+}
+
+impl SyncOptions {
+    /// Create new default options (CRC comparison, nothing removed)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the parameters used for added and replaced files
+    pub fn with_params(mut self, params: ZipParameters) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Set how changed files are detected
+    pub fn with_detection(mut self, detection: ChangeDetection) -> Self {
+        self.detection = detection;
+        self
+    }
+
+    /// Remove entries whose files no longer exist in the directory
+    pub fn with_remove_missing(mut self, remove_missing: bool) -> Self {
+        self.remove_missing = remove_missing;
+        self
+    }
+}
+
+/// Summary of a [`ZipFile::sync_directory`](crate::ZipFile::sync_directory) run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncReport {
+    /// Files that were new to the archive
+    pub added: Vec<String>,
+    /// Entries that were replaced because their file changed
+    pub updated: Vec<String>,
+    /// Entries that were removed because their file is gone
+    pub removed: Vec<String>,
+}
+
+impl SyncReport {
+    /// Whether the archive was already in sync
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Machine-readable listing of an archive, from [`ZipFile::manifest`](crate::ZipFile::manifest)
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ArchiveManifest {
+    /// Archive comment
+    pub comment: String,
+    /// Every entry, in archive order
+    pub entries: Vec<ManifestEntry>,
+}
+
+/// A single entry of an [`ArchiveManifest`]
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ManifestEntry {
+    /// Name of the entry
+    pub name: String,
+    /// Whether the entry is a directory
+    pub directory: bool,
+    /// Uncompressed size in bytes
+    pub size: u64,
+    /// Compressed size in bytes
+    pub compressed_size: u64,
+    /// CRC32 of the uncompressed data
+    pub crc32: u32,
+    /// Compression method
+    pub compression_method: CompressionMethod,
+    /// Encryption method
+    pub encryption_method: EncryptionMethod,
+    /// Last modification time as stored in the archive (local time, 2 second resolution),
+    /// formatted as `YYYY-MM-DDTHH:MM:SS`
+    pub last_modified: String,
+}
+
+#[cfg(feature = "serde")]
+impl ManifestEntry {
+    /// Format a DOS date and time as `YYYY-MM-DDTHH:MM:SS`
+    pub(crate) fn format_dos_time(dos_time: u32) -> String {
+        let date = dos_time >> 16;
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            ((date >> 9) & 0x7f) + 1980,
+            (date >> 5) & 0x0f,
+            date & 0x1f,
+            (dos_time >> 11) & 0x1f,
+            (dos_time >> 5) & 0x3f,
+            (dos_time & 0x1f) * 2,
+        )
+    }
+}
+
+/// Options for [`ZipFile::extract_all_with_options`](crate::ZipFile::extract_all_with_options)
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ExtractOptions {
+    /// Re-read every written file and compare its CRC32 with the one stored in the archive
+    pub verify_crc: bool,
+    /// Fail before writing anything if the destination lacks space for the uncompressed entries
+    pub check_free_space: bool,
+    /// Limit on the average rate at which entries are written, in bytes per second
+    pub max_bytes_per_second: Option<u64>,
+}
+
+impl ExtractOptions {
+    /// Create new default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Verify the CRC32 of every written file after extraction
+    pub fn with_verify_crc(mut self, verify_crc: bool) -> Self {
+        self.verify_crc = verify_crc;
+        self
+    }
+
+    /// Check that the destination has room for the uncompressed entries before extracting
+    pub fn with_check_free_space(mut self, check_free_space: bool) -> Self {
+        self.check_free_space = check_free_space;
+        self
+    }
+
+    /// Extract entries one by one, pausing in between so they are written at
+    /// no more than `bytes_per_second` on average
+    ///
+    /// The rate is an average over the whole archive; each entry is still
+    /// written at full speed.
+    pub fn with_max_bytes_per_second(mut self, bytes_per_second: u64) -> Self {
+        self.max_bytes_per_second = Some(bytes_per_second);
+        self
+    }
+}
+
+/// SHA-256 digest of an extracted entry, from
+/// [`ZipFile::extract_all_with_digests`](crate::ZipFile::extract_all_with_digests)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntryDigest {
+    /// Name of the entry
+    pub name: String,
+    /// SHA-256 of the entry's uncompressed contents
+    pub sha256: [u8; 32],
+}
+
+impl EntryDigest {
+    /// Get the digest as a lowercase hex string
+    pub fn sha256_hex(&self) -> String {
+        self.sha256.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+/// Entry that was just written by
+/// [`ZipFile::extract_all_with_callback`](crate::ZipFile::extract_all_with_callback)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedEntry {
+    /// Name of the entry
+    pub name: String,
+    /// Path the entry was written to, under its normalized name if
+    /// [`NameNormalization`] is set
+    pub path: PathBuf,
+    /// Size of the written file in bytes (0 for directories)
+    pub size: u64,
+    /// Whether the entry is a directory
+    pub is_directory: bool,
+    /// How long extracting the entry took
+    pub duration: Duration,
+}
+
+/// File that already exists where an entry is about to be extracted, passed
+/// to the resolver of
+/// [`ZipFile::extract_all_with_resolver`](crate::ZipFile::extract_all_with_resolver)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileConflict {
+    /// Name of the entry being extracted
+    pub name: String,
+    /// Path of the existing file
+    pub path: PathBuf,
+    /// Uncompressed size of the entry in bytes
+    pub size: u64,
+    /// Size of the existing file in bytes
+    pub existing_size: u64,
+}
+
+/// What to do about a [`FileConflict`]
+///
+/// The `*All` variants apply the same answer to every later conflict
+/// without asking again, like "Yes to all" in a GUI archiver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// Replace the existing file
+    Overwrite,
+    /// Replace this and every later conflicting file
+    OverwriteAll,
+    /// Keep the existing file and don't extract the entry
+    Skip,
+    /// Skip this and every later conflicting entry
+    SkipAll,
+    /// Extract next to the existing file as `name (1).ext`, `name (2).ext`, ...
+    Rename,
+    /// Rename this and every later conflicting entry
+    RenameAll,
+    /// Stop extracting, failing with `ZipError::OperationCancelled`
+    Abort,
+}
+
+impl ConflictResolution {
+    /// Get the resolution for this one conflict, without the "apply to all" part
+    pub fn single(self) -> Self {
+        match self {
+            ConflictResolution::OverwriteAll => ConflictResolution::Overwrite,
+            ConflictResolution::SkipAll => ConflictResolution::Skip,
+            ConflictResolution::RenameAll => ConflictResolution::Rename,
+            resolution => resolution,
+        }
+    }
+
+    /// Whether the resolution applies to every later conflict too
+    pub fn applies_to_all(self) -> bool {
+        self != self.single()
+    }
+}
+
+/// Progress event sent by [`ZipFile::extract_all_events`](crate::ZipFile::extract_all_events)
+///
+/// Events arrive in order: one `Started`, then for every entry an
+/// `EntryStarted`, a `Progress` whenever more bytes were written, and an
+/// `EntryFinished`, and finally either `Finished` or `Error`. Nothing is sent
+/// after `Finished` or `Error`.
+#[derive(Debug)]
+pub enum ZipEvent {
+    /// Extraction started
+    Started {
+        /// Number of entries that will be extracted, directories included
+        entries: usize,
+        /// Total uncompressed size of all entries, in bytes
+        bytes_total: u64,
+    },
+    /// An entry is about to be extracted
+    EntryStarted {
+        /// Name of the entry
+        name: String,
+        /// Uncompressed size of the entry, in bytes
+        size: u64,
+    },
+    /// Bytes extracted so far, across all entries
+    Progress {
+        /// Uncompressed bytes written so far
+        bytes: u64,
+    },
+    /// An entry was extracted
+    EntryFinished {
+        /// Name of the entry
+        name: String,
+    },
+    /// Every entry was extracted
+    Finished,
+    /// Extraction stopped because of an error
+    Error(ZipError),
+}
+
+/// Outcome of testing a single entry with [`ZipFile::test`](crate::ZipFile::test)
+#[derive(Debug)]
+pub struct EntryTestResult {
+    /// Name of the entry
+    pub name: String,
+    /// Why the entry failed (CRC mismatch, corrupt data, wrong password, ...), if it did
+    pub error: Option<ZipError>,
+}
+
+impl EntryTestResult {
+    /// Whether the entry decompressed cleanly and its CRC matched
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Per-entry report produced by [`ZipFile::test`](crate::ZipFile::test)
+#[derive(Debug, Default)]
+pub struct TestReport {
+    /// Results for every entry, in archive order
+    pub entries: Vec<EntryTestResult>,
+}
+
+impl TestReport {
+    /// Whether every entry passed
+    pub fn is_ok(&self) -> bool {
+        self.entries.iter().all(EntryTestResult::is_ok)
+    }
+
+    /// Iterate over the entries that failed
+    pub fn failures(&self) -> impl Iterator<Item = &EntryTestResult> {
+        self.entries.iter().filter(|entry| !entry.is_ok())
+    }
+}
+
+/// Options controlling what gets embedded when sealing an archive
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SealOptions {
+    /// Embed a table of contents listing every entry with its size and CRC32
+    pub embed_toc: bool,
+    /// Signature bytes to embed (e.g. a detached signature produced by the pipeline)
+    pub signature: Option<Vec<u8>>,
+    /// Provenance information to embed (builder, source revision, ...)
+    pub provenance: Option<String>,
+}
+
+impl SealOptions {
+    /// Entry name used for the embedded table of contents
+    pub const TOC_ENTRY: &'static str = "META-INF/SEAL/TOC";
+    /// Entry name used for the embedded signature
+    pub const SIGNATURE_ENTRY: &'static str = "META-INF/SEAL/SIGNATURE";
+    /// Entry name used for the embedded provenance information
+    pub const PROVENANCE_ENTRY: &'static str = "META-INF/SEAL/PROVENANCE";
+
+    /// Create new default options (nothing is embedded)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Embed a table of contents of the archive
+    pub fn with_toc(mut self, embed_toc: bool) -> Self {
+        self.embed_toc = embed_toc;
+        self
+    }
+
+    /// Embed a signature
+    pub fn with_signature<B: Into<Vec<u8>>>(mut self, signature: B) -> Self {
+        self.signature = Some(signature.into());
+        self
+    }
+
+    /// Embed provenance information
+    pub fn with_provenance<S: Into<String>>(mut self, provenance: S) -> Self {
+        self.provenance = Some(provenance.into());
+        self
+    }
+}
+
+/// Resource limits for the GraalVM isolate, see [`init_with_options`](crate::init_with_options)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct InitOptions {
+    /// Maximum Java heap size in MiB; by default the heap may grow to a share of physical memory
+    pub max_heap_mb: Option<u64>,
+    /// Address space to reserve for the isolate's heap in bytes, which caps how far it can grow
+    pub reserved_address_space: Option<u64>,
+}
+
+impl InitOptions {
+    /// Create new default options (GraalVM's default sizing)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the Java heap to `max_heap_mb` MiB
+    ///
+    /// Keeps memory use down on small devices; raise it when processing huge
+    /// archives fails with `ZipError::OutOfMemory`.
+    pub fn with_max_heap_mb(mut self, max_heap_mb: u64) -> Self {
+        self.max_heap_mb = Some(max_heap_mb);
+        self
+    }
+
+    /// Reserve `bytes` of address space for the isolate
+    pub fn with_reserved_address_space(mut self, bytes: u64) -> Self {
+        self.reserved_address_space = Some(bytes);
+        self
+    }
+}
+
+/// Versions baked into the native library, see [`native_version`](crate::native_version)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VersionInfo {
+    /// Version of zip4j the library was built with
+    pub zip4j: String,
+    /// Version of the native interface, incremented on incompatible changes
+    pub abi: u32,
+    /// Version of the GraalVM that compiled the library
+    pub graalvm: String,
+}
+
+impl fmt::Display for VersionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "zip4j {}, ABI {}, GraalVM {}", self.zip4j, self.abi, self.graalvm)
+    }
+}
+
+/// Heap usage and open handles of the GraalVM isolate, see [`diagnostics`](crate::diagnostics)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostics {
+    /// Bytes of Java heap in use
+    pub heap_used: u64,
+    /// Bytes of Java heap currently committed
+    pub heap_committed: u64,
+    /// Maximum size the Java heap may grow to in bytes
+    pub heap_max: u64,
+    /// OS threads attached to the shared isolate
+    pub attached_threads: usize,
+    /// Archives opened and not yet closed
+    pub open_archives: usize,
+    /// Entry handles not yet released
+    pub open_entries: usize,
+    /// Open input, output and chunked data streams
+    pub open_streams: usize,
+    /// Registered parameter sets not yet released
+    pub open_parameter_sets: usize,
+}
@@ -1,37 +1,106 @@
 use std::os::raw::{c_char, c_int, c_longlong};
+use std::path::{Component, Path, PathBuf};
 use crate::error::Result;
 use crate::ffi::{self, helpers};
-use crate::types::{CompressionMethod, EncryptionMethod};
+use crate::types::{CompressionMethod, EncryptionMethod, ZipEntryInfo};
 
 /// Represents an entry (file or directory) within a zip archive
 #[derive(Debug)]
 pub struct ZipEntry {
     handle: c_longlong,
+    /// Generation of the archive the handle was created in
+    generation: u64,
+    /// Isolate the handle belongs to, see [`ffi::isolate_id`]
+    isolate: usize,
+    /// Generation of that isolate, see [`ffi::epoch`]
+    epoch: u64,
+    /// Metadata fetched in bulk by [`ZipFile::entries`](crate::ZipFile::entries), if any
+    info: Option<ZipEntryInfo>,
 }
 
 impl ZipEntry {
-    /// Create a new ZipEntry from a handle
-    pub(crate) fn new(handle: c_longlong) -> Result<Self> {
+    /// Create a new ZipEntry from a handle obtained at the given archive generation
+    pub(crate) fn new(handle: c_longlong, generation: u64) -> Result<Self> {
         if handle == 0 {
             return Err(crate::error::ZipError::InvalidHandle);
         }
-        Ok(Self { handle })
+        Ok(Self { handle, generation, isolate: ffi::isolate_id(), epoch: ffi::epoch(), info: None })
+    }
+
+    /// Attach metadata that was already fetched, so the getters don't query it again
+    pub(crate) fn with_info(mut self, info: ZipEntryInfo) -> Self {
+        self.info = Some(info);
+        self
     }
     
     /// Get the internal handle
     pub(crate) fn handle(&self) -> c_longlong {
         self.handle
     }
+
+    /// Get the archive generation the handle was created in
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Get the isolate the handle belongs to
+    pub(crate) fn isolate(&self) -> usize {
+        self.isolate
+    }
+
+    /// Get the generation of the isolate the handle belongs to
+    pub(crate) fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Get the isolate thread to call into for this entry, failing with
+    /// `ZipError::InvalidHandle` outside the isolate it was created in or
+    /// after that isolate was cleaned up
+    fn thread(&self) -> Result<ffi::ThreadGuard> {
+        ffi::handle_thread(self.isolate, self.epoch)
+    }
+
+    /// Wrap an entry handle obtained from the zip4j-abi C API, for use with `zip_file`
+    ///
+    /// The entry takes ownership of the handle and releases it when dropped.
+    /// It counts as current for `zip_file` as the archive is now, so it goes
+    /// stale like any other entry once the archive is modified. Fails with
+    /// `ZipError::InvalidHandle` if `handle` is 0 or `zip_file` belongs to
+    /// another isolate.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be an entry handle, e.g. from `zip4j_get_entry_by_name`
+    /// or [`into_raw_handle`](Self::into_raw_handle), of the archive behind
+    /// `zip_file`, and mustn't be owned or released by anything else.
+    pub unsafe fn from_raw_handle(handle: c_longlong, zip_file: &crate::ZipFile) -> Result<Self> {
+        if zip_file.isolate() != ffi::isolate_id() || zip_file.epoch() != ffi::epoch() {
+            return Err(crate::error::ZipError::InvalidHandle);
+        }
+        Self::new(handle, zip_file.generation())
+    }
+
+    /// Give up ownership of the native entry handle, for use with the zip4j-abi C API
+    ///
+    /// The handle stays valid until it is released with `zip4j_release_entry`
+    /// or handed back to [`from_raw_handle`](Self::from_raw_handle).
+    pub fn into_raw_handle(mut self) -> c_longlong {
+        std::mem::replace(&mut self.handle, 0)
+    }
     
     /// Get the name of this entry
     pub fn name(&self) -> Result<String> {
+        if let Some(info) = &self.info {
+            return Ok(info.name.clone());
+        }
+
         const BUFFER_SIZE: usize = 1024;
         let mut buffer = vec![0u8; BUFFER_SIZE];
         let mut name_length: c_int = 0;
 
         let result = unsafe {
             ffi::zip4j_entry_get_name(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 buffer.as_mut_ptr() as *mut c_char,
                 BUFFER_SIZE as c_int,
@@ -40,7 +109,7 @@ impl ZipEntry {
         };
 
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
 
         helpers::read_string_from_buffer_u8(&buffer, name_length)
@@ -48,18 +117,22 @@ impl ZipEntry {
     
     /// Get the uncompressed size of this entry in bytes
     pub fn size(&self) -> Result<u64> {
+        if let Some(info) = &self.info {
+            return Ok(info.size);
+        }
+
         let mut size: c_longlong = 0;
         
         let result = unsafe {
             ffi::zip4j_entry_get_size(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 &mut size
             )
         };
         
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
         
         Ok(size as u64)
@@ -67,18 +140,22 @@ impl ZipEntry {
     
     /// Get the compressed size of this entry in bytes
     pub fn compressed_size(&self) -> Result<u64> {
+        if let Some(info) = &self.info {
+            return Ok(info.compressed_size);
+        }
+
         let mut compressed_size: c_longlong = 0;
         
         let result = unsafe {
             ffi::zip4j_entry_get_compressed_size(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 &mut compressed_size
             )
         };
         
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
         
         Ok(compressed_size as u64)
@@ -86,18 +163,22 @@ impl ZipEntry {
     
     /// Check if this entry is a directory
     pub fn is_directory(&self) -> Result<bool> {
+        if let Some(info) = &self.info {
+            return Ok(info.is_directory);
+        }
+
         let mut is_directory: c_int = 0;
         
         let result = unsafe {
             ffi::zip4j_entry_is_directory(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 &mut is_directory
             )
         };
         
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
         
         Ok(is_directory != 0)
@@ -105,18 +186,22 @@ impl ZipEntry {
     
     /// Check if this entry is encrypted
     pub fn is_encrypted(&self) -> Result<bool> {
+        if let Some(info) = &self.info {
+            return Ok(info.is_encrypted);
+        }
+
         let mut is_encrypted: c_int = 0;
         
         let result = unsafe {
             ffi::zip4j_entry_is_encrypted(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 &mut is_encrypted
             )
         };
         
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
         
         Ok(is_encrypted != 0)
@@ -124,18 +209,22 @@ impl ZipEntry {
     
     /// Get the CRC32 checksum of this entry
     pub fn crc32(&self) -> Result<u32> {
+        if let Some(info) = &self.info {
+            return Ok(info.crc32);
+        }
+
         let mut crc: c_longlong = 0;
         
         let result = unsafe {
             ffi::zip4j_entry_get_crc(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 &mut crc
             )
         };
         
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
         
         Ok(crc as u32)
@@ -143,18 +232,22 @@ impl ZipEntry {
     
     /// Get the last modified time of this entry (DOS time format)
     pub fn last_modified_time(&self) -> Result<u32> {
+        if let Some(info) = &self.info {
+            return Ok(info.last_modified_time);
+        }
+
         let mut time: c_longlong = 0;
         
         let result = unsafe {
             ffi::zip4j_entry_get_last_modified_time(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 &mut time
             )
         };
         
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
         
         Ok(time as u32)
@@ -162,18 +255,22 @@ impl ZipEntry {
     
     /// Get the compression method used for this entry
     pub fn compression_method(&self) -> Result<CompressionMethod> {
+        if let Some(info) = &self.info {
+            return Ok(info.compression_method);
+        }
+
         let mut method: c_int = 0;
         
         let result = unsafe {
             ffi::zip4j_entry_get_compression_method(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 &mut method
             )
         };
         
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
         
         Ok(CompressionMethod::from(method))
@@ -181,23 +278,175 @@ impl ZipEntry {
     
     /// Get the encryption method used for this entry
     pub fn encryption_method(&self) -> Result<EncryptionMethod> {
+        if let Some(info) = &self.info {
+            return Ok(info.encryption_method);
+        }
+
         let mut method: c_int = 0;
         
         let result = unsafe {
             ffi::zip4j_entry_get_encryption_method(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 &mut method
             )
         };
         
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
         
         Ok(EncryptionMethod::from(method))
     }
     
+    /// Get the version-needed-to-extract header field
+    ///
+    /// The low byte is the minimum ZIP specification version a reader must
+    /// support, times ten: e.g. 20 for deflate, 45 for Zip64, 51 for AES.
+    pub fn version_needed_to_extract(&self) -> Result<u16> {
+        let mut version: c_int = 0;
+        
+        let result = unsafe {
+            ffi::zip4j_entry_get_version_needed(
+                *self.thread()?,
+                self.handle,
+                &mut version
+            )
+        };
+        
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+        
+        Ok(version as u16)
+    }
+    
+    /// Get the version-made-by header field
+    ///
+    /// The high byte identifies the host system that created the entry (0 for
+    /// MS-DOS/Windows, 3 for Unix, 19 for macOS); the low byte is the ZIP
+    /// specification version it was written with, times ten.
+    pub fn version_made_by(&self) -> Result<u16> {
+        let mut version: c_int = 0;
+        
+        let result = unsafe {
+            ffi::zip4j_entry_get_version_made_by(
+                *self.thread()?,
+                self.handle,
+                &mut version
+            )
+        };
+        
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+        
+        Ok(version as u16)
+    }
+    
+    /// Get the offset of this entry's local header within the archive file
+    ///
+    /// For split archives the offset is within the part that holds the header.
+    pub fn offset(&self) -> Result<u64> {
+        Ok(self.offsets()?.0)
+    }
+    
+    /// Get the offset of this entry's compressed data within the archive file
+    ///
+    /// The data spans [`compressed_size`](ZipEntry::compressed_size) bytes from
+    /// here (including the encryption header, if any), so it can be fetched
+    /// with a single HTTP range request.
+    pub fn data_offset(&self) -> Result<u64> {
+        Ok(self.offsets()?.1)
+    }
+    
+    /// Get the local header and compressed data offsets
+    fn offsets(&self) -> Result<(u64, u64)> {
+        let mut header_offset: c_longlong = 0;
+        let mut data_offset: c_longlong = 0;
+        
+        let result = unsafe {
+            ffi::zip4j_entry_get_offsets(
+                *self.thread()?,
+                self.handle,
+                &mut header_offset,
+                &mut data_offset
+            )
+        };
+        
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+        
+        Ok((header_offset as u64, data_offset as u64))
+    }
+    
+    /// Get the raw general purpose bit flags of this entry
+    pub fn general_purpose_flags(&self) -> Result<u16> {
+        if let Some(info) = &self.info {
+            return Ok(info.general_purpose_flags);
+        }
+
+        let mut flags: c_int = 0;
+        
+        let result = unsafe {
+            ffi::zip4j_entry_get_general_purpose_flags(
+                *self.thread()?,
+                self.handle,
+                &mut flags
+            )
+        };
+        
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+        
+        Ok(flags as u16)
+    }
+    
+    /// Check whether sizes and CRC follow the data in a data descriptor (bit 3)
+    pub fn has_data_descriptor(&self) -> Result<bool> {
+        Ok(self.general_purpose_flags()? & (1 << 3) != 0)
+    }
+    
+    /// Check whether the name and comment are flagged as UTF-8 (bit 11)
+    pub fn has_utf8_name(&self) -> Result<bool> {
+        Ok(self.general_purpose_flags()? & (1 << 11) != 0)
+    }
+    
+    /// Check whether the entry uses PKWARE strong encryption (bit 6)
+    pub fn uses_strong_encryption(&self) -> Result<bool> {
+        Ok(self.general_purpose_flags()? & (1 << 6) != 0)
+    }
+    
+    /// Read all metadata of this entry into an owned [`ZipEntryInfo`]
+    pub fn info(&self) -> Result<ZipEntryInfo> {
+        if let Some(info) = &self.info {
+            return Ok(info.clone());
+        }
+
+        Ok(ZipEntryInfo {
+            name: self.name()?,
+            size: self.size()?,
+            compressed_size: self.compressed_size()?,
+            crc32: self.crc32()?,
+            compression_method: self.compression_method()?,
+            encryption_method: self.encryption_method()?,
+            is_directory: self.is_directory()?,
+            is_encrypted: self.is_encrypted()?,
+            last_modified_time: self.last_modified_time()?,
+            general_purpose_flags: self.general_purpose_flags()?,
+        })
+    }
+    
+    /// Compute the path this entry is extracted to below `dest_dir`
+    ///
+    /// Fails with `ZipError::InvalidParameter` if the name would escape
+    /// `dest_dir`, e.g. through `..` or an absolute path.
+    pub fn extraction_path<P: AsRef<Path>>(&self, dest_dir: P) -> Result<PathBuf> {
+        extraction_path(dest_dir.as_ref(), &self.name()?)
+    }
+    
     /// Get the compression ratio as a percentage (0-100)
     pub fn compression_ratio(&self) -> Result<f64> {
         let size = self.size()? as f64;
@@ -212,11 +461,34 @@ impl ZipEntry {
     }
 }
 
+/// Join an entry name to the destination directory, refusing names that escape it
+pub(crate) fn extraction_path(dest_dir: &Path, name: &str) -> Result<PathBuf> {
+    let mut path = dest_dir.to_path_buf();
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            _ => {
+                return Err(crate::error::ZipError::InvalidParameter(
+                    format!("Entry name escapes the destination directory: {}", name)
+                ));
+            }
+        }
+    }
+    Ok(path)
+}
+
 impl Drop for ZipEntry {
     fn drop(&mut self) {
-        // Release the entry handle
-        unsafe {
-            ffi::zip4j_release_entry(ffi::get_thread(), self.handle);
+        // Release the entry handle in the isolate it belongs to, unless it was
+        // given up, the runtime was cleaned up in the meantime, or the isolate
+        // can't be reached from here
+        if self.handle != 0 {
+            if let Some(thread) = ffi::release_thread(self.isolate, self.epoch) {
+                unsafe {
+                    ffi::zip4j_release_entry(*thread, self.handle);
+                }
+            }
         }
     }
 }
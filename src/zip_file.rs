@@ -1,19 +1,54 @@
-use std::path::Path;
+use std::borrow::Borrow;
+use std::collections::{btree_map, hash_map, BTreeMap, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
 use std::os::raw::{c_char, c_int, c_longlong};
+use glob::{MatchOptions, Pattern};
+use zeroize::Zeroizing;
 use crate::error::Result;
 use crate::ffi::{self, helpers};
+use crate::telemetry;
 use crate::zip_entry::ZipEntry;
-use crate::types::ZipParameters;
+use crate::types::{
+    AddDirOptions, ChangeDetection, CompressionMethod, ConflictResolution, DiffKind, DirEntry,
+    DuplicatePolicy, EncryptionMethod, EntryCondition, EntryDiff, EntryDigest, EntryHealth,
+    EntrySource, EntryTestResult, ExtractOptions, ExtractReport, ExtractedEntry, FileConflict,
+    NameNormalization, NativeParameters, OpenMode, Password, Progress, SalvageReport, SaveMode,
+    SealOptions, SymlinkPolicy, SyncOptions, SyncReport, TestReport, ZipEntryBuilder, ZipEntryInfo,
+    ZipEvent, ZipParameters,
+};
 
 /// Represents a zip file that can be read from or written to
+///
+/// `ZipFile` is `Send` and `Sync`: it can be moved into worker threads or
+/// async tasks, and shared by reference to read entries from several threads
+/// at once. Each OS thread is attached to the GraalVM isolate the first time
+/// it calls into the native library.
 #[derive(Debug)]
 pub struct ZipFile {
     handle: c_longlong,
-    path: String,
+    path: PathBuf,
+    sealed: bool,
+    /// Number of modifications so far, so iterators can notice that indices shifted
+    generation: u64,
+    save_mode: SaveMode,
+    /// Whether a modification is currently applied to a staged copy
+    staging: bool,
+    duplicate_policy: DuplicatePolicy,
+    name_normalization: NameNormalization,
+    /// Isolate the handle belongs to, see [`ffi::isolate_id`]
+    isolate: usize,
+    /// Generation of that isolate, see [`ffi::epoch`]
+    epoch: u64,
 }
 
 impl ZipFile {
     /// Create a new zip file or open an existing one
+    ///
+    /// Use [`create_new`](Self::create_new) or [`open_existing`](Self::open_existing)
+    /// to fail instead of silently doing the other.
     /// 
     /// # Arguments
     /// 
@@ -27,7 +62,9 @@ impl ZipFile {
     /// let zip = ZipFile::new("archive.zip")?;
     /// # Ok::<(), zip2rs::ZipError>(())
     /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()), err))]
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let _timer = telemetry::OperationTimer::start("new");
         ffi::ensure_initialized()?;
 
         let path_str = path.as_ref().to_string_lossy().to_string();
@@ -36,19 +73,148 @@ impl ZipFile {
 
         let result = unsafe {
             ffi::zip4j_create(
-                ffi::get_thread(),
+                *ffi::get_thread()?,
                 c_path.as_ptr() as *mut c_char,
                 &mut handle
             )
         };
 
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result).with_context("open", path.as_ref().display()));
         }
 
-        Ok(Self { handle, path: path_str })
+        Ok(Self::from_handle(handle, path.as_ref().into()))
     }
     
+    /// Open a zip file, choosing whether it must or must not exist yet
+    ///
+    /// A new archive is only written to disk once something is added to it,
+    /// except with `OpenMode::CreateNew`, which claims the path right away by
+    /// creating an empty file so nothing else can create it in the meantime.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the zip file
+    /// * `mode` - Whether to create the archive, open it, or both
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{OpenMode, ZipFile};
+    ///
+    /// let zip = ZipFile::open("dist/release.zip", OpenMode::CreateNew)?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()), err))]
+    pub fn open<P: AsRef<Path>>(path: P, mode: OpenMode) -> Result<Self> {
+        match mode {
+            OpenMode::CreateNew => {
+                match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                    Ok(_) => {}
+                    Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                        return Err(crate::error::ZipError::FileExists);
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+                // zip4j treats the empty file as an empty archive
+                Self::new(&path).inspect_err(|_| {
+                    let _ = std::fs::remove_file(&path);
+                })
+            }
+            OpenMode::OpenExisting if !path.as_ref().try_exists()? => Err(crate::error::ZipError::FileNotFound),
+            _ => Self::new(path),
+        }
+    }
+
+    /// Start a new zip file, failing with `ZipError::FileExists` if the file already exists
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()), err))]
+    pub fn create_new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open(path, OpenMode::CreateNew)
+    }
+
+    /// Open an existing zip file, failing with `ZipError::FileNotFound` if it is missing
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()), err))]
+    pub fn open_existing<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open(path, OpenMode::OpenExisting)
+    }
+
+    /// Wrap a freshly opened native handle
+    fn from_handle(handle: c_longlong, path: PathBuf) -> Self {
+        Self {
+            handle,
+            path,
+            sealed: false,
+            generation: 0,
+            save_mode: SaveMode::InPlace,
+            staging: false,
+            duplicate_policy: DuplicatePolicy::First,
+            name_normalization: NameNormalization::Unchanged,
+            isolate: ffi::isolate_id(),
+            epoch: ffi::epoch(),
+        }
+    }
+
+    /// Wrap an archive handle obtained from the zip4j-abi C API
+    ///
+    /// The zip file takes ownership of the handle and closes it when dropped.
+    /// Fails with `ZipError::InvalidHandle` if `handle` is 0, or with the
+    /// native error if the library doesn't know the handle; the handle is
+    /// left to the caller then.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be an open archive handle, e.g. from `zip4j_create` or
+    /// [`into_raw_handle`](Self::into_raw_handle), that belongs to the current
+    /// isolate and isn't owned or closed by anything else.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let handle = ZipFile::new("archive.zip")?.into_raw_handle();
+    /// // ... pass `handle` to code using the C API directly ...
+    /// let zip = unsafe { ZipFile::from_raw_handle(handle)? };
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    pub unsafe fn from_raw_handle(handle: c_longlong) -> Result<Self> {
+        if handle == 0 {
+            return Err(crate::error::ZipError::InvalidHandle);
+        }
+
+        let mut zip_file = Self::from_handle(handle, PathBuf::new());
+        match zip_file.file_path() {
+            Ok(path) => {
+                zip_file.path = path;
+                Ok(zip_file)
+            }
+            Err(e) => {
+                zip_file.handle = 0;
+                Err(e)
+            }
+        }
+    }
+
+    /// Give up ownership of the native archive handle, for use with the zip4j-abi C API
+    ///
+    /// The handle stays open: close it with `zip4j_close`, or hand it back to
+    /// [`from_raw_handle`](Self::from_raw_handle). It is only valid in the
+    /// isolate the zip file was opened in, and is 0 if the zip file was
+    /// already closed.
+    pub fn into_raw_handle(mut self) -> c_longlong {
+        std::mem::replace(&mut self.handle, 0)
+    }
+
+    /// Turn the zip file into a [`ZipReader`](crate::ZipReader), which only allows reading
+    pub fn into_reader(self) -> crate::ZipReader {
+        self.into()
+    }
+
+    /// Turn the zip file into a [`ZipWriter`](crate::ZipWriter), which only allows modifications
+    pub fn into_writer(self) -> crate::ZipWriter {
+        self.into()
+    }
+
     /// Create a new zip file or open an existing one with a password
     /// 
     /// # Arguments
@@ -64,17 +230,19 @@ impl ZipFile {
     /// let zip = ZipFile::with_password("archive.zip", "secret")?;
     /// # Ok::<(), zip2rs::ZipError>(())
     /// ```
-    pub fn with_password<P: AsRef<Path>, S: AsRef<str>>(path: P, password: S) -> Result<Self> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()), err))]
+    pub fn with_password<P: AsRef<Path>, S: Into<Password>>(path: P, password: S) -> Result<Self> {
+        let _timer = telemetry::OperationTimer::start("with_password");
         ffi::ensure_initialized()?;
 
         let path_str = path.as_ref().to_string_lossy().to_string();
         let c_path = helpers::to_c_string(&path_str)?;
-        let c_password = helpers::to_c_string(password.as_ref())?;
+        let c_password = password.into().to_c_string()?;
         let mut handle: c_longlong = 0;
 
         let result = unsafe {
             ffi::zip4j_create_with_password(
-                ffi::get_thread(),
+                *ffi::get_thread()?,
                 c_path.as_ptr() as *mut c_char,
                 c_password.as_ptr() as *mut c_char,
                 &mut handle
@@ -82,44 +250,328 @@ impl ZipFile {
         };
 
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result).with_context("open", path.as_ref().display()));
         }
 
-        Ok(Self { handle, path: path_str })
+        Ok(Self::from_handle(handle, path.as_ref().into()))
     }
     
+    /// Create a split zip archive containing the given files
+    ///
+    /// The archive is written as `name.z01`, `name.z02`, ... parts of at most
+    /// `split_size` bytes each, followed by the final `name.zip`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the final part (must not exist yet)
+    /// * `file_paths` - Files to store in the archive
+    /// * `split_size` - Maximum size of each part in bytes (at least 64 KiB)
+    /// * `params` - Compression and encryption parameters
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{ZipFile, ZipParameters};
+    ///
+    /// // 100 MiB parts
+    /// let zip = ZipFile::create_split("backup.zip", &["db.dump", "uploads.tar"], 100 * 1024 * 1024, &ZipParameters::new())?;
+    /// assert!(zip.is_split_archive()?);
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()), err))]
+    pub fn create_split<P: AsRef<Path>, F: AsRef<Path>>(path: P, file_paths: &[F], split_size: u64, params: &ZipParameters) -> Result<Self> {
+        let _timer = telemetry::OperationTimer::start("create_split");
+        ffi::ensure_initialized()?;
+
+        let path_str = path.as_ref().to_string_lossy().to_string();
+        let c_path = helpers::to_c_string(&path_str)?;
+        let paths: Vec<_> = file_paths.iter().map(|p| p.as_ref().to_string_lossy()).collect();
+        let c_paths = helpers::to_c_string_list(&paths)?;
+        let native_params = params.to_native()?;
+        let mut handle: c_longlong = 0;
+
+        let result = unsafe {
+            ffi::zip4j_create_split_zip_from_files(
+                *ffi::get_thread()?,
+                c_path.as_ptr() as *mut c_char,
+                c_paths.as_ptr() as *mut c_char,
+                c_paths.len() as c_int,
+                native_params.handle(),
+                split_size as c_longlong,
+                &mut handle
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("create_split", path.as_ref().display()));
+        }
+
+        Ok(Self::from_handle(handle, path.as_ref().into()))
+    }
+
+    /// Create a split zip archive containing a folder and everything below it
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the final part (must not exist yet)
+    /// * `folder` - Folder to store in the archive
+    /// * `split_size` - Maximum size of each part in bytes (at least 64 KiB)
+    /// * `params` - Compression and encryption parameters
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display(), folder = %folder.as_ref().display()), err))]
+    pub fn create_split_from_folder<P: AsRef<Path>, F: AsRef<Path>>(path: P, folder: F, split_size: u64, params: &ZipParameters) -> Result<Self> {
+        let _timer = telemetry::OperationTimer::start("create_split_from_folder");
+        ffi::ensure_initialized()?;
+
+        let path_str = path.as_ref().to_string_lossy().to_string();
+        let c_path = helpers::to_c_string(&path_str)?;
+        let c_folder = helpers::to_c_string(&folder.as_ref().to_string_lossy())?;
+        let native_params = params.to_native()?;
+        let mut handle: c_longlong = 0;
+
+        let result = unsafe {
+            ffi::zip4j_create_split_zip_from_folder(
+                *ffi::get_thread()?,
+                c_path.as_ptr() as *mut c_char,
+                c_folder.as_ptr() as *mut c_char,
+                native_params.handle(),
+                split_size as c_longlong,
+                &mut handle
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("create_split_from_folder", path.as_ref().display()));
+        }
+
+        Ok(Self::from_handle(handle, path.as_ref().into()))
+    }
+
+    /// Create a new archive whose first entry is stored uncompressed and unencrypted
+    ///
+    /// Container formats such as EPUB/OCF (and some OOXML validators) require a
+    /// designated entry to come first in the file, stored, unencrypted and
+    /// without an extra field, so it can be sniffed at a fixed offset. That entry
+    /// is written here before anything else; entries added afterwards are
+    /// appended after it, whatever their parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the archive (must not exist yet)
+    /// * `first_entry` - Name of the leading entry (e.g. `mimetype`)
+    /// * `data` - Contents of the leading entry
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{ZipFile, ZipParameters};
+    ///
+    /// let mut epub = ZipFile::create_container("book.epub", "mimetype", b"application/epub+zip")?;
+    /// epub.add_directory_with_params("book/META-INF", &ZipParameters::new())?;
+    /// epub.add_directory_with_params("book/OEBPS", &ZipParameters::new())?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()), err))]
+    pub fn create_container<P: AsRef<Path>, S: AsRef<str>>(path: P, first_entry: S, data: &[u8]) -> Result<Self> {
+        let _timer = telemetry::OperationTimer::start("create_container");
+        ffi::ensure_initialized()?;
+
+        let path_str = path.as_ref().to_string_lossy().to_string();
+        let c_path = helpers::to_c_string(&path_str)?;
+        let c_name = helpers::to_c_string(first_entry.as_ref())?;
+        let mut handle: c_longlong = 0;
+
+        let result = unsafe {
+            ffi::zip4j_create_container(
+                *ffi::get_thread()?,
+                c_path.as_ptr() as *mut c_char,
+                c_name.as_ptr() as *mut c_char,
+                data.as_ptr() as *mut c_char,
+                data.len() as c_int,
+                &mut handle
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("create_container", path.as_ref().display()));
+        }
+
+        Ok(Self::from_handle(handle, path.as_ref().into()))
+    }
+
     /// Set or change the password for the zip file
     /// 
     /// # Arguments
     /// 
     /// * `password` - New password for the zip file
-    pub fn set_password<S: AsRef<str>>(&mut self, password: S) -> Result<()> {
-        let c_password = helpers::to_c_string(password.as_ref())?;
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn set_password<S: Into<Password>>(&mut self, password: S) -> Result<()> {
+        let c_password = password.into().to_c_string()?;
         
         let result = unsafe {
             ffi::zip4j_set_password(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 c_password.as_ptr() as *mut c_char
             )
         };
         
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
         
         Ok(())
     }
     
+    /// Check whether a password opens this archive, without extracting it
+    ///
+    /// The password is checked against the smallest encrypted entry: AES entries
+    /// through their password verification bytes, ZipCrypto entries by reading
+    /// the entry and validating its CRC. Returns `true` if the archive has no
+    /// encrypted entries.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let mut zip = ZipFile::new("secrets.zip")?;
+    /// if zip.verify_password("user input")? {
+    ///     zip.set_password("user input")?;
+    ///     zip.extract_all("output")?;
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn verify_password<S: Into<Password>>(&self, password: S) -> Result<bool> {
+        let c_password = password.into().to_c_string()?;
+        let mut is_valid: c_int = 0;
+
+        let result = unsafe {
+            ffi::zip4j_verify_password(
+                *self.thread()?,
+                self.handle,
+                c_password.as_ptr() as *mut c_char,
+                &mut is_valid
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
+        Ok(is_valid != 0)
+    }
+
+    /// Find which of several candidate passwords opens this archive
+    ///
+    /// Checks the candidates in order the same way as [`ZipFile::verify_password`],
+    /// but against a single entry and reader, so it is much cheaper than calling
+    /// that in a loop. Returns the index of the first matching candidate, or `None`
+    /// if none match. If the archive has no encrypted entries, the first candidate
+    /// matches.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let known = ["2019-backup", "2020-backup", "hunter2"];
+    /// let mut zip = ZipFile::new("old.zip")?;
+    /// if let Some(index) = zip.find_password(&known)? {
+    ///     zip.set_password(known[index])?;
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), candidates = candidates.len()), err))]
+    pub fn find_password<P: Clone + Into<Password>>(&self, candidates: &[P]) -> Result<Option<usize>> {
+        let mut c_candidates = Zeroizing::new(Vec::new());
+        for candidate in candidates {
+            c_candidates.extend_from_slice(&candidate.clone().into().to_c_string()?);
+        }
+        let mut index: c_int = -1;
+
+        let result = unsafe {
+            ffi::zip4j_find_password(
+                *self.thread()?,
+                self.handle,
+                c_candidates.as_ptr() as *mut c_char,
+                c_candidates.len() as c_int,
+                &mut index
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
+        Ok(usize::try_from(index).ok())
+    }
+
+    /// Re-encrypt every encrypted entry with a new password
+    ///
+    /// Unlike [`ZipFile::set_password`], which only changes the password used to
+    /// read the archive, this decrypts the stored data with `old_password` and
+    /// rewrites the archive with every encrypted entry protected by
+    /// `new_password`. Entries keep their encryption method; unencrypted entries
+    /// stay unencrypted. Split archives are not supported.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let mut zip = ZipFile::new("secrets.zip")?;
+    /// zip.change_password("old-secret", "new-secret")?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn change_password<S1: Into<Password>, S2: Into<Password>>(&mut self, old_password: S1, new_password: S2) -> Result<()> {
+        self.rewrite_encryption(&old_password.into(), &new_password.into(), -1)
+    }
+
+    /// Re-encrypt every encrypted entry with a new password and encryption method
+    ///
+    /// Works like [`ZipFile::change_password`], but also switches the encrypted
+    /// entries to `method`. `EncryptionMethod::None` stores them decrypted.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn change_password_with_method<S1: Into<Password>, S2: Into<Password>>(&mut self, old_password: S1, new_password: S2, method: EncryptionMethod) -> Result<()> {
+        self.rewrite_encryption(&old_password.into(), &new_password.into(), method.into())
+    }
+
+    /// Rewrite encrypted entries with a new password (`method` of -1 keeps each entry's method)
+    fn rewrite_encryption(&mut self, old_password: &Password, new_password: &Password, method: c_int) -> Result<()> {
+        self.modify(|zip| {
+            let c_old = old_password.to_c_string()?;
+            let c_new = new_password.to_c_string()?;
+
+            let result = unsafe {
+                ffi::zip4j_change_password(
+                    *zip.thread()?,
+                    zip.handle,
+                    c_old.as_ptr() as *mut c_char,
+                    c_new.as_ptr() as *mut c_char,
+                    method
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+
+            Ok(())
+        })
+    }
+
     /// Check if the zip file is valid
     ///
     /// Note: A newly created ZIP file may not be valid until entries are added
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
     pub fn is_valid(&self) -> Result<bool> {
         let mut is_valid: c_int = 0;
 
         let result = unsafe {
             ffi::zip4j_is_valid(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 &mut is_valid
             )
@@ -127,147 +579,451 @@ impl ZipFile {
 
         // Don't treat "invalid" as an error - it's just a state
         if helpers::is_error(result) && result != ffi::constants::ERROR_INVALID_PARAMETER {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
 
         Ok(is_valid != 0)
     }
     
     /// Check if the zip file is encrypted
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
     pub fn is_encrypted(&self) -> Result<bool> {
         let mut is_encrypted: c_int = 0;
         
         let result = unsafe {
             ffi::zip4j_is_encrypted(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 &mut is_encrypted
             )
         };
         
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
         
         Ok(is_encrypted != 0)
     }
     
     /// Check if the zip file is a split archive
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
     pub fn is_split_archive(&self) -> Result<bool> {
         let mut is_split: c_int = 0;
         
         let result = unsafe {
             ffi::zip4j_is_split_archive(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 &mut is_split
             )
         };
         
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
         
         Ok(is_split != 0)
     }
-    
-    /// Get the file path of the zip file
-    pub fn file_path(&self) -> Result<String> {
-        const BUFFER_SIZE: usize = 1024;
-        let mut buffer = vec![0u8; BUFFER_SIZE];
-        let mut path_length: c_int = 0;
+
+    /// Get the size of the archive on disk in bytes
+    ///
+    /// For split archives this is the total size of all parts. An archive
+    /// that hasn't been written yet has a size of 0.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("backup.zip")?;
+    /// let uncompressed: u64 = zip.entry_infos()?.iter().map(|info| info.size).sum();
+    /// println!("{} bytes, {} uncompressed", zip.archive_size()?, uncompressed);
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn archive_size(&self) -> Result<u64> {
+        let mut size: c_longlong = 0;
 
         let result = unsafe {
-            ffi::zip4j_get_file_path(
-                ffi::get_thread(),
+            ffi::zip4j_get_archive_size(
+                *self.thread()?,
                 self.handle,
-                buffer.as_mut_ptr() as *mut c_char,
-                BUFFER_SIZE as c_int,
-                &mut path_length
+                &mut size
             )
         };
 
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
 
-        helpers::read_string_from_buffer_u8(&buffer, path_length)
+        Ok(size as u64)
     }
-    
-    /// Get the comment of the zip file
-    ///
-    /// Returns an empty string if the ZIP file has no comment or is invalid
-    pub fn comment(&self) -> Result<String> {
-        // Check if the ZIP is valid first
-        if !self.is_valid()? {
-            return Ok(String::new());
-        }
 
-        const BUFFER_SIZE: usize = 1024;
-        let mut buffer = vec![0u8; BUFFER_SIZE];
-        let mut comment_length: c_int = 0;
+    /// Merge a split archive into a single zip file
+    ///
+    /// The parts of this archive are left untouched; the consolidated archive is
+    /// written to `output_path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_path` - Path of the merged zip file
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("backup.zip")?;
+    /// if zip.is_split_archive()? {
+    ///     zip.merge_split("backup-merged.zip")?;
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), output = %output_path.as_ref().display()), err))]
+    pub fn merge_split<P: AsRef<Path>>(&self, output_path: P) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("merge_split");
+        let path_str = output_path.as_ref().to_string_lossy();
+        let c_path = helpers::to_c_string(&path_str)?;
 
         let result = unsafe {
-            ffi::zip4j_get_comment(
-                ffi::get_thread(),
+            ffi::zip4j_merge_split_files(
+                *self.thread()?,
                 self.handle,
-                buffer.as_mut_ptr() as *mut c_char,
-                BUFFER_SIZE as c_int,
-                &mut comment_length
+                c_path.as_ptr() as *mut c_char
             )
         };
 
         if helpers::is_error(result) {
-            return Ok(String::new()); // Return empty string instead of error
+            return Err(crate::error::ZipError::from_native(result).with_context("merge_split", output_path.as_ref().display()));
         }
 
-        helpers::read_string_from_buffer_u8(&buffer, comment_length)
+        Ok(())
     }
 
-    /// Set the comment of the zip file
+    /// Merge a split archive into a single zip file, reporting progress
     ///
-    /// # Arguments
+    /// `on_progress` is called periodically with the bytes merged so far;
+    /// returning `false` cancels the merge, which then fails with
+    /// `ZipError::OperationCancelled`.
     ///
-    /// * `comment` - New comment for the zip file
+    /// # Examples
     ///
-    /// # Note
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
     ///
-    /// This operation may fail if the ZIP file is empty or invalid.
-    /// Add entries to the ZIP file first to ensure it's valid.
-    pub fn set_comment<S: AsRef<str>>(&mut self, comment: S) -> Result<()> {
+    /// let zip = ZipFile::new("backup.zip")?;
+    /// zip.merge_split_with_progress("backup-merged.zip", |progress| {
+    ///     println!("{}% ({} / {} bytes)", progress.percent, progress.bytes_done, progress.bytes_total);
+    ///     true
+    /// })?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), output = %output_path.as_ref().display()), err))]
+    pub fn merge_split_with_progress<P, F>(&self, output_path: P, on_progress: F) -> Result<()>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&Progress) -> bool,
+    {
+        let _timer = telemetry::OperationTimer::start("merge_split_with_progress");
+        let path_str = output_path.as_ref().to_string_lossy();
+        let c_path = helpers::to_c_string(&path_str)?;
+        let mut monitor: c_longlong = 0;
+
+        let result = unsafe {
+            ffi::zip4j_merge_split_files_async(
+                *self.thread()?,
+                self.handle,
+                c_path.as_ptr() as *mut c_char,
+                &mut monitor
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("merge_split", output_path.as_ref().display()));
+        }
+
+        OperationMonitor { handle: monitor }.wait(on_progress)
+    }
+
+    /// Rewrite this archive as a new split archive
+    ///
+    /// Every entry is copied into `output_path` and its `.z01`, `.z02`, ... parts,
+    /// keeping names, modification times, comments, compression methods and
+    /// encryption. Data is re-compressed along the way, and encrypted entries are
+    /// re-encrypted with the archive password (see [`ZipFile::with_password`]).
+    /// Other attributes, such as Unix permissions, are not carried over.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_path` - Path of the final part (must not exist yet)
+    /// * `split_size` - Maximum size of each part in bytes (at least 64 KiB)
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("artifact.zip")?;
+    /// // Parts small enough for an upload limit of 25 MiB
+    /// let split = zip.split_to("upload/artifact.zip", 25 * 1024 * 1024)?;
+    /// assert!(split.is_split_archive()?);
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), output = %output_path.as_ref().display(), split_size = split_size), err))]
+    pub fn split_to<P: AsRef<Path>>(&self, output_path: P, split_size: u64) -> Result<ZipFile> {
+        let _timer = telemetry::OperationTimer::start("split_to");
+        let path_str = output_path.as_ref().to_string_lossy().to_string();
+        let c_path = helpers::to_c_string(&path_str)?;
+        let mut handle: c_longlong = 0;
+
+        let result = unsafe {
+            ffi::zip4j_split_archive(
+                *self.thread()?,
+                self.handle,
+                c_path.as_ptr() as *mut c_char,
+                split_size as c_longlong,
+                &mut handle
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("split_to", output_path.as_ref().display()));
+        }
+
+        Ok(Self::from_handle(handle, output_path.as_ref().into()))
+    }
+
+    /// Rewrite this archive as a new split archive, reporting progress
+    ///
+    /// Works like [`ZipFile::split_to`]. `on_progress` is called periodically
+    /// with the uncompressed bytes copied so far; returning `false` cancels the
+    /// operation, removes the parts written so far and fails with
+    /// `ZipError::OperationCancelled`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), output = %output_path.as_ref().display(), split_size = split_size), err))]
+    pub fn split_to_with_progress<P, F>(&self, output_path: P, split_size: u64, on_progress: F) -> Result<ZipFile>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&Progress) -> bool,
+    {
+        let _timer = telemetry::OperationTimer::start("split_to_with_progress");
+        let path_str = output_path.as_ref().to_string_lossy().to_string();
+        let c_path = helpers::to_c_string(&path_str)?;
+        let mut handle: c_longlong = 0;
+        let mut monitor: c_longlong = 0;
+
+        let result = unsafe {
+            ffi::zip4j_split_archive_async(
+                *self.thread()?,
+                self.handle,
+                c_path.as_ptr() as *mut c_char,
+                split_size as c_longlong,
+                &mut handle,
+                &mut monitor
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("split_to", output_path.as_ref().display()));
+        }
+
+        let split = Self::from_handle(handle, output_path.as_ref().into());
+        OperationMonitor { handle: monitor }.wait(on_progress)?;
+
+        Ok(split)
+    }
+    
+    /// Copy the archive to another location
+    ///
+    /// The copy is byte for byte and this zip file stays open on the original.
+    /// Split archives can't be copied this way; use
+    /// [`merge_split`](Self::merge_split) instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_path` - Path of the copy, replaced if it exists
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("app.zip")?;
+    /// zip.save_as("app-patched.zip")?;
+    /// let mut patched = ZipFile::new("app-patched.zip")?;
+    /// patched.remove_file("debug.log")?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), output = %output_path.as_ref().display()), err))]
+    pub fn save_as<P: AsRef<Path>>(&self, output_path: P) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("save_as");
+        self.save_copy(output_path.as_ref(), false)
+    }
+
+    /// Copy the archive to another location, keeping only its entries and
+    /// central directory
+    ///
+    /// Bytes no central directory record refers to, such as data left behind
+    /// by earlier rewrites, are dropped. Zip64 archives are not supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_path` - Path of the copy, replaced if it exists
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), output = %output_path.as_ref().display()), err))]
+    pub fn save_as_compacted<P: AsRef<Path>>(&self, output_path: P) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("save_as_compacted");
+        self.save_copy(output_path.as_ref(), true)
+    }
+
+    /// Copy the archive, optionally compacted, to `output_path`
+    fn save_copy(&self, output_path: &Path, compact: bool) -> Result<()> {
+        let path_str = output_path.to_string_lossy();
+        let c_path = helpers::to_c_string(&path_str)?;
+
+        let result = unsafe {
+            ffi::zip4j_save_as(
+                *self.thread()?,
+                self.handle,
+                c_path.as_ptr() as *mut c_char,
+                compact as c_int
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
+        Ok(())
+    }
+
+    /// Get the path the zip file was opened with
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the absolute path of the zip file, as resolved by the native library
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn file_path(&self) -> Result<PathBuf> {
+        const BUFFER_SIZE: usize = 1024;
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+        let mut path_length: c_int = 0;
+
+        let result = unsafe {
+            ffi::zip4j_get_file_path(
+                *self.thread()?,
+                self.handle,
+                buffer.as_mut_ptr() as *mut c_char,
+                BUFFER_SIZE as c_int,
+                &mut path_length
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
+        helpers::read_string_from_buffer_u8(&buffer, path_length).map(PathBuf::from)
+    }
+    
+    /// Compute the path the entry named `entry_name` is extracted to below `dest_dir`
+    ///
+    /// Fails with `ZipError::InvalidParameter` if the name would escape
+    /// `dest_dir`, e.g. through `..` or an absolute path. The entry doesn't
+    /// need to exist.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("site.zip")?;
+    /// let index = zip.extraction_path("public/index.html", "/srv/www")?;
+    /// zip.extract_file("public/index.html", "/srv/www")?;
+    /// assert!(index.exists());
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    pub fn extraction_path<S: AsRef<str>, P: AsRef<Path>>(&self, entry_name: S, dest_dir: P) -> Result<PathBuf> {
+        crate::zip_entry::extraction_path(dest_dir.as_ref(), entry_name.as_ref())
+    }
+
+    /// Get the comment of the zip file
+    ///
+    /// Returns an empty string if the ZIP file has no comment or is invalid
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn comment(&self) -> Result<String> {
         // Check if the ZIP is valid first
         if !self.is_valid()? {
-            return Err(crate::error::ZipError::InvalidParameter(
-                "Cannot set comment on an empty or invalid ZIP file. Add entries first.".to_string()
-            ));
+            return Ok(String::new());
         }
 
-        let c_comment = helpers::to_c_string(comment.as_ref())?;
+        const BUFFER_SIZE: usize = 1024;
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+        let mut comment_length: c_int = 0;
 
         let result = unsafe {
-            ffi::zip4j_set_comment(
-                ffi::get_thread(),
+            ffi::zip4j_get_comment(
+                *self.thread()?,
                 self.handle,
-                c_comment.as_ptr() as *mut c_char
+                buffer.as_mut_ptr() as *mut c_char,
+                BUFFER_SIZE as c_int,
+                &mut comment_length
             )
         };
 
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Ok(String::new()); // Return empty string instead of error
         }
 
-        Ok(())
+        helpers::read_string_from_buffer_u8(&buffer, comment_length)
+    }
+
+    /// Set the comment of the zip file
+    ///
+    /// # Arguments
+    ///
+    /// * `comment` - New comment for the zip file
+    ///
+    /// # Note
+    ///
+    /// This operation may fail if the ZIP file is empty or invalid.
+    /// Add entries to the ZIP file first to ensure it's valid.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn set_comment<S: AsRef<str>>(&mut self, comment: S) -> Result<()> {
+        self.modify(|zip| {
+            // Check if the ZIP is valid first
+            if !zip.is_valid()? {
+                return Err(crate::error::ZipError::InvalidParameter(
+                    "Cannot set comment on an empty or invalid ZIP file. Add entries first.".to_string()
+                ));
+            }
+
+            let c_comment = helpers::to_c_string(comment.as_ref())?;
+
+            let result = unsafe {
+                ffi::zip4j_set_comment(
+                    *zip.thread()?,
+                    zip.handle,
+                    c_comment.as_ptr() as *mut c_char
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+
+            Ok(())
+        })
     }
     
     /// Get the number of entries in the zip file
     ///
     /// Returns 0 for empty or invalid ZIP files
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
     pub fn entry_count(&self) -> Result<usize> {
         let mut count: c_longlong = 0;
 
         let result = unsafe {
             ffi::zip4j_get_entry_count(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 &mut count
             )
@@ -286,12 +1042,13 @@ impl ZipFile {
     /// # Arguments
     /// 
     /// * `index` - Zero-based index of the entry
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), index = index), err))]
     pub fn get_entry_by_index(&self, index: usize) -> Result<ZipEntry> {
         let mut entry_handle: c_longlong = 0;
         
         let result = unsafe {
             ffi::zip4j_get_entry_by_index(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 index as c_longlong,
                 &mut entry_handle
@@ -299,24 +1056,46 @@ impl ZipFile {
         };
         
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
         
-        ZipEntry::new(entry_handle)
+        ZipEntry::new(entry_handle, self.generation)
     }
     
     /// Get an entry by its name
+    ///
+    /// If several entries share the name, the one returned depends on the
+    /// [`DuplicatePolicy`] set with [`set_duplicate_policy`](Self::set_duplicate_policy).
     /// 
     /// # Arguments
     /// 
     /// * `name` - Name of the entry (full path within the zip)
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), entry = name.as_ref()), err))]
     pub fn get_entry_by_name<S: AsRef<str>>(&self, name: S) -> Result<ZipEntry> {
+        if self.duplicate_policy != DuplicatePolicy::First {
+            // Match the way the native lookup does, which also finds differently normalized names
+            let wanted = self.normalize_names([name.as_ref()])?.pop().unwrap_or_default();
+            let names = self.normalize_names(self.list_entries()?.iter().map(|info| info.name.as_str()))?;
+            let positions: Vec<usize> = names.iter()
+                .enumerate()
+                .filter(|(_, normalized)| **normalized == wanted)
+                .map(|(index, _)| index)
+                .collect();
+
+            if let [_, .., last] = positions[..] {
+                return match self.duplicate_policy {
+                    DuplicatePolicy::Error => Err(crate::error::ZipError::DuplicateEntry(name.as_ref().to_string())),
+                    _ => self.get_entry_by_index(last),
+                };
+            }
+        }
+
         let c_name = helpers::to_c_string(name.as_ref())?;
         let mut entry_handle: c_longlong = 0;
         
         let result = unsafe {
             ffi::zip4j_get_entry_by_name(
-                ffi::get_thread(),
+                *self.thread()?,
                 self.handle,
                 c_name.as_ptr() as *mut c_char,
                 &mut entry_handle
@@ -324,406 +1103,3528 @@ impl ZipFile {
         };
         
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result).with_context("get_entry_by_name", name.as_ref()));
         }
         
-        ZipEntry::new(entry_handle)
+        ZipEntry::new(entry_handle, self.generation)
     }
 
-    /// Add a file to the zip archive with default parameters
+    /// Find entries that share their name with another entry
     ///
-    /// # Arguments
+    /// ZIP archives may legally hold several entries with the same name, which
+    /// tools resolve differently; this is a common way to smuggle content past
+    /// a scanner.
     ///
-    /// * `file_path` - Path to the file to add
-    pub fn add_file<P: AsRef<Path>>(&mut self, file_path: P) -> Result<()> {
-        let path_str = file_path.as_ref().to_string_lossy();
-        let c_path = helpers::to_c_string(&path_str)?;
+    /// # Returns
+    ///
+    /// For each duplicated name, the indices of its entries in archive order
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("upload.zip")?;
+    /// for indices in zip.duplicates()? {
+    ///     let name = zip.get_entry_by_index(indices[0])?.name()?;
+    ///     println!("{} appears {} times", name, indices.len());
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn duplicates(&self) -> Result<Vec<Vec<usize>>> {
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut by_name: HashMap<String, usize> = HashMap::new();
+        for (index, info) in self.list_entries()?.into_iter().enumerate() {
+            match by_name.entry(info.name) {
+                hash_map::Entry::Occupied(group) => groups[*group.get()].push(index),
+                hash_map::Entry::Vacant(slot) => {
+                    slot.insert(groups.len());
+                    groups.push(vec![index]);
+                }
+            }
+        }
+
+        groups.retain(|indices| indices.len() > 1);
+        Ok(groups)
+    }
+
+    /// Set which entry [`get_entry_by_name`](Self::get_entry_by_name) returns
+    /// when several entries share a name
+    pub fn set_duplicate_policy(&mut self, policy: DuplicatePolicy) {
+        self.duplicate_policy = policy;
+    }
 
+    /// Set how entry names are normalized when looking entries up and extracting them
+    ///
+    /// With [`NameNormalization::Nfc`] or [`NameNormalization::Nfd`], lookups
+    /// by name ([`get_entry_by_name`](Self::get_entry_by_name),
+    /// [`contains`](Self::contains), [`extract_file`](Self::extract_file))
+    /// also find entries whose stored name only differs in its Unicode
+    /// normalization, and extracted files are named in the chosen form.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{NameNormalization, ZipFile};
+    ///
+    /// // Made on macOS, where the name is stored decomposed
+    /// let mut zip = ZipFile::new("from-mac.zip")?;
+    /// zip.set_name_normalization(NameNormalization::Nfc)?;
+    /// let entry = zip.get_entry_by_name("café.txt")?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn set_name_normalization(&mut self, normalization: NameNormalization) -> Result<()> {
         let result = unsafe {
-            ffi::zip4j_add_file(
-                ffi::get_thread(),
+            ffi::zip4j_set_name_normalization(
+                *self.thread()?,
                 self.handle,
-                c_path.as_ptr() as *mut c_char
+                normalization.into()
             )
         };
 
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
 
+        self.name_normalization = normalization;
         Ok(())
     }
 
-    /// Add a file to the zip archive with custom parameters
-    ///
-    /// # Arguments
+    /// Normalize names the way lookups and extraction do with the form set by
+    /// [`set_name_normalization`](Self::set_name_normalization)
     ///
-    /// * `file_path` - Path to the file to add
-    /// * `params` - Compression and encryption parameters
-    pub fn add_file_with_params<P: AsRef<Path>>(&mut self, file_path: P, params: &ZipParameters) -> Result<()> {
-        let path_str = file_path.as_ref().to_string_lossy();
-        let c_path = helpers::to_c_string(&path_str)?;
+    /// Names are returned unchanged, without a native call, unless a form is set.
+    pub(crate) fn normalize_names<'a, I>(&self, names: I) -> Result<Vec<String>>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let names = names.into_iter();
+        if self.name_normalization == NameNormalization::Unchanged {
+            return Ok(names.map(str::to_string).collect());
+        }
+
+        let c_names = helpers::to_c_string_list(names)?;
+        let mut buffer = vec![0u8; c_names.len().max(1)];
+        loop {
+            let mut length: c_int = 0;
+
+            let result = unsafe {
+                ffi::zip4j_normalize_names(
+                    *self.thread()?,
+                    self.handle,
+                    c_names.as_ptr() as *mut c_char,
+                    c_names.len() as c_int,
+                    buffer.as_mut_ptr() as *mut c_char,
+                    buffer.len() as c_int,
+                    &mut length
+                )
+            };
 
-        let c_password = match &params.password {
-            Some(pwd) => {
-                let c_pwd = helpers::to_c_string(pwd)?;
-                c_pwd.as_ptr() as *mut c_char
+            if result == ffi::constants::ERROR_BUFFER_TOO_SMALL {
+                buffer.resize(length as usize, 0);
+                continue;
             }
-            None => std::ptr::null_mut(),
-        };
 
-        let result = unsafe {
-            ffi::zip4j_add_file_with_params(
-                ffi::get_thread(),
-                self.handle,
-                c_path.as_ptr() as *mut c_char,
-                params.compression_level.into(),
-                params.compression_method.into(),
-                params.encryption_method.into(),
-                params.aes_key_strength.into(),
-                c_password,
-            )
-        };
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
 
-        if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            buffer.truncate(length as usize);
+            return helpers::read_string_list(&buffer);
         }
-
-        Ok(())
     }
 
-    /// Add a directory to the zip archive
+    /// Check whether the archive contains an entry with the given name
     ///
-    /// # Arguments
-    ///
-    /// * `dir_path` - Path to the directory to add
-    pub fn add_directory<P: AsRef<Path>>(&mut self, dir_path: P) -> Result<()> {
-        let path_str = dir_path.as_ref().to_string_lossy();
-        let c_path = helpers::to_c_string(&path_str)?;
+    /// Unlike [`ZipFile::get_entry_by_name`], a missing entry is not an error
+    /// and no entry handle is created.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), entry = name.as_ref()), err))]
+    pub fn contains<S: AsRef<str>>(&self, name: S) -> Result<bool> {
+        let c_name = helpers::to_c_string(name.as_ref())?;
+        let mut found: c_int = 0;
 
         let result = unsafe {
-            ffi::zip4j_add_directory(
-                ffi::get_thread(),
+            ffi::zip4j_contains_entry(
+                *self.thread()?,
                 self.handle,
-                c_path.as_ptr() as *mut c_char
+                c_name.as_ptr() as *mut c_char,
+                &mut found
             )
         };
 
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
 
-        Ok(())
+        Ok(found != 0)
     }
 
-    /// Add a directory to the zip archive with custom parameters
+    /// Build a serializable listing of every entry, for publishing alongside the archive
     ///
-    /// # Arguments
+    /// # Examples
     ///
-    /// * `dir_path` - Path to the directory to add
-    /// * `params` - Compression and encryption parameters
-    pub fn add_directory_with_params<P: AsRef<Path>>(&mut self, dir_path: P, params: &ZipParameters) -> Result<()> {
-        let path_str = dir_path.as_ref().to_string_lossy();
-        let c_path = helpers::to_c_string(&path_str)?;
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("release.zip")?;
+    /// let json = serde_json::to_string_pretty(&zip.manifest()?).unwrap();
+    /// std::fs::write("release.zip.json", json)?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg(feature = "serde")]
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn manifest(&self) -> Result<crate::types::ArchiveManifest> {
+        use crate::types::{ArchiveManifest, ManifestEntry};
 
-        let c_password = match &params.password {
-            Some(pwd) => {
-                let c_pwd = helpers::to_c_string(pwd)?;
-                c_pwd.as_ptr() as *mut c_char
-            }
-            None => std::ptr::null_mut(),
-        };
+        let entries = self.entry_infos()?.into_iter()
+            .map(|info| ManifestEntry {
+                last_modified: ManifestEntry::format_dos_time(info.last_modified_time),
+                name: info.name,
+                directory: info.is_directory,
+                size: info.size,
+                compressed_size: info.compressed_size,
+                crc32: info.crc32,
+                compression_method: info.compression_method,
+                encryption_method: info.encryption_method,
+            })
+            .collect();
 
-        let result = unsafe {
-            ffi::zip4j_add_directory_with_params(
-                ffi::get_thread(),
+        Ok(ArchiveManifest { comment: self.comment()?, entries })
+    }
+
+    /// Add a file to the zip archive with default parameters
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - Path to the file to add
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), path = %file_path.as_ref().display()), err))]
+    pub fn add_file<P: AsRef<Path>>(&mut self, file_path: P) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("add_file");
+        self.modify(|zip| {
+            let path_str = file_path.as_ref().to_string_lossy();
+            let c_path = helpers::to_c_string(&path_str)?;
+
+            let result = unsafe {
+                ffi::zip4j_add_file(
+                    *zip.thread()?,
+                    zip.handle,
+                    c_path.as_ptr() as *mut c_char
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result).with_context("add_file", file_path.as_ref().display()));
+            }
+
+            telemetry::record_compressed_file(file_path.as_ref());
+            Ok(())
+        })
+    }
+
+    /// Add a file to the zip archive with custom parameters
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - Path to the file to add
+    /// * `params` - Compression and encryption parameters
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), path = %file_path.as_ref().display()), err))]
+    pub fn add_file_with_params<P: AsRef<Path>>(&mut self, file_path: P, params: &ZipParameters) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("add_file_with_params");
+        self.modify(|zip| {
+            let native_params = params.to_native()?;
+            zip.add_file_native(file_path.as_ref(), &native_params)?;
+            zip.apply_alignment(params)
+        })
+    }
+
+    /// Add a file to the zip archive under a different name
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - Path to the file to add
+    /// * `name_in_zip` - Path of the entry inside the archive
+    /// * `params` - Compression and encryption parameters
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{ZipFile, ZipParameters};
+    ///
+    /// let mut zip = ZipFile::new("release.zip")?;
+    /// zip.add_file_as("target/release/app", "bin/app", &ZipParameters::new())?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), path = %file_path.as_ref().display(), entry = name_in_zip.as_ref()), err))]
+    pub fn add_file_as<P: AsRef<Path>, S: AsRef<str>>(&mut self, file_path: P, name_in_zip: S, params: &ZipParameters) -> Result<()> {
+        let params = params.clone().with_file_name_in_zip(name_in_zip.as_ref());
+        self.add_file_with_params(file_path, &params)
+    }
+
+    /// Add many files to the zip archive in a single operation
+    ///
+    /// Names in the archive are stored relative to `base_dir`, so packaging a
+    /// build output tree keeps its folder structure. All files are added with
+    /// one native call and a single archive rewrite.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_paths` - Paths of the files to add (must be located under `base_dir`)
+    /// * `base_dir` - Directory the stored names are relative to
+    /// * `params` - Compression and encryption parameters
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::path::PathBuf;
+    /// use zip2rs::{ZipFile, ZipParameters};
+    ///
+    /// let mut zip = ZipFile::new("release.zip")?;
+    /// let files = vec![PathBuf::from("target/dist/app"), PathBuf::from("target/dist/lib/libfoo.so")];
+    /// // Stored as "app" and "lib/libfoo.so"
+    /// zip.add_files(&files, "target/dist", &ZipParameters::new())?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), base_dir = %base_dir.as_ref().display(), count = file_paths.len()), err))]
+    pub fn add_files<P: AsRef<Path>, B: AsRef<Path>>(&mut self, file_paths: &[P], base_dir: B, params: &ZipParameters) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("add_files");
+        self.modify(|zip| {
+            if file_paths.is_empty() {
+                return Ok(());
+            }
+
+            let paths: Vec<_> = file_paths.iter().map(|p| p.as_ref().to_string_lossy()).collect();
+            let c_paths = helpers::to_c_string_list(&paths)?;
+            let c_base = helpers::to_c_string(&base_dir.as_ref().to_string_lossy())?;
+            let native_params = params.to_native()?;
+
+            let result = unsafe {
+                ffi::zip4j_add_files(
+                    *zip.thread()?,
+                    zip.handle,
+                    c_paths.as_ptr() as *mut c_char,
+                    c_paths.len() as c_int,
+                    c_base.as_ptr() as *mut c_char,
+                    native_params.handle()
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+
+            for path in file_paths {
+                telemetry::record_compressed_file(path.as_ref());
+            }
+            zip.apply_alignment(params)
+        })
+    }
+
+    /// Add a directory to the zip archive
+    ///
+    /// # Arguments
+    ///
+    /// * `dir_path` - Path to the directory to add
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), path = %dir_path.as_ref().display()), err))]
+    pub fn add_directory<P: AsRef<Path>>(&mut self, dir_path: P) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("add_directory");
+        self.modify(|zip| {
+            let path_str = dir_path.as_ref().to_string_lossy();
+            let c_path = helpers::to_c_string(&path_str)?;
+
+            let result = unsafe {
+                ffi::zip4j_add_directory(
+                    *zip.thread()?,
+                    zip.handle,
+                    c_path.as_ptr() as *mut c_char
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result).with_context("add_directory", dir_path.as_ref().display()));
+            }
+
+            telemetry::record_compressed_dir(dir_path.as_ref());
+            Ok(())
+        })
+    }
+
+    /// Add a directory to the zip archive with custom parameters
+    ///
+    /// # Arguments
+    ///
+    /// * `dir_path` - Path to the directory to add
+    /// * `params` - Compression and encryption parameters
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), path = %dir_path.as_ref().display()), err))]
+    pub fn add_directory_with_params<P: AsRef<Path>>(&mut self, dir_path: P, params: &ZipParameters) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("add_directory_with_params");
+        self.modify(|zip| {
+            let path_str = dir_path.as_ref().to_string_lossy();
+            let c_path = helpers::to_c_string(&path_str)?;
+            let native_params = params.to_native()?;
+
+            let result = unsafe {
+                ffi::zip4j_add_directory_with_parameters(
+                    *zip.thread()?,
+                    zip.handle,
+                    c_path.as_ptr() as *mut c_char,
+                    native_params.handle()
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result).with_context("add_directory", dir_path.as_ref().display()));
+            }
+
+            telemetry::record_compressed_dir(dir_path.as_ref());
+            zip.apply_alignment(params)
+        })
+    }
+
+    /// Add a directory to the zip archive with additional options
+    ///
+    /// When exclusion filters, skipped links or stored extensions are configured, the directory is
+    /// walked up front and the remaining files and folders are added in one native call per
+    /// compression method.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir_path` - Path to the directory to add
+    /// * `options` - Parameters and directory-specific options
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{AddDirOptions, SymlinkPolicy, ZipFile};
+    ///
+    /// let mut zip = ZipFile::new("site.zip")?;
+    /// // Store the contents of "public/" at the archive root
+    /// zip.add_directory_with_options("public", &AddDirOptions::new().with_include_root(false))?;
+    ///
+    /// // Skip VCS metadata and build output
+    /// let options = AddDirOptions::new()
+    ///     .with_exclude_pattern(".git")
+    ///     .with_exclude_pattern("**/node_modules")
+    ///     .with_exclude(|path| path.ends_with("target"));
+    /// zip.add_directory_with_options("project", &options)?;
+    ///
+    /// // Keep already-compressed assets stored, like aapt
+    /// let options = AddDirOptions::new()
+    ///     .with_stored_extension(".png")
+    ///     .with_stored_extension(".so")
+    ///     .with_stored_extension(".resources.arsc");
+    /// zip.add_directory_with_options("apk-root", &options)?;
+    ///
+    /// // Back up a home directory without dotfiles, keeping links as links
+    /// let options = AddDirOptions::new()
+    ///     .with_hidden(false)
+    ///     .with_symlinks(SymlinkPolicy::Link);
+    /// zip.add_directory_with_options("/home/me", &options)?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), path = %dir_path.as_ref().display()), err))]
+    pub fn add_directory_with_options<P: AsRef<Path>>(&mut self, dir_path: P, options: &AddDirOptions) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("add_directory_with_options");
+        self.modify(|zip| {
+            if options.needs_walk() {
+                let dir = std::fs::canonicalize(dir_path.as_ref())?;
+                let mut paths = Vec::new();
+                let base = match dir.parent() {
+                    Some(parent) if options.include_root => {
+                        paths.push(dir.clone());
+                        parent.to_path_buf()
+                    }
+                    _ => dir.clone(),
+                };
+
+                let patterns = options.exclude_patterns.iter()
+                    .map(|pattern| compile_glob(pattern))
+                    .collect::<Result<Vec<_>>>()?;
+                collect_directory(&dir, &dir, options, &patterns, &mut paths)?;
+                if options.skip_unchanged {
+                    paths = zip.changed_paths(paths, &base, &options.params)?;
+                }
+
+                let params = options.zip_params();
+                let stored_params = params.clone().with_compression_method(CompressionMethod::Store);
+                let (stored, compressed): (Vec<_>, Vec<_>) = paths.into_iter().partition(|path| options.is_stored(path));
+
+                if let Some(bytes_per_second) = options.max_bytes_per_second {
+                    let mut throttle = Throttle::new(bytes_per_second);
+                    let files = compressed.iter().map(|path| (path, &params))
+                        .chain(stored.iter().map(|path| (path, &stored_params)));
+                    for (path, params) in files {
+                        zip.add_files(std::slice::from_ref(path), &base, params)?;
+                        throttle.pace(std::fs::symlink_metadata(path).map_or(0, |metadata| metadata.len()));
+                    }
+                    return Ok(());
+                }
+
+                zip.add_files(&compressed, &base, &params)?;
+                return zip.add_files(&stored, &base, &stored_params);
+            }
+
+            let path_str = dir_path.as_ref().to_string_lossy();
+            let c_path = helpers::to_c_string(&path_str)?;
+            let native_params = options.to_native()?;
+
+            let result = unsafe {
+                ffi::zip4j_add_directory_with_parameters(
+                    *zip.thread()?,
+                    zip.handle,
+                    c_path.as_ptr() as *mut c_char,
+                    native_params.handle()
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result).with_context("add_directory", dir_path.as_ref().display()));
+            }
+
+            telemetry::record_compressed_dir(dir_path.as_ref());
+            zip.apply_alignment(&options.params)
+        })
+    }
+
+    /// Bring the archive in line with a directory
+    ///
+    /// Files that are new are added and files that changed (see
+    /// [`ZipFile::diff_with_dir`]) replace their entries. With
+    /// [`SyncOptions::remove_missing`] set, entries whose files are gone are
+    /// removed as well. The archive is rewritten once, however many entries
+    /// change, which makes repeated backups of a mostly unchanged tree cheap.
+    ///
+    /// The sync is staged in a temporary copy of the archive even with
+    /// [`SaveMode::InPlace`], so a failure part way through leaves the archive
+    /// as it was.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{SyncOptions, ZipFile};
+    ///
+    /// let mut zip = ZipFile::new("backup.zip")?;
+    /// let report = zip.sync_directory("documents", &SyncOptions::new().with_remove_missing(true))?;
+    /// println!("{} added, {} updated, {} removed", report.added.len(), report.updated.len(), report.removed.len());
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), path = %dir_path.as_ref().display()), err))]
+    pub fn sync_directory<P: AsRef<Path>>(&mut self, dir_path: P, options: &SyncOptions) -> Result<SyncReport> {
+        self.modify_with(true, |zip| {
+            let dir = std::fs::canonicalize(dir_path.as_ref())?;
+            let mut report = SyncReport::default();
+
+            for diff in zip.diff_with_dir_using(&dir, options.detection)? {
+                match diff.kind {
+                    DiffKind::OnlyOnDisk => report.added.push(diff.name),
+                    DiffKind::Modified => report.updated.push(diff.name),
+                    DiffKind::OnlyInArchive if options.remove_missing => report.removed.push(diff.name),
+                    DiffKind::OnlyInArchive => {}
+                }
+            }
+
+            if report.is_empty() {
+                return Ok(report);
+            }
+
+            let c_remove = helpers::to_c_string_list(&report.removed)?;
+            let paths: Vec<_> = report.added.iter().chain(&report.updated)
+                .map(|name| dir.join(name).to_string_lossy().into_owned())
+                .collect();
+            let c_paths = helpers::to_c_string_list(&paths)?;
+            let c_base = helpers::to_c_string(&dir.to_string_lossy())?;
+            let native_params = options.params.to_native()?;
+
+            let result = unsafe {
+                ffi::zip4j_sync_files(
+                    *zip.thread()?,
+                    zip.handle,
+                    c_remove.as_ptr() as *mut c_char,
+                    c_remove.len() as c_int,
+                    c_paths.as_ptr() as *mut c_char,
+                    c_paths.len() as c_int,
+                    c_base.as_ptr() as *mut c_char,
+                    native_params.handle()
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+
+            for path in &paths {
+                telemetry::record_compressed_file(Path::new(path));
+            }
+            zip.apply_alignment(&options.params)?;
+            Ok(report)
+        })
+    }
+
+    /// Add data from a byte slice to the zip archive
+    ///
+    /// # Arguments
+    ///
+    /// * `entry_name` - Name for the entry in the zip file
+    /// * `data` - Byte data to add
+    /// * `params` - Compression and encryption parameters
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), entry = entry_name.as_ref(), len = data.len()), err))]
+    pub fn add_data<S: AsRef<str>>(&mut self, entry_name: S, data: &[u8], params: &ZipParameters) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("add_data");
+        self.modify(|zip| {
+            let c_name = helpers::to_c_string(entry_name.as_ref())?;
+
+            let c_password = params.password.as_ref().map(Password::to_c_string).transpose()?;
+
+            let result = unsafe {
+                ffi::zip4j_add_data(
+                    *zip.thread()?,
+                    zip.handle,
+                    c_name.as_ptr() as *mut c_char,
+                    data.as_ptr() as *mut c_char,
+                    data.len() as c_int,
+                    params.compression_level.into(),
+                    params.compression_method.into(),
+                    params.encryption_method.into(),
+                    params.aes_key_strength.into(),
+                    c_password.as_ref().map_or(std::ptr::null_mut(), |pwd| pwd.as_ptr() as *mut c_char),
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result).with_context("add_data", entry_name.as_ref()));
+            }
+
+            telemetry::record_compressed(data.len() as u64);
+            zip.apply_alignment(params)
+        })
+    }
+
+    /// Add data produced in chunks to the zip archive
+    ///
+    /// Chunks are handed to zip4j as they are produced, so data generated on the
+    /// fly never has to be concatenated into one contiguous buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry_name` - Name for the entry in the zip file
+    /// * `chunks` - Chunks of data, in order
+    /// * `params` - Compression and encryption parameters
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{ZipFile, ZipParameters};
+    ///
+    /// let mut zip = ZipFile::new("export.zip")?;
+    /// let rows = (0..1000).map(|id| format!("{},row {}\n", id, id));
+    /// zip.add_data_chunked("rows.csv", rows, &ZipParameters::new())?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), entry = entry_name.as_ref()), err))]
+    pub fn add_data_chunked<S, I>(&mut self, entry_name: S, chunks: I, params: &ZipParameters) -> Result<()>
+    where
+        S: AsRef<str>,
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let _timer = telemetry::OperationTimer::start("add_data_chunked");
+        self.modify(|zip| {
+            let native_params = params.to_native()?;
+            zip.write_chunks(entry_name.as_ref(), chunks, &native_params)?;
+            zip.apply_alignment(params)
+        })
+    }
+
+    /// Add an entry described by a `ZipEntryBuilder`
+    ///
+    /// Name, data source, parameters and metadata (modification time, Unix mode,
+    /// comment, CRC) are applied in one call.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The entry to add
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn add(&mut self, entry: ZipEntryBuilder) -> Result<()> {
+        self.modify(|zip| {
+            let native_params = entry.to_native()?;
+            match &entry.source {
+                EntrySource::Data(data) => zip.write_chunks(&entry.name, [data], &native_params)?,
+                EntrySource::File(path) => zip.add_file_native(path, &native_params)?,
+            }
+
+            if let Some(mode) = entry.unix_mode {
+                let c_name = helpers::to_c_string(&entry.name)?;
+
+                let result = unsafe {
+                    ffi::zip4j_set_entry_unix_mode(
+                        *zip.thread()?,
+                        zip.handle,
+                        c_name.as_ptr() as *mut c_char,
+                        mode as c_int
+                    )
+                };
+
+                if helpers::is_error(result) {
+                    return Err(crate::error::ZipError::from_native(result));
+                }
+            }
+
+            zip.apply_alignment(&entry.params)
+        })
+    }
+
+    /// Pad the archive so every stored entry's data starts on an `alignment`-byte boundary
+    ///
+    /// The equivalent of Android's `zipalign`: the archive is rewritten with a
+    /// padding extra field in the local header of each stored (uncompressed)
+    /// entry, so its data can be memory-mapped straight from the file. Entries
+    /// are copied without being recompressed. Use 4 for APK resources or 4096 to
+    /// page-align native libraries. Split and Zip64 archives are not supported.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let mut apk = ZipFile::new("app-unaligned.apk")?;
+    /// apk.align_archive(4)?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), alignment = alignment), err))]
+    pub fn align_archive(&mut self, alignment: u32) -> Result<()> {
+        self.modify(|zip| {
+            if alignment == 0 || alignment > 0x8000 {
+                return Err(crate::error::ZipError::InvalidParameter(format!("Unsupported alignment: {}", alignment)));
+            }
+
+            let result = unsafe {
+                ffi::zip4j_align_archive(
+                    *zip.thread()?,
+                    zip.handle,
+                    alignment as c_int
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Rewrite the archive so it holds nothing but its entries and central directory
+    ///
+    /// Bytes that no central directory record refers to, such as data left
+    /// behind by interrupted or repeated rewrites, are dropped and the entries
+    /// are packed back to back. Entries are copied without being recompressed.
+    /// Split and Zip64 archives are not supported.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes reclaimed
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let mut zip = ZipFile::new("cache.zip")?;
+    /// let reclaimed = zip.compact()?;
+    /// println!("Reclaimed {} bytes", reclaimed);
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn compact(&mut self) -> Result<u64> {
+        let _timer = telemetry::OperationTimer::start("compact");
+        self.modify(|zip| {
+            let mut reclaimed: c_longlong = 0;
+
+            let result = unsafe {
+                ffi::zip4j_compact(
+                    *zip.thread()?,
+                    zip.handle,
+                    &mut reclaimed
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+
+            Ok(reclaimed.max(0) as u64)
+        })
+    }
+
+    /// Re-align the archive after adding entries, if the parameters ask for it
+    fn apply_alignment(&mut self, params: &ZipParameters) -> Result<()> {
+        match params.alignment {
+            Some(alignment) => self.align_archive(alignment),
+            None => Ok(()),
+        }
+    }
+
+    /// Extract all files from the zip archive to a destination directory
+    ///
+    /// # Arguments
+    ///
+    /// * `dest_path` - Directory where files should be extracted
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), dest = %dest_path.as_ref().display()), err))]
+    pub fn extract_all<P: AsRef<Path>>(&self, dest_path: P) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("extract_all");
+        let path_str = dest_path.as_ref().to_string_lossy();
+        let c_path = helpers::to_c_string(&path_str)?;
+
+        let result = unsafe {
+            ffi::zip4j_extract_all(
+                *self.thread()?,
+                self.handle,
+                c_path.as_ptr() as *mut c_char
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("extract_all", dest_path.as_ref().display()));
+        }
+
+        telemetry::record_extracted_with(|| Ok(self.list_entries()?.iter().map(|info| info.size).sum()));
+        Ok(())
+    }
+
+    /// Extract all files using several threads
+    ///
+    /// The worker threads run inside the native library, each reading the
+    /// archive through its own handle and taking the next entry as soon as it
+    /// is done with one, so archives with many entries extract faster on
+    /// multi-core machines. If any entry fails, the remaining workers stop and
+    /// the first error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `dest_path` - Directory where files should be extracted
+    /// * `threads` - Number of worker threads, or 0 to use the available parallelism
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("dataset.zip")?;
+    /// zip.extract_all_parallel("dataset", 0)?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), dest = %dest_path.as_ref().display(), threads = threads), err))]
+    pub fn extract_all_parallel<P: AsRef<Path>>(&self, dest_path: P, threads: usize) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("extract_all_parallel");
+        let threads = match threads {
+            0 => std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+            threads => threads,
+        };
+        let path_str = dest_path.as_ref().to_string_lossy();
+        let c_path = helpers::to_c_string(&path_str)?;
+
+        let result = unsafe {
+            ffi::zip4j_extract_all_parallel(
+                *self.thread()?,
+                self.handle,
+                c_path.as_ptr() as *mut c_char,
+                threads.min(c_int::MAX as usize) as c_int
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("extract_all", dest_path.as_ref().display()));
+        }
+
+        telemetry::record_extracted_with(|| Ok(self.list_entries()?.iter().map(|info| info.size).sum()));
+        Ok(())
+    }
+
+    /// Extract all files from the zip archive with extra options
+    ///
+    /// With [`ExtractOptions::verify_crc`] set, every written file is read back
+    /// and its CRC32 compared with the one stored in the archive, catching
+    /// corruption introduced on the way to disk. Returns the names of the entries
+    /// whose files are missing or don't match; AES entries that store no CRC are
+    /// authenticated during extraction instead and always pass.
+    ///
+    /// With [`ExtractOptions::check_free_space`] set, the uncompressed sizes of
+    /// all entries are added up first and extraction fails with
+    /// `ZipError::InsufficientSpace` before writing anything if the destination
+    /// file system has less room available. With
+    /// [`ExtractOptions::max_bytes_per_second`] set, entries are extracted one
+    /// at a time with pauses in between to keep the average write rate down.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{ExtractOptions, ZipFile};
+    ///
+    /// let zip = ZipFile::new("installer.zip")?;
+    /// let failed = zip.extract_all_with_options("install", &ExtractOptions::new().with_verify_crc(true))?;
+    /// assert!(failed.is_empty(), "corrupted files: {:?}", failed);
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), dest = %dest_path.as_ref().display()), err))]
+    pub fn extract_all_with_options<P: AsRef<Path>>(&self, dest_path: P, options: &ExtractOptions) -> Result<Vec<String>> {
+        let _timer = telemetry::OperationTimer::start("extract_all_with_options");
+        if options.check_free_space {
+            let required: u64 = self.list_entries()?.iter().map(|info| info.size).sum();
+            let available = self.usable_space(dest_path.as_ref())?;
+            if required > available {
+                return Err(crate::error::ZipError::InsufficientSpace { required, available });
+            }
+        }
+
+        if let Some(bytes_per_second) = options.max_bytes_per_second {
+            let mut throttle = Throttle::new(bytes_per_second);
+            for target in self.extraction_targets(dest_path.as_ref())? {
+                let ExtractionTarget { entry, path, .. } = target?;
+                if entry.is_directory()? {
+                    std::fs::create_dir_all(path?)?;
+                } else {
+                    self.extract_entry(&entry, &dest_path)?;
+                    throttle.pace(entry.size()?);
+                }
+            }
+        } else {
+            self.extract_all(&dest_path)?;
+        }
+
+        let mut failed = Vec::new();
+        if !options.verify_crc {
+            return Ok(failed);
+        }
+
+        let path_str = dest_path.as_ref().to_string_lossy();
+        let c_path = helpers::to_c_string(&path_str)?;
+
+        for entry in self.entries()? {
+            let entry = entry?;
+            let mut matches: c_int = 0;
+
+            let result = unsafe {
+                ffi::zip4j_verify_extracted_entry(
+                    *self.thread()?,
+                    self.handle,
+                    entry.handle(),
+                    c_path.as_ptr() as *mut c_char,
+                    &mut matches
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result).with_context("verify_extracted_entry", entry.name().unwrap_or_default()));
+            }
+
+            if matches == 0 {
+                failed.push(entry.name()?);
+            }
+        }
+
+        Ok(failed)
+    }
+
+    /// Extract all files while computing the SHA-256 of each one
+    ///
+    /// The digest is computed from the decompressed bytes as they are written,
+    /// so no second pass over the extracted files is needed. Returns one digest
+    /// per file entry, in archive order; directories are created but not listed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("release.zip")?;
+    /// for digest in zip.extract_all_with_digests("release")? {
+    ///     println!("{}  {}", digest.sha256_hex(), digest.name);
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), dest = %dest_path.as_ref().display()), err))]
+    pub fn extract_all_with_digests<P: AsRef<Path>>(&self, dest_path: P) -> Result<Vec<EntryDigest>> {
+        let _timer = telemetry::OperationTimer::start("extract_all_with_digests");
+        let path_str = dest_path.as_ref().to_string_lossy();
+        let c_path = helpers::to_c_string(&path_str)?;
+        let mut digests = Vec::new();
+
+        for entry in self.entries()? {
+            let entry = entry?;
+            let mut sha256 = [0u8; 32];
+
+            let result = unsafe {
+                ffi::zip4j_extract_entry_with_digest(
+                    *self.thread()?,
+                    self.handle,
+                    entry.handle(),
+                    c_path.as_ptr() as *mut c_char,
+                    sha256.as_mut_ptr() as *mut c_char
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result).with_context("extract_entry", entry.name().unwrap_or_default()));
+            }
+
+            telemetry::record_extracted_with(|| entry.size());
+            if !entry.is_directory()? {
+                digests.push(EntryDigest { name: entry.name()?, sha256 });
+            }
+        }
+
+        Ok(digests)
+    }
+
+    /// Extract only the entries `filter` accepts
+    ///
+    /// The closure is called with the metadata of every entry, in archive
+    /// order, and the entry is extracted only if it returns `true`. Parent
+    /// folders of accepted files are created even when their own directory
+    /// entries are skipped. Returns the number of entries extracted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("photos.zip")?;
+    /// // Only JPEGs, and nothing over 50 MiB
+    /// let extracted = zip.extract_all_filtered("photos", |info| {
+    ///     info.name.ends_with(".jpg") && info.size <= 50 * 1024 * 1024
+    /// })?;
+    /// println!("extracted {} photos", extracted);
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), dest = %dest_path.as_ref().display()), err))]
+    pub fn extract_all_filtered<P, F>(&self, dest_path: P, mut filter: F) -> Result<usize>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&ZipEntryInfo) -> bool,
+    {
+        let _timer = telemetry::OperationTimer::start("extract_all_filtered");
+        let dest_path = dest_path.as_ref();
+        let mut extracted = 0;
+
+        // The metadata comes from the bulk listing, so filtering costs no native calls
+        for target in self.extraction_targets(dest_path)? {
+            let ExtractionTarget { entry, path, .. } = target?;
+            let info = entry.info()?;
+            if !filter(&info) {
+                continue;
+            }
+
+            if info.is_directory {
+                std::fs::create_dir_all(path?)?;
+            } else {
+                self.extract_entry(&entry, dest_path)?;
+            }
+            extracted += 1;
+        }
+
+        Ok(extracted)
+    }
+
+    /// Extract all files, asking `resolve` what to do whenever a file already exists
+    ///
+    /// The resolver is only called for files that are already present in the
+    /// destination; everything else is extracted as usual. Once it answers
+    /// with one of the `*All` resolutions, that answer is used for every later
+    /// conflict without calling it again. Returning
+    /// [`ConflictResolution::Abort`] stops the extraction, which then fails
+    /// with `ZipError::OperationCancelled`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{ConflictResolution, ZipFile};
+    ///
+    /// let zip = ZipFile::new("update.zip")?;
+    /// zip.extract_all_with_resolver("app", |conflict| {
+    ///     if conflict.path.ends_with("settings.ini") {
+    ///         // Keep the user's settings
+    ///         ConflictResolution::Skip
+    ///     } else {
+    ///         ConflictResolution::Overwrite
+    ///     }
+    /// })?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), dest = %dest_path.as_ref().display()), err))]
+    pub fn extract_all_with_resolver<P, F>(&self, dest_path: P, mut resolve: F) -> Result<()>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&FileConflict) -> ConflictResolution,
+    {
+        let _timer = telemetry::OperationTimer::start("extract_all_with_resolver");
+        let dest_path = dest_path.as_ref();
+        let mut for_all = None;
+
+        for target in self.extraction_targets(dest_path)? {
+            let ExtractionTarget { entry, name, path } = target?;
+            let path = path?;
+            if entry.is_directory()? {
+                std::fs::create_dir_all(&path)?;
+                continue;
+            }
+
+            let existing_size = match std::fs::metadata(&path) {
+                Ok(metadata) => metadata.len(),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    self.extract_entry(&entry, dest_path)?;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            let resolution = match for_all {
+                Some(resolution) => resolution,
+                None => {
+                    let conflict = FileConflict { name: entry.name()?, path, size: entry.size()?, existing_size };
+                    let resolution = resolve(&conflict);
+                    if resolution.applies_to_all() {
+                        for_all = Some(resolution.single());
+                    }
+                    resolution.single()
+                }
+            };
+
+            match resolution {
+                ConflictResolution::Overwrite => self.extract_entry(&entry, dest_path)?,
+                ConflictResolution::Rename => {
+                    let name = free_entry_name(dest_path, &name)?;
+                    self.extract_entry_as(&entry, dest_path, &name)?;
+                }
+                ConflictResolution::Abort => return Err(crate::error::ZipError::OperationCancelled),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Extract all files and report what was written
+    ///
+    /// Unlike [`extract_all`](Self::extract_all), a failing entry doesn't stop
+    /// the extraction: it is recorded in the report and the remaining entries
+    /// are still extracted. Entries whose names would escape the destination
+    /// (absolute paths, `..`) are written below it with the offending parts
+    /// removed, and listed in [`ExtractReport::sanitized`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("upload.zip")?;
+    /// let report = zip.extract_all_with_report("upload")?;
+    /// println!("{} files, {} bytes", report.files_written, report.bytes_written);
+    /// for (name, path) in &report.sanitized {
+    ///     eprintln!("{} was written to {}", name, path.display());
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), dest = %dest_path.as_ref().display()), err))]
+    pub fn extract_all_with_report<P: AsRef<Path>>(&self, dest_path: P) -> Result<ExtractReport> {
+        let _timer = telemetry::OperationTimer::start("extract_all_with_report");
+        let dest_path = dest_path.as_ref();
+        let mut report = ExtractReport::default();
+
+        for target in self.extraction_targets(dest_path)? {
+            let ExtractionTarget { entry, name: disk_name, path } = target?;
+            let name = entry.name()?;
+            let safe_name = sanitize_entry_name(&disk_name);
+            if safe_name.is_empty() {
+                report.skipped.push(name);
+                continue;
+            }
+
+            let is_directory = entry.is_directory()?;
+            let escapes = path.is_err();
+            let path = match path {
+                Ok(path) => path,
+                Err(_) => crate::zip_entry::extraction_path(dest_path, &safe_name)?,
+            };
+            let result = if is_directory {
+                std::fs::create_dir_all(&path).map_err(Into::into)
+            } else if escapes {
+                self.extract_entry_as(&entry, dest_path, &safe_name)
+            } else {
+                self.extract_entry(&entry, dest_path)
+            };
+
+            if let Err(error) = result {
+                report.failed.push((name, error));
+                continue;
+            }
+
+            if is_directory {
+                report.directories_created += 1;
+            } else {
+                report.files_written += 1;
+                report.bytes_written += entry.size()?;
+            }
+            if escapes {
+                report.sanitized.push((name, path));
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Pair every entry with the name and path it is extracted to below `dest_path`
+    ///
+    /// Names are normalized the way the native side writes them (see
+    /// [`set_name_normalization`](Self::set_name_normalization)), in one native
+    /// call for all entries. The entries carry the metadata of the bulk
+    /// listing, so their getters don't cross into the native library.
+    fn extraction_targets<'a>(&'a self, dest_path: &'a Path) -> Result<impl Iterator<Item = Result<ExtractionTarget>> + 'a> {
+        let entries = self.entries()?;
+        let names = self.normalize_names(entries.cursor.positions.as_slice().iter().map(|(_, info)| info.name.as_str()))?;
+
+        Ok(entries.zip(names).map(move |(entry, name)| {
+            let path = crate::zip_entry::extraction_path(dest_path, &name);
+            Ok(ExtractionTarget { entry: entry?, name, path })
+        }))
+    }
+
+    /// Extract an entry under a different name below `dest_path`
+    fn extract_entry_as(&self, entry: &ZipEntry, dest_path: &Path, name: &str) -> Result<()> {
+        self.ensure_current(entry)?;
+
+        let path_str = dest_path.to_string_lossy();
+        let c_path = helpers::to_c_string(&path_str)?;
+        let c_name = helpers::to_c_string(name)?;
+
+        let result = unsafe {
+            ffi::zip4j_extract_entry_as(
+                *self.thread()?,
+                self.handle,
+                entry.handle(),
+                c_path.as_ptr() as *mut c_char,
+                c_name.as_ptr() as *mut c_char
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("extract_entry", entry.name().unwrap_or_default()));
+        }
+
+        telemetry::record_extracted_with(|| entry.size());
+        Ok(())
+    }
+
+    /// Extract all files, calling `on_entry` as soon as each entry is written
+    ///
+    /// The callback gets the entry's final path, its size on disk and how long
+    /// it took to extract, so manifests can be updated or file attributes set
+    /// while extraction goes on rather than by walking the destination
+    /// afterwards. Directories are reported too. Returning `false` stops the
+    /// extraction, which then fails with `ZipError::OperationCancelled`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("installer.zip")?;
+    /// let mut installed = Vec::new();
+    /// zip.extract_all_with_callback("install", |entry| {
+    ///     println!("{} ({} bytes in {:?})", entry.path.display(), entry.size, entry.duration);
+    ///     installed.push(entry.path.clone());
+    ///     true
+    /// })?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), dest = %dest_path.as_ref().display()), err))]
+    pub fn extract_all_with_callback<P, F>(&self, dest_path: P, mut on_entry: F) -> Result<()>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&ExtractedEntry) -> bool,
+    {
+        let _timer = telemetry::OperationTimer::start("extract_all_with_callback");
+        let dest_path = dest_path.as_ref();
+
+        for target in self.extraction_targets(dest_path)? {
+            let ExtractionTarget { entry, path, .. } = target?;
+            let start = Instant::now();
+            let path = path?;
+            let is_directory = entry.is_directory()?;
+
+            let size = if is_directory {
+                std::fs::create_dir_all(&path)?;
+                0
+            } else {
+                self.extract_entry(&entry, dest_path)?;
+                std::fs::metadata(&path)?.len()
+            };
+
+            let extracted = ExtractedEntry {
+                name: entry.name()?,
+                path,
+                size,
+                is_directory,
+                duration: start.elapsed(),
+            };
+            if !on_entry(&extracted) {
+                return Err(crate::error::ZipError::OperationCancelled);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Extract all files on a background thread, reporting progress as events
+    ///
+    /// The archive is moved to the worker thread and events are sent over the
+    /// returned channel as extraction goes (see [`ZipEvent`] for their order),
+    /// which is convenient for driving a progress bar or a websocket without
+    /// holding on to a callback. Progress is reported while large entries are
+    /// being written, not only between entries. Dropping the receiver cancels
+    /// the extraction.
+    ///
+    /// The worker thread calls into the shared isolate, so an archive opened
+    /// inside [`Isolate::run`](crate::Isolate::run) only gets a
+    /// `ZipError::InvalidHandle` error event.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{ZipEvent, ZipFile};
+    ///
+    /// let zip = ZipFile::new("assets.zip")?;
+    /// let mut total = 0;
+    /// for event in zip.extract_all_events("assets") {
+    ///     match event {
+    ///         ZipEvent::Started { bytes_total, .. } => total = bytes_total,
+    ///         ZipEvent::Progress { bytes } => println!("{}/{} bytes", bytes, total),
+    ///         ZipEvent::Error(e) => return Err(e),
+    ///         _ => {}
+    ///     }
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    pub fn extract_all_events<P: Into<PathBuf>>(self, dest_path: P) -> Receiver<ZipEvent> {
+        let dest_path = dest_path.into();
+        let (sender, receiver) = mpsc::channel();
+
+        // Dropped here rather than on the worker, where its handle couldn't be released
+        if self.isolate != 0 {
+            let _ = sender.send(ZipEvent::Error(crate::error::ZipError::InvalidHandle));
+            return receiver;
+        }
+
+        std::thread::spawn(move || {
+            if let Err(e) = self.send_extract_events(&dest_path, &sender) {
+                let _ = sender.send(ZipEvent::Error(e));
+            }
+        });
+
+        receiver
+    }
+
+    /// Extract entries one by one for [`extract_all_events`](Self::extract_all_events)
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), dest = %dest_path.display()), err))]
+    fn send_extract_events(&self, dest_path: &Path, sender: &Sender<ZipEvent>) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("extract_all_events");
+        let targets = self.extraction_targets(dest_path)?;
+        let infos = self.list_entries()?;
+        let bytes_total = infos.iter().map(|info| info.size).sum();
+        if sender.send(ZipEvent::Started { entries: infos.len(), bytes_total }).is_err() {
+            return Ok(());
+        }
+
+        let mut bytes = 0;
+        let mut reported = 0;
+        for target in targets {
+            let ExtractionTarget { entry, path, .. } = target?;
+            let name = entry.name()?;
+            let size = entry.size()?;
+            if sender.send(ZipEvent::EntryStarted { name: name.clone(), size }).is_err() {
+                return Ok(());
+            }
+
+            if entry.is_directory()? {
+                std::fs::create_dir_all(path?)?;
+            } else {
+                let mut receiver_gone = false;
+                let outcome = self.extract_entry_monitored(&entry, dest_path, |progress| {
+                    // zip4j resets the monitor once the entry is done, so only ever report growth
+                    let done = bytes + progress.bytes_done.min(size);
+                    if done > reported {
+                        reported = done;
+                        receiver_gone = sender.send(ZipEvent::Progress { bytes: done }).is_err();
+                    }
+                    !receiver_gone
+                });
+                if receiver_gone {
+                    return Ok(());
+                }
+                outcome?;
+            }
+
+            bytes += size;
+            if bytes > reported {
+                reported = bytes;
+                if sender.send(ZipEvent::Progress { bytes }).is_err() {
+                    return Ok(());
+                }
+            }
+            if sender.send(ZipEvent::EntryFinished { name }).is_err() {
+                return Ok(());
+            }
+        }
+
+        let _ = sender.send(ZipEvent::Finished);
+        Ok(())
+    }
+
+    /// Extract a file entry below `dest_path` on a native worker thread, passing
+    /// its progress to `on_progress` as it goes
+    ///
+    /// Returning `false` from `on_progress` cancels the extraction, which then
+    /// fails with `ZipError::OperationCancelled`.
+    fn extract_entry_monitored<F>(&self, entry: &ZipEntry, dest_path: &Path, on_progress: F) -> Result<()>
+    where
+        F: FnMut(&Progress) -> bool,
+    {
+        self.ensure_current(entry)?;
+
+        let path_str = dest_path.to_string_lossy();
+        let c_path = helpers::to_c_string(&path_str)?;
+        let mut monitor: c_longlong = 0;
+
+        let result = unsafe {
+            ffi::zip4j_extract_entry_async(
+                *self.thread()?,
+                self.handle,
+                entry.handle(),
+                c_path.as_ptr() as *mut c_char,
+                &mut monitor
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("extract_entry", entry.name().unwrap_or_default()));
+        }
+
+        OperationMonitor { handle: monitor }.wait(on_progress)
+            .map_err(|e| e.with_context("extract_entry", entry.name().unwrap_or_default()))?;
+        telemetry::record_extracted_with(|| entry.size());
+        Ok(())
+    }
+
+    /// Extract a specific file by name from the zip archive
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - Name of the file to extract
+    /// * `dest_path` - Directory where the file should be extracted
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), entry = file_name.as_ref(), dest = %dest_path.as_ref().display()), err))]
+    pub fn extract_file<S: AsRef<str>, P: AsRef<Path>>(&self, file_name: S, dest_path: P) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("extract_file");
+        let c_name = helpers::to_c_string(file_name.as_ref())?;
+        let path_str = dest_path.as_ref().to_string_lossy();
+        let c_path = helpers::to_c_string(&path_str)?;
+
+        let result = unsafe {
+            ffi::zip4j_extract_file(
+                *self.thread()?,
+                self.handle,
+                c_name.as_ptr() as *mut c_char,
+                c_path.as_ptr() as *mut c_char
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("extract_file", file_name.as_ref()));
+        }
+
+        telemetry::record_extracted_with(|| self.get_entry_by_name(file_name.as_ref())?.size());
+        Ok(())
+    }
+
+    /// Extract a specific entry to a destination directory
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The entry to extract
+    /// * `dest_path` - Directory where the entry should be extracted
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), entry_handle = entry.handle(), dest = %dest_path.as_ref().display()), err))]
+    pub fn extract_entry<P: AsRef<Path>>(&self, entry: &ZipEntry, dest_path: P) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("extract_entry");
+        self.ensure_current(entry)?;
+
+        let path_str = dest_path.as_ref().to_string_lossy();
+        let c_path = helpers::to_c_string(&path_str)?;
+
+        let result = unsafe {
+            ffi::zip4j_extract_entry(
+                *self.thread()?,
+                self.handle,
+                entry.handle(),
+                c_path.as_ptr() as *mut c_char
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("extract_entry", entry.name().unwrap_or_default()));
+        }
+
+        telemetry::record_extracted_with(|| entry.size());
+        Ok(())
+    }
+
+    /// Test the integrity of every entry, like `unzip -t`
+    ///
+    /// Each entry is decompressed (and decrypted, using the archive password)
+    /// without writing anything to disk, and its CRC32 is checked. Problems with
+    /// individual entries are collected in the report instead of stopping the test.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("artifact.zip")?;
+    /// let report = zip.test()?;
+    /// for failure in report.failures() {
+    ///     eprintln!("{}: {}", failure.name, failure.error.as_ref().unwrap());
+    /// }
+    /// assert!(report.is_ok());
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn test(&self) -> Result<TestReport> {
+        let _timer = telemetry::OperationTimer::start("test");
+        let mut report = TestReport::default();
+
+        for entry in self.entries()? {
+            report.entries.push(self.test_entry(&entry?)?);
+        }
+
+        Ok(report)
+    }
+
+    /// Test the integrity of every entry using several threads
+    ///
+    /// Works like [`ZipFile::test`], but spreads the entries over worker
+    /// threads that each take the next untested entry as soon as they are done
+    /// with one, which cuts the time to validate large archives roughly by the
+    /// number of cores. The report still lists entries in archive order.
+    ///
+    /// # Arguments
+    ///
+    /// * `threads` - Number of worker threads, or 0 to use the available parallelism
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("release.zip")?;
+    /// let report = zip.test_parallel(0)?;
+    /// assert!(report.is_ok());
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), threads = threads), err))]
+    pub fn test_parallel(&self, threads: usize) -> Result<TestReport> {
+        let _timer = telemetry::OperationTimer::start("test_parallel");
+        let threads = match threads {
+            0 => std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+            threads => threads,
+        };
+        let entries = self.entries()?.collect::<Result<Vec<_>>>()?;
+        let next = AtomicUsize::new(0);
+
+        let mut results: Vec<(usize, Result<EntryTestResult>)> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads.min(entries.len()))
+                .map(|_| scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(entry) = entries.get(index) else { break };
+                        results.push((index, self.test_entry(entry)));
+                    }
+                    results
+                }))
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("test worker panicked"))
+                .collect()
+        });
+
+        results.sort_by_key(|(index, _)| *index);
+        Ok(TestReport {
+            entries: results.into_iter().map(|(_, result)| result).collect::<Result<_>>()?,
+        })
+    }
+
+    /// Decompress a single entry without writing it anywhere and record the outcome
+    fn test_entry(&self, entry: &ZipEntry) -> Result<EntryTestResult> {
+        let result = unsafe {
+            ffi::zip4j_test_entry(
+                *self.thread()?,
+                self.handle,
+                entry.handle()
+            )
+        };
+
+        let error = if helpers::is_error(result) {
+            Some(crate::error::ZipError::from_native(result))
+        } else {
+            None
+        };
+
+        Ok(EntryTestResult { name: entry.name()?, error })
+    }
+
+    /// Extract every readable entry, skipping the ones that fail
+    ///
+    /// A best-effort alternative to [`ZipFile::extract_all`] for data recovery:
+    /// an entry that can't be read (corrupt data, CRC mismatch, wrong password,
+    /// ...) is skipped, its partially written file removed, and extraction
+    /// continues with the next entry. The report lists what was recovered and
+    /// why the rest failed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("damaged.zip")?;
+    /// let report = zip.salvage("recovered")?;
+    /// println!("recovered {} entries", report.recovered.len());
+    /// for (name, error) in &report.failed {
+    ///     eprintln!("lost {}: {}", name, error);
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), dest = %dest_path.as_ref().display()), err))]
+    pub fn salvage<P: AsRef<Path>>(&self, dest_path: P) -> Result<SalvageReport> {
+        let path_str = dest_path.as_ref().to_string_lossy();
+        let c_path = helpers::to_c_string(&path_str)?;
+        let mut report = SalvageReport::default();
+
+        // Size and modification time of a file, to tell whether an attempt wrote to it
+        let stamp = |path: &Path| std::fs::metadata(path).ok()
+            .filter(|metadata| metadata.is_file())
+            .map(|metadata| (metadata.len(), metadata.modified().ok()));
+
+        for target in self.extraction_targets(dest_path.as_ref())? {
+            let ExtractionTarget { entry, path, .. } = target?;
+            let name = entry.name()?;
+            let before = path.as_deref().ok().map(stamp);
+
+            let result = unsafe {
+                ffi::zip4j_extract_entry(
+                    *self.thread()?,
+                    self.handle,
+                    entry.handle(),
+                    c_path.as_ptr() as *mut c_char
+                )
+            };
+
+            if helpers::is_error(result) {
+                let error = crate::error::ZipError::from_native(result);
+                // Only remove a file this attempt created or truncated, and never
+                // touch anything outside the destination for a name that escapes it
+                if let (Ok(partial), Some(before)) = (&path, before) {
+                    let after = stamp(partial);
+                    if after.is_some() && after != before {
+                        let _ = std::fs::remove_file(partial);
+                    }
+                }
+                report.failed.push((name, error));
+            } else {
+                telemetry::record_extracted_with(|| entry.size());
+                report.recovered.push(name);
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Scan every entry and report which ones are intact, truncated or damaged
+    ///
+    /// Unlike [`ZipFile::test`], which reports any failure as an error, this
+    /// classifies each entry so recovery tools can decide what is worth
+    /// salvaging. The archive's central directory must still be readable.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{EntryCondition, ZipFile};
+    ///
+    /// let zip = ZipFile::new("damaged.zip")?;
+    /// for health in zip.scan()? {
+    ///     if health.condition != EntryCondition::Intact {
+    ///         println!("{}: {:?} ({})", health.name, health.condition, health.detail.unwrap_or_default());
+    ///     }
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn scan(&self) -> Result<Vec<EntryHealth>> {
+        let mut report = Vec::new();
+
+        for entry in self.entries()? {
+            let entry = entry?;
+            let mut health: c_int = 0;
+
+            let result = unsafe {
+                ffi::zip4j_scan_entry(
+                    *self.thread()?,
+                    self.handle,
+                    entry.handle(),
+                    &mut health
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+
+            let condition = EntryCondition::from(health);
+            let detail = if condition == EntryCondition::Intact {
+                None
+            } else {
+                Some(helpers::get_last_error(self.handle)?)
+            };
+
+            report.push(EntryHealth { name: entry.name()?, condition, detail });
+        }
+
+        Ok(report)
+    }
+
+    /// Compare the archive with the files in a directory
+    ///
+    /// Entries are matched with files by their path relative to `dir_path`.
+    /// Files whose size matches their entry are compared by CRC32; use
+    /// [`ZipFile::diff_with_dir_using`] to compare modification times instead.
+    /// Entries missing on disk are listed first, in archive order, followed by
+    /// files the archive doesn't contain. Directories without an entry are not
+    /// reported.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{DiffKind, ZipFile};
+    ///
+    /// let zip = ZipFile::new("backup.zip")?;
+    /// for diff in zip.diff_with_dir("documents")? {
+    ///     if diff.kind == DiffKind::Modified {
+    ///         println!("changed: {}", diff.name);
+    ///     }
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), path = %dir_path.as_ref().display()), err))]
+    pub fn diff_with_dir<P: AsRef<Path>>(&self, dir_path: P) -> Result<Vec<EntryDiff>> {
+        self.diff_with_dir_using(dir_path, ChangeDetection::default())
+    }
+
+    /// Compare the archive with the files in a directory, choosing how changes are detected
+    ///
+    /// See [`ZipFile::diff_with_dir`].
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), path = %dir_path.as_ref().display()), err))]
+    pub fn diff_with_dir_using<P: AsRef<Path>>(&self, dir_path: P, detection: ChangeDetection) -> Result<Vec<EntryDiff>> {
+        let root = dir_path.as_ref();
+        if !root.is_dir() {
+            return Err(crate::error::ZipError::InvalidParameter(
+                format!("Not a directory: {}", root.display())
+            ));
+        }
+
+        let mut diffs = Vec::new();
+        let mut archived = HashSet::new();
+
+        for entry in self.entries()? {
+            let entry = entry?;
+            let name = entry.name()?;
+
+            let kind = match self.compare_entry(&entry, &root.join(&name), detection)? {
+                ffi::constants::DIFF_MISSING => Some(DiffKind::OnlyInArchive),
+                ffi::constants::DIFF_MODIFIED => Some(DiffKind::Modified),
+                _ => None,
+            };
+            if let Some(kind) = kind {
+                diffs.push(EntryDiff { name: name.clone(), kind });
+            }
+            archived.insert(name);
+        }
+
+        let mut files = Vec::new();
+        collect_files(root, root, &mut files)?;
+        for name in files {
+            if !archived.contains(&name) {
+                diffs.push(EntryDiff { name, kind: DiffKind::OnlyOnDisk });
+            }
+        }
+
+        Ok(diffs)
+    }
+
+    /// Extract data from an entry to a byte vector (in-memory extraction)
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The entry to extract
+    ///
+    /// # Returns
+    ///
+    /// A vector containing the extracted data
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), entry_handle = entry.handle()), err))]
+    pub fn extract_data(&self, entry: &ZipEntry) -> Result<Vec<u8>> {
+        let _timer = telemetry::OperationTimer::start("extract_data");
+        self.ensure_current(entry)?;
+
+        // Start with a reasonable buffer size
+        let mut buffer_size = entry.size()? as usize;
+        if buffer_size == 0 {
+            buffer_size = 1024; // Default size for unknown sizes
+        }
+
+        let mut buffer = vec![0u8; buffer_size];
+        let mut data_length: c_int = 0;
+
+        let result = unsafe {
+            ffi::zip4j_extract_data(
+                *self.thread()?,
+                self.handle,
+                entry.handle(),
+                buffer.as_mut_ptr() as *mut c_char,
+                buffer_size as c_int,
+                &mut data_length
+            )
+        };
+
+        if result == crate::ffi::constants::ERROR_BUFFER_TOO_SMALL {
+            // Resize buffer and try again
+            buffer.resize(data_length as usize, 0);
+            let result = unsafe {
+                ffi::zip4j_extract_data(
+                    *self.thread()?,
+                    self.handle,
+                    entry.handle(),
+                    buffer.as_mut_ptr() as *mut c_char,
+                    buffer.len() as c_int,
+                    &mut data_length
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result).with_context("extract_data", entry.name().unwrap_or_default()));
+            }
+        } else if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("extract_data", entry.name().unwrap_or_default()));
+        }
+
+        // Truncate buffer to actual data length
+        buffer.truncate(data_length as usize);
+        telemetry::record_extracted(buffer.len() as u64);
+        Ok(buffer)
+    }
+
+    /// Extract several entries into memory in parallel
+    ///
+    /// Entries are decompressed on the rayon thread pool and collected into a
+    /// map keyed by entry name, which suits loading a whole asset archive at
+    /// startup. The first entry that fails to extract aborts the whole call.
+    /// Requires the `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("assets.zip")?;
+    /// let entries = zip.entries()?.collect::<Result<Vec<_>, _>>()?;
+    /// let assets = zip.par_extract_data(&entries)?;
+    /// println!("loaded {} assets", assets.len());
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg(feature = "rayon")]
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), count = entries.len()), err))]
+    pub fn par_extract_data(&self, entries: &[ZipEntry]) -> Result<HashMap<String, Vec<u8>>> {
+        use rayon::prelude::*;
+
+        entries
+            .par_iter()
+            .map(|entry| Ok((entry.name()?, self.extract_data(entry)?)))
+            .collect()
+    }
+
+    /// Read part of an entry's uncompressed data into memory
+    ///
+    /// Returns up to `len` bytes starting at `offset`, or fewer if the entry
+    /// ends first. Unencrypted stored entries are read directly from the
+    /// archive file, so this is cheap even for huge entries; compressed or
+    /// encrypted entries have to be decoded from the start up to `offset`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("assets.pak")?;
+    /// let entry = zip.get_entry_by_name("video/intro.mp4")?;
+    /// let header = zip.extract_range(&entry, 0, 64)?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), entry_handle = entry.handle(), offset = offset, len = len), err))]
+    pub fn extract_range(&self, entry: &ZipEntry, offset: u64, len: usize) -> Result<Vec<u8>> {
+        let _timer = telemetry::OperationTimer::start("extract_range");
+        self.ensure_current(entry)?;
+
+        let len = len.min(c_int::MAX as usize);
+        let mut buffer = vec![0u8; len];
+        let mut data_length: c_int = 0;
+
+        let result = unsafe {
+            ffi::zip4j_extract_range(
+                *self.thread()?,
+                self.handle,
+                entry.handle(),
+                offset as c_longlong,
+                buffer.as_mut_ptr() as *mut c_char,
+                len as c_int,
+                &mut data_length
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("extract_range", entry.name().unwrap_or_default()));
+        }
+
+        buffer.truncate(data_length as usize);
+        telemetry::record_extracted(buffer.len() as u64);
+        Ok(buffer)
+    }
+
+    /// Remove a file from the zip archive by name
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - Name of the file to remove
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), entry = file_name.as_ref()), err))]
+    pub fn remove_file<S: AsRef<str>>(&mut self, file_name: S) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("remove_file");
+        self.modify(|zip| {
+            let c_name = helpers::to_c_string(file_name.as_ref())?;
+
+            let result = unsafe {
+                ffi::zip4j_remove_file(
+                    *zip.thread()?,
+                    zip.handle,
+                    c_name.as_ptr() as *mut c_char
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result).with_context("remove_file", file_name.as_ref()));
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Remove several files from the zip archive by name in a single operation
+    ///
+    /// # Arguments
+    ///
+    /// * `file_names` - Names of the files to remove
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), count = file_names.len()), err))]
+    pub fn remove_files<S: AsRef<str>>(&mut self, file_names: &[S]) -> Result<()> {
+        self.modify(|zip| {
+            if file_names.is_empty() {
+                return Ok(());
+            }
+
+            let names = helpers::to_c_string_list(file_names)?;
+
+            let result = unsafe {
+                ffi::zip4j_remove_files(
+                    *zip.thread()?,
+                    zip.handle,
+                    names.as_ptr() as *mut c_char,
+                    names.len() as c_int
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Remove all entries whose name matches a glob pattern
+    ///
+    /// `*` and `?` do not cross `/`, while `**` matches any number of folders.
+    /// Only the matched entries are removed, a matched folder entry leaves its contents in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Glob pattern such as `logs/**/*.tmp`
+    ///
+    /// # Returns
+    ///
+    /// The number of entries that were removed
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), pattern = pattern.as_ref()), err))]
+    pub fn remove_matching<S: AsRef<str>>(&mut self, pattern: S) -> Result<usize> {
+        self.modify(|zip| {
+            let pattern = compile_glob(pattern.as_ref())?;
+            let matched: Vec<String> = zip.entry_names()?.into_iter()
+                .filter(|name| pattern.matches_with(name, GLOB_OPTIONS))
+                .collect();
+
+            if matched.is_empty() {
+                return Ok(0);
+            }
+
+            // Removed by exact name, a matched folder entry must not take its children with it
+            let mut seen = HashSet::new();
+            let names: Vec<&str> = matched.iter()
+                .filter(|name| seen.insert(name.as_str()))
+                .flat_map(|name| [name.as_str(), ""])
+                .collect();
+            let names = helpers::to_c_string_list(&names)?;
+
+            let result = unsafe {
+                ffi::zip4j_rewrite_entries(
+                    *zip.thread()?,
+                    zip.handle,
+                    names.as_ptr() as *mut c_char,
+                    names.len() as c_int
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+
+            Ok(matched.len())
+        })
+    }
+
+    /// Remove an entry from the zip archive
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The entry to remove
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), entry_handle = entry.handle()), err))]
+    pub fn remove_entry(&mut self, entry: &ZipEntry) -> Result<()> {
+        let _timer = telemetry::OperationTimer::start("remove_entry");
+        self.ensure_current(entry)?;
+        self.modify(|zip| {
+            let result = unsafe {
+                ffi::zip4j_remove_entry(
+                    *zip.thread()?,
+                    zip.handle,
+                    entry.handle()
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result).with_context("remove_entry", entry.name().unwrap_or_default()));
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Rename entries by applying a mapping function to every entry name
+    ///
+    /// The closure receives each entry name and returns the new name, or `None`
+    /// to leave the entry untouched. Names are matched exactly, except that
+    /// entries below a renamed folder entry move along with it unless the
+    /// closure renames them as well. All renames are applied in a single native
+    /// operation, and an empty new name fails with `ZipError::InvalidParameter`
+    /// before anything is renamed.
+    ///
+    /// # Arguments
+    ///
+    /// * `mapping` - Function mapping an entry name to its new name
+    ///
+    /// # Returns
+    ///
+    /// The number of entries that were renamed
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let mut zip = ZipFile::new("archive.zip")?;
+    /// // Normalize Windows path separators
+    /// zip.rename_entries(|name| {
+    ///     name.contains('\\').then(|| name.replace('\\', "/"))
+    /// })?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn rename_entries<F>(&mut self, mut mapping: F) -> Result<usize>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        self.modify(|zip| {
+            let mut renames = Vec::new();
+            let mut seen = HashSet::new();
+            for name in zip.entry_names()? {
+                // Entries sharing a name are renamed together
+                if !seen.insert(name.clone()) {
+                    continue;
+                }
+                if let Some(new_name) = mapping(&name) {
+                    if new_name.trim().is_empty() {
+                        return Err(crate::error::ZipError::InvalidParameter(
+                            format!("New name for {} must not be empty", name)
+                        ));
+                    }
+                    if new_name != name {
+                        renames.push(name);
+                        renames.push(new_name);
+                    }
+                }
+            }
+
+            if renames.is_empty() {
+                return Ok(0);
+            }
+
+            let names = helpers::to_c_string_list(&renames)?;
+
+            let result = unsafe {
+                ffi::zip4j_rename_entries(
+                    *zip.thread()?,
+                    zip.handle,
+                    names.as_ptr() as *mut c_char,
+                    names.len() as c_int
+                )
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+
+            Ok(renames.len() / 2)
+        })
+    }
+
+    /// Move every entry under a folder to another folder
+    ///
+    /// Entry data is not recompressed; only the names are rewritten, in a single
+    /// native operation. A missing trailing `/` is added to both prefixes, and an
+    /// empty `new_prefix` moves the folder contents to the archive root.
+    ///
+    /// # Arguments
+    ///
+    /// * `old_prefix` - Folder to move, e.g. `old_dir/`
+    /// * `new_prefix` - Destination folder, e.g. `new_dir/`
+    ///
+    /// # Returns
+    ///
+    /// The number of entries that were renamed
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), old_prefix = old_prefix.as_ref(), new_prefix = new_prefix.as_ref()), err))]
+    pub fn move_prefix<S: AsRef<str>, T: AsRef<str>>(&mut self, old_prefix: S, new_prefix: T) -> Result<usize> {
+        let old_prefix = folder_prefix(old_prefix.as_ref());
+        let new_prefix = folder_prefix(new_prefix.as_ref());
+
+        if old_prefix.is_empty() {
+            return Err(crate::error::ZipError::InvalidParameter(
+                "Folder prefix to move must not be empty".to_string()
+            ));
+        }
+
+        self.rename_entries(|name| {
+            name.strip_prefix(old_prefix.as_str())
+                .map(|rest| format!("{}{}", new_prefix, rest))
+                .filter(|new_name| !new_name.is_empty())
+        })
+    }
+
+    /// Apply several modifications together
+    ///
+    /// The closure records removals, renames and additions on a [`Batch`]. On
+    /// return, all removals and renames are applied in a single rewrite of the
+    /// archive, after which the additions are appended. Entry data is not
+    /// recompressed, and names are matched exactly, so removing or renaming a
+    /// folder entry leaves its contents alone. If a new name is taken by an
+    /// entry the batch leaves in place, the batch fails with
+    /// `ZipError::InvalidParameter` before anything is changed. Combine with
+    /// [`SaveMode::Atomic`] to make the whole batch all-or-nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{ZipFile, ZipParameters};
+    ///
+    /// let mut zip = ZipFile::new("app.zip")?;
+    /// zip.batch(|tx| {
+    ///     tx.remove("debug.log");
+    ///     tx.rename("config.default.json", "config.json");
+    ///     tx.add_data("VERSION", b"1.2.3", &ZipParameters::new());
+    /// })?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn batch<F>(&mut self, build: F) -> Result<()>
+    where
+        F: FnOnce(&mut Batch),
+    {
+        let mut batch = Batch::default();
+        build(&mut batch);
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        self.modify(|zip| {
+            if !batch.rewrites.is_empty() {
+                let names: Vec<&str> = batch.rewrites.iter()
+                    .flat_map(|(name, new_name)| [name.as_str(), new_name.as_deref().unwrap_or("")])
+                    .collect();
+                let names = helpers::to_c_string_list(&names)?;
+
+                let result = unsafe {
+                    ffi::zip4j_rewrite_entries(
+                        *zip.thread()?,
+                        zip.handle,
+                        names.as_ptr() as *mut c_char,
+                        names.len() as c_int
+                    )
+                };
+
+                if helpers::is_error(result) {
+                    return Err(crate::error::ZipError::from_native(result));
+                }
+            }
+
+            for entry in batch.additions {
+                zip.add(entry)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Seal the archive, making it read-only
+    ///
+    /// After sealing, every operation that would modify the archive fails
+    /// with [`ZipError::Sealed`](crate::ZipError::Sealed).
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn seal(&mut self) -> Result<()> {
+        self.seal_with_options(&SealOptions::default())
+    }
+
+    /// Seal the archive, embedding the requested metadata first
+    ///
+    /// The table of contents, signature and provenance are stored uncompressed
+    /// under the entry names defined on [`SealOptions`]. The table of contents
+    /// lists every entry present before sealing, one per line, as
+    /// `<crc32 hex> <size> <name>`.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - What to embed before the archive is sealed
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn seal_with_options(&mut self, options: &SealOptions) -> Result<()> {
+        self.modify(|zip| {
+            let params = ZipParameters::new().with_compression_method(CompressionMethod::Store);
+
+            if options.embed_toc {
+                let mut toc = String::new();
+                for entry in zip.entries()? {
+                    let entry = entry?;
+                    toc.push_str(&format!("{:08x} {} {}\n", entry.crc32()?, entry.size()?, entry.name()?));
+                }
+                zip.add_data(SealOptions::TOC_ENTRY, toc.as_bytes(), &params)?;
+            }
+
+            if let Some(signature) = &options.signature {
+                zip.add_data(SealOptions::SIGNATURE_ENTRY, signature, &params)?;
+            }
+
+            if let Some(provenance) = &options.provenance {
+                zip.add_data(SealOptions::PROVENANCE_ENTRY, provenance.as_bytes(), &params)?;
+            }
+
+            zip.sealed = true;
+            Ok(())
+        })
+    }
+
+    /// Set how modifications are written to disk
+    ///
+    /// With [`SaveMode::Atomic`] every modification is applied to a temporary
+    /// copy in the archive's directory, which atomically replaces the archive
+    /// once the modification succeeded. A crash or error midway leaves the
+    /// original archive untouched, at the cost of copying it for each
+    /// modification. Split archives only support [`SaveMode::InPlace`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::{SaveMode, ZipFile, ZipParameters};
+    ///
+    /// let mut zip = ZipFile::new("archive.zip")?;
+    /// zip.set_save_mode(SaveMode::Atomic);
+    /// zip.add_data("config.json", b"{}", &ZipParameters::new())?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    pub fn set_save_mode(&mut self, mode: SaveMode) {
+        self.save_mode = mode;
+    }
+
+    /// Get how modifications are written to disk
+    pub fn save_mode(&self) -> SaveMode {
+        self.save_mode
+    }
+
+    /// Take an exclusive advisory lock on the archive, waiting until it is free
+    ///
+    /// The lock is held on a lock file next to the archive, named after it with
+    /// `.lock` appended, so it survives the archive being rewritten and
+    /// replaced. It is released by [`unlock`](Self::unlock) or when the zip file
+    /// is closed. Locking is advisory: it only keeps out other zip files that
+    /// lock the same archive, whether in this process or another one. The lock
+    /// file is left in place.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let mut zip = ZipFile::new("backup.zip")?;
+    /// zip.lock()?;
+    /// zip.add_directory("documents")?;
+    /// zip.unlock()?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn lock(&mut self) -> Result<()> {
+        self.acquire_lock(true).map(|_| ())
+    }
+
+    /// Take an exclusive advisory lock on the archive if nobody else holds it
+    ///
+    /// See [`lock`](Self::lock) for how the lock works.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the lock was taken (or was already held by this zip file),
+    /// `false` if it is held elsewhere
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn try_lock(&mut self) -> Result<bool> {
+        self.acquire_lock(false)
+    }
+
+    /// Release the lock taken by [`lock`](Self::lock) or [`try_lock`](Self::try_lock), if any
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn unlock(&mut self) -> Result<()> {
+        let result = unsafe {
+            ffi::zip4j_unlock(*self.thread()?, self.handle)
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
+        Ok(())
+    }
+
+    /// Take the advisory lock, optionally waiting for it
+    fn acquire_lock(&mut self, wait: bool) -> Result<bool> {
+        let mut acquired: c_int = 0;
+
+        let result = unsafe {
+            ffi::zip4j_lock(
+                *self.thread()?,
+                self.handle,
+                wait as c_int,
+                &mut acquired
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
+        Ok(acquired != 0)
+    }
+
+    /// Check if the archive has been sealed
+    pub fn is_sealed(&self) -> bool {
+        self.sealed
+    }
+
+    /// Close the archive, reporting any error that dropping it would swallow
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let mut zip = ZipFile::new("output.zip")?;
+    /// zip.add_file("report.pdf")?;
+    /// zip.close()?;
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn close(mut self) -> Result<()> {
+        self.try_close()
+    }
+
+    /// Close the archive without consuming it
+    ///
+    /// Further operations on the zip file fail with
+    /// [`ZipError::InvalidHandle`](crate::ZipError::InvalidHandle), and closing
+    /// it again does nothing. The handle is released even if an error is
+    /// returned.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn try_close(&mut self) -> Result<()> {
+        if self.handle == 0 {
+            return Ok(());
+        }
+
+        let result = unsafe {
+            ffi::zip4j_close(*self.thread()?, self.handle)
+        };
+
+        let error = helpers::is_error(result).then(|| crate::error::ZipError::from_native(result));
+        self.handle = 0;
+        if let Some(error) = error {
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// Fail with `ZipError::Sealed` if the archive may no longer be modified
+    fn ensure_writable(&self) -> Result<()> {
+        if self.sealed {
+            return Err(crate::error::ZipError::Sealed);
+        }
+        Ok(())
+    }
+
+    /// Run a modification, staging it in a temporary copy of the archive when
+    /// the save mode is [`SaveMode::Atomic`]
+    ///
+    /// The copy replaces the archive only if the modification succeeded. Nested
+    /// modifications are part of the outermost one. Entries obtained before a
+    /// successful modification become stale.
+    fn modify<T, F>(&mut self, op: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        let stage = self.save_mode == SaveMode::Atomic;
+        self.modify_with(stage, op)
+    }
+
+    /// Run a modification, staging it in a temporary copy of the archive when
+    /// `stage` is set, whatever the save mode
+    ///
+    /// Used by operations made of several native steps that must not leave
+    /// the archive half-changed when a later step fails.
+    fn modify_with<T, F>(&mut self, stage: bool, op: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        self.ensure_writable()?;
+        if !stage || self.staging {
+            let outcome = op(self);
+            if outcome.is_ok() {
+                self.generation += 1;
+            }
+            return outcome;
+        }
+
+        let result = unsafe {
+            ffi::zip4j_begin_staging(*self.thread()?, self.handle)
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
+        self.staging = true;
+        let outcome = op(self);
+        self.staging = false;
+
+        let result = unsafe {
+            ffi::zip4j_end_staging(*self.thread()?, self.handle, outcome.is_ok() as c_int)
+        };
+
+        if helpers::is_error(result) && outcome.is_ok() {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
+        if outcome.is_ok() {
+            self.generation += 1;
+        }
+        outcome
+    }
+
+    /// Fail with `ZipError::StaleEntry` if the entry was obtained before the archive was last modified,
+    /// or with `ZipError::InvalidHandle` if it belongs to another isolate
+    fn ensure_current(&self, entry: &ZipEntry) -> Result<()> {
+        if entry.isolate() != self.isolate || entry.epoch() != self.epoch {
+            return Err(crate::error::ZipError::InvalidHandle);
+        }
+        if entry.generation() != self.generation {
+            return Err(crate::error::ZipError::StaleEntry);
+        }
+        Ok(())
+    }
+
+    /// Get the isolate thread to call into for this archive
+    ///
+    /// Fails with `ZipError::InvalidHandle` outside the isolate the archive was
+    /// opened in, or after [`cleanup`](crate::cleanup) tore it down, where its
+    /// handle would refer to something else.
+    fn thread(&self) -> Result<ffi::ThreadGuard> {
+        ffi::handle_thread(self.isolate, self.epoch)
+    }
+
+    /// Get the bytes available on the file system that holds `path`
+    fn usable_space(&self, path: &Path) -> Result<u64> {
+        let path_str = path.to_string_lossy();
+        let c_path = helpers::to_c_string(&path_str)?;
+        let mut space: c_longlong = 0;
+
+        let result = unsafe {
+            ffi::zip4j_get_usable_space(
+                *self.thread()?,
+                self.handle,
+                c_path.as_ptr() as *mut c_char,
+                &mut space
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
+        Ok(space as u64)
+    }
+
+    /// Add a file from disk using already registered parameters
+    fn add_file_native(&mut self, file_path: &Path, native_params: &NativeParameters) -> Result<()> {
+        let path_str = file_path.to_string_lossy();
+        let c_path = helpers::to_c_string(&path_str)?;
+
+        let result = unsafe {
+            ffi::zip4j_add_file_with_parameters(
+                *self.thread()?,
+                self.handle,
+                c_path.as_ptr() as *mut c_char,
+                native_params.handle()
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("add_file", file_path.display()));
+        }
+
+        telemetry::record_compressed_file(file_path);
+        Ok(())
+    }
+
+    /// Stream chunks of data into a new entry using already registered parameters
+    fn write_chunks<I>(&mut self, entry_name: &str, chunks: I, native_params: &NativeParameters) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let c_name = helpers::to_c_string(entry_name)?;
+        let mut handle: c_longlong = 0;
+
+        let result = unsafe {
+            ffi::zip4j_begin_data(
+                *self.thread()?,
+                self.handle,
+                c_name.as_ptr() as *mut c_char,
+                native_params.handle(),
+                &mut handle
+            )
+        };
+
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result).with_context("add_data", entry_name));
+        }
+
+        let mut writer = DataWriter { handle, finished: false };
+        for chunk in chunks {
+            for part in chunk.as_ref().chunks(c_int::MAX as usize) {
+                writer.write(part)?;
+                telemetry::record_compressed(part.len() as u64);
+            }
+        }
+
+        writer.finish()
+    }
+
+    /// Get an iterator over all entries in the zip file
+    ///
+    /// The metadata of all entries is fetched up front in a single native
+    /// call, so the getters of the yielded entries don't cross into the native
+    /// library again.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn entries(&self) -> Result<ZipEntryIterator> {
+        Ok(Entries {
+            zip_file: self,
+            cursor: EntryCursor::new(self)?,
+        })
+    }
+
+    /// Turn the zip file into an iterator over its entries that owns the archive
+    ///
+    /// Unlike [`ZipFile::entries`], the iterator doesn't borrow the zip file,
+    /// so entries can be streamed from another thread.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let entries = ZipFile::new("archive.zip")?.into_entries()?;
+    /// std::thread::spawn(move || {
+    ///     for entry in entries {
+    ///         println!("{}", entry?.name()?);
+    ///     }
+    ///     Ok::<(), zip2rs::ZipError>(())
+    /// });
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn into_entries(self) -> Result<IntoEntries> {
+        let cursor = EntryCursor::new(&self)?;
+        Ok(Entries { zip_file: self, cursor })
+    }
+
+    /// Get an iterator over the entries whose name matches a glob pattern
+    ///
+    /// `*` and `?` do not cross `/`, while `**` matches any number of folders.
+    /// Names are matched up front from the bulk listing, and entry handles are
+    /// only created for matching entries as the iterator advances.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("sources.zip")?;
+    /// for entry in zip.entries_matching("src/**/*.rs")? {
+    ///     println!("{}", entry?.name()?);
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), pattern = pattern.as_ref()), err))]
+    pub fn entries_matching<S: AsRef<str>>(&self, pattern: S) -> Result<impl Iterator<Item = Result<ZipEntry>> + '_> {
+        let pattern = compile_glob(pattern.as_ref())?;
+        let infos = self.list_entries()?;
+
+        Ok(infos.into_iter()
+            .enumerate()
+            .filter(move |(_, info)| pattern.matches_with(&info.name, GLOB_OPTIONS))
+            .map(move |(index, info)| self.get_entry_by_index(index).map(|entry| entry.with_info(info))))
+    }
+
+    /// Read the metadata of every entry at once
+    ///
+    /// The returned [`ZipEntryInfo`] values hold no native handles, so they can
+    /// be sent across threads or kept after the archive is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let infos = ZipFile::new("archive.zip")?.entry_infos()?;
+    /// std::thread::spawn(move || {
+    ///     for info in infos {
+    ///         println!("{} ({} bytes)", info.name, info.size);
+    ///     }
+    /// });
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn entry_infos(&self) -> Result<Vec<ZipEntryInfo>> {
+        self.list_entries()
+    }
+
+    /// List the files and folders directly inside a folder of the archive
+    ///
+    /// `folder` is a path inside the archive such as `assets/`; an empty string
+    /// lists the top level. Folders that have no entry of their own but appear
+    /// in other entries' paths are included too. Children are sorted by path.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("game.zip")?;
+    /// for child in zip.list_dir("assets/")? {
+    ///     println!("{}{}", child.name, if child.is_dir { "/" } else { "" });
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display(), folder = folder.as_ref()), err))]
+    pub fn list_dir<S: AsRef<str>>(&self, folder: S) -> Result<Vec<DirEntry>> {
+        let mut folders = self.folder_tree()?;
+        let children = folders.remove(&folder_prefix(folder.as_ref())).unwrap_or_default();
+        Ok(children.into_values().collect())
+    }
+
+    /// Walk the archive as a tree of folders, depth first
+    ///
+    /// Every file and folder is yielded with its depth (0 for the top level),
+    /// folders before their contents and siblings sorted by path. Folders that
+    /// only appear in other entries' paths are included. Call
+    /// [`Walk::skip_current_dir`] to skip the rest of a folder.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let zip = ZipFile::new("project.zip")?;
+    /// let mut walk = zip.walk()?;
+    /// while let Some((depth, entry)) = walk.next() {
+    ///     println!("{}{}", "  ".repeat(depth), entry.name);
+    ///     if entry.is_dir && entry.name == "node_modules" {
+    ///         walk.skip_current_dir();
+    ///     }
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn walk(&self) -> Result<Walk> {
+        let mut folders = self.folder_tree()?;
+        let top = folders.remove("").unwrap_or_default();
+        Ok(Walk { folders, stack: vec![top.into_values()] })
+    }
+
+    /// Group every file and folder under its parent folder (`""` for the top level)
+    fn folder_tree(&self) -> Result<HashMap<String, BTreeMap<String, DirEntry>>> {
+        let mut folders: HashMap<String, BTreeMap<String, DirEntry>> = HashMap::new();
+
+        for info in self.list_entries()? {
+            let name = info.name.clone();
+            let mut info = Some(info);
+            let mut parent = String::new();
+            let mut rest = name.as_str();
+
+            while let Some((segment, remainder)) = rest.split_once('/') {
+                let path = format!("{}{}/", parent, segment);
+                let child = folders.entry(parent).or_default().entry(path.clone()).or_insert_with(|| DirEntry {
+                    name: segment.to_string(),
+                    path: path.clone(),
+                    is_dir: true,
+                    info: None,
+                });
+                if remainder.is_empty() {
+                    child.info = info.take();
+                }
+                parent = path;
+                rest = remainder;
+            }
+
+            if !rest.is_empty() {
+                folders.entry(parent).or_default().insert(name.clone(), DirEntry {
+                    name: rest.to_string(),
+                    path: name.clone(),
+                    is_dir: false,
+                    info,
+                });
+            }
+        }
+
+        Ok(folders)
+    }
+
+    /// Get the names of all entries, in archive order
+    ///
+    /// Cheaper than [`ZipFile::entries`] when only the names are needed, as no
+    /// entry handles or other metadata are created.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// for name in ZipFile::new("archive.zip")?.entry_names()? {
+    ///     println!("{}", name);
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(archive = %self.path.display()), err))]
+    pub fn entry_names(&self) -> Result<Vec<String>> {
+        let mut buffer = vec![0u8; ENTRY_LIST_BUFFER_SIZE];
+
+        loop {
+            let mut length: c_int = 0;
+
+            let result = unsafe {
+                ffi::zip4j_list_entry_names(
+                    *self.thread()?,
+                    self.handle,
+                    buffer.as_mut_ptr() as *mut c_char,
+                    buffer.len() as c_int,
+                    &mut length
+                )
+            };
+
+            // The archive may have grown between the two calls, so keep retrying
+            if result == ffi::constants::ERROR_BUFFER_TOO_SMALL {
+                buffer.resize(length as usize, 0);
+                continue;
+            }
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+
+            buffer.truncate(length as usize);
+            return helpers::read_string_list(&buffer);
+        }
+    }
+
+    /// Fetch the metadata of every entry with a single native call
+    fn list_entries(&self) -> Result<Vec<ZipEntryInfo>> {
+        let mut buffer = vec![0u8; ENTRY_LIST_BUFFER_SIZE];
+
+        loop {
+            let mut length: c_int = 0;
+
+            let result = unsafe {
+                ffi::zip4j_list_entries(
+                    *self.thread()?,
+                    self.handle,
+                    buffer.as_mut_ptr() as *mut c_char,
+                    buffer.len() as c_int,
+                    &mut length
+                )
+            };
+
+            // The archive may have grown between the two calls, so keep retrying
+            if result == ffi::constants::ERROR_BUFFER_TOO_SMALL {
+                buffer.resize(length as usize, 0);
+                continue;
+            }
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+
+            buffer.truncate(length as usize);
+            return parse_entry_infos(&buffer);
+        }
+    }
+
+    /// Drop the paths whose entries already exist with the same size and CRC32
+    fn changed_paths(&self, paths: Vec<PathBuf>, base: &Path, params: &ZipParameters) -> Result<Vec<PathBuf>> {
+        let mut existing = HashMap::new();
+        for entry in self.entries()? {
+            let entry = entry?;
+            existing.insert(entry.name()?, entry);
+        }
+
+        let prefix = folder_prefix(params.root_folder_name.as_deref().unwrap_or(""));
+        let mut changed = Vec::new();
+        for path in paths {
+            let relative = path.strip_prefix(base).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+            let unchanged = if path.is_dir() {
+                existing.contains_key(&format!("{}{}/", prefix, relative))
+            } else {
+                match existing.get(&format!("{}{}", prefix, relative)) {
+                    Some(entry) => self.compare_entry(entry, &path, ChangeDetection::Crc)? == ffi::constants::DIFF_UNCHANGED,
+                    None => false,
+                }
+            };
+            if !unchanged {
+                changed.push(path);
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Compare an entry with a file on disk, returning one of the `DIFF_*` constants
+    fn compare_entry(&self, entry: &ZipEntry, path: &Path, detection: ChangeDetection) -> Result<c_int> {
+        let c_path = helpers::to_c_string(&path.to_string_lossy())?;
+        let mut status: c_int = 0;
+
+        let result = unsafe {
+            ffi::zip4j_compare_entry_with_file(
+                *self.thread()?,
                 self.handle,
+                entry.handle(),
                 c_path.as_ptr() as *mut c_char,
-                params.compression_level.into(),
-                params.compression_method.into(),
-                params.encryption_method.into(),
-                params.aes_key_strength.into(),
-                c_password,
+                detection.into(),
+                &mut status
             )
         };
 
-        if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
+        Ok(status)
+    }
+
+    /// Get the internal handle (for advanced use cases)
+    pub(crate) fn handle(&self) -> c_longlong {
+        self.handle
+    }
+
+    /// Get the number of modifications so far
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Get the isolate the handle belongs to
+    pub(crate) fn isolate(&self) -> usize {
+        self.isolate
+    }
+
+    /// Get the generation of the isolate the handle belongs to
+    pub(crate) fn epoch(&self) -> u64 {
+        self.epoch
+    }
+}
+
+impl Drop for ZipFile {
+    fn drop(&mut self) {
+        // Close the zip file handle in the isolate it belongs to, unless it was
+        // closed explicitly or that isolate can't be reached from here, in
+        // which case it is freed when the isolate is torn down
+        if self.handle != 0 {
+            if let Some(thread) = ffi::release_thread(self.isolate, self.epoch) {
+                unsafe {
+                    ffi::zip4j_close(*thread, self.handle);
+                }
+            }
+        }
+    }
+}
+
+// Keep the thread-safety guarantees documented on `ZipFile` from regressing
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<ZipFile>();
+    assert_send_sync::<ZipEntry>();
+};
+
+/// Glob matching rules for entry names: wildcards stop at folder separators
+pub(crate) const GLOB_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Compile a glob pattern, reporting syntax errors as invalid parameters
+pub(crate) fn compile_glob(pattern: &str) -> Result<Pattern> {
+    Pattern::new(pattern).map_err(|e| crate::error::ZipError::InvalidParameter(
+        format!("Invalid glob pattern '{}': {}", pattern, e)
+    ))
+}
+
+/// Recursively collect the files and folders below `current` that pass the exclusion filters
+fn collect_directory(root: &Path, current: &Path, options: &AddDirOptions, patterns: &[Pattern], paths: &mut Vec<PathBuf>) -> Result<()> {
+    let mut children = std::fs::read_dir(current)?.collect::<std::io::Result<Vec<_>>>()?;
+    children.sort_by_key(|child| child.file_name());
+
+    for child in children {
+        let path = child.path();
+        let relative = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+
+        let file_type = child.file_type()?;
+        let excluded = options.exclude.as_ref().is_some_and(|filter| filter.is_excluded(&path))
+            || patterns.iter().any(|pattern| pattern.matches_with(&relative, GLOB_OPTIONS))
+            || (!options.include_hidden && is_hidden(&child)?)
+            || (file_type.is_symlink() && options.symlinks == SymlinkPolicy::Skip);
+        if excluded {
+            continue;
+        }
+
+        // Linked directories are only descended into when links are followed
+        let follow_links = options.symlinks == SymlinkPolicy::Follow && !options.params.symlink;
+        let descend = file_type.is_dir() || (file_type.is_symlink() && follow_links && path.is_dir());
+
+        paths.push(path.clone());
+        if descend {
+            collect_directory(root, &path, options, patterns, paths)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Decode the records written by `zip4j_list_entries` (see the Java side for the layout)
+fn parse_entry_infos(records: &[u8]) -> Result<Vec<ZipEntryInfo>> {
+    fn take_bytes<'a>(rest: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+        if rest.len() < len {
+            return Err(crate::error::ZipError::Unknown("Malformed entry listing".to_string()));
+        }
+        let (head, tail) = rest.split_at(len);
+        *rest = tail;
+        Ok(head)
+    }
+
+    fn take<const N: usize>(rest: &mut &[u8]) -> Result<[u8; N]> {
+        Ok(take_bytes(rest, N)?.try_into().expect("length was checked"))
+    }
+
+    let mut infos = Vec::new();
+    let mut rest = records;
+    while !rest.is_empty() {
+        let name_length = u32::from_le_bytes(take(&mut rest)?) as usize;
+        let name = std::str::from_utf8(take_bytes(&mut rest, name_length)?)?.to_string();
+
+        infos.push(ZipEntryInfo {
+            name,
+            size: u64::from_le_bytes(take(&mut rest)?),
+            compressed_size: u64::from_le_bytes(take(&mut rest)?),
+            crc32: u32::from_le_bytes(take(&mut rest)?),
+            compression_method: CompressionMethod::from(c_int::from_le_bytes(take(&mut rest)?)),
+            encryption_method: EncryptionMethod::from(c_int::from_le_bytes(take(&mut rest)?)),
+            is_directory: take::<1>(&mut rest)?[0] != 0,
+            is_encrypted: take::<1>(&mut rest)?[0] != 0,
+            last_modified_time: u32::from_le_bytes(take(&mut rest)?),
+            general_purpose_flags: u16::from_le_bytes(take(&mut rest)?),
+        });
+    }
+
+    Ok(infos)
+}
+
+/// Recursively collect the names of the files below `current`, relative to `root` and using `/` separators
+fn collect_files(root: &Path, current: &Path, names: &mut Vec<String>) -> Result<()> {
+    let mut children = std::fs::read_dir(current)?.collect::<std::io::Result<Vec<_>>>()?;
+    children.sort_by_key(|child| child.file_name());
+
+    for child in children {
+        let path = child.path();
+        if path.is_dir() {
+            collect_files(root, &path, names)?;
+        } else {
+            names.push(path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check if a directory entry is hidden, following zip4j's definition
+#[cfg(windows)]
+fn is_hidden(entry: &std::fs::DirEntry) -> Result<bool> {
+    use std::os::windows::fs::MetadataExt;
+
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    Ok(entry.metadata()?.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0)
+}
+
+/// Check if a directory entry is hidden, following zip4j's definition
+#[cfg(not(windows))]
+fn is_hidden(entry: &std::fs::DirEntry) -> Result<bool> {
+    Ok(entry.file_name().to_string_lossy().starts_with('.'))
+}
+
+/// Normalize a folder name so it ends with `/` (empty stays empty)
+fn folder_prefix(prefix: &str) -> String {
+    if prefix.is_empty() || prefix.ends_with('/') {
+        prefix.to_string()
+    } else {
+        format!("{}/", prefix)
+    }
+}
+
+/// Find a name like `name (1).ext` next to `name` that doesn't exist below `dest_dir` yet,
+/// failing with `ZipError::FileExists` if every counter is taken
+fn free_entry_name(dest_dir: &Path, name: &str) -> Result<String> {
+    let (folder, file_name) = name.rsplit_once('/').unwrap_or(("", name));
+    let (stem, extension) = match file_name.rfind('.') {
+        Some(index) if index > 0 => file_name.split_at(index),
+        _ => (file_name, ""),
+    };
+
+    for counter in 1..=u32::MAX {
+        let candidate = match folder {
+            "" => format!("{} ({}){}", stem, counter, extension),
+            folder => format!("{}/{} ({}){}", folder, stem, counter, extension),
+        };
+        if !crate::zip_entry::extraction_path(dest_dir, &candidate)?.exists() {
+            return Ok(candidate);
         }
+    }
+    Err(crate::error::ZipError::FileExists)
+}
 
-        Ok(())
+/// Drop the parts of an entry name that would escape the destination
+/// (roots, drive prefixes, `.` and `..`), keeping the rest
+fn sanitize_entry_name(name: &str) -> String {
+    Path::new(name)
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Initial buffer size for the bulk entry listing, enough for a few hundred entries
+const ENTRY_LIST_BUFFER_SIZE: usize = 16 * 1024;
+
+/// Entry paired with where it is extracted to, from [`ZipFile::extraction_targets`]
+struct ExtractionTarget {
+    entry: ZipEntry,
+    /// Entry name as written to disk, normalized like the native side does
+    name: String,
+    /// Path below the destination, or the error for a name that escapes it
+    path: Result<PathBuf>,
+}
+
+/// Spreads I/O out so it averages at most a given number of bytes per second
+struct Throttle {
+    bytes_per_second: u64,
+    started: Instant,
+    bytes: u64,
+}
+
+impl Throttle {
+    fn new(bytes_per_second: u64) -> Self {
+        Self { bytes_per_second: bytes_per_second.max(1), started: Instant::now(), bytes: 0 }
     }
 
-    /// Add data from a byte slice to the zip archive
-    ///
-    /// # Arguments
-    ///
-    /// * `entry_name` - Name for the entry in the zip file
-    /// * `data` - Byte data to add
-    /// * `params` - Compression and encryption parameters
-    pub fn add_data<S: AsRef<str>>(&mut self, entry_name: S, data: &[u8], params: &ZipParameters) -> Result<()> {
-        let c_name = helpers::to_c_string(entry_name.as_ref())?;
+    /// Account for `bytes` of I/O, sleeping until the average rate is back within the limit
+    fn pace(&mut self, bytes: u64) {
+        self.bytes += bytes;
+        let due = Duration::from_secs_f64(self.bytes as f64 / self.bytes_per_second as f64);
+        if let Some(wait) = due.checked_sub(self.started.elapsed()) {
+            std::thread::sleep(wait);
+        }
+    }
+}
 
-        let c_password = match &params.password {
-            Some(pwd) => {
-                let c_pwd = helpers::to_c_string(pwd)?;
-                c_pwd.as_ptr() as *mut c_char
-            }
-            None => std::ptr::null_mut(),
+/// How often the progress of a running operation is polled
+const PROGRESS_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Progress monitor of an operation running on a native worker thread, released when dropped
+struct OperationMonitor {
+    handle: c_longlong,
+}
+
+impl OperationMonitor {
+    /// Get the current progress of the operation
+    fn progress(&self) -> Result<Progress> {
+        let mut percent: c_int = 0;
+        let mut bytes_done: c_longlong = 0;
+        let mut bytes_total: c_longlong = 0;
+
+        let result = unsafe {
+            ffi::zip4j_get_progress_percentage(*ffi::get_thread()?, self.handle, &mut percent)
         };
 
+        if helpers::is_error(result) {
+            return Err(crate::error::ZipError::from_native(result));
+        }
+
         let result = unsafe {
-            ffi::zip4j_add_data(
-                ffi::get_thread(),
-                self.handle,
-                c_name.as_ptr() as *mut c_char,
-                data.as_ptr() as *mut c_char,
-                data.len() as c_int,
-                params.compression_level.into(),
-                params.compression_method.into(),
-                params.encryption_method.into(),
-                params.aes_key_strength.into(),
-                c_password,
-            )
+            ffi::zip4j_get_progress_work(*ffi::get_thread()?, self.handle, &mut bytes_done, &mut bytes_total)
         };
 
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
 
-        Ok(())
+        Ok(Progress {
+            percent: percent.clamp(0, 100) as u8,
+            bytes_done: bytes_done.max(0) as u64,
+            bytes_total: bytes_total.max(0) as u64,
+        })
     }
 
-    /// Extract all files from the zip archive to a destination directory
-    ///
-    /// # Arguments
-    ///
-    /// * `dest_path` - Directory where files should be extracted
-    pub fn extract_all<P: AsRef<Path>>(&self, dest_path: P) -> Result<()> {
-        let path_str = dest_path.as_ref().to_string_lossy();
-        let c_path = helpers::to_c_string(&path_str)?;
+    /// Poll the operation until it finishes, cancelling it when `on_progress` returns `false`
+    fn wait<F: FnMut(&Progress) -> bool>(self, mut on_progress: F) -> Result<()> {
+        let mut cancelled = false;
+
+        loop {
+            let mut finished: c_int = 0;
+
+            let result = unsafe {
+                ffi::zip4j_is_operation_finished(*ffi::get_thread()?, self.handle, &mut finished)
+            };
+
+            if helpers::is_error(result) {
+                return Err(crate::error::ZipError::from_native(result));
+            }
+
+            let keep_going = on_progress(&self.progress()?);
+            if finished != 0 {
+                break;
+            }
+
+            if !keep_going && !cancelled {
+                let result = unsafe {
+                    ffi::zip4j_cancel_operation(*ffi::get_thread()?, self.handle)
+                };
+
+                if helpers::is_error(result) {
+                    return Err(crate::error::ZipError::from_native(result));
+                }
+                cancelled = true;
+            }
+
+            std::thread::sleep(PROGRESS_POLL_INTERVAL);
+        }
 
         let result = unsafe {
-            ffi::zip4j_extract_all(
-                ffi::get_thread(),
-                self.handle,
-                c_path.as_ptr() as *mut c_char
-            )
+            ffi::zip4j_get_operation_result(*ffi::get_thread()?, self.handle)
         };
 
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
 
         Ok(())
     }
+}
 
-    /// Extract a specific file by name from the zip archive
-    ///
-    /// # Arguments
-    ///
-    /// * `file_name` - Name of the file to extract
-    /// * `dest_path` - Directory where the file should be extracted
-    pub fn extract_file<S: AsRef<str>, P: AsRef<Path>>(&self, file_name: S, dest_path: P) -> Result<()> {
-        let c_name = helpers::to_c_string(file_name.as_ref())?;
-        let path_str = dest_path.as_ref().to_string_lossy();
-        let c_path = helpers::to_c_string(&path_str)?;
+impl Drop for OperationMonitor {
+    fn drop(&mut self) {
+        // Release the progress monitor handle
+        if let Ok(thread) = ffi::get_thread() {
+            unsafe {
+                ffi::zip4j_release_progress_monitor(*thread, self.handle);
+            }
+        }
+    }
+}
+
+/// Entry being added chunk by chunk, abandoned if dropped before it is finished
+struct DataWriter {
+    handle: c_longlong,
+    finished: bool,
+}
 
+impl DataWriter {
+    /// Write a chunk of data to the entry
+    fn write(&mut self, data: &[u8]) -> Result<()> {
         let result = unsafe {
-            ffi::zip4j_extract_file(
-                ffi::get_thread(),
+            ffi::zip4j_write_data(
+                *ffi::get_thread()?,
                 self.handle,
-                c_name.as_ptr() as *mut c_char,
-                c_path.as_ptr() as *mut c_char
+                data.as_ptr() as *mut c_char,
+                data.len() as c_int
             )
         };
 
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
 
         Ok(())
     }
 
-    /// Extract a specific entry to a destination directory
-    ///
-    /// # Arguments
-    ///
-    /// * `entry` - The entry to extract
-    /// * `dest_path` - Directory where the entry should be extracted
-    pub fn extract_entry<P: AsRef<Path>>(&self, entry: &ZipEntry, dest_path: P) -> Result<()> {
-        let path_str = dest_path.as_ref().to_string_lossy();
-        let c_path = helpers::to_c_string(&path_str)?;
+    /// Finish the entry and wait until it is written
+    fn finish(mut self) -> Result<()> {
+        self.finished = true;
 
         let result = unsafe {
-            ffi::zip4j_extract_entry(
-                ffi::get_thread(),
-                self.handle,
-                entry.handle(),
-                c_path.as_ptr() as *mut c_char
-            )
+            ffi::zip4j_finish_data(*ffi::get_thread()?, self.handle)
         };
 
         if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+            return Err(crate::error::ZipError::from_native(result));
         }
 
         Ok(())
     }
+}
 
-    /// Extract data from an entry to a byte vector (in-memory extraction)
-    ///
-    /// # Arguments
-    ///
-    /// * `entry` - The entry to extract
-    ///
-    /// # Returns
-    ///
-    /// A vector containing the extracted data
-    pub fn extract_data(&self, entry: &ZipEntry) -> Result<Vec<u8>> {
-        // Start with a reasonable buffer size
-        let mut buffer_size = entry.size()? as usize;
-        if buffer_size == 0 {
-            buffer_size = 1024; // Default size for unknown sizes
+impl Drop for DataWriter {
+    fn drop(&mut self) {
+        if !self.finished {
+            if let Ok(thread) = ffi::get_thread() {
+                unsafe {
+                    ffi::zip4j_abort_data(*thread, self.handle);
+                }
+            }
         }
+    }
+}
 
-        let mut buffer = vec![0u8; buffer_size];
-        let mut data_length: c_int = 0;
-
-        let result = unsafe {
-            ffi::zip4j_extract_data(
-                ffi::get_thread(),
-                self.handle,
-                entry.handle(),
-                buffer.as_mut_ptr() as *mut c_char,
-                buffer_size as c_int,
-                &mut data_length
-            )
-        };
+/// Position of an entry iterator within the bulk entry listing
+///
+/// Entry handles are only created for the entries that are actually yielded,
+/// so skipping ahead or iterating from the back jumps straight to the wanted
+/// index. If the archive is modified, the remaining entries are looked up
+/// again by name, so their new indices are used and removed ones are skipped.
+struct EntryCursor {
+    generation: u64,
+    positions: std::vec::IntoIter<(usize, ZipEntryInfo)>,
+}
 
-        if result == crate::ffi::constants::ERROR_BUFFER_TOO_SMALL {
-            // Resize buffer and try again
-            buffer.resize(data_length as usize, 0);
-            let result = unsafe {
-                ffi::zip4j_extract_data(
-                    ffi::get_thread(),
-                    self.handle,
-                    entry.handle(),
-                    buffer.as_mut_ptr() as *mut c_char,
-                    buffer.len() as c_int,
-                    &mut data_length
-                )
-            };
+impl EntryCursor {
+    fn new(zip_file: &ZipFile) -> Result<Self> {
+        Ok(Self {
+            generation: zip_file.generation,
+            positions: zip_file.list_entries()?.into_iter().enumerate().collect::<Vec<_>>().into_iter(),
+        })
+    }
 
-            if helpers::is_error(result) {
-                return Err(crate::error::ZipError::from_code(result));
-            }
-        } else if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+    /// Re-read the listing if the archive was modified since it was taken
+    fn sync(&mut self, zip_file: &ZipFile) -> Result<()> {
+        if self.generation == zip_file.generation {
+            return Ok(());
         }
 
-        // Truncate buffer to actual data length
-        buffer.truncate(data_length as usize);
-        Ok(buffer)
+        let remaining: HashSet<String> = self.positions.as_slice().iter()
+            .map(|(_, info)| info.name.clone())
+            .collect();
+        self.positions = zip_file.list_entries()?.into_iter()
+            .enumerate()
+            .filter(|(_, info)| remaining.contains(&info.name))
+            .collect::<Vec<_>>()
+            .into_iter();
+        self.generation = zip_file.generation;
+        Ok(())
     }
 
-    /// Remove a file from the zip archive by name
-    ///
-    /// # Arguments
-    ///
-    /// * `file_name` - Name of the file to remove
-    pub fn remove_file<S: AsRef<str>>(&mut self, file_name: S) -> Result<()> {
-        let c_name = helpers::to_c_string(file_name.as_ref())?;
+    fn len(&self) -> usize {
+        self.positions.len()
+    }
+}
 
-        let result = unsafe {
-            ffi::zip4j_remove_file(
-                ffi::get_thread(),
-                self.handle,
-                c_name.as_ptr() as *mut c_char
-            )
-        };
+/// Iterator over entries in a zip file, generic over how the zip file is held
+///
+/// Use it through [`ZipEntryIterator`] (borrowing) or [`IntoEntries`] (owning).
+pub struct Entries<Z: Borrow<ZipFile>> {
+    zip_file: Z,
+    cursor: EntryCursor,
+}
 
-        if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+/// Iterator over entries in a zip file
+pub type ZipEntryIterator<'a> = Entries<&'a ZipFile>;
+
+/// Iterator over entries that owns its zip file, from [`ZipFile::into_entries`]
+///
+/// It has no borrow to tie it to, so it can be moved to another thread or
+/// stored in a struct. The archive is closed when the iterator is dropped.
+pub type IntoEntries = Entries<ZipFile>;
+
+impl<Z: Borrow<ZipFile>> Entries<Z> {
+    /// Get the zip file being iterated over
+    pub fn zip_file(&self) -> &ZipFile {
+        self.zip_file.borrow()
+    }
+
+    /// Create the entry at `index`, with its already fetched metadata attached
+    fn entry_at(&self, (index, info): (usize, ZipEntryInfo)) -> Result<ZipEntry> {
+        self.zip_file().get_entry_by_index(index).map(|entry| entry.with_info(info))
+    }
+
+    /// Take the next position from the front or back, catching up with modifications first
+    fn advance(&mut self, n: usize, from_back: bool) -> Option<Result<ZipEntry>> {
+        if let Err(e) = self.cursor.sync(self.zip_file.borrow()) {
+            return Some(Err(e));
         }
 
-        Ok(())
+        let position = if from_back {
+            self.cursor.positions.nth_back(n)?
+        } else {
+            self.cursor.positions.nth(n)?
+        };
+        Some(self.entry_at(position))
     }
+}
 
-    /// Remove an entry from the zip archive
+impl Entries<ZipFile> {
+    /// Get mutable access to the zip file being iterated over
     ///
-    /// # Arguments
+    /// Entries can be removed or replaced while iterating; the iterator picks
+    /// up the new layout and skips entries that no longer exist. The reported
+    /// length catches up when the next entry is requested.
     ///
-    /// * `entry` - The entry to remove
-    pub fn remove_entry(&mut self, entry: &ZipEntry) -> Result<()> {
-        let result = unsafe {
-            ffi::zip4j_remove_entry(
-                ffi::get_thread(),
-                self.handle,
-                entry.handle()
-            )
-        };
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zip2rs::ZipFile;
+    ///
+    /// let mut entries = ZipFile::new("archive.zip")?.into_entries()?;
+    /// while let Some(entry) = entries.next() {
+    ///     let entry = entry?;
+    ///     if entry.name()?.ends_with(".tmp") {
+    ///         entries.zip_file_mut().remove_entry(&entry)?;
+    ///     }
+    /// }
+    /// # Ok::<(), zip2rs::ZipError>(())
+    /// ```
+    pub fn zip_file_mut(&mut self) -> &mut ZipFile {
+        &mut self.zip_file
+    }
+}
 
-        if helpers::is_error(result) {
-            return Err(crate::error::ZipError::from_code(result));
+impl<Z: Borrow<ZipFile>> Iterator for Entries<Z> {
+    type Item = Result<ZipEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nth(0)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.advance(n, false)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.cursor.len();
+        // Entries removed since the listing was taken are only skipped once the next one is requested
+        if self.cursor.generation == self.zip_file().generation {
+            (len, Some(len))
+        } else {
+            (0, Some(len))
         }
+    }
 
-        Ok(())
+    fn count(mut self) -> usize {
+        let _ = self.cursor.sync(self.zip_file.borrow());
+        self.cursor.len()
     }
 
-    /// Get an iterator over all entries in the zip file
-    pub fn entries(&self) -> Result<ZipEntryIterator> {
-        let count = self.entry_count()?;
-        Ok(ZipEntryIterator {
-            zip_file: self,
-            current_index: 0,
-            total_count: count,
-        })
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
     }
+}
 
-    /// Get the internal handle (for advanced use cases)
-    pub(crate) fn handle(&self) -> c_longlong {
-        self.handle
+impl<Z: Borrow<ZipFile>> DoubleEndedIterator for Entries<Z> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.nth_back(0)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.advance(n, true)
     }
 }
 
-impl Drop for ZipFile {
-    fn drop(&mut self) {
-        // Close the zip file handle
-        unsafe {
-            ffi::zip4j_close(ffi::get_thread(), self.handle);
-        }
+/// Only for the borrowing iterator: the archive can't be modified while it is
+/// borrowed, whereas [`Entries::zip_file_mut`] may remove entries the
+/// length still counts.
+impl ExactSizeIterator for Entries<&ZipFile> {
+    fn len(&self) -> usize {
+        self.cursor.len()
     }
 }
 
-/// Iterator over entries in a zip file
-pub struct ZipEntryIterator<'a> {
-    zip_file: &'a ZipFile,
-    current_index: usize,
-    total_count: usize,
+impl<Z: Borrow<ZipFile>> std::iter::FusedIterator for Entries<Z> {}
+
+/// Modifications recorded by [`ZipFile::batch`]
+///
+/// Each call sees the effect of the previous ones: renaming an entry and then
+/// removing it under its new name removes it, and adding an entry replaces any
+/// entry or earlier addition with the same name.
+#[derive(Debug, Default)]
+pub struct Batch {
+    /// Original entry names with their final name, or `None` to remove them
+    rewrites: Vec<(String, Option<String>)>,
+    additions: Vec<ZipEntryBuilder>,
 }
 
-impl<'a> Iterator for ZipEntryIterator<'a> {
-    type Item = Result<ZipEntry>;
+impl Batch {
+    /// Remove an entry
+    pub fn remove<S: AsRef<str>>(&mut self, name: S) -> &mut Self {
+        let name = name.as_ref();
+        self.additions.retain(|entry| entry.name != name);
+        match self.rewrites.iter().position(|(_, current)| current.as_deref() == Some(name)) {
+            Some(index) => self.rewrites[index].1 = None,
+            // An entry renamed or removed earlier no longer goes by its original name
+            None if self.rewrites.iter().any(|(original, _)| original == name) => {}
+            None => self.rewrites.push((name.to_string(), None)),
+        }
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.current_index >= self.total_count {
-            return None;
+    /// Rename an entry; `new_name` must not be taken by another entry
+    pub fn rename<S: AsRef<str>, T: Into<String>>(&mut self, name: S, new_name: T) -> &mut Self {
+        let name = name.as_ref();
+        let new_name = new_name.into();
+        if let Some(entry) = self.additions.iter_mut().find(|entry| entry.name == name) {
+            entry.name = new_name;
+            return self;
         }
+        match self.rewrites.iter().position(|(_, current)| current.as_deref() == Some(name)) {
+            Some(index) => self.rewrites[index].1 = Some(new_name),
+            None if self.rewrites.iter().any(|(original, _)| original == name) => {}
+            None => self.rewrites.push((name.to_string(), Some(new_name))),
+        }
+        self
+    }
 
-        let result = self.zip_file.get_entry_by_index(self.current_index);
-        self.current_index += 1;
-        Some(result)
+    /// Add an entry from memory
+    pub fn add_data<S: Into<String>>(&mut self, name: S, data: &[u8], params: &ZipParameters) -> &mut Self {
+        self.add(ZipEntryBuilder::from_data(name, data).with_params(params.clone()))
     }
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        let remaining = self.total_count - self.current_index;
-        (remaining, Some(remaining))
+    /// Add an entry from a file on disk
+    pub fn add_file<S: Into<String>, P: Into<PathBuf>>(&mut self, name: S, path: P, params: &ZipParameters) -> &mut Self {
+        self.add(ZipEntryBuilder::from_file(name, path).with_params(params.clone()))
+    }
+
+    /// Add an entry described by a `ZipEntryBuilder`
+    pub fn add(&mut self, entry: ZipEntryBuilder) -> &mut Self {
+        self.remove(&entry.name);
+        self.additions.push(entry);
+        self
+    }
+
+    /// Check if no modification was recorded
+    pub fn is_empty(&self) -> bool {
+        self.rewrites.is_empty() && self.additions.is_empty()
     }
 }
 
-impl<'a> ExactSizeIterator for ZipEntryIterator<'a> {
-    fn len(&self) -> usize {
-        self.total_count - self.current_index
+/// Depth-first walk over the files and folders of an archive, from [`ZipFile::walk`]
+///
+/// Holds no native handles, so it can outlive the archive.
+pub struct Walk {
+    folders: HashMap<String, BTreeMap<String, DirEntry>>,
+    stack: Vec<btree_map::IntoValues<String, DirEntry>>,
+}
+
+impl Walk {
+    /// Skip the contents of the folder that was just yielded, or the remaining
+    /// siblings if it was a file
+    pub fn skip_current_dir(&mut self) {
+        self.stack.pop();
+    }
+}
+
+impl Iterator for Walk {
+    type Item = (usize, DirEntry);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(children) = self.stack.last_mut() {
+            let Some(child) = children.next() else {
+                self.stack.pop();
+                continue;
+            };
+
+            let depth = self.stack.len() - 1;
+            if child.is_dir {
+                let grandchildren = self.folders.remove(&child.path).unwrap_or_default();
+                self.stack.push(grandchildren.into_values());
+            }
+            return Some((depth, child));
+        }
+
+        None
     }
 }
@@ -0,0 +1,94 @@
+use std::ops::Deref;
+use std::path::Path;
+use crate::error::Result;
+use crate::types::{DuplicatePolicy, NameNormalization, Password};
+use crate::zip_file::ZipFile;
+use crate::zip_writer::ZipWriter;
+
+/// A zip file opened for reading only
+///
+/// Dereferences to [`ZipFile`], so every method that only reads the archive is
+/// available, while the compiler rejects the ones that modify it. Convert back
+/// with [`into_inner`](Self::into_inner) or [`into_writer`](Self::into_writer)
+/// to modify the archive again.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use zip2rs::ZipReader;
+///
+/// let zip = ZipReader::open("archive.zip")?;
+/// for name in zip.entry_names()? {
+///     println!("{}", name);
+/// }
+/// # Ok::<(), zip2rs::ZipError>(())
+/// ```
+#[derive(Debug)]
+pub struct ZipReader {
+    inner: ZipFile,
+}
+
+impl ZipReader {
+    /// Open an existing zip file for reading, failing with `ZipError::FileNotFound` if it is missing
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        ZipFile::open_existing(path).map(Self::from)
+    }
+
+    /// Open an existing encrypted zip file for reading, failing with `ZipError::FileNotFound` if it is missing
+    pub fn with_password<P: AsRef<Path>, S: Into<Password>>(path: P, password: S) -> Result<Self> {
+        let mut reader = Self::open(path)?;
+        reader.set_password(password)?;
+        Ok(reader)
+    }
+
+    /// See [`ZipFile::set_password`]
+    pub fn set_password<S: Into<Password>>(&mut self, password: S) -> Result<()> {
+        self.inner.set_password(password)
+    }
+
+    /// See [`ZipFile::set_name_normalization`]
+    pub fn set_name_normalization(&mut self, normalization: NameNormalization) -> Result<()> {
+        self.inner.set_name_normalization(normalization)
+    }
+
+    /// See [`ZipFile::set_duplicate_policy`]
+    pub fn set_duplicate_policy(&mut self, policy: DuplicatePolicy) {
+        self.inner.set_duplicate_policy(policy)
+    }
+
+    /// Get the underlying zip file back
+    pub fn into_inner(self) -> ZipFile {
+        self.inner
+    }
+
+    /// Turn the reader into a writer for the same archive
+    pub fn into_writer(self) -> ZipWriter {
+        ZipWriter::from(self.inner)
+    }
+}
+
+impl From<ZipFile> for ZipReader {
+    fn from(inner: ZipFile) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<ZipReader> for ZipFile {
+    fn from(reader: ZipReader) -> Self {
+        reader.inner
+    }
+}
+
+impl Deref for ZipReader {
+    type Target = ZipFile;
+
+    fn deref(&self) -> &ZipFile {
+        &self.inner
+    }
+}
+
+impl AsRef<ZipFile> for ZipReader {
+    fn as_ref(&self) -> &ZipFile {
+        &self.inner
+    }
+}
@@ -0,0 +1,228 @@
+use std::path::Path;
+use crate::error::Result;
+use crate::types::{
+    AddDirOptions, EncryptionMethod, Password, SaveMode, SealOptions, SyncOptions, SyncReport, ZipEntryBuilder,
+    ZipParameters,
+};
+use crate::zip_file::{Batch, ZipFile};
+use crate::zip_reader::ZipReader;
+
+/// A zip file opened for adding and removing entries
+///
+/// Only exposes the methods that build the archive, so reading entries back
+/// can't sneak into code that is meant to produce it. Call
+/// [`finish`](Self::finish) or [`seal`](Self::seal) to read the result.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use zip2rs::{ZipParameters, ZipWriter};
+///
+/// let mut writer = ZipWriter::create("release.zip")?;
+/// writer.add_file("target/release/app")?;
+/// writer.add_data("VERSION", b"1.2.3", &ZipParameters::new())?;
+/// let reader = writer.finish();
+/// println!("{} entries", reader.entry_count()?);
+/// # Ok::<(), zip2rs::ZipError>(())
+/// ```
+#[derive(Debug)]
+pub struct ZipWriter {
+    inner: ZipFile,
+}
+
+impl ZipWriter {
+    /// Start a new zip file, failing with `ZipError::FileExists` if the file already exists
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        ZipFile::create_new(path).map(Self::from)
+    }
+
+    /// Open a zip file to modify it, creating it if it doesn't exist
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        ZipFile::new(path).map(Self::from)
+    }
+
+    /// See [`ZipFile::set_password`]
+    pub fn set_password<S: Into<Password>>(&mut self, password: S) -> Result<()> {
+        self.inner.set_password(password)
+    }
+
+    /// See [`ZipFile::change_password`]
+    pub fn change_password<S1: Into<Password>, S2: Into<Password>>(&mut self, old_password: S1, new_password: S2) -> Result<()> {
+        self.inner.change_password(old_password, new_password)
+    }
+
+    /// See [`ZipFile::change_password_with_method`]
+    pub fn change_password_with_method<S1: Into<Password>, S2: Into<Password>>(&mut self, old_password: S1, new_password: S2, method: EncryptionMethod) -> Result<()> {
+        self.inner.change_password_with_method(old_password, new_password, method)
+    }
+
+    /// See [`ZipFile::add_file`]
+    pub fn add_file<P: AsRef<Path>>(&mut self, file_path: P) -> Result<()> {
+        self.inner.add_file(file_path)
+    }
+
+    /// See [`ZipFile::add_file_with_params`]
+    pub fn add_file_with_params<P: AsRef<Path>>(&mut self, file_path: P, params: &ZipParameters) -> Result<()> {
+        self.inner.add_file_with_params(file_path, params)
+    }
+
+    /// See [`ZipFile::add_file_as`]
+    pub fn add_file_as<P: AsRef<Path>, S: AsRef<str>>(&mut self, file_path: P, name_in_zip: S, params: &ZipParameters) -> Result<()> {
+        self.inner.add_file_as(file_path, name_in_zip, params)
+    }
+
+    /// See [`ZipFile::add_files`]
+    pub fn add_files<P: AsRef<Path>, B: AsRef<Path>>(&mut self, file_paths: &[P], base_dir: B, params: &ZipParameters) -> Result<()> {
+        self.inner.add_files(file_paths, base_dir, params)
+    }
+
+    /// See [`ZipFile::add_directory`]
+    pub fn add_directory<P: AsRef<Path>>(&mut self, dir_path: P) -> Result<()> {
+        self.inner.add_directory(dir_path)
+    }
+
+    /// See [`ZipFile::add_directory_with_params`]
+    pub fn add_directory_with_params<P: AsRef<Path>>(&mut self, dir_path: P, params: &ZipParameters) -> Result<()> {
+        self.inner.add_directory_with_params(dir_path, params)
+    }
+
+    /// See [`ZipFile::add_directory_with_options`]
+    pub fn add_directory_with_options<P: AsRef<Path>>(&mut self, dir_path: P, options: &AddDirOptions) -> Result<()> {
+        self.inner.add_directory_with_options(dir_path, options)
+    }
+
+    /// See [`ZipFile::sync_directory`]
+    pub fn sync_directory<P: AsRef<Path>>(&mut self, dir_path: P, options: &SyncOptions) -> Result<SyncReport> {
+        self.inner.sync_directory(dir_path, options)
+    }
+
+    /// See [`ZipFile::add_data`]
+    pub fn add_data<S: AsRef<str>>(&mut self, entry_name: S, data: &[u8], params: &ZipParameters) -> Result<()> {
+        self.inner.add_data(entry_name, data, params)
+    }
+
+    /// See [`ZipFile::add_data_chunked`]
+    pub fn add_data_chunked<S, I>(&mut self, entry_name: S, chunks: I, params: &ZipParameters) -> Result<()>
+    where
+        S: AsRef<str>,
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        self.inner.add_data_chunked(entry_name, chunks, params)
+    }
+
+    /// See [`ZipFile::add`]
+    pub fn add(&mut self, entry: ZipEntryBuilder) -> Result<()> {
+        self.inner.add(entry)
+    }
+
+    /// See [`ZipFile::align_archive`]
+    pub fn align_archive(&mut self, alignment: u32) -> Result<()> {
+        self.inner.align_archive(alignment)
+    }
+
+    /// See [`ZipFile::compact`]
+    pub fn compact(&mut self) -> Result<u64> {
+        self.inner.compact()
+    }
+
+    /// See [`ZipFile::remove_file`]
+    pub fn remove_file<S: AsRef<str>>(&mut self, file_name: S) -> Result<()> {
+        self.inner.remove_file(file_name)
+    }
+
+    /// See [`ZipFile::remove_files`]
+    pub fn remove_files<S: AsRef<str>>(&mut self, file_names: &[S]) -> Result<()> {
+        self.inner.remove_files(file_names)
+    }
+
+    /// See [`ZipFile::remove_matching`]
+    pub fn remove_matching<S: AsRef<str>>(&mut self, pattern: S) -> Result<usize> {
+        self.inner.remove_matching(pattern)
+    }
+
+    /// See [`ZipFile::rename_entries`]
+    pub fn rename_entries<F>(&mut self, mapping: F) -> Result<usize>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        self.inner.rename_entries(mapping)
+    }
+
+    /// See [`ZipFile::move_prefix`]
+    pub fn move_prefix<S: AsRef<str>, T: AsRef<str>>(&mut self, old_prefix: S, new_prefix: T) -> Result<usize> {
+        self.inner.move_prefix(old_prefix, new_prefix)
+    }
+
+    /// See [`ZipFile::batch`]
+    pub fn batch<F>(&mut self, build: F) -> Result<()>
+    where
+        F: FnOnce(&mut Batch),
+    {
+        self.inner.batch(build)
+    }
+
+    /// See [`ZipFile::set_comment`]
+    pub fn set_comment<S: AsRef<str>>(&mut self, comment: S) -> Result<()> {
+        self.inner.set_comment(comment)
+    }
+
+    /// See [`ZipFile::set_save_mode`]
+    pub fn set_save_mode(&mut self, mode: SaveMode) {
+        self.inner.set_save_mode(mode)
+    }
+
+    /// See [`ZipFile::lock`]
+    pub fn lock(&mut self) -> Result<()> {
+        self.inner.lock()
+    }
+
+    /// See [`ZipFile::try_lock`]
+    pub fn try_lock(&mut self) -> Result<bool> {
+        self.inner.try_lock()
+    }
+
+    /// See [`ZipFile::unlock`]
+    pub fn unlock(&mut self) -> Result<()> {
+        self.inner.unlock()
+    }
+
+    /// Seal the archive and turn the writer into a reader; see [`ZipFile::seal`]
+    pub fn seal(self) -> Result<ZipReader> {
+        self.seal_with_options(&SealOptions::default())
+    }
+
+    /// Seal the archive with the requested metadata and turn the writer into a
+    /// reader; see [`ZipFile::seal_with_options`]
+    pub fn seal_with_options(mut self, options: &SealOptions) -> Result<ZipReader> {
+        self.inner.seal_with_options(options)?;
+        Ok(self.finish())
+    }
+
+    /// Stop writing and read the archive back
+    pub fn finish(self) -> ZipReader {
+        ZipReader::from(self.inner)
+    }
+
+    /// Get the underlying zip file back
+    pub fn into_inner(self) -> ZipFile {
+        self.inner
+    }
+
+    /// See [`ZipFile::close`]
+    pub fn close(self) -> Result<()> {
+        self.inner.close()
+    }
+}
+
+impl From<ZipFile> for ZipWriter {
+    fn from(inner: ZipFile) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<ZipWriter> for ZipFile {
+    fn from(writer: ZipWriter) -> Self {
+        writer.inner
+    }
+}
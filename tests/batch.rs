@@ -0,0 +1,51 @@
+mod common;
+
+use zip2rs::{ZipError, ZipFile, ZipParameters};
+
+fn archive(name: &str, entries: &[&str]) -> ZipFile {
+    let dir = common::scratch_dir(name);
+    let mut zip = ZipFile::new(dir.join("archive.zip")).unwrap();
+    for entry in entries {
+        zip.add_data(*entry, entry.as_bytes(), &ZipParameters::new()).unwrap();
+    }
+    zip
+}
+
+#[test]
+fn batch_rejects_a_target_taken_by_a_remaining_entry() {
+    let mut zip = archive("batch-taken", &["a.txt", "b.txt", "c.txt"]);
+
+    let result = zip.batch(|tx| {
+        tx.remove("c.txt");
+        tx.rename("a.txt", "b.txt");
+    });
+
+    assert!(matches!(result.unwrap_err().root(), ZipError::InvalidParameter(_)));
+    assert_eq!(zip.entry_names().unwrap(), ["a.txt", "b.txt", "c.txt"]);
+}
+
+#[test]
+fn batch_can_reuse_the_name_of_a_renamed_entry() {
+    let mut zip = archive("batch-reuse", &["a.txt", "b.txt"]);
+
+    zip.batch(|tx| {
+        tx.rename("a.txt", "old/a.txt");
+        tx.rename("b.txt", "a.txt");
+        tx.remove("b.txt");
+        tx.add_data("b.txt", b"new", &ZipParameters::new());
+    })
+    .unwrap();
+
+    let mut names = zip.entry_names().unwrap();
+    names.sort();
+    assert_eq!(names, ["a.txt", "b.txt", "old/a.txt"]);
+    assert_eq!(zip.extract_data(&zip.get_entry_by_name("a.txt").unwrap()).unwrap(), b"b.txt");
+}
+
+#[test]
+fn remove_matching_keeps_the_contents_of_a_matched_folder() {
+    let mut zip = archive("remove-matching-folder", &["logs/", "logs/app.log", "notes.txt"]);
+
+    assert_eq!(zip.remove_matching("logs/").unwrap(), 1);
+    assert_eq!(zip.entry_names().unwrap(), ["logs/app.log", "notes.txt"]);
+}
@@ -0,0 +1,21 @@
+mod common;
+
+use zip2rs::{ZipError, ZipFile, ZipParameters};
+
+#[test]
+fn archives_opened_before_cleanup_are_invalid_afterwards() {
+    let _serial = common::serial();
+    let dir = common::scratch_dir("cleanup-epoch");
+    let mut zip = ZipFile::new(dir.join("stale.zip")).unwrap();
+    zip.add_data("a.txt", b"a", &ZipParameters::new()).unwrap();
+    let entry = zip.get_entry_by_name("a.txt").unwrap();
+
+    zip2rs::cleanup().unwrap();
+
+    assert!(matches!(zip.entry_count(), Err(ZipError::InvalidHandle)));
+    let fresh = ZipFile::new(dir.join("fresh.zip")).unwrap();
+    assert!(matches!(fresh.extract_data(&entry), Err(ZipError::InvalidHandle)));
+    drop(entry);
+    drop(zip);
+    assert_eq!(fresh.entry_count().unwrap(), 0);
+}
@@ -0,0 +1,24 @@
+mod common;
+
+use zip2rs::{ZipFile, ZipParameters};
+
+#[test]
+fn owning_iterator_size_hint_allows_for_removed_entries() {
+    let _serial = common::serial();
+    let dir = common::scratch_dir("entries-removed");
+    let mut zip = ZipFile::new(dir.join("archive.zip")).unwrap();
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        zip.add_data(name, name.as_bytes(), &ZipParameters::new()).unwrap();
+    }
+    assert_eq!(zip.entries().unwrap().len(), 3);
+
+    let mut entries = zip.into_entries().unwrap();
+    let first = entries.next().unwrap().unwrap();
+    assert_eq!(entries.size_hint(), (2, Some(2)));
+    entries.zip_file_mut().remove_file("b.txt").unwrap();
+    drop(first);
+
+    assert_eq!(entries.size_hint(), (0, Some(2)));
+    let rest: Vec<String> = entries.map(|entry| entry.unwrap().name().unwrap()).collect();
+    assert_eq!(rest, ["c.txt"]);
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use zip2rs::{ConflictResolution, ZipFile, ZipParameters};
+
+#[test]
+fn extract_all_filtered_writes_only_accepted_entries() {
+    let dir = common::scratch_dir("extract-filtered");
+    let mut zip = ZipFile::new(dir.join("archive.zip")).unwrap();
+    for name in ["docs/a.txt", "b.bin"] {
+        zip.add_data(name, name.as_bytes(), &ZipParameters::new()).unwrap();
+    }
+
+    let out = dir.join("out");
+    let extracted = zip.extract_all_filtered(&out, |info| info.name.ends_with(".txt")).unwrap();
+
+    assert_eq!(extracted, 1);
+    assert_eq!(std::fs::read(out.join("docs/a.txt")).unwrap(), b"docs/a.txt");
+    assert!(!out.join("b.bin").exists());
+}
+
+#[test]
+fn extract_all_with_resolver_renames_next_to_existing_files() {
+    let dir = common::scratch_dir("extract-resolver");
+    let mut zip = ZipFile::new(dir.join("archive.zip")).unwrap();
+    zip.add_data("a.txt", b"new", &ZipParameters::new()).unwrap();
+    let out = dir.join("out");
+    std::fs::create_dir_all(&out).unwrap();
+    std::fs::write(out.join("a.txt"), b"old").unwrap();
+
+    let mut conflicts = Vec::new();
+    zip.extract_all_with_resolver(&out, |conflict| {
+        conflicts.push(conflict.path.clone());
+        ConflictResolution::Rename
+    }).unwrap();
+
+    assert_eq!(conflicts, [out.join("a.txt")]);
+    assert_eq!(std::fs::read(out.join("a.txt")).unwrap(), b"old");
+    assert_eq!(std::fs::read(out.join("a (1).txt")).unwrap(), b"new");
+}
+
+#[test]
+fn extract_all_with_report_writes_escaping_entries_below_the_destination() {
+    let dir = common::scratch_dir("extract-report");
+    let mut zip = ZipFile::new(dir.join("archive.zip")).unwrap();
+    zip.add_data("ok.txt", b"fine", &ZipParameters::new()).unwrap();
+    zip.add_data("../evil.txt", b"escapes", &ZipParameters::new()).unwrap();
+
+    let out = dir.join("out");
+    let report = zip.extract_all_with_report(&out).unwrap();
+
+    assert_eq!(report.files_written, 2);
+    assert_eq!(report.sanitized, [("../evil.txt".to_string(), out.join("evil.txt"))]);
+    assert_eq!(std::fs::read(out.join("evil.txt")).unwrap(), b"escapes");
+    assert!(!dir.join("evil.txt").exists());
+}
@@ -0,0 +1,18 @@
+mod common;
+
+use zip2rs::{ZipError, ZipFile, ZipParameters};
+
+#[test]
+fn entries_only_go_stale_after_a_successful_modification() {
+    let _serial = common::serial();
+    let dir = common::scratch_dir("generation");
+    let mut zip = ZipFile::new(dir.join("archive.zip")).unwrap();
+    zip.add_data("a.txt", b"a", &ZipParameters::new()).unwrap();
+    let entry = zip.get_entry_by_name("a.txt").unwrap();
+
+    assert!(zip.remove_file("bad\0name").is_err());
+    assert_eq!(zip.extract_data(&entry).unwrap(), b"a");
+
+    zip.add_data("b.txt", b"b", &ZipParameters::new()).unwrap();
+    assert!(matches!(zip.extract_data(&entry), Err(ZipError::StaleEntry)));
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use zip2rs::{Isolate, ZipError, ZipEvent, ZipFile, ZipParameters};
+
+#[test]
+fn archives_are_only_usable_in_their_own_isolate() {
+    let _serial = common::serial();
+    let dir = common::scratch_dir("isolate-escape");
+    let sandbox = Isolate::new().unwrap();
+
+    let escaped = sandbox.run(|| {
+        let mut zip = ZipFile::new(dir.join("inner.zip")).unwrap();
+        zip.add_data("inner.txt", b"inner", &ZipParameters::new()).unwrap();
+        zip
+    });
+    assert!(matches!(escaped.entry_names(), Err(ZipError::InvalidHandle)));
+    assert_eq!(sandbox.run(|| escaped.entry_names()).unwrap(), ["inner.txt"]);
+
+    let shared = ZipFile::new(dir.join("outer.zip")).unwrap();
+    assert!(matches!(sandbox.run(|| shared.entry_count()), Err(ZipError::InvalidHandle)));
+    assert_eq!(shared.entry_count().unwrap(), 0);
+
+    sandbox.run(|| drop(escaped));
+}
+
+#[test]
+fn shared_archive_dropped_inside_run_is_closed() {
+    let _serial = common::serial();
+    let dir = common::scratch_dir("isolate-drop");
+    let zip = ZipFile::new(dir.join("shared.zip")).unwrap();
+    let open_before = zip2rs::diagnostics().unwrap().open_archives;
+
+    let sandbox = Isolate::new().unwrap();
+    sandbox.run(|| drop(zip));
+
+    assert_eq!(zip2rs::diagnostics().unwrap().open_archives, open_before - 1);
+}
+
+#[test]
+fn events_of_a_dedicated_isolate_archive_report_an_invalid_handle() {
+    let _serial = common::serial();
+    let dir = common::scratch_dir("isolate-events");
+    let sandbox = Isolate::new().unwrap();
+
+    let events: Vec<_> = sandbox.run(|| {
+        let mut zip = ZipFile::new(dir.join("inner.zip")).unwrap();
+        zip.add_data("inner.txt", b"inner", &ZipParameters::new()).unwrap();
+        zip.extract_all_events(dir.join("out")).iter().collect()
+    });
+
+    assert!(matches!(events[..], [ZipEvent::Error(ZipError::InvalidHandle)]));
+    assert!(!dir.join("out").exists());
+}
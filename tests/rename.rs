@@ -0,0 +1,32 @@
+mod common;
+
+use zip2rs::{ZipError, ZipFile, ZipParameters};
+
+fn archive(name: &str, entries: &[&str]) -> ZipFile {
+    let dir = common::scratch_dir(name);
+    let mut zip = ZipFile::new(dir.join("archive.zip")).unwrap();
+    for entry in entries {
+        zip.add_data(*entry, entry.as_bytes(), &ZipParameters::new()).unwrap();
+    }
+    zip
+}
+
+#[test]
+fn rename_entries_matches_names_exactly() {
+    let mut zip = archive("rename-exact", &["a", "ab.txt"]);
+
+    let renamed = zip.rename_entries(|name| (name == "a").then(|| "x".to_string())).unwrap();
+
+    assert_eq!(renamed, 1);
+    assert_eq!(zip.entry_names().unwrap(), ["x", "ab.txt"]);
+}
+
+#[test]
+fn rename_entries_rejects_empty_names_before_renaming() {
+    let mut zip = archive("rename-empty", &["a.txt", "b.txt"]);
+
+    let result = zip.rename_entries(|name| Some(if name == "a.txt" { "c.txt".to_string() } else { String::new() }));
+
+    assert!(matches!(result, Err(ZipError::InvalidParameter(_))));
+    assert_eq!(zip.entry_names().unwrap(), ["a.txt", "b.txt"]);
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use zip2rs::{ffi, InitOptions, Zip4jRuntime, ZipError};
+
+#[test]
+fn last_guard_only_tears_down_a_runtime_it_started() {
+    let _serial = common::serial();
+
+    zip2rs::cleanup().unwrap();
+    let guard = Zip4jRuntime::acquire().unwrap();
+    assert!(ffi::is_initialized());
+    drop(guard);
+    assert!(!ffi::is_initialized());
+
+    zip2rs::init().unwrap();
+    let first = Zip4jRuntime::acquire().unwrap();
+    let second = Zip4jRuntime::acquire().unwrap();
+    drop(first);
+    drop(second);
+    assert!(ffi::is_initialized());
+}
+
+#[test]
+fn init_with_options_accepts_the_running_options_only() {
+    let _serial = common::serial();
+    zip2rs::cleanup().unwrap();
+
+    let huge = InitOptions::new().with_max_heap_mb(u64::MAX / 1024);
+    assert!(matches!(zip2rs::init_with_options(&huge), Err(ZipError::InvalidParameter(_))));
+    assert!(!ffi::is_initialized());
+
+    let small = InitOptions::new().with_max_heap_mb(48);
+    zip2rs::init_with_options(&small).unwrap();
+    zip2rs::init_with_options(&small).unwrap();
+    zip2rs::init().unwrap();
+    let larger = InitOptions::new().with_max_heap_mb(96);
+    assert!(matches!(zip2rs::init_with_options(&larger), Err(ZipError::UnsupportedOperation)));
+
+    zip2rs::cleanup().unwrap();
+}
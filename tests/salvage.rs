@@ -0,0 +1,23 @@
+mod common;
+
+use zip2rs::{ZipFile, ZipParameters};
+
+#[test]
+fn salvage_never_removes_files_outside_the_destination() {
+    let dir = common::scratch_dir("salvage-escape");
+    let dest = dir.join("recovered");
+    let outside = dir.join("keep.txt");
+    std::fs::write(&outside, b"not part of the archive").unwrap();
+
+    let mut zip = ZipFile::new(dir.join("escape.zip")).unwrap();
+    zip.add_data("good.txt", b"fine", &ZipParameters::new()).unwrap();
+    zip.add_data("../keep.txt", b"escapes", &ZipParameters::new()).unwrap();
+
+    let report = zip.salvage(&dest).unwrap();
+
+    assert_eq!(report.recovered, ["good.txt"]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, "../keep.txt");
+    assert_eq!(std::fs::read(&outside).unwrap(), b"not part of the archive");
+    assert_eq!(std::fs::read(dest.join("good.txt")).unwrap(), b"fine");
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use zip2rs::{ZipError, ZipParameters, ZipReader, ZipWriter};
+
+#[test]
+fn writer_reaches_rename_and_remove_operations() {
+    let dir = common::scratch_dir("writer-rename");
+    let mut writer = ZipWriter::create(dir.join("archive.zip")).unwrap();
+    for name in ["a.txt", "b.tmp", "c.txt"] {
+        writer.add_data(name, name.as_bytes(), &ZipParameters::new()).unwrap();
+    }
+
+    assert_eq!(writer.remove_matching("*.tmp").unwrap(), 1);
+    assert_eq!(writer.rename_entries(|name| (name == "a.txt").then(|| "docs/a.txt".to_string())).unwrap(), 1);
+    writer.batch(|batch| {
+        batch.rename("c.txt", "docs/c.txt");
+    }).unwrap();
+
+    assert_eq!(writer.finish().entry_names().unwrap(), ["docs/a.txt", "docs/c.txt"]);
+}
+
+#[test]
+fn reader_with_password_requires_an_existing_archive() {
+    let dir = common::scratch_dir("reader-password");
+
+    let result = ZipReader::with_password(dir.join("missing.zip"), "secret");
+
+    assert!(matches!(result.unwrap_err().root(), ZipError::FileNotFound));
+    assert!(!dir.join("missing.zip").exists());
+}
@@ -2,27 +2,60 @@ package io.github.ran.zip4j_abi;
 
 import net.lingala.zip4j.ZipFile;
 import net.lingala.zip4j.exception.ZipException;
+import net.lingala.zip4j.model.AESExtraDataRecord;
 import net.lingala.zip4j.model.FileHeader;
+import net.lingala.zip4j.model.Zip4jConfig;
+import net.lingala.zip4j.model.ZipModel;
 import net.lingala.zip4j.model.ZipParameters;
 import net.lingala.zip4j.model.enums.*;
 import net.lingala.zip4j.progress.ProgressMonitor;
 import net.lingala.zip4j.io.inputstream.ZipInputStream;
+import net.lingala.zip4j.io.outputstream.SplitOutputStream;
 import net.lingala.zip4j.io.outputstream.ZipOutputStream;
+import net.lingala.zip4j.util.InternalZipConstants;
 
 import org.graalvm.nativeimage.IsolateThread;
+import org.graalvm.nativeimage.RuntimeOptions;
 import org.graalvm.nativeimage.c.function.CEntryPoint;
 import org.graalvm.nativeimage.c.type.*;
 import org.graalvm.nativeimage.c.type.CTypeConversion;
 import org.graalvm.word.WordFactory;
 
 import java.io.*;
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.nio.CharBuffer;
+import java.nio.charset.CharacterCodingException;
+import java.nio.charset.Charset;
+import java.nio.channels.FileChannel;
+import java.nio.channels.FileLock;
+import java.nio.channels.OverlappingFileLockException;
 import java.nio.charset.StandardCharsets;
 import java.nio.file.Files;
 import java.nio.file.Path;
 import java.nio.file.Paths;
+import java.nio.file.StandardCopyOption;
+import java.nio.file.StandardOpenOption;
+import java.security.MessageDigest;
+import java.text.Normalizer;
+import java.time.LocalDateTime;
+import java.util.ArrayList;
+import java.util.Arrays;
+import java.util.Collections;
+import java.util.HashSet;
+import java.util.LinkedHashMap;
 import java.util.List;
+import java.util.Map;
+import java.util.Set;
+import java.util.concurrent.ArrayBlockingQueue;
+import java.util.concurrent.BlockingQueue;
 import java.util.concurrent.ConcurrentHashMap;
+import java.util.concurrent.TimeUnit;
+import java.util.concurrent.atomic.AtomicInteger;
 import java.util.concurrent.atomic.AtomicLong;
+import java.util.function.Function;
+import java.util.zip.CRC32;
+import java.util.zip.DataFormatException;
 
 /**
  * Comprehensive C ABI for Zip4j library using GraalVM Native Image.
@@ -52,6 +85,10 @@ import java.util.concurrent.atomic.AtomicLong;
  */
 public class Zip4JC {
     
+    // ========== ABI Version ==========
+    // Incremented whenever an entry point changes incompatibly
+    public static final int ABI_VERSION = 1;
+    
     // ========== Error Codes ==========
     public static final int SUCCESS = 0;
     public static final int ERROR_INVALID_HANDLE = -1;
@@ -90,15 +127,66 @@ public class Zip4JC {
     public static final int AES_KEY_STRENGTH_192 = 2;
     public static final int AES_KEY_STRENGTH_256 = 3;
     
+    // ========== Entry Health ==========
+    public static final int HEALTH_INTACT = 0;
+    public static final int HEALTH_TRUNCATED = 1;
+    public static final int HEALTH_CRC_MISMATCH = 2;
+    public static final int HEALTH_WRONG_PASSWORD = 3;
+    public static final int HEALTH_CORRUPT = 4;
+    
+    // ========== Change Detection ==========
+    public static final int CHANGE_DETECTION_CRC = 0;
+    public static final int CHANGE_DETECTION_MTIME = 1;
+    
+    // ========== Entry Differences ==========
+    public static final int DIFF_UNCHANGED = 0;
+    public static final int DIFF_MISSING = 1;
+    public static final int DIFF_MODIFIED = 2;
+    
+    // ========== Name Normalization ==========
+    public static final int NAME_NORMALIZATION_NONE = 0;
+    public static final int NAME_NORMALIZATION_NFC = 1;
+    public static final int NAME_NORMALIZATION_NFD = 2;
+    
+    // ========== Output Streams ==========
+    public static final int OUTPUT_NONE = 0;
+    public static final int OUTPUT_STDOUT = 1;
+    public static final int OUTPUT_STDERR = 2;
+    
+    // ========== Entry Listing ==========
+    // Fixed part of a zip4j_list_entries record: name length, size, compressed size, CRC,
+    // compression method, encryption method, directory and encrypted flags, DOS time, bit flags
+    public static final int ENTRY_RECORD_SIZE = 40;
+    
     // ========== Global State Management ==========
     private static final ConcurrentHashMap<Long, ZipFile> zipFiles = new ConcurrentHashMap<>();
+    private static final ConcurrentHashMap<Long, char[]> zipPasswords = new ConcurrentHashMap<>();
     private static final ConcurrentHashMap<Long, FileHeader> zipEntries = new ConcurrentHashMap<>();
+    private static final ConcurrentHashMap<Long, ZipFile> entryZipFiles = new ConcurrentHashMap<>();
     private static final ConcurrentHashMap<Long, ZipInputStream> inputStreams = new ConcurrentHashMap<>();
     private static final ConcurrentHashMap<Long, ZipOutputStream> outputStreams = new ConcurrentHashMap<>();
     private static final ConcurrentHashMap<Long, ProgressMonitor> progressMonitors = new ConcurrentHashMap<>();
     private static final ConcurrentHashMap<Long, byte[]> dataBuffers = new ConcurrentHashMap<>();
-    private static final ConcurrentHashMap<Long, String> lastErrors = new ConcurrentHashMap<>();
+    /**
+     * Last error message per handle and calling thread id, so concurrent callers don't overwrite each other's.
+     * A handle's messages go away when it is released, whichever thread set them.
+     */
+    private static final ConcurrentHashMap<Long, ConcurrentHashMap<Long, String>> lastErrors = new ConcurrentHashMap<>();
+    /** Last exception reported on each calling thread and the error code it was mapped to, until read by zip4j_take_last_exception or the thread's next call */
+    private static final ThreadLocal<Throwable> lastException = new ThreadLocal<>();
+    private static final ThreadLocal<Integer> lastExceptionCode = new ThreadLocal<>();
+    private static final ConcurrentHashMap<Long, ZipParameters> parameterSets = new ConcurrentHashMap<>();
+    private static final ConcurrentHashMap<Long, char[]> parameterPasswords = new ConcurrentHashMap<>();
+    private static final ConcurrentHashMap<Long, ChunkInputStream> dataWriters = new ConcurrentHashMap<>();
+    private static final ConcurrentHashMap<Long, File> stagedTargets = new ConcurrentHashMap<>();
+    private static final ConcurrentHashMap<Long, FileLock> archiveLocks = new ConcurrentHashMap<>();
+    private static final ConcurrentHashMap<Long, Normalizer.Form> nameForms = new ConcurrentHashMap<>();
     private static final AtomicLong handleCounter = new AtomicLong(1);
+    /** Lines printed to System.out or System.err while output is captured, oldest first */
+    private static final BlockingQueue<OutputLine> capturedOutput = new ArrayBlockingQueue<>(1024);
+    /** Streams replaced while output is captured, null otherwise */
+    private static PrintStream originalOut;
+    private static PrintStream originalErr;
     
     // ========== Initialization and Cleanup ==========
     
@@ -127,14 +215,25 @@ public class Zip4JC {
             outputStreams.values().forEach(stream -> {
                 try { stream.close(); } catch (Exception ignored) {}
             });
+            dataWriters.values().forEach(ChunkInputStream::abort);
             
+            zipPasswords.values().forEach(Zip4JC::wipe);
+            parameterPasswords.values().forEach(Zip4JC::wipe);
+
             zipFiles.clear();
+            zipPasswords.clear();
             zipEntries.clear();
+            entryZipFiles.clear();
             inputStreams.clear();
             outputStreams.clear();
             progressMonitors.clear();
             dataBuffers.clear();
             lastErrors.clear();
+            lastException.remove();
+            lastExceptionCode.remove();
+            parameterSets.clear();
+            parameterPasswords.clear();
+            dataWriters.clear();
             
             return SUCCESS;
         } catch (Throwable e) {
@@ -142,6 +241,25 @@ public class Zip4JC {
         }
     }
     
+    /**
+     * Sets the maximum heap size of the isolate in bytes. Applies to the isolate of the calling
+     * thread, so call it right after creating the isolate.
+     */
+    @CEntryPoint(name = "zip4j_set_max_heap_size")
+    public static int setMaxHeapSize(IsolateThread thread, long bytes) {
+        forgetLastException();
+        try {
+            if (bytes <= 0) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            RuntimeOptions.set("MaxHeapSize", bytes);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(0, e);
+        }
+    }
+
     // ========== Helper Methods ==========
     
     /**
@@ -154,9 +272,36 @@ public class Zip4JC {
         }
 
         if (handle > 0) {
-            lastErrors.put(handle, errorMessage);
+            setLastError(handle, errorMessage);
         }
 
+        int code = errorCodeFor(e);
+        lastException.set(e);
+        lastExceptionCode.set(code);
+        return code;
+    }
+
+    /**
+     * Forgets the exception of an earlier call on the calling thread, so an error that doesn't
+     * come from an exception isn't described by it. Called first by every entry point that does
+     * work of its own; those that only report or release what an earlier call left behind keep it.
+     */
+    private static void forgetLastException() {
+        lastException.remove();
+        lastExceptionCode.remove();
+    }
+
+    /**
+     * Records the last error message of the calling thread for a handle.
+     */
+    private static void setLastError(long handle, String errorMessage) {
+        lastErrors.computeIfAbsent(handle, h -> new ConcurrentHashMap<>()).put(Thread.currentThread().getId(), errorMessage);
+    }
+
+    /**
+     * Maps an exception to the error code reported for it.
+     */
+    private static int errorCodeFor(Throwable e) {
         if (e instanceof ZipException) {
             return ERROR_ZIP_EXCEPTION;
         } else if (e instanceof IOException) {
@@ -198,372 +343,3418 @@ public class Zip4JC {
     }
     
     /**
-     * Convert compression level integer to enum.
+     * Helper method to read a list of consecutive null-terminated UTF-8 strings from a C buffer.
      */
-    private static CompressionLevel getCompressionLevel(int level) {
-        switch (level) {
-            case COMPRESSION_LEVEL_NONE: return CompressionLevel.NO_COMPRESSION;
-            case COMPRESSION_LEVEL_FASTEST: return CompressionLevel.FASTEST;
-            case COMPRESSION_LEVEL_NORMAL: return CompressionLevel.NORMAL;
-            case COMPRESSION_LEVEL_MAXIMUM: return CompressionLevel.MAXIMUM;
-            default: return CompressionLevel.NORMAL;
+    private static List<String> readStringList(CCharPointer buffer, int length) {
+        List<String> strings = new ArrayList<>();
+        int start = 0;
+        for (int i = 0; i < length; i++) {
+            if (buffer.read(i) == 0) {
+                byte[] bytes = new byte[i - start];
+                for (int j = start; j < i; j++) {
+                    bytes[j - start] = buffer.read(j);
+                }
+                strings.add(new String(bytes, StandardCharsets.UTF_8));
+                start = i + 1;
+            }
         }
+        return strings;
     }
-    
+
     /**
-     * Convert compression method integer to enum.
+     * Helper method to read a null-terminated password without going through an immutable String.
+     * Each byte becomes one char, and every ZipFile is opened with UTF-8 password conversion turned
+     * off, so zip4j uses the caller's bytes verbatim. Returns null for a null pointer.
      */
-    private static CompressionMethod getCompressionMethod(int method) {
-        switch (method) {
-            case COMPRESSION_STORE: return CompressionMethod.STORE;
-            case COMPRESSION_DEFLATE: return CompressionMethod.DEFLATE;
-            default: return CompressionMethod.DEFLATE;
+    private static char[] readPassword(CCharPointer password) {
+        if (password.equal(WordFactory.nullPointer())) {
+            return null;
+        }
+
+        int length = 0;
+        while (password.read(length) != 0) {
+            length++;
+        }
+
+        char[] result = new char[length];
+        for (int i = 0; i < length; i++) {
+            result[i] = (char) (password.read(i) & 0xFF);
         }
+        return result;
     }
-    
+
     /**
-     * Convert encryption method integer to enum.
+     * Helper method to open a ZipFile that takes password chars as raw bytes (see readPassword).
      */
-    private static EncryptionMethod getEncryptionMethod(int method) {
-        switch (method) {
-            case ENCRYPTION_NONE: return EncryptionMethod.NONE;
-            case ENCRYPTION_STANDARD: return EncryptionMethod.ZIP_STANDARD;
-            case ENCRYPTION_AES_128: return EncryptionMethod.AES;
-            case ENCRYPTION_AES_256: return EncryptionMethod.AES;
-            default: return EncryptionMethod.NONE;
-        }
+    private static ZipFile openZipFile(File file, char[] password) {
+        ZipFile zipFile = new ZipFile(file, password);
+        zipFile.setUseUtf8CharsetForPasswords(false);
+        return zipFile;
     }
-    
+
     /**
-     * Convert AES key strength integer to enum.
+     * Helper method to open a ZipFile by path that takes password chars as raw bytes.
      */
-    private static AesKeyStrength getAesKeyStrength(int strength) {
-        switch (strength) {
-            case AES_KEY_STRENGTH_128: return AesKeyStrength.KEY_STRENGTH_128;
-            case AES_KEY_STRENGTH_192: return AesKeyStrength.KEY_STRENGTH_192;
-            case AES_KEY_STRENGTH_256: return AesKeyStrength.KEY_STRENGTH_256;
-            default: return AesKeyStrength.KEY_STRENGTH_256;
-        }
+    private static ZipFile openZipFile(String path, char[] password) {
+        return openZipFile(new File(path), password);
     }
 
-    // ========== ZipFile Management ==========
+    /**
+     * Helper method to open a ZipOutputStream that takes password chars as raw bytes.
+     */
+    private static ZipOutputStream openZipOutputStream(OutputStream outputStream, char[] password) throws IOException {
+        Zip4jConfig config = new Zip4jConfig(InternalZipConstants.ZIP4J_DEFAULT_CHARSET, InternalZipConstants.BUFF_SIZE, false);
+        return new ZipOutputStream(outputStream, password, config, new ZipModel());
+    }
 
     /**
-     * Creates a new ZipFile instance.
+     * Helper method to look up an entry by name. When a normalization form is set for the zip
+     * file, names that only differ in their Unicode normalization match as well.
      */
-    @CEntryPoint(name = "zip4j_create")
-    public static int createZipFile(IsolateThread thread, CCharPointer filePath, CLongPointer zipHandle) {
-        try {
-            if (filePath.equal(WordFactory.nullPointer()) || zipHandle.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
-            }
+    private static FileHeader findFileHeader(long zipHandle, ZipFile zipFile, String name) throws ZipException {
+        FileHeader fileHeader = zipFile.getFileHeader(name);
+        Normalizer.Form form = nameForms.get(zipHandle);
+        if (fileHeader != null || form == null) {
+            return fileHeader;
+        }
 
-            String path = CTypeConversion.toJavaString(filePath);
-            if (path == null || path.trim().isEmpty()) {
-                return ERROR_INVALID_PARAMETER;
+        String normalized = Normalizer.normalize(name, form);
+        for (FileHeader candidate : zipFile.getFileHeaders()) {
+            if (Normalizer.normalize(candidate.getFileName(), form).equals(normalized)) {
+                return candidate;
             }
-
-            ZipFile zipFile = new ZipFile(path);
-            long handle = handleCounter.getAndIncrement();
-            zipFiles.put(handle, zipFile);
-            lastErrors.remove(handle);
-
-            zipHandle.write(handle);
-            return SUCCESS;
-        } catch (Throwable e) {
-            return handleException(0, e);
         }
+        return null;
     }
 
     /**
-     * Creates a new ZipFile instance with password.
+     * Helper method to get an entry's name normalized to form, or as stored if form is null.
      */
-    @CEntryPoint(name = "zip4j_create_with_password")
-    public static int createZipFileWithPassword(IsolateThread thread, CCharPointer filePath,
-                                               CCharPointer password, CLongPointer zipHandle) {
-        try {
-            if (filePath.equal(WordFactory.nullPointer()) || zipHandle.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
-            }
+    private static String normalizedName(FileHeader fileHeader, Normalizer.Form form) {
+        return form != null ? Normalizer.normalize(fileHeader.getFileName(), form) : fileHeader.getFileName();
+    }
 
-            String path = CTypeConversion.toJavaString(filePath);
-            String pass = !password.equal(WordFactory.nullPointer()) ? CTypeConversion.toJavaString(password) : null;
+    /**
+     * Helper method to resolve the file an entry is extracted to below destination under its
+     * normalized name, refusing names that break out of the destination directory.
+     */
+    private static File extractionTarget(File destination, FileHeader fileHeader, Normalizer.Form form)
+            throws IOException {
+        File target = new File(destination, normalizedName(fileHeader, form));
+        if (!(target.getCanonicalPath() + File.separator).startsWith(destination.getCanonicalPath() + File.separator)) {
+            throw new ZipException("illegal file name that breaks out of the target directory: "
+                    + fileHeader.getFileName());
+        }
+        return target;
+    }
 
-            if (path == null || path.trim().isEmpty()) {
-                return ERROR_INVALID_PARAMETER;
+    /**
+     * Helper method to extract an entry, writing it under its name normalized to the form set
+     * for the zip file, if any.
+     */
+    private static void extractNormalized(long zipHandle, ZipFile zipFile, FileHeader fileHeader, String path)
+            throws ZipException {
+        Normalizer.Form form = nameForms.get(zipHandle);
+        // zip4j refuses to start a task while another one on the same ZipFile is running
+        synchronized (zipFile) {
+            if (form == null) {
+                zipFile.extractFile(fileHeader, path);
+            } else {
+                zipFile.extractFile(fileHeader, path, Normalizer.normalize(fileHeader.getFileName(), form));
             }
-
-            ZipFile zipFile = new ZipFile(path, pass != null ? pass.toCharArray() : null);
-            long handle = handleCounter.getAndIncrement();
-            zipFiles.put(handle, zipFile);
-            lastErrors.remove(handle);
-
-            zipHandle.write(handle);
-            return SUCCESS;
-        } catch (Throwable e) {
-            return handleException(0, e);
         }
     }
 
     /**
-     * Sets or changes the password for an existing ZipFile.
+     * Helper method to release an archive lock and close its lock file.
      */
-    @CEntryPoint(name = "zip4j_set_password")
-    public static int setPassword(IsolateThread thread, long zipHandle, CCharPointer password) {
-        try {
-            ZipFile zipFile = zipFiles.get(zipHandle);
-            if (zipFile == null) {
-                return ERROR_INVALID_HANDLE;
+    private static void releaseLock(FileLock lock) throws IOException {
+        if (lock != null) {
+            try {
+                lock.release();
+            } finally {
+                lock.channel().close();
             }
+        }
+    }
 
-            String pass = !password.equal(WordFactory.nullPointer()) ? CTypeConversion.toJavaString(password) : null;
-            zipFile.setPassword(pass != null ? pass.toCharArray() : null);
-
-            return SUCCESS;
-        } catch (Throwable e) {
-            return handleException(zipHandle, e);
+    /**
+     * Helper method to overwrite a password that is no longer needed.
+     */
+    private static void wipe(char[] password) {
+        if (password != null) {
+            Arrays.fill(password, '\0');
         }
     }
 
     /**
-     * Closes and releases a ZipFile instance.
+     * Helper method to get a copy of a parameter set, applying its password to the zip file.
+     * Returns null if the parameters handle is unknown.
      */
-    @CEntryPoint(name = "zip4j_close")
-    public static int closeZipFile(IsolateThread thread, long zipHandle) {
-        try {
-            ZipFile zipFile = zipFiles.remove(zipHandle);
-            if (zipFile == null) {
-                return ERROR_INVALID_HANDLE;
-            }
+    private static ZipParameters prepareParameters(ZipFile zipFile, long paramsHandle) {
+        ZipParameters zipParameters = parameterSets.get(paramsHandle);
+        if (zipParameters == null) {
+            return null;
+        }
 
-            lastErrors.remove(zipHandle);
-            return SUCCESS;
-        } catch (Throwable e) {
-            return handleException(zipHandle, e);
+        char[] pass = parameterPasswords.get(paramsHandle);
+        if (pass != null) {
+            zipFile.setPassword(pass.clone());
         }
+
+        return new ZipParameters(zipParameters);
     }
 
     /**
-     * Checks if the zip file is valid.
+     * Helper method to write in-memory header changes back to the archive. zip4j has no public
+     * API for this, but setting the archive comment rewrites the whole central directory.
      */
-    @CEntryPoint(name = "zip4j_is_valid")
-    public static int isValidZipFile(IsolateThread thread, long zipHandle, CIntPointer isValid) {
-        try {
-            if (isValid.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
-            }
+    private static void rewriteCentralDirectory(ZipFile zipFile) throws ZipException {
+        String comment = zipFile.getComment();
+        zipFile.setComment(comment != null ? comment : "");
+    }
 
-            ZipFile zipFile = zipFiles.get(zipHandle);
-            if (zipFile == null) {
-                return ERROR_INVALID_HANDLE;
-            }
+    /**
+     * Helper method to get the archive file holding an entry's local header: the archive itself,
+     * or the right part of a split archive.
+     */
+    private static File entryArchivePart(ZipFile zipFile, FileHeader fileHeader) throws ZipException {
+        return zipFile.isSplitArchive()
+                ? zipFile.getSplitZipFiles().get(fileHeader.getDiskNumberStart())
+                : zipFile.getFile();
+    }
 
-            isValid.write(zipFile.isValidZipFile() ? 1 : 0);
-            return SUCCESS;
-        } catch (Throwable e) {
-            return handleException(zipHandle, e);
+    /**
+     * Helper method to find where an entry's data starts by reading its local header, whose extra
+     * field may differ from the central directory's.
+     */
+    private static long localDataOffset(File archive, FileHeader fileHeader) throws IOException {
+        long offset = fileHeader.getOffsetLocalHeader();
+        byte[] localHeader = new byte[30];
+        try (RandomAccessFile file = new RandomAccessFile(archive, "r")) {
+            file.seek(offset);
+            file.readFully(localHeader);
+        }
+
+        if ((localHeader[0] & 0xFF | (localHeader[1] & 0xFF) << 8 | (localHeader[2] & 0xFF) << 16
+                | (localHeader[3] & 0xFF) << 24) != 0x04034b50) {
+            throw new ZipException("invalid local header signature for entry: " + fileHeader.getFileName());
         }
+
+        int nameLength = localHeader[26] & 0xFF | (localHeader[27] & 0xFF) << 8;
+        int extraLength = localHeader[28] & 0xFF | (localHeader[29] & 0xFF) << 8;
+        return offset + localHeader.length + nameLength + extraLength;
     }
 
     /**
-     * Checks if the zip file is encrypted.
+     * Helper method to map an entry's compression method to its constant.
      */
-    @CEntryPoint(name = "zip4j_is_encrypted")
-    public static int isEncrypted(IsolateThread thread, long zipHandle, CIntPointer isEncrypted) {
-        try {
-            if (isEncrypted.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
-            }
-
-            ZipFile zipFile = zipFiles.get(zipHandle);
-            if (zipFile == null) {
-                return ERROR_INVALID_HANDLE;
-            }
+    private static int compressionMethodCode(FileHeader fileHeader) {
+        return fileHeader.getCompressionMethod() == CompressionMethod.STORE ? COMPRESSION_STORE : COMPRESSION_DEFLATE;
+    }
 
-            isEncrypted.write(zipFile.isEncrypted() ? 1 : 0);
-            return SUCCESS;
-        } catch (Throwable e) {
-            return handleException(zipHandle, e);
+    /**
+     * Helper method to map an entry's encryption method and AES key strength to its constant.
+     */
+    private static int encryptionMethodCode(FileHeader fileHeader) {
+        EncryptionMethod method = fileHeader.getEncryptionMethod();
+        switch (method) {
+            case NONE: return ENCRYPTION_NONE;
+            case ZIP_STANDARD: return ENCRYPTION_STANDARD;
+            case AES:
+                AesKeyStrength keyStrength = fileHeader.getAesExtraDataRecord().getAesKeyStrength();
+                return (keyStrength == AesKeyStrength.KEY_STRENGTH_128) ? ENCRYPTION_AES_128 : ENCRYPTION_AES_256;
+            default: return ENCRYPTION_NONE;
         }
     }
 
     /**
-     * Checks if the zip file is split archive.
+     * Helper method to read an entry's general purpose bit flags as an unsigned 16-bit value.
      */
-    @CEntryPoint(name = "zip4j_is_split_archive")
-    public static int isSplitArchive(IsolateThread thread, long zipHandle, CIntPointer isSplit) {
-        try {
-            if (isSplit.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
-            }
+    private static int generalPurposeFlags(FileHeader fileHeader) {
+        byte[] flagBytes = fileHeader.getGeneralPurposeFlag();
+        return flagBytes == null ? 0 : (flagBytes[0] & 0xFF) | (flagBytes[1] & 0xFF) << 8;
+    }
 
-            ZipFile zipFile = zipFiles.get(zipHandle);
-            if (zipFile == null) {
-                return ERROR_INVALID_HANDLE;
+    /**
+     * Helper method to compute the CRC32 of a file on disk.
+     */
+    private static long fileCrc(Path file) throws IOException {
+        CRC32 crc = new CRC32();
+        try (InputStream inputStream = Files.newInputStream(file)) {
+            byte[] readBuffer = new byte[8192];
+            int bytesRead;
+            while ((bytesRead = inputStream.read(readBuffer)) != -1) {
+                crc.update(readBuffer, 0, bytesRead);
             }
-
-            isSplit.write(zipFile.isSplitArchive() ? 1 : 0);
-            return SUCCESS;
-        } catch (Throwable e) {
-            return handleException(zipHandle, e);
         }
+        return crc.getValue();
     }
 
     /**
-     * Gets the file path of the zip file.
+     * Helper method to find the encrypted entry that is cheapest to verify a password against.
+     * Returns null if the archive has no encrypted entries.
      */
-    @CEntryPoint(name = "zip4j_get_file_path")
-    public static int getFilePath(IsolateThread thread, long zipHandle, CCharPointer buffer,
-                                 int bufferSize, CIntPointer pathLength) {
-        try {
-            if (buffer.equal(WordFactory.nullPointer()) || pathLength.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
-            }
-
-            ZipFile zipFile = zipFiles.get(zipHandle);
-            if (zipFile == null) {
-                return ERROR_INVALID_HANDLE;
+    private static FileHeader smallestEncryptedEntry(ZipFile zipFile) throws ZipException {
+        FileHeader smallest = null;
+        for (FileHeader fileHeader : zipFile.getFileHeaders()) {
+            if (fileHeader.isEncrypted() && !fileHeader.isDirectory()
+                    && (smallest == null || fileHeader.getCompressedSize() < smallest.getCompressedSize())) {
+                smallest = fileHeader;
             }
-
-            String path = zipFile.getFile().getAbsolutePath();
-            return copyStringToBuffer(path, buffer, bufferSize, pathLength);
-        } catch (Throwable e) {
-            return handleException(zipHandle, e);
         }
+        return smallest;
     }
 
     /**
-     * Gets the comment of the zip file.
+     * Helper method to check a password against an encrypted entry, using a separate reader so the
+     * archive's own password is left alone. AES entries are checked with their password
+     * verification bytes; ZipCrypto entries are read to the end so the CRC confirms the check
+     * byte, which matches one in 256 wrong passwords.
      */
-    @CEntryPoint(name = "zip4j_get_comment")
-    public static int getComment(IsolateThread thread, long zipHandle, CCharPointer buffer,
-                                int bufferSize, CIntPointer commentLength) {
-        try {
-            if (buffer.equal(WordFactory.nullPointer()) || commentLength.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
+    private static boolean checkPassword(ZipFile reader, FileHeader fileHeader, char[] password) throws IOException {
+        reader.setPassword(password);
+        try (ZipInputStream inputStream = reader.getInputStream(fileHeader)) {
+            if (fileHeader.getEncryptionMethod() != EncryptionMethod.AES) {
+                byte[] buffer = new byte[8192];
+                while (inputStream.read(buffer) != -1) {
+                    // Drain the entry so the CRC is verified
+                }
             }
-
-            ZipFile zipFile = zipFiles.get(zipHandle);
-            if (zipFile == null) {
-                return ERROR_INVALID_HANDLE;
+            return true;
+        } catch (ZipException e) {
+            if (e.getType() == ZipException.Type.WRONG_PASSWORD) {
+                return false;
             }
-
-            String comment = zipFile.getComment();
-            if (comment == null) {
-                comment = "";
+            throw e;
+        } catch (IOException e) {
+            // A wrong ZipCrypto key that passes the check byte usually garbles the compressed data
+            if (e.getCause() instanceof DataFormatException) {
+                return false;
             }
-            return copyStringToBuffer(comment, buffer, bufferSize, commentLength);
-        } catch (Throwable e) {
-            return handleException(zipHandle, e);
+            throw e;
         }
     }
 
     /**
-     * Sets the comment of the zip file.
+     * Helper method to register a ZipFile under a new handle, remembering its password.
+     */
+    private static long registerZipFile(ZipFile zipFile, char[] password) {
+        long handle = handleCounter.getAndIncrement();
+        zipFiles.put(handle, zipFile);
+        if (password != null) {
+            char[] copy = password.clone();
+            zipFile.setPassword(copy);
+            zipPasswords.put(handle, copy);
+        }
+        lastErrors.remove(handle);
+        return handle;
+    }
+
+    /**
+     * Helper method to register a progress monitor under a new handle.
+     */
+    private static long registerProgressMonitor(ProgressMonitor progressMonitor) {
+        long handle = handleCounter.getAndIncrement();
+        progressMonitors.put(handle, progressMonitor);
+        return handle;
+    }
+
+    /**
+     * Helper method to copy every entry of an archive into a new split archive, reporting the
+     * uncompressed bytes copied to the progress monitor. Partially written parts are deleted if
+     * the copy fails or is cancelled.
+     */
+    private static void writeSplitArchive(ZipFile zipFile, File output, long splitSize, char[] password,
+                                          ProgressMonitor progressMonitor) throws IOException {
+        if (output.exists()) {
+            throw new ZipException("split zip file already exists: " + output.getPath());
+        }
+
+        List<FileHeader> fileHeaders = zipFile.getFileHeaders();
+        long totalWork = 0;
+        for (FileHeader fileHeader : fileHeaders) {
+            totalWork += fileHeader.getUncompressedSize();
+        }
+        progressMonitor.setTotalWork(totalWork);
+
+        try (ZipOutputStream outputStream = openZipOutputStream(new SplitOutputStream(output, splitSize), password)) {
+            copyEntries(zipFile, fileHeaders, outputStream, Zip4JC::parametersFromHeader, progressMonitor);
+        } catch (IOException e) {
+            deleteSplitParts(output);
+            throw e;
+        }
+    }
+
+    /**
+     * Helper method to copy entries and the archive comment into a zip output stream, reporting
+     * the uncompressed bytes copied to the progress monitor and stopping if it is cancelled.
+     */
+    private static void copyEntries(ZipFile zipFile, List<FileHeader> fileHeaders, ZipOutputStream outputStream,
+                                    Function<FileHeader, ZipParameters> parameters,
+                                    ProgressMonitor progressMonitor) throws IOException {
+        byte[] buffer = new byte[8192];
+        for (FileHeader fileHeader : fileHeaders) {
+            outputStream.putNextEntry(parameters.apply(fileHeader));
+            if (!fileHeader.isDirectory()) {
+                try (ZipInputStream inputStream = zipFile.getInputStream(fileHeader)) {
+                    int read;
+                    while ((read = inputStream.read(buffer)) != -1) {
+                        if (progressMonitor.isCancelAllTasks()) {
+                            progressMonitor.setResult(ProgressMonitor.Result.CANCELLED);
+                            progressMonitor.setState(ProgressMonitor.State.READY);
+                            throw new ZipException("Task cancelled");
+                        }
+                        outputStream.write(buffer, 0, read);
+                        progressMonitor.updateWorkCompleted(read);
+                    }
+                }
+            }
+            outputStream.closeEntry();
+        }
+
+        String comment = zipFile.getComment();
+        if (comment != null && !comment.isEmpty()) {
+            outputStream.setComment(comment);
+        }
+    }
+
+    /**
+     * Helper method to delete a split archive and its numbered parts.
+     */
+    private static void deleteSplitParts(File output) {
+        String name = output.getName();
+        String baseName = name.contains(".") ? name.substring(0, name.lastIndexOf('.')) : name;
+        for (int part = 1; ; part++) {
+            File partFile = new File(output.getParentFile(), String.format("%s.z%02d", baseName, part));
+            if (!partFile.delete()) {
+                break;
+            }
+        }
+        output.delete();
+    }
+
+    /**
+     * Helper method to copy an archive byte for byte, padding the local header extra field of
+     * every stored entry so its data starts on an alignment boundary, like zipalign. The padding
+     * is a 0xD935 alignment extra field, which replaces any earlier one. An alignment of 0 copies
+     * the entries unchanged. Either way only bytes referenced by the central directory are kept.
+     * Zip64 archives are not supported.
+     */
+    private static void writeAlignedArchive(File source, File target, int alignment) throws IOException {
+        try (RandomAccessFile input = new RandomAccessFile(source, "r");
+             OutputStream output = new BufferedOutputStream(new FileOutputStream(target))) {
+            ByteBuffer endRecord = readEndOfCentralDirectory(input);
+            int entryCount = endRecord.getShort(10) & 0xFFFF;
+            long centralDirectorySize = endRecord.getInt(12) & 0xFFFFFFFFL;
+            long centralDirectoryOffset = endRecord.getInt(16) & 0xFFFFFFFFL;
+            if (entryCount == 0xFFFF || centralDirectorySize == 0xFFFFFFFFL || centralDirectoryOffset == 0xFFFFFFFFL) {
+                throw new UnsupportedOperationException("aligning Zip64 archives is not supported");
+            }
+
+            ByteBuffer centralDirectory = ByteBuffer.allocate((int) centralDirectorySize).order(ByteOrder.LITTLE_ENDIAN);
+            input.seek(centralDirectoryOffset);
+            input.readFully(centralDirectory.array());
+
+            long position = 0;
+            int record = 0;
+            byte[] copyBuffer = new byte[8192];
+            for (int i = 0; i < entryCount; i++) {
+                if (centralDirectory.getInt(record) != 0x02014b50) {
+                    throw new ZipException("invalid central directory header signature");
+                }
+                int flags = centralDirectory.getShort(record + 8) & 0xFFFF;
+                int method = centralDirectory.getShort(record + 10) & 0xFFFF;
+                long compressedSize = centralDirectory.getInt(record + 20) & 0xFFFFFFFFL;
+                long localOffset = centralDirectory.getInt(record + 42) & 0xFFFFFFFFL;
+
+                ByteBuffer localHeader = ByteBuffer.allocate(30).order(ByteOrder.LITTLE_ENDIAN);
+                input.seek(localOffset);
+                input.readFully(localHeader.array());
+                if (localHeader.getInt(0) != 0x04034b50) {
+                    throw new ZipException("invalid local header signature");
+                }
+                byte[] name = new byte[localHeader.getShort(26) & 0xFFFF];
+                byte[] extra = new byte[localHeader.getShort(28) & 0xFFFF];
+                input.readFully(name);
+                input.readFully(extra);
+                long dataOffset = input.getFilePointer();
+
+                long dataLength = compressedSize;
+                if ((flags & 0x08) != 0) {
+                    input.seek(dataOffset + compressedSize);
+                    dataLength += Integer.reverseBytes(input.readInt()) == 0x08074b50 ? 16 : 12;
+                }
+
+                if (method == 0 && alignment > 0) {
+                    extra = alignExtraField(extra, position + localHeader.capacity() + name.length, alignment);
+                    localHeader.putShort(28, (short) extra.length);
+                }
+
+                centralDirectory.putInt(record + 42, (int) position);
+                output.write(localHeader.array());
+                output.write(name);
+                output.write(extra);
+                position += localHeader.capacity() + name.length + extra.length;
+
+                input.seek(dataOffset);
+                for (long remaining = dataLength; remaining > 0; ) {
+                    int bytesRead = input.read(copyBuffer, 0, (int) Math.min(copyBuffer.length, remaining));
+                    if (bytesRead == -1) {
+                        throw new EOFException("entry data extends past the end of the archive");
+                    }
+                    output.write(copyBuffer, 0, bytesRead);
+                    remaining -= bytesRead;
+                }
+                position += dataLength;
+
+                record += 46 + (centralDirectory.getShort(record + 28) & 0xFFFF)
+                        + (centralDirectory.getShort(record + 30) & 0xFFFF)
+                        + (centralDirectory.getShort(record + 32) & 0xFFFF);
+            }
+
+            if (position > 0xFFFFFFFFL) {
+                throw new UnsupportedOperationException("aligned archive would need Zip64");
+            }
+
+            endRecord.putInt(16, (int) position);
+            output.write(centralDirectory.array());
+            output.write(endRecord.array());
+        }
+    }
+
+    /**
+     * Helper method to turn renames into the exact name of every entry they change, the way
+     * zip4j_rename_entries applies them: an entry whose name is a key gets its value, and entries
+     * below a directory key ending in '/' move along with the closest such directory.
+     */
+    private static Map<String, String> renamePlan(ZipFile zipFile, Map<String, String> renames) throws ZipException {
+        Map<String, String> plan = new LinkedHashMap<>();
+        for (FileHeader fileHeader : zipFile.getFileHeaders()) {
+            String name = fileHeader.getFileName();
+            String newName = renames.get(name);
+            if (newName == null) {
+                String directory = null;
+                for (String oldName : renames.keySet()) {
+                    if (oldName.endsWith("/") && name.startsWith(oldName)
+                            && (directory == null || oldName.length() > directory.length())) {
+                        directory = oldName;
+                    }
+                }
+                if (directory == null) {
+                    continue;
+                }
+                newName = renames.get(directory) + name.substring(directory.length());
+            }
+            if (fileHeader.isDirectory() && !newName.endsWith("/")) {
+                newName += "/";
+            }
+            plan.put(name, newName);
+        }
+        return plan;
+    }
+
+    /**
+     * Helper method to rename and remove entries by their exact names, as given by a plan mapping
+     * old names to new ones or to null for removal. Non-Zip64 archives are rewritten byte for
+     * byte in one pass; Zip64 archives go through zip4j, whose prefix matching would also touch
+     * other entries, so those fail with UnsupportedOperationException unless zip4j ends up
+     * applying exactly the plan.
+     */
+    private static void applyRenamePlan(long zipHandle, ZipFile zipFile, Map<String, String> plan) throws IOException {
+        File file = zipFile.getFile();
+        if (plan.isEmpty() || !file.exists()) {
+            return;
+        }
+
+        if (zipFile.isSplitArchive()) {
+            throw new UnsupportedOperationException("renaming entries of split archives is not supported");
+        }
+
+        File temp = File.createTempFile(file.getName(), ".tmp", file.getAbsoluteFile().getParentFile());
+        try {
+            writeRewrittenArchive(file, temp, plan, zipFile.getCharset());
+            Files.move(temp.toPath(), file.toPath(), StandardCopyOption.REPLACE_EXISTING);
+        } catch (UnsupportedOperationException e) {
+            applyRenamePlanWithZip4j(zipFile, plan);
+            return;
+        } finally {
+            temp.delete();
+        }
+
+        zipFiles.put(zipHandle, openZipFile(file, zipPasswords.get(zipHandle)));
+    }
+
+    /**
+     * Helper method to apply a rename plan with zip4j's removeFiles and renameFiles, after checking
+     * that their prefix matching changes exactly the entries the plan names.
+     */
+    private static void applyRenamePlanWithZip4j(ZipFile zipFile, Map<String, String> plan) throws ZipException {
+        List<String> removals = new ArrayList<>();
+        Map<String, String> renames = new LinkedHashMap<>();
+        for (Map.Entry<String, String> change : plan.entrySet()) {
+            if (change.getValue() == null) {
+                removals.add(change.getKey());
+            } else {
+                renames.put(change.getKey(), change.getValue());
+            }
+        }
+
+        for (FileHeader fileHeader : zipFile.getFileHeaders()) {
+            String name = fileHeader.getFileName();
+            boolean removed = false;
+            for (String removal : removals) {
+                removed |= name.equals(removal) || (removal.endsWith("/") && name.startsWith(removal));
+            }
+            if (removed != (plan.containsKey(name) && plan.get(name) == null)) {
+                throw new UnsupportedOperationException("removing these entries from a Zip64 archive would remove others");
+            }
+            if (removed) {
+                continue;
+            }
+
+            // zip4j takes the first key in no particular order that the name starts with
+            String expected = plan.getOrDefault(name, name);
+            for (Map.Entry<String, String> rename : renames.entrySet()) {
+                if (name.startsWith(rename.getKey())
+                        && !expected.equals(rename.getValue() + name.substring(rename.getKey().length()))) {
+                    throw new UnsupportedOperationException("renaming these entries in a Zip64 archive would rename others");
+                }
+            }
+        }
+
+        if (!removals.isEmpty()) {
+            zipFile.removeFiles(removals);
+        }
+        if (!renames.isEmpty()) {
+            zipFile.renameFiles(renames);
+        }
+    }
+
+    /**
+     * Helper method to copy an archive byte for byte while renaming and dropping entries in the
+     * same pass. Entries mapped to null are dropped; entry data is never recompressed. Zip64
+     * archives are not supported.
+     */
+    private static void writeRewrittenArchive(File source, File target, Map<String, String> renames,
+                                              Charset charset) throws IOException {
+        try (RandomAccessFile input = new RandomAccessFile(source, "r");
+             OutputStream output = new BufferedOutputStream(new FileOutputStream(target))) {
+            ByteBuffer endRecord = readEndOfCentralDirectory(input);
+            int entryCount = endRecord.getShort(10) & 0xFFFF;
+            long centralDirectorySize = endRecord.getInt(12) & 0xFFFFFFFFL;
+            long centralDirectoryOffset = endRecord.getInt(16) & 0xFFFFFFFFL;
+            if (entryCount == 0xFFFF || centralDirectorySize == 0xFFFFFFFFL || centralDirectoryOffset == 0xFFFFFFFFL) {
+                throw new UnsupportedOperationException("rewriting Zip64 archives is not supported");
+            }
+
+            ByteBuffer centralDirectory = ByteBuffer.allocate((int) centralDirectorySize).order(ByteOrder.LITTLE_ENDIAN);
+            input.seek(centralDirectoryOffset);
+            input.readFully(centralDirectory.array());
+
+            ByteArrayOutputStream newCentralDirectory = new ByteArrayOutputStream();
+            long position = 0;
+            int record = 0;
+            int kept = 0;
+            byte[] copyBuffer = new byte[8192];
+            for (int i = 0; i < entryCount; i++) {
+                if (centralDirectory.getInt(record) != 0x02014b50) {
+                    throw new ZipException("invalid central directory header signature");
+                }
+                int flags = centralDirectory.getShort(record + 8) & 0xFFFF;
+                long compressedSize = centralDirectory.getInt(record + 20) & 0xFFFFFFFFL;
+                int nameLength = centralDirectory.getShort(record + 28) & 0xFFFF;
+                int recordLength = 46 + nameLength + (centralDirectory.getShort(record + 30) & 0xFFFF)
+                        + (centralDirectory.getShort(record + 32) & 0xFFFF);
+                long localOffset = centralDirectory.getInt(record + 42) & 0xFFFFFFFFL;
+
+                byte[] oldName = Arrays.copyOfRange(centralDirectory.array(), record + 46, record + 46 + nameLength);
+                String name = new String(oldName, (flags & 0x0800) != 0 ? StandardCharsets.UTF_8 : charset);
+                byte[] newName = oldName;
+                if (renames.containsKey(name)) {
+                    String renamed = renames.get(name);
+                    if (renamed == null) {
+                        record += recordLength;
+                        continue;
+                    }
+                    newName = renamed.getBytes(StandardCharsets.UTF_8);
+                    if (newName.length != renamed.length()) {
+                        flags |= 0x0800;
+                    }
+                }
+
+                ByteBuffer localHeader = ByteBuffer.allocate(30).order(ByteOrder.LITTLE_ENDIAN);
+                input.seek(localOffset);
+                input.readFully(localHeader.array());
+                if (localHeader.getInt(0) != 0x04034b50) {
+                    throw new ZipException("invalid local header signature");
+                }
+                input.skipBytes(localHeader.getShort(26) & 0xFFFF);
+                byte[] extra = new byte[localHeader.getShort(28) & 0xFFFF];
+                input.readFully(extra);
+                long dataOffset = input.getFilePointer();
+
+                long dataLength = compressedSize;
+                if ((flags & 0x08) != 0) {
+                    input.seek(dataOffset + compressedSize);
+                    dataLength += Integer.reverseBytes(input.readInt()) == 0x08074b50 ? 16 : 12;
+                }
+
+                localHeader.putShort(6, (short) flags).putShort(26, (short) newName.length);
+                output.write(localHeader.array());
+                output.write(newName);
+                output.write(extra);
+
+                input.seek(dataOffset);
+                for (long remaining = dataLength; remaining > 0; ) {
+                    int bytesRead = input.read(copyBuffer, 0, (int) Math.min(copyBuffer.length, remaining));
+                    if (bytesRead == -1) {
+                        throw new EOFException("entry data extends past the end of the archive");
+                    }
+                    output.write(copyBuffer, 0, bytesRead);
+                    remaining -= bytesRead;
+                }
+
+                ByteBuffer header = ByteBuffer.allocate(46).order(ByteOrder.LITTLE_ENDIAN);
+                header.put(centralDirectory.array(), record, 46);
+                header.putShort(8, (short) flags).putShort(28, (short) newName.length).putInt(42, (int) position);
+                newCentralDirectory.write(header.array());
+                newCentralDirectory.write(newName);
+                newCentralDirectory.write(centralDirectory.array(), record + 46 + nameLength, recordLength - 46 - nameLength);
+
+                position += localHeader.capacity() + newName.length + extra.length + dataLength;
+                record += recordLength;
+                kept++;
+            }
+
+            if (position > 0xFFFFFFFFL || newCentralDirectory.size() > 0xFFFFFFFFL) {
+                throw new UnsupportedOperationException("rewritten archive would need Zip64");
+            }
+
+            endRecord.putShort(8, (short) kept).putShort(10, (short) kept)
+                    .putInt(12, newCentralDirectory.size()).putInt(16, (int) position);
+            newCentralDirectory.writeTo(output);
+            output.write(endRecord.array());
+        }
+    }
+
+    /**
+     * Helper method to write a new archive holding a single stored, unencrypted entry with no extra
+     * field or data descriptor, as EPUB/OCF requires of its leading mimetype entry.
+     */
+    private static void writeContainerArchive(File file, String name, byte[] data) throws IOException {
+        byte[] nameBytes = name.getBytes(StandardCharsets.UTF_8);
+        boolean utf8 = nameBytes.length != name.length();
+        CRC32 crc = new CRC32();
+        crc.update(data);
+
+        LocalDateTime now = LocalDateTime.now();
+        int dosTime = now.getHour() << 11 | now.getMinute() << 5 | now.getSecond() / 2;
+        int dosDate = (now.getYear() - 1980) << 9 | now.getMonthValue() << 5 | now.getDayOfMonth();
+
+        ByteBuffer localHeader = ByteBuffer.allocate(30 + nameBytes.length).order(ByteOrder.LITTLE_ENDIAN);
+        localHeader.putInt(0x04034b50).putShort((short) 10).putShort((short) (utf8 ? 0x0800 : 0))
+                .putShort((short) 0).putShort((short) dosTime).putShort((short) dosDate)
+                .putInt((int) crc.getValue()).putInt(data.length).putInt(data.length)
+                .putShort((short) nameBytes.length).putShort((short) 0).put(nameBytes);
+
+        ByteBuffer centralDirectory = ByteBuffer.allocate(46 + nameBytes.length).order(ByteOrder.LITTLE_ENDIAN);
+        centralDirectory.putInt(0x02014b50).putShort((short) 20).putShort((short) 10)
+                .putShort((short) (utf8 ? 0x0800 : 0)).putShort((short) 0).putShort((short) dosTime)
+                .putShort((short) dosDate).putInt((int) crc.getValue()).putInt(data.length).putInt(data.length)
+                .putShort((short) nameBytes.length).putShort((short) 0).putShort((short) 0)
+                .putShort((short) 0).putShort((short) 0).putInt(0).putInt(0).put(nameBytes);
+
+        ByteBuffer endRecord = ByteBuffer.allocate(22).order(ByteOrder.LITTLE_ENDIAN);
+        endRecord.putInt(0x06054b50).putShort((short) 0).putShort((short) 0).putShort((short) 1)
+                .putShort((short) 1).putInt(centralDirectory.capacity())
+                .putInt(localHeader.capacity() + data.length).putShort((short) 0);
+
+        try (OutputStream output = new BufferedOutputStream(new FileOutputStream(file))) {
+            output.write(localHeader.array());
+            output.write(data);
+            output.write(centralDirectory.array());
+            output.write(endRecord.array());
+        }
+    }
+
+    /**
+     * Helper method to read the end of central directory record, including the archive comment.
+     */
+    private static ByteBuffer readEndOfCentralDirectory(RandomAccessFile input) throws IOException {
+        long length = input.length();
+        int tailLength = (int) Math.min(length, 22 + 0xFFFF);
+        byte[] tail = new byte[tailLength];
+        input.seek(length - tailLength);
+        input.readFully(tail);
+
+        ByteBuffer buffer = ByteBuffer.wrap(tail).order(ByteOrder.LITTLE_ENDIAN);
+        for (int offset = tailLength - 22; offset >= 0; offset--) {
+            if (buffer.getInt(offset) == 0x06054b50 && offset + 22 + (buffer.getShort(offset + 20) & 0xFFFF) == tailLength) {
+                return ByteBuffer.wrap(Arrays.copyOfRange(tail, offset, tailLength)).order(ByteOrder.LITTLE_ENDIAN);
+            }
+        }
+        throw new ZipException("end of central directory record not found");
+    }
+
+    /**
+     * Helper method to rebuild a local extra field so entry data that would otherwise start at
+     * dataOffset lands on an alignment boundary. Earlier 0xD935 alignment fields are dropped; an
+     * extra field that doesn't parse (e.g. raw zero padding) is kept as is.
+     */
+    private static byte[] alignExtraField(byte[] extra, long dataOffset, int alignment) {
+        ByteArrayOutputStream fields = new ByteArrayOutputStream();
+        ByteBuffer buffer = ByteBuffer.wrap(extra).order(ByteOrder.LITTLE_ENDIAN);
+        int offset = 0;
+        while (offset + 4 <= extra.length) {
+            int id = buffer.getShort(offset) & 0xFFFF;
+            int size = buffer.getShort(offset + 2) & 0xFFFF;
+            if (offset + 4 + size > extra.length) {
+                break;
+            }
+            if (id != 0xD935) {
+                fields.write(extra, offset, 4 + size);
+            }
+            offset += 4 + size;
+        }
+        byte[] kept = offset == extra.length ? fields.toByteArray() : extra;
+
+        int padding = (int) ((alignment - (dataOffset + kept.length) % alignment) % alignment);
+        if (padding == 0) {
+            return kept;
+        }
+        while (padding < 6) {
+            padding += alignment;
+        }
+
+        ByteBuffer aligned = ByteBuffer.allocate(kept.length + padding).order(ByteOrder.LITTLE_ENDIAN);
+        aligned.put(kept);
+        aligned.putShort((short) 0xD935);
+        aligned.putShort((short) (padding - 4));
+        aligned.putShort((short) alignment);
+        return aligned.array();
+    }
+
+    /**
+     * Helper method to build parameters that re-create an existing entry: same name, timestamp,
+     * comment, compression method and encryption.
+     */
+    private static ZipParameters parametersFromHeader(FileHeader fileHeader) {
+        ZipParameters zipParameters = new ZipParameters();
+        zipParameters.setFileNameInZip(fileHeader.getFileName());
+        zipParameters.setLastModifiedFileTime(fileHeader.getLastModifiedTimeEpoch());
+        zipParameters.setFileComment(fileHeader.getFileComment());
+        zipParameters.setEntrySize(fileHeader.getUncompressedSize());
+
+        CompressionMethod compressionMethod = fileHeader.getCompressionMethod();
+        if (fileHeader.isEncrypted()) {
+            zipParameters.setEncryptFiles(true);
+            zipParameters.setEncryptionMethod(fileHeader.getEncryptionMethod());
+
+            AESExtraDataRecord aesExtraDataRecord = fileHeader.getAesExtraDataRecord();
+            if (aesExtraDataRecord != null) {
+                zipParameters.setAesKeyStrength(aesExtraDataRecord.getAesKeyStrength());
+                zipParameters.setAesVersion(aesExtraDataRecord.getAesVersion());
+                compressionMethod = aesExtraDataRecord.getCompressionMethod();
+            }
+        }
+        zipParameters.setCompressionMethod(compressionMethod);
+
+        return zipParameters;
+    }
+
+    /**
+     * Convert compression level integer to enum.
+     */
+    private static CompressionLevel getCompressionLevel(int level) {
+        switch (level) {
+            case COMPRESSION_LEVEL_NONE: return CompressionLevel.NO_COMPRESSION;
+            case COMPRESSION_LEVEL_FASTEST: return CompressionLevel.FASTEST;
+            case COMPRESSION_LEVEL_NORMAL: return CompressionLevel.NORMAL;
+            case COMPRESSION_LEVEL_MAXIMUM: return CompressionLevel.MAXIMUM;
+            default: return CompressionLevel.NORMAL;
+        }
+    }
+    
+    /**
+     * Convert compression method integer to enum.
+     */
+    private static CompressionMethod getCompressionMethod(int method) {
+        switch (method) {
+            case COMPRESSION_STORE: return CompressionMethod.STORE;
+            case COMPRESSION_DEFLATE: return CompressionMethod.DEFLATE;
+            default: return CompressionMethod.DEFLATE;
+        }
+    }
+    
+    /**
+     * Convert encryption method integer to enum.
+     */
+    private static EncryptionMethod getEncryptionMethod(int method) {
+        switch (method) {
+            case ENCRYPTION_NONE: return EncryptionMethod.NONE;
+            case ENCRYPTION_STANDARD: return EncryptionMethod.ZIP_STANDARD;
+            case ENCRYPTION_AES_128: return EncryptionMethod.AES;
+            case ENCRYPTION_AES_256: return EncryptionMethod.AES;
+            default: return EncryptionMethod.NONE;
+        }
+    }
+    
+    /**
+     * Convert AES key strength integer to enum.
+     */
+    private static AesKeyStrength getAesKeyStrength(int strength) {
+        switch (strength) {
+            case AES_KEY_STRENGTH_128: return AesKeyStrength.KEY_STRENGTH_128;
+            case AES_KEY_STRENGTH_192: return AesKeyStrength.KEY_STRENGTH_192;
+            case AES_KEY_STRENGTH_256: return AesKeyStrength.KEY_STRENGTH_256;
+            default: return AesKeyStrength.KEY_STRENGTH_256;
+        }
+    }
+
+    // ========== Parameters ==========
+
+    /**
+     * Creates a reusable parameter set for add operations.
+     */
+    @CEntryPoint(name = "zip4j_create_parameters")
+    public static int createParameters(IsolateThread thread, int compressionLevel, int compressionMethod,
+                                       int encryptionMethod, int aesKeyStrength, CCharPointer password,
+                                       CLongPointer paramsHandle) {
+        forgetLastException();
+        try {
+            if (paramsHandle.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipParameters zipParameters = new ZipParameters();
+            zipParameters.setCompressionLevel(getCompressionLevel(compressionLevel));
+            zipParameters.setCompressionMethod(getCompressionMethod(compressionMethod));
+            zipParameters.setEncryptionMethod(getEncryptionMethod(encryptionMethod));
+
+            if (encryptionMethod == ENCRYPTION_AES_128 || encryptionMethod == ENCRYPTION_AES_256) {
+                zipParameters.setAesKeyStrength(getAesKeyStrength(aesKeyStrength));
+            }
+
+            long handle = handleCounter.getAndIncrement();
+
+            char[] pass = readPassword(password);
+            if (pass != null && pass.length > 0) {
+                zipParameters.setEncryptFiles(true);
+                parameterPasswords.put(handle, pass);
+            }
+
+            parameterSets.put(handle, zipParameters);
+            paramsHandle.write(handle);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(0, e);
+        }
+    }
+
+    /**
+     * Releases a parameter set.
+     */
+    @CEntryPoint(name = "zip4j_release_parameters")
+    public static int releaseParameters(IsolateThread thread, long paramsHandle) {
+        try {
+            ZipParameters zipParameters = parameterSets.remove(paramsHandle);
+            wipe(parameterPasswords.remove(paramsHandle));
+            if (zipParameters == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            lastErrors.remove(paramsHandle);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(paramsHandle, e);
+        }
+    }
+
+    /**
+     * Sets whether the root folder itself is included when adding a directory.
+     */
+    @CEntryPoint(name = "zip4j_parameters_set_include_root_folder")
+    public static int setParametersIncludeRootFolder(IsolateThread thread, long paramsHandle, int includeRootFolder) {
+        forgetLastException();
+        try {
+            ZipParameters zipParameters = parameterSets.get(paramsHandle);
+            if (zipParameters == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            zipParameters.setIncludeRootFolder(includeRootFolder != 0);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(paramsHandle, e);
+        }
+    }
+
+    /**
+     * Sets whether hidden files and hidden folders are read when adding a directory.
+     */
+    @CEntryPoint(name = "zip4j_parameters_set_read_hidden")
+    public static int setParametersReadHidden(IsolateThread thread, long paramsHandle, int readHiddenFiles, int readHiddenFolders) {
+        forgetLastException();
+        try {
+            ZipParameters zipParameters = parameterSets.get(paramsHandle);
+            if (zipParameters == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            zipParameters.setReadHiddenFiles(readHiddenFiles != 0);
+            zipParameters.setReadHiddenFolders(readHiddenFolders != 0);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(paramsHandle, e);
+        }
+    }
+
+    /**
+     * Sets whether symbolic links are stored as link entries instead of the files they point to.
+     */
+    @CEntryPoint(name = "zip4j_parameters_set_symbolic_link")
+    public static int setParametersSymbolicLink(IsolateThread thread, long paramsHandle, int storeLink) {
+        forgetLastException();
+        try {
+            ZipParameters zipParameters = parameterSets.get(paramsHandle);
+            if (zipParameters == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            zipParameters.setSymbolicLinkAction(storeLink != 0
+                    ? ZipParameters.SymbolicLinkAction.INCLUDE_LINK_ONLY
+                    : ZipParameters.SymbolicLinkAction.INCLUDE_LINKED_FILE_ONLY);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(paramsHandle, e);
+        }
+    }
+
+    /**
+     * Sets the virtual root folder under which files added from disk are placed.
+     */
+    @CEntryPoint(name = "zip4j_parameters_set_root_folder_name")
+    public static int setParametersRootFolderName(IsolateThread thread, long paramsHandle, CCharPointer rootFolderName) {
+        forgetLastException();
+        try {
+            ZipParameters zipParameters = parameterSets.get(paramsHandle);
+            if (zipParameters == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String name = !rootFolderName.equal(WordFactory.nullPointer()) ? CTypeConversion.toJavaString(rootFolderName) : null;
+            zipParameters.setRootFolderNameInZip(name);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(paramsHandle, e);
+        }
+    }
+
+    /**
+     * Sets the name under which a file added from disk is stored in the zip.
+     */
+    @CEntryPoint(name = "zip4j_parameters_set_file_name_in_zip")
+    public static int setParametersFileNameInZip(IsolateThread thread, long paramsHandle, CCharPointer fileNameInZip) {
+        forgetLastException();
+        try {
+            ZipParameters zipParameters = parameterSets.get(paramsHandle);
+            if (zipParameters == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String name = !fileNameInZip.equal(WordFactory.nullPointer()) ? CTypeConversion.toJavaString(fileNameInZip) : null;
+            zipParameters.setFileNameInZip(name);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(paramsHandle, e);
+        }
+    }
+
+    /**
+     * Sets the folder that names of files added from disk are computed relative to.
+     */
+    @CEntryPoint(name = "zip4j_parameters_set_default_folder_path")
+    public static int setParametersDefaultFolderPath(IsolateThread thread, long paramsHandle, CCharPointer defaultFolderPath) {
+        forgetLastException();
+        try {
+            ZipParameters zipParameters = parameterSets.get(paramsHandle);
+            if (zipParameters == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String path = !defaultFolderPath.equal(WordFactory.nullPointer()) ? CTypeConversion.toJavaString(defaultFolderPath) : null;
+            zipParameters.setDefaultFolderPath(path);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(paramsHandle, e);
+        }
+    }
+
+    /**
+     * Sets the last modification time, in milliseconds since the epoch, stored for added entries.
+     */
+    @CEntryPoint(name = "zip4j_parameters_set_last_modified_time")
+    public static int setParametersLastModifiedTime(IsolateThread thread, long paramsHandle, long lastModifiedTime) {
+        forgetLastException();
+        try {
+            ZipParameters zipParameters = parameterSets.get(paramsHandle);
+            if (zipParameters == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            zipParameters.setLastModifiedFileTime(lastModifiedTime);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(paramsHandle, e);
+        }
+    }
+
+    /**
+     * Sets the precomputed CRC32 of the data being added.
+     */
+    @CEntryPoint(name = "zip4j_parameters_set_entry_crc")
+    public static int setParametersEntryCrc(IsolateThread thread, long paramsHandle, long crc) {
+        forgetLastException();
+        try {
+            ZipParameters zipParameters = parameterSets.get(paramsHandle);
+            if (zipParameters == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            zipParameters.setEntryCRC(crc);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(paramsHandle, e);
+        }
+    }
+
+    /**
+     * Sets the comment stored for added entries.
+     */
+    @CEntryPoint(name = "zip4j_parameters_set_file_comment")
+    public static int setParametersFileComment(IsolateThread thread, long paramsHandle, CCharPointer fileComment) {
+        forgetLastException();
+        try {
+            ZipParameters zipParameters = parameterSets.get(paramsHandle);
+            if (zipParameters == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String comment = !fileComment.equal(WordFactory.nullPointer()) ? CTypeConversion.toJavaString(fileComment) : null;
+            zipParameters.setFileComment(comment);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(paramsHandle, e);
+        }
+    }
+
+    // ========== ZipFile Management ==========
+
+    /**
+     * Creates a new ZipFile instance.
+     */
+    @CEntryPoint(name = "zip4j_create")
+    public static int createZipFile(IsolateThread thread, CCharPointer filePath, CLongPointer zipHandle) {
+        forgetLastException();
+        try {
+            if (filePath.equal(WordFactory.nullPointer()) || zipHandle.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String path = CTypeConversion.toJavaString(filePath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            ZipFile zipFile = openZipFile(path, null);
+            long handle = handleCounter.getAndIncrement();
+            zipFiles.put(handle, zipFile);
+            lastErrors.remove(handle);
+
+            zipHandle.write(handle);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(0, e);
+        }
+    }
+
+    /**
+     * Creates a new ZipFile instance with password.
+     */
+    @CEntryPoint(name = "zip4j_create_with_password")
+    public static int createZipFileWithPassword(IsolateThread thread, CCharPointer filePath,
+                                               CCharPointer password, CLongPointer zipHandle) {
+        forgetLastException();
+        try {
+            if (filePath.equal(WordFactory.nullPointer()) || zipHandle.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String path = CTypeConversion.toJavaString(filePath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            char[] pass = readPassword(password);
+            ZipFile zipFile = openZipFile(path, pass);
+            long handle = handleCounter.getAndIncrement();
+            zipFiles.put(handle, zipFile);
+            if (pass != null) {
+                zipPasswords.put(handle, pass);
+            }
+            lastErrors.remove(handle);
+
+            zipHandle.write(handle);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(0, e);
+        }
+    }
+
+    /**
+     * Creates a new archive whose first entry is stored uncompressed and unencrypted, as container
+     * formats such as EPUB/OCF require of their mimetype entry. Entries added later are appended
+     * after it. The archive must not exist yet.
+     */
+    @CEntryPoint(name = "zip4j_create_container")
+    public static int createContainer(IsolateThread thread, CCharPointer filePath, CCharPointer entryName,
+                                      CCharPointer data, int dataLength, CLongPointer zipHandle) {
+        forgetLastException();
+        try {
+            if (filePath.equal(WordFactory.nullPointer()) || entryName.equal(WordFactory.nullPointer())
+                    || zipHandle.equal(WordFactory.nullPointer())
+                    || (dataLength > 0 && data.equal(WordFactory.nullPointer()))) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String path = CTypeConversion.toJavaString(filePath);
+            String name = CTypeConversion.toJavaString(entryName);
+            if (path == null || path.trim().isEmpty() || name == null || name.isEmpty() || dataLength < 0) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            File file = new File(path);
+            if (file.exists()) {
+                throw new ZipException("archive already exists: " + path);
+            }
+
+            byte[] dataBytes = new byte[dataLength];
+            for (int i = 0; i < dataLength; i++) {
+                dataBytes[i] = data.read(i);
+            }
+
+            writeContainerArchive(file, name, dataBytes);
+            zipHandle.write(registerZipFile(openZipFile(file, null), null));
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(0, e);
+        }
+    }
+
+    /**
+     * Sets or changes the password for an existing ZipFile.
+     */
+    @CEntryPoint(name = "zip4j_set_password")
+    public static int setPassword(IsolateThread thread, long zipHandle, CCharPointer password) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            char[] pass = readPassword(password);
+            zipFile.setPassword(pass);
+            wipe(pass != null ? zipPasswords.put(zipHandle, pass) : zipPasswords.remove(zipHandle));
+
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Encodes a UTF-8 password in another charset, for archives whose password was encoded in a
+     * legacy codepage. Fails with ERROR_INVALID_PARAMETER if the charset is unknown or cannot
+     * represent every character. The encoded bytes are not null-terminated.
+     */
+    @CEntryPoint(name = "zip4j_encode_password")
+    public static int encodePassword(IsolateThread thread, CCharPointer password, CCharPointer charsetName,
+                                     CCharPointer buffer, int bufferSize, CIntPointer encodedLength) {
+        forgetLastException();
+        char[] bytes = null;
+        CharBuffer chars = null;
+        ByteBuffer encoded = null;
+        try {
+            if (password.equal(WordFactory.nullPointer()) || charsetName.equal(WordFactory.nullPointer())
+                    || buffer.equal(WordFactory.nullPointer()) || encodedLength.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String name = CTypeConversion.toJavaString(charsetName);
+            if (!Charset.isSupported(name)) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            bytes = readPassword(password);
+            ByteBuffer utf8 = ByteBuffer.allocate(bytes.length);
+            for (char b : bytes) {
+                utf8.put((byte) b);
+            }
+            utf8.flip();
+            chars = StandardCharsets.UTF_8.newDecoder().decode(utf8);
+            Arrays.fill(utf8.array(), (byte) 0);
+            encoded = Charset.forName(name).newEncoder().encode(chars);
+
+            encodedLength.write(encoded.remaining());
+            if (encoded.remaining() > bufferSize) {
+                return ERROR_BUFFER_TOO_SMALL;
+            }
+
+            for (int i = 0; encoded.hasRemaining(); i++) {
+                buffer.write(i, encoded.get());
+            }
+            return SUCCESS;
+        } catch (CharacterCodingException | IllegalArgumentException e) {
+            return ERROR_INVALID_PARAMETER;
+        } catch (Throwable e) {
+            return handleException(0, e);
+        } finally {
+            wipe(bytes);
+            if (chars != null) {
+                Arrays.fill(chars.array(), '\0');
+            }
+            if (encoded != null) {
+                Arrays.fill(encoded.array(), (byte) 0);
+            }
+        }
+    }
+
+    /**
+     * Closes and releases a ZipFile instance.
+     */
+    @CEntryPoint(name = "zip4j_close")
+    public static int closeZipFile(IsolateThread thread, long zipHandle) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.remove(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            wipe(zipPasswords.remove(zipHandle));
+            lastErrors.remove(zipHandle);
+            nameForms.remove(zipHandle);
+            boolean staged = stagedTargets.remove(zipHandle) != null;
+            try {
+                zipFile.close();
+            } finally {
+                try {
+                    if (staged) {
+                        Files.deleteIfExists(zipFile.getFile().toPath());
+                    }
+                } finally {
+                    releaseLock(archiveLocks.remove(zipHandle));
+                }
+            }
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Sets the Unicode normalization form used to match entry names in lookups and to name
+     * extracted files, or NAME_NORMALIZATION_NONE to use names exactly as stored.
+     */
+    @CEntryPoint(name = "zip4j_set_name_normalization")
+    public static int setNameNormalization(IsolateThread thread, long zipHandle, int form) {
+        forgetLastException();
+        try {
+            if (!zipFiles.containsKey(zipHandle)) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            switch (form) {
+                case NAME_NORMALIZATION_NONE:
+                    nameForms.remove(zipHandle);
+                    break;
+                case NAME_NORMALIZATION_NFC:
+                    nameForms.put(zipHandle, Normalizer.Form.NFC);
+                    break;
+                case NAME_NORMALIZATION_NFD:
+                    nameForms.put(zipHandle, Normalizer.Form.NFD);
+                    break;
+                default:
+                    return ERROR_INVALID_PARAMETER;
+            }
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Normalizes names the way lookups and extraction of the zip file do, to the form set with
+     * zip4j_set_name_normalization. The names are read as consecutive null-terminated strings and
+     * written back the same way, in the same order. Writes the required size and returns
+     * ERROR_BUFFER_TOO_SMALL if the buffer can't hold them.
+     */
+    @CEntryPoint(name = "zip4j_normalize_names")
+    public static int normalizeNames(IsolateThread thread, long zipHandle, CCharPointer names, int namesLength,
+                                     CCharPointer buffer, int bufferSize, CIntPointer length) {
+        forgetLastException();
+        try {
+            if (names.equal(WordFactory.nullPointer()) || buffer.equal(WordFactory.nullPointer())
+                    || length.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            if (!zipFiles.containsKey(zipHandle)) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            Normalizer.Form form = nameForms.get(zipHandle);
+            List<byte[]> normalized = new ArrayList<>();
+            int total = 0;
+            for (String name : readStringList(names, namesLength)) {
+                byte[] bytes = (form != null ? Normalizer.normalize(name, form) : name).getBytes(StandardCharsets.UTF_8);
+                normalized.add(bytes);
+                total += bytes.length + 1;
+            }
+
+            length.write(total);
+            if (total > bufferSize) {
+                return ERROR_BUFFER_TOO_SMALL;
+            }
+
+            int offset = 0;
+            for (byte[] name : normalized) {
+                for (byte b : name) {
+                    buffer.write(offset++, b);
+                }
+                buffer.write(offset++, (byte) 0);
+            }
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Checks if the zip file is valid.
+     */
+    @CEntryPoint(name = "zip4j_is_valid")
+    public static int isValidZipFile(IsolateThread thread, long zipHandle, CIntPointer isValid) {
+        forgetLastException();
+        try {
+            if (isValid.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            isValid.write(zipFile.isValidZipFile() ? 1 : 0);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Checks if the zip file is encrypted.
+     */
+    @CEntryPoint(name = "zip4j_is_encrypted")
+    public static int isEncrypted(IsolateThread thread, long zipHandle, CIntPointer isEncrypted) {
+        forgetLastException();
+        try {
+            if (isEncrypted.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            isEncrypted.write(zipFile.isEncrypted() ? 1 : 0);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Checks if the zip file is split archive.
+     */
+    @CEntryPoint(name = "zip4j_is_split_archive")
+    public static int isSplitArchive(IsolateThread thread, long zipHandle, CIntPointer isSplit) {
+        forgetLastException();
+        try {
+            if (isSplit.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            isSplit.write(zipFile.isSplitArchive() ? 1 : 0);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Gets the size of the zip file on disk, summing all parts of a split archive.
+     */
+    @CEntryPoint(name = "zip4j_get_archive_size")
+    public static int getArchiveSize(IsolateThread thread, long zipHandle, CLongPointer size) {
+        forgetLastException();
+        try {
+            if (size.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            long total = 0;
+            if (zipFile.getFile().exists()) {
+                for (File part : zipFile.getSplitZipFiles()) {
+                    total += part.length();
+                }
+            }
+            size.write(total);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Gets the file path of the zip file.
+     */
+    @CEntryPoint(name = "zip4j_get_file_path")
+    public static int getFilePath(IsolateThread thread, long zipHandle, CCharPointer buffer,
+                                 int bufferSize, CIntPointer pathLength) {
+        forgetLastException();
+        try {
+            if (buffer.equal(WordFactory.nullPointer()) || pathLength.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String path = zipFile.getFile().getAbsolutePath();
+            return copyStringToBuffer(path, buffer, bufferSize, pathLength);
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Gets the comment of the zip file.
+     */
+    @CEntryPoint(name = "zip4j_get_comment")
+    public static int getComment(IsolateThread thread, long zipHandle, CCharPointer buffer,
+                                int bufferSize, CIntPointer commentLength) {
+        forgetLastException();
+        try {
+            if (buffer.equal(WordFactory.nullPointer()) || commentLength.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String comment = zipFile.getComment();
+            if (comment == null) {
+                comment = "";
+            }
+            return copyStringToBuffer(comment, buffer, bufferSize, commentLength);
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Sets the comment of the zip file.
      */
     @CEntryPoint(name = "zip4j_set_comment")
     public static int setComment(IsolateThread thread, long zipHandle, CCharPointer comment) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String commentStr = !comment.equal(WordFactory.nullPointer()) ?
+                CTypeConversion.toJavaString(comment) : null;
+            zipFile.setComment(commentStr);
+
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    // ========== ZipEntry Management ==========
+
+    /**
+     * Gets the number of entries in the zip file.
+     */
+    @CEntryPoint(name = "zip4j_get_entry_count")
+    public static int getEntryCount(IsolateThread thread, long zipHandle, CLongPointer entryCount) {
+        forgetLastException();
+        try {
+            if (entryCount.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            List<FileHeader> fileHeaders = zipFile.getFileHeaders();
+            entryCount.write(fileHeaders != null ? fileHeaders.size() : 0);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Gets a ZipEntry handle by index.
+     */
+    @CEntryPoint(name = "zip4j_get_entry_by_index")
+    public static int getEntryByIndex(IsolateThread thread, long zipHandle, long index, CLongPointer entryHandle) {
+        forgetLastException();
+        try {
+            if (entryHandle.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            List<FileHeader> fileHeaders = zipFile.getFileHeaders();
+            if (fileHeaders == null || index < 0 || index >= fileHeaders.size()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            FileHeader fileHeader = fileHeaders.get((int) index);
+            long handle = handleCounter.getAndIncrement();
+            zipEntries.put(handle, fileHeader);
+            entryZipFiles.put(handle, zipFile);
+
+            entryHandle.write(handle);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Checks whether an entry with the given name exists, without creating an entry handle.
+     */
+    @CEntryPoint(name = "zip4j_contains_entry")
+    public static int containsEntry(IsolateThread thread, long zipHandle, CCharPointer entryName, CIntPointer found) {
+        forgetLastException();
+        try {
+            if (entryName.equal(WordFactory.nullPointer()) || found.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String name = CTypeConversion.toJavaString(entryName);
+            if (name == null || name.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            found.write(findFileHeader(zipHandle, zipFile, name) != null ? 1 : 0);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Writes the metadata of every entry into the buffer in one call, as consecutive little-endian
+     * records: name length (int), name (UTF-8), size and compressed size (long), CRC, compression
+     * method and encryption method (int), directory and encrypted flags (byte), DOS time (int) and
+     * general purpose flags (short). Writes the required size and returns ERROR_BUFFER_TOO_SMALL
+     * if the buffer can't hold them.
+     */
+    @CEntryPoint(name = "zip4j_list_entries")
+    public static int listEntries(IsolateThread thread, long zipHandle, CCharPointer buffer, int bufferSize,
+                                  CIntPointer length) {
+        forgetLastException();
+        try {
+            if (buffer.equal(WordFactory.nullPointer()) || length.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            List<FileHeader> fileHeaders = zipFile.getFileHeaders();
+            List<byte[]> names = new ArrayList<>();
+            int total = 0;
+            for (FileHeader fileHeader : fileHeaders) {
+                byte[] name = fileHeader.getFileName().getBytes(StandardCharsets.UTF_8);
+                names.add(name);
+                total += ENTRY_RECORD_SIZE + name.length;
+            }
+
+            length.write(total);
+            if (total > bufferSize) {
+                return ERROR_BUFFER_TOO_SMALL;
+            }
+
+            ByteBuffer records = ByteBuffer.allocate(total).order(ByteOrder.LITTLE_ENDIAN);
+            for (int i = 0; i < fileHeaders.size(); i++) {
+                FileHeader fileHeader = fileHeaders.get(i);
+                records.putInt(names.get(i).length);
+                records.put(names.get(i));
+                records.putLong(fileHeader.getUncompressedSize());
+                records.putLong(fileHeader.getCompressedSize());
+                records.putInt((int) fileHeader.getCrc());
+                records.putInt(compressionMethodCode(fileHeader));
+                records.putInt(encryptionMethodCode(fileHeader));
+                records.put((byte) (fileHeader.isDirectory() ? 1 : 0));
+                records.put((byte) (fileHeader.isEncrypted() ? 1 : 0));
+                records.putInt((int) fileHeader.getLastModifiedTime());
+                records.putShort((short) generalPurposeFlags(fileHeader));
+            }
+
+            byte[] bytes = records.array();
+            for (int i = 0; i < bytes.length; i++) {
+                buffer.write(i, bytes[i]);
+            }
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Writes the names of all entries into the buffer, each followed by a null terminator. Writes
+     * the required size and returns ERROR_BUFFER_TOO_SMALL if the buffer can't hold them.
+     */
+    @CEntryPoint(name = "zip4j_list_entry_names")
+    public static int listEntryNames(IsolateThread thread, long zipHandle, CCharPointer buffer, int bufferSize,
+                                     CIntPointer length) {
+        forgetLastException();
+        try {
+            if (buffer.equal(WordFactory.nullPointer()) || length.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            List<byte[]> names = new ArrayList<>();
+            int total = 0;
+            for (FileHeader fileHeader : zipFile.getFileHeaders()) {
+                byte[] name = fileHeader.getFileName().getBytes(StandardCharsets.UTF_8);
+                names.add(name);
+                total += name.length + 1;
+            }
+
+            length.write(total);
+            if (total > bufferSize) {
+                return ERROR_BUFFER_TOO_SMALL;
+            }
+
+            int offset = 0;
+            for (byte[] name : names) {
+                for (byte b : name) {
+                    buffer.write(offset++, b);
+                }
+                buffer.write(offset++, (byte) 0);
+            }
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Gets a ZipEntry handle by name.
+     */
+    @CEntryPoint(name = "zip4j_get_entry_by_name")
+    public static int getEntryByName(IsolateThread thread, long zipHandle, CCharPointer entryName, CLongPointer entryHandle) {
+        forgetLastException();
+        try {
+            if (entryName.equal(WordFactory.nullPointer()) || entryHandle.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String name = CTypeConversion.toJavaString(entryName);
+            if (name == null || name.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            FileHeader fileHeader = findFileHeader(zipHandle, zipFile, name);
+            if (fileHeader == null) {
+                return ERROR_ENTRY_NOT_FOUND;
+            }
+
+            long handle = handleCounter.getAndIncrement();
+            zipEntries.put(handle, fileHeader);
+            entryZipFiles.put(handle, zipFile);
+
+            entryHandle.write(handle);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Releases a ZipEntry handle.
+     */
+    @CEntryPoint(name = "zip4j_release_entry")
+    public static int releaseEntry(IsolateThread thread, long entryHandle) {
+        try {
+            FileHeader fileHeader = zipEntries.remove(entryHandle);
+            entryZipFiles.remove(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(entryHandle, e);
+        }
+    }
+
+    /**
+     * Gets the name of a ZipEntry.
+     */
+    @CEntryPoint(name = "zip4j_entry_get_name")
+    public static int getEntryName(IsolateThread thread, long entryHandle, CCharPointer buffer,
+                                  int bufferSize, CIntPointer nameLength) {
+        forgetLastException();
+        try {
+            if (buffer.equal(WordFactory.nullPointer()) || nameLength.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String fileName = fileHeader.getFileName();
+            return copyStringToBuffer(fileName, buffer, bufferSize, nameLength);
+        } catch (Throwable e) {
+            return handleException(entryHandle, e);
+        }
+    }
+
+    /**
+     * Gets the uncompressed size of a ZipEntry.
+     */
+    @CEntryPoint(name = "zip4j_entry_get_size")
+    public static int getEntrySize(IsolateThread thread, long entryHandle, CLongPointer size) {
+        forgetLastException();
+        try {
+            if (size.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            size.write(fileHeader.getUncompressedSize());
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(entryHandle, e);
+        }
+    }
+
+    /**
+     * Gets the compressed size of a ZipEntry.
+     */
+    @CEntryPoint(name = "zip4j_entry_get_compressed_size")
+    public static int getEntryCompressedSize(IsolateThread thread, long entryHandle, CLongPointer compressedSize) {
+        forgetLastException();
+        try {
+            if (compressedSize.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            compressedSize.write(fileHeader.getCompressedSize());
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(entryHandle, e);
+        }
+    }
+
+    /**
+     * Checks if a ZipEntry is a directory.
+     */
+    @CEntryPoint(name = "zip4j_entry_is_directory")
+    public static int isEntryDirectory(IsolateThread thread, long entryHandle, CIntPointer isDirectory) {
+        forgetLastException();
+        try {
+            if (isDirectory.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            isDirectory.write(fileHeader.isDirectory() ? 1 : 0);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(entryHandle, e);
+        }
+    }
+
+    /**
+     * Checks if a ZipEntry is encrypted.
+     */
+    @CEntryPoint(name = "zip4j_entry_is_encrypted")
+    public static int isEntryEncrypted(IsolateThread thread, long entryHandle, CIntPointer isEncrypted) {
+        forgetLastException();
+        try {
+            if (isEncrypted.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            isEncrypted.write(fileHeader.isEncrypted() ? 1 : 0);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(entryHandle, e);
+        }
+    }
+
+    /**
+     * Gets the CRC32 checksum of a ZipEntry.
+     */
+    @CEntryPoint(name = "zip4j_entry_get_crc")
+    public static int getEntryCrc(IsolateThread thread, long entryHandle, CLongPointer crc) {
+        forgetLastException();
+        try {
+            if (crc.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            crc.write(fileHeader.getCrc());
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(entryHandle, e);
+        }
+    }
+
+    /**
+     * Gets the version-needed-to-extract field of a ZipEntry.
+     */
+    @CEntryPoint(name = "zip4j_entry_get_version_needed")
+    public static int getEntryVersionNeeded(IsolateThread thread, long entryHandle, CIntPointer version) {
+        forgetLastException();
+        try {
+            if (version.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            version.write(fileHeader.getVersionNeededToExtract());
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(entryHandle, e);
+        }
+    }
+
+    /**
+     * Gets the version-made-by field of a ZipEntry.
+     */
+    @CEntryPoint(name = "zip4j_entry_get_version_made_by")
+    public static int getEntryVersionMadeBy(IsolateThread thread, long entryHandle, CIntPointer version) {
+        forgetLastException();
+        try {
+            if (version.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            version.write(fileHeader.getVersionMadeBy());
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(entryHandle, e);
+        }
+    }
+
+    /**
+     * Gets where a ZipEntry's local header and compressed data start within the archive file. The
+     * data offset comes from the local header, whose extra field may differ from the central
+     * directory's. For split archives the offsets are within the part holding the local header.
+     */
+    @CEntryPoint(name = "zip4j_entry_get_offsets")
+    public static int getEntryOffsets(IsolateThread thread, long entryHandle, CLongPointer headerOffset,
+                                      CLongPointer dataOffset) {
+        forgetLastException();
+        try {
+            if (headerOffset.equal(WordFactory.nullPointer()) || dataOffset.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            ZipFile zipFile = entryZipFiles.get(entryHandle);
+            if (fileHeader == null || zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            headerOffset.write(fileHeader.getOffsetLocalHeader());
+            dataOffset.write(localDataOffset(entryArchivePart(zipFile, fileHeader), fileHeader));
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(entryHandle, e);
+        }
+    }
+
+    /**
+     * Gets the general purpose bit flags of a ZipEntry.
+     */
+    @CEntryPoint(name = "zip4j_entry_get_general_purpose_flags")
+    public static int getEntryGeneralPurposeFlags(IsolateThread thread, long entryHandle, CIntPointer flags) {
+        forgetLastException();
+        try {
+            if (flags.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            flags.write(generalPurposeFlags(fileHeader));
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(entryHandle, e);
+        }
+    }
+
+    /**
+     * Gets the last modified time of a ZipEntry (DOS time format).
+     */
+    @CEntryPoint(name = "zip4j_entry_get_last_modified_time")
+    public static int getEntryLastModifiedTime(IsolateThread thread, long entryHandle, CLongPointer lastModifiedTime) {
+        forgetLastException();
+        try {
+            if (lastModifiedTime.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            lastModifiedTime.write(fileHeader.getLastModifiedTime());
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(entryHandle, e);
+        }
+    }
+
+    // ========== File Addition Operations ==========
+
+    /**
+     * Adds a file to the zip archive with default parameters.
+     */
+    @CEntryPoint(name = "zip4j_add_file")
+    public static int addFile(IsolateThread thread, long zipHandle, CCharPointer filePath) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (filePath.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String path = CTypeConversion.toJavaString(filePath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            zipFile.addFile(path);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Adds a file to the zip archive with custom parameters.
+     */
+    @CEntryPoint(name = "zip4j_add_file_with_params")
+    public static int addFileWithParams(IsolateThread thread, long zipHandle, CCharPointer filePath,
+                                       int compressionLevel, int compressionMethod, int encryptionMethod,
+                                       int aesKeyStrength, CCharPointer password) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (filePath.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String path = CTypeConversion.toJavaString(filePath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            ZipParameters zipParameters = new ZipParameters();
+            zipParameters.setCompressionLevel(getCompressionLevel(compressionLevel));
+            zipParameters.setCompressionMethod(getCompressionMethod(compressionMethod));
+            zipParameters.setEncryptionMethod(getEncryptionMethod(encryptionMethod));
+
+            if (encryptionMethod == ENCRYPTION_AES_128 || encryptionMethod == ENCRYPTION_AES_256) {
+                zipParameters.setAesKeyStrength(getAesKeyStrength(aesKeyStrength));
+            }
+
+            char[] pass = readPassword(password);
+            if (pass != null && pass.length > 0) {
+                zipParameters.setEncryptFiles(true);
+                zipFile.setPassword(pass);
+            }
+
+            zipFile.addFile(path, zipParameters);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Adds a file to the zip archive using a parameter set.
+     */
+    @CEntryPoint(name = "zip4j_add_file_with_parameters")
+    public static int addFileWithParameters(IsolateThread thread, long zipHandle, CCharPointer filePath, long paramsHandle) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (filePath.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String path = CTypeConversion.toJavaString(filePath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            ZipParameters zipParameters = prepareParameters(zipFile, paramsHandle);
+            if (zipParameters == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            zipFile.addFile(path, zipParameters);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Adds multiple files to the zip archive in a single operation.
+     * The buffer holds consecutive null-terminated file paths; names in the zip
+     * are computed relative to the base path when one is given.
+     */
+    @CEntryPoint(name = "zip4j_add_files")
+    public static int addFiles(IsolateThread thread, long zipHandle, CCharPointer filePaths, int filePathsLength,
+                               CCharPointer basePath, long paramsHandle) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (filePaths.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipParameters zipParameters = prepareParameters(zipFile, paramsHandle);
+            if (zipParameters == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (!basePath.equal(WordFactory.nullPointer())) {
+                String base = CTypeConversion.toJavaString(basePath);
+                if (base != null && !base.trim().isEmpty()) {
+                    zipParameters.setDefaultFolderPath(base);
+                }
+            }
+
+            List<File> files = new ArrayList<>();
+            for (String path : readStringList(filePaths, filePathsLength)) {
+                files.add(new File(path));
+            }
+
+            if (!files.isEmpty()) {
+                zipFile.addFiles(files, zipParameters);
+            }
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Adds a set of files and removes a set of entries in one go, so an archive can be brought in
+     * line with a directory with a single rewrite. Files are added first, replacing entries of the
+     * same name, so a failed add never leaves entries removed; the names to remove should only
+     * list entries that are not being replaced.
+     */
+    @CEntryPoint(name = "zip4j_sync_files")
+    public static int syncFiles(IsolateThread thread, long zipHandle, CCharPointer removeNames, int removeNamesLength,
+                                CCharPointer filePaths, int filePathsLength, CCharPointer basePath, long paramsHandle) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (removeNames.equal(WordFactory.nullPointer()) || filePaths.equal(WordFactory.nullPointer())
+                    || basePath.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipParameters zipParameters = prepareParameters(zipFile, paramsHandle);
+            if (zipParameters == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String base = CTypeConversion.toJavaString(basePath);
+            if (base == null || base.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+            zipParameters.setDefaultFolderPath(base);
+
+            List<String> names = readStringList(removeNames, removeNamesLength);
+            List<File> files = new ArrayList<>();
+            for (String path : readStringList(filePaths, filePathsLength)) {
+                files.add(new File(path));
+            }
+
+            if (!files.isEmpty()) {
+                zipParameters.setOverrideExistingFilesInZip(true);
+                zipFile.addFiles(files, zipParameters);
+            }
+
+            if (!names.isEmpty()) {
+                zipFile.removeFiles(names);
+            }
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Adds a directory to the zip archive.
+     */
+    @CEntryPoint(name = "zip4j_add_directory")
+    public static int addDirectory(IsolateThread thread, long zipHandle, CCharPointer dirPath) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (dirPath.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String path = CTypeConversion.toJavaString(dirPath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            zipFile.addFolder(Paths.get(path).toFile());
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Adds a directory to the zip archive with custom parameters.
+     */
+    @CEntryPoint(name = "zip4j_add_directory_with_params")
+    public static int addDirectoryWithParams(IsolateThread thread, long zipHandle, CCharPointer dirPath,
+                                            int compressionLevel, int compressionMethod, int encryptionMethod,
+                                            int aesKeyStrength, CCharPointer password) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (dirPath.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String path = CTypeConversion.toJavaString(dirPath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            ZipParameters zipParameters = new ZipParameters();
+            zipParameters.setCompressionLevel(getCompressionLevel(compressionLevel));
+            zipParameters.setCompressionMethod(getCompressionMethod(compressionMethod));
+            zipParameters.setEncryptionMethod(getEncryptionMethod(encryptionMethod));
+
+            if (encryptionMethod == ENCRYPTION_AES_128 || encryptionMethod == ENCRYPTION_AES_256) {
+                zipParameters.setAesKeyStrength(getAesKeyStrength(aesKeyStrength));
+            }
+
+            char[] pass = readPassword(password);
+            if (pass != null && pass.length > 0) {
+                zipParameters.setEncryptFiles(true);
+                zipFile.setPassword(pass);
+            }
+
+            zipFile.addFolder(Paths.get(path).toFile(), zipParameters);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Adds a directory to the zip archive using a parameter set.
+     */
+    @CEntryPoint(name = "zip4j_add_directory_with_parameters")
+    public static int addDirectoryWithParameters(IsolateThread thread, long zipHandle, CCharPointer dirPath, long paramsHandle) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (dirPath.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String path = CTypeConversion.toJavaString(dirPath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            ZipParameters zipParameters = prepareParameters(zipFile, paramsHandle);
+            if (zipParameters == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            zipFile.addFolder(Paths.get(path).toFile(), zipParameters);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Sets the Unix permission bits stored for an entry. Regular-file type bits are added if the
+     * mode has none, and the entry is marked as made on Unix so extractors honour the mode.
+     */
+    @CEntryPoint(name = "zip4j_set_entry_unix_mode")
+    public static int setEntryUnixMode(IsolateThread thread, long zipHandle, CCharPointer entryName, int mode) {
+        forgetLastException();
+        try {
+            if (entryName.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String name = CTypeConversion.toJavaString(entryName);
+            FileHeader fileHeader = zipFile.getFileHeader(name);
+            if (fileHeader == null) {
+                return ERROR_ENTRY_NOT_FOUND;
+            }
+
+            if ((mode & 0xF000) == 0) {
+                mode |= 0x8000;
+            }
+
+            byte[] attributes = new byte[4];
+            byte[] existing = fileHeader.getExternalFileAttributes();
+            if (existing != null) {
+                System.arraycopy(existing, 0, attributes, 0, Math.min(existing.length, 2));
+            }
+            attributes[2] = (byte) (mode & 0xFF);
+            attributes[3] = (byte) ((mode >> 8) & 0xFF);
+
+            fileHeader.setExternalFileAttributes(attributes);
+            fileHeader.setVersionMadeBy((3 << 8) | (fileHeader.getVersionMadeBy() & 0xFF));
+
+            rewriteCentralDirectory(zipFile);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    // ========== Extraction Operations ==========
+
+    /**
+     * Extracts all files from the zip archive to a destination directory.
+     */
+    @CEntryPoint(name = "zip4j_extract_all")
+    public static int extractAll(IsolateThread thread, long zipHandle, CCharPointer destPath) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (destPath.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String path = CTypeConversion.toJavaString(destPath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            Normalizer.Form form = nameForms.get(zipHandle);
+            if (form != null) {
+                // zip4j would extract a renamed directory's children under their stored names as well,
+                // so directories are only created and every file is extracted on its own
+                File destination = new File(path);
+                for (FileHeader fileHeader : zipFile.getFileHeaders()) {
+                    if (fileHeader.isDirectory()) {
+                        Files.createDirectories(extractionTarget(destination, fileHeader, form).toPath());
+                    } else {
+                        extractNormalized(zipHandle, zipFile, fileHeader, path);
+                    }
+                }
+            } else {
+                synchronized (zipFile) {
+                    zipFile.extractAll(path);
+                }
+            }
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Extracts all entries to a destination directory using several worker threads. All
+     * directories are created first; then each worker reads the archive through its own ZipFile,
+     * taking the next file entry when it is done with one.
+     */
+    @CEntryPoint(name = "zip4j_extract_all_parallel")
+    public static int extractAllParallel(IsolateThread thread, long zipHandle, CCharPointer destPath, int threads) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (destPath.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String path = CTypeConversion.toJavaString(destPath);
+            if (path == null || path.trim().isEmpty() || threads < 1) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            File destination = new File(path);
+            Normalizer.Form form = nameForms.get(zipHandle);
+            List<FileHeader> fileHeaders = zipFile.getFileHeaders();
+
+            // Create every directory before the workers start, so they never race to create the same one
+            List<Integer> fileIndices = new ArrayList<>();
+            List<String> fileNames = new ArrayList<>();
+            for (int i = 0; i < fileHeaders.size(); i++) {
+                FileHeader fileHeader = fileHeaders.get(i);
+                File target = extractionTarget(destination, fileHeader, form);
+                if (fileHeader.isDirectory()) {
+                    Files.createDirectories(target.toPath());
+                } else {
+                    Files.createDirectories(target.getAbsoluteFile().getParentFile().toPath());
+                    fileIndices.add(i);
+                    fileNames.add(normalizedName(fileHeader, form));
+                }
+            }
+
+            AtomicInteger next = new AtomicInteger();
+            List<Throwable> failures = Collections.synchronizedList(new ArrayList<>());
+
+            List<Thread> workers = new ArrayList<>();
+            for (int t = 0; t < Math.min(threads, fileIndices.size()); t++) {
+                Thread worker = new Thread(() -> {
+                    try (ZipFile reader = openZipFile(zipFile.getFile(), zipPasswords.get(zipHandle))) {
+                        List<FileHeader> readerHeaders = reader.getFileHeaders();
+                        for (int i = next.getAndIncrement(); i < fileIndices.size() && failures.isEmpty(); i = next.getAndIncrement()) {
+                            reader.extractFile(readerHeaders.get(fileIndices.get(i)), path, fileNames.get(i));
+                        }
+                    } catch (Throwable e) {
+                        failures.add(e);
+                    }
+                }, "zip4j-extract-" + t);
+                worker.setDaemon(true);
+                worker.start();
+                workers.add(worker);
+            }
+
+            for (Thread worker : workers) {
+                worker.join();
+            }
+
+            if (!failures.isEmpty()) {
+                return handleException(zipHandle, failures.get(0));
+            }
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Gets the bytes available to this process on the file system holding path. The path
+     * doesn't need to exist yet; its closest existing ancestor is used.
+     */
+    @CEntryPoint(name = "zip4j_get_usable_space")
+    public static int getUsableSpace(IsolateThread thread, long zipHandle, CCharPointer destPath, CLongPointer space) {
+        forgetLastException();
+        try {
+            if (destPath.equal(WordFactory.nullPointer()) || space.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String path = CTypeConversion.toJavaString(destPath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            File existing = new File(path).getAbsoluteFile();
+            while (!existing.exists()) {
+                existing = existing.getParentFile();
+                if (existing == null) {
+                    return ERROR_FILE_NOT_FOUND;
+                }
+            }
+
+            space.write(existing.getUsableSpace());
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Extracts an entry below destPath, computing the SHA-256 of its contents while writing them.
+     * The 32 digest bytes are written to the digest buffer; directories get the digest of no data.
+     */
+    @CEntryPoint(name = "zip4j_extract_entry_with_digest")
+    public static int extractEntryWithDigest(IsolateThread thread, long zipHandle, long entryHandle,
+                                             CCharPointer destPath, CCharPointer digest) {
+        forgetLastException();
+        try {
+            if (destPath.equal(WordFactory.nullPointer()) || digest.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String path = CTypeConversion.toJavaString(destPath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            File destination = new File(path);
+            File target = new File(destination, fileHeader.getFileName());
+            if (!target.getCanonicalPath().startsWith(destination.getCanonicalPath() + File.separator)) {
+                throw new ZipException("illegal file name that breaks out of the target directory: "
+                        + fileHeader.getFileName());
+            }
+
+            MessageDigest messageDigest = MessageDigest.getInstance("SHA-256");
+            if (fileHeader.isDirectory()) {
+                Files.createDirectories(target.toPath());
+            } else {
+                Files.createDirectories(target.getAbsoluteFile().getParentFile().toPath());
+                try (ZipInputStream zipInputStream = zipFile.getInputStream(fileHeader);
+                     OutputStream outputStream = new FileOutputStream(target)) {
+                    byte[] readBuffer = new byte[8192];
+                    int bytesRead;
+                    while ((bytesRead = zipInputStream.read(readBuffer)) != -1) {
+                        messageDigest.update(readBuffer, 0, bytesRead);
+                        outputStream.write(readBuffer, 0, bytesRead);
+                    }
+                }
+                long lastModified = fileHeader.getLastModifiedTimeEpoch();
+                if (lastModified > 0) {
+                    target.setLastModified(lastModified);
+                }
+            }
+
+            byte[] hash = messageDigest.digest();
+            for (int i = 0; i < hash.length; i++) {
+                digest.write(i, hash[i]);
+            }
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Checks the file extracted for an entry by recomputing its CRC32 and comparing it with the
+     * archive's. Writes 0 if the file is missing or differs. Directories, symbolic links and AES
+     * entries that store no CRC (AE-2) always match.
+     */
+    @CEntryPoint(name = "zip4j_verify_extracted_entry")
+    public static int verifyExtractedEntry(IsolateThread thread, long zipHandle, long entryHandle,
+                                           CCharPointer destPath, CIntPointer matches) {
+        forgetLastException();
+        try {
+            if (destPath.equal(WordFactory.nullPointer()) || matches.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            if (!zipFiles.containsKey(zipHandle)) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String path = CTypeConversion.toJavaString(destPath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            Path file = Paths.get(path, fileHeader.getFileName());
+            AESExtraDataRecord aesRecord = fileHeader.getAesExtraDataRecord();
+            if (fileHeader.isDirectory() || Files.isSymbolicLink(file)
+                    || (aesRecord != null && aesRecord.getAesVersion() == AesVersion.TWO)) {
+                matches.write(1);
+                return SUCCESS;
+            }
+
+            if (!Files.isRegularFile(file)) {
+                matches.write(0);
+                return SUCCESS;
+            }
+
+            matches.write(fileCrc(file) == (fileHeader.getCrc() & 0xFFFFFFFFL) ? 1 : 0);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Compares an entry with a file on disk. Writes DIFF_MISSING if there is no such file, or
+     * DIFF_MODIFIED if its size differs or, depending on the detection mode, its CRC32 or
+     * modification time does. Modification times are compared at the two second resolution of the
+     * ZIP format; AES entries that store no CRC (AE-2) are compared by size only in CRC mode.
+     */
+    @CEntryPoint(name = "zip4j_compare_entry_with_file")
+    public static int compareEntryWithFile(IsolateThread thread, long zipHandle, long entryHandle,
+                                           CCharPointer filePath, int detection, CIntPointer status) {
+        forgetLastException();
+        try {
+            if (filePath.equal(WordFactory.nullPointer()) || status.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            if (!zipFiles.containsKey(zipHandle)) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String path = CTypeConversion.toJavaString(filePath);
+            if (path == null || path.trim().isEmpty()
+                    || (detection != CHANGE_DETECTION_CRC && detection != CHANGE_DETECTION_MTIME)) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            Path file = Paths.get(path);
+            if (fileHeader.isDirectory()) {
+                status.write(Files.isDirectory(file) ? DIFF_UNCHANGED : DIFF_MISSING);
+                return SUCCESS;
+            }
+
+            if (!Files.isRegularFile(file)) {
+                status.write(DIFF_MISSING);
+                return SUCCESS;
+            }
+
+            boolean modified;
+            if (Files.size(file) != fileHeader.getUncompressedSize()) {
+                modified = true;
+            } else if (detection == CHANGE_DETECTION_MTIME) {
+                long diskTime = Files.getLastModifiedTime(file).toMillis();
+                modified = Math.abs(diskTime - fileHeader.getLastModifiedTimeEpoch()) >= 2000;
+            } else {
+                AESExtraDataRecord aesRecord = fileHeader.getAesExtraDataRecord();
+                boolean hasCrc = aesRecord == null || aesRecord.getAesVersion() != AesVersion.TWO;
+                modified = hasCrc && fileCrc(file) != (fileHeader.getCrc() & 0xFFFFFFFFL);
+            }
+
+            status.write(modified ? DIFF_MODIFIED : DIFF_UNCHANGED);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Extracts a specific file by name from the zip archive.
+     */
+    @CEntryPoint(name = "zip4j_extract_file")
+    public static int extractFile(IsolateThread thread, long zipHandle, CCharPointer fileName, CCharPointer destPath) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (fileName.equal(WordFactory.nullPointer()) || destPath.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String name = CTypeConversion.toJavaString(fileName);
+            String path = CTypeConversion.toJavaString(destPath);
+
+            if (name == null || name.trim().isEmpty() || path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            FileHeader fileHeader = findFileHeader(zipHandle, zipFile, name);
+            if (fileHeader == null) {
+                return ERROR_ENTRY_NOT_FOUND;
+            }
+
+            extractNormalized(zipHandle, zipFile, fileHeader, path);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Extracts a specific entry using a ZipEntry handle.
+     */
+    @CEntryPoint(name = "zip4j_extract_entry")
+    public static int extractEntry(IsolateThread thread, long zipHandle, long entryHandle, CCharPointer destPath) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (destPath.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String path = CTypeConversion.toJavaString(destPath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            extractNormalized(zipHandle, zipFile, fileHeader, path);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Starts extracting a file entry on a worker thread, writing it under its normalized name
+     * like zip4j_extract_entry. Directory entries are refused; create them directly instead.
+     */
+    @CEntryPoint(name = "zip4j_extract_entry_async")
+    public static int extractEntryAsync(IsolateThread thread, long zipHandle, long entryHandle, CCharPointer destPath,
+                                        CLongPointer monitorHandle) {
+        forgetLastException();
+        try {
+            if (destPath.equal(WordFactory.nullPointer()) || monitorHandle.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            String path = CTypeConversion.toJavaString(destPath);
+            if (path == null || path.trim().isEmpty() || fileHeader.isDirectory()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            synchronized (zipFile) {
+                zipFile.setRunInThread(true);
+                try {
+                    extractNormalized(zipHandle, zipFile, fileHeader, path);
+                } finally {
+                    zipFile.setRunInThread(false);
+                }
+            }
+
+            monitorHandle.write(registerProgressMonitor(zipFile.getProgressMonitor()));
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Extracts a specific entry using a ZipEntry handle, writing it under a different
+     * name relative to the destination directory.
+     */
+    @CEntryPoint(name = "zip4j_extract_entry_as")
+    public static int extractEntryAs(IsolateThread thread, long zipHandle, long entryHandle, CCharPointer destPath,
+                                     CCharPointer newName) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (destPath.equal(WordFactory.nullPointer()) || newName.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String path = CTypeConversion.toJavaString(destPath);
+            String name = CTypeConversion.toJavaString(newName);
+            if (path == null || path.trim().isEmpty() || name == null || name.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            // zip4j refuses to start a task while another one on the same ZipFile is running
+            synchronized (zipFile) {
+                zipFile.extractFile(fileHeader, path, name);
+            }
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    // ========== Modification Operations ==========
+
+    /**
+     * Removes a file from the zip archive by name.
+     */
+    @CEntryPoint(name = "zip4j_remove_file")
+    public static int removeFile(IsolateThread thread, long zipHandle, CCharPointer fileName) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (fileName.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            String name = CTypeConversion.toJavaString(fileName);
+            if (name == null || name.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            zipFile.removeFile(name);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Removes multiple files from the zip archive in a single operation.
+     * The buffer holds consecutive null-terminated entry names.
+     */
+    @CEntryPoint(name = "zip4j_remove_files")
+    public static int removeFiles(IsolateThread thread, long zipHandle, CCharPointer fileNames, int fileNamesLength) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (fileNames.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            List<String> names = readStringList(fileNames, fileNamesLength);
+            if (!names.isEmpty()) {
+                zipFile.removeFiles(names);
+            }
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Removes an entry from the zip archive using a ZipEntry handle.
+     */
+    @CEntryPoint(name = "zip4j_remove_entry")
+    public static int removeEntry(IsolateThread thread, long zipHandle, long entryHandle) {
+        forgetLastException();
+        try {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            zipFile.removeFile(fileHeader);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    // ========== Streaming Operations ==========
+
+    /**
+     * Creates a ZipInputStream for reading entries from a zip file.
+     */
+    @CEntryPoint(name = "zip4j_create_input_stream")
+    public static int createInputStream(IsolateThread thread, long zipHandle, long entryHandle, CLongPointer streamHandle) {
+        forgetLastException();
+        try {
+            if (streamHandle.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            ZipInputStream zipInputStream = zipFile.getInputStream(fileHeader);
+            long handle = handleCounter.getAndIncrement();
+            inputStreams.put(handle, zipInputStream);
+
+            streamHandle.write(handle);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Reads data from a ZipInputStream.
+     */
+    @CEntryPoint(name = "zip4j_stream_read")
+    public static int streamRead(IsolateThread thread, long streamHandle, CCharPointer buffer, int bufferSize, CIntPointer bytesRead) {
+        forgetLastException();
+        try {
+            if (buffer.equal(WordFactory.nullPointer()) || bytesRead.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipInputStream zipInputStream = inputStreams.get(streamHandle);
+            if (zipInputStream == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            byte[] readBuffer = new byte[bufferSize];
+            int read = zipInputStream.read(readBuffer);
+
+            if (read > 0) {
+                for (int i = 0; i < read; i++) {
+                    buffer.write(i, readBuffer[i]);
+                }
+            }
+
+            bytesRead.write(read);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(streamHandle, e);
+        }
+    }
+
+    /**
+     * Closes a ZipInputStream.
+     */
+    @CEntryPoint(name = "zip4j_close_input_stream")
+    public static int closeInputStream(IsolateThread thread, long streamHandle) {
+        try {
+            ZipInputStream zipInputStream = inputStreams.remove(streamHandle);
+            if (zipInputStream == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            zipInputStream.close();
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(streamHandle, e);
+        }
+    }
+
+    // ========== Progress Monitoring ==========
+
+    /**
+     * Gets the progress monitor for a zip file operation.
+     */
+    @CEntryPoint(name = "zip4j_get_progress_monitor")
+    public static int getProgressMonitor(IsolateThread thread, long zipHandle, CLongPointer monitorHandle) {
+        forgetLastException();
+        try {
+            if (monitorHandle.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            ProgressMonitor progressMonitor = zipFile.getProgressMonitor();
+            long handle = handleCounter.getAndIncrement();
+            progressMonitors.put(handle, progressMonitor);
+
+            monitorHandle.write(handle);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Gets the current progress percentage.
+     */
+    @CEntryPoint(name = "zip4j_get_progress_percentage")
+    public static int getProgressPercentage(IsolateThread thread, long monitorHandle, CIntPointer percentage) {
+        forgetLastException();
+        try {
+            if (percentage.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ProgressMonitor progressMonitor = progressMonitors.get(monitorHandle);
+            if (progressMonitor == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            percentage.write(progressMonitor.getPercentDone());
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(monitorHandle, e);
+        }
+    }
+
+    /**
+     * Checks if the operation is finished.
+     */
+    @CEntryPoint(name = "zip4j_is_operation_finished")
+    public static int isOperationFinished(IsolateThread thread, long monitorHandle, CIntPointer isFinished) {
+        forgetLastException();
+        try {
+            if (isFinished.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ProgressMonitor progressMonitor = progressMonitors.get(monitorHandle);
+            if (progressMonitor == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            isFinished.write(progressMonitor.getState() == ProgressMonitor.State.READY ? 1 : 0);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(monitorHandle, e);
+        }
+    }
+
+    /**
+     * Cancels the current operation.
+     */
+    @CEntryPoint(name = "zip4j_cancel_operation")
+    public static int cancelOperation(IsolateThread thread, long monitorHandle) {
+        forgetLastException();
+        try {
+            ProgressMonitor progressMonitor = progressMonitors.get(monitorHandle);
+            if (progressMonitor == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            progressMonitor.setCancelAllTasks(true);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(monitorHandle, e);
+        }
+    }
+
+    /**
+     * Gets the amount of work completed and the total amount of work, in bytes.
+     */
+    @CEntryPoint(name = "zip4j_get_progress_work")
+    public static int getProgressWork(IsolateThread thread, long monitorHandle, CLongPointer workCompleted,
+                                      CLongPointer totalWork) {
+        forgetLastException();
+        try {
+            if (workCompleted.equal(WordFactory.nullPointer()) || totalWork.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            ProgressMonitor progressMonitor = progressMonitors.get(monitorHandle);
+            if (progressMonitor == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            workCompleted.write(progressMonitor.getWorkCompleted());
+            totalWork.write(progressMonitor.getTotalWork());
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(monitorHandle, e);
+        }
+    }
+
+    /**
+     * Gets the outcome of a finished operation as an error code.
+     */
+    @CEntryPoint(name = "zip4j_get_operation_result")
+    public static int getOperationResult(IsolateThread thread, long monitorHandle) {
+        forgetLastException();
         try {
-            ZipFile zipFile = zipFiles.get(zipHandle);
-            if (zipFile == null) {
+            ProgressMonitor progressMonitor = progressMonitors.get(monitorHandle);
+            if (progressMonitor == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            String commentStr = !comment.equal(WordFactory.nullPointer()) ?
-                CTypeConversion.toJavaString(comment) : null;
-            zipFile.setComment(commentStr);
+            ProgressMonitor.Result result = progressMonitor.getResult();
+            if (result == ProgressMonitor.Result.CANCELLED) {
+                return ERROR_OPERATION_CANCELLED;
+            }
+            if (result == ProgressMonitor.Result.ERROR) {
+                Exception exception = progressMonitor.getException();
+                return exception != null ? handleException(monitorHandle, exception) : ERROR_UNKNOWN;
+            }
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(monitorHandle, e);
+        }
+    }
+
+    /**
+     * Releases a progress monitor handle.
+     */
+    @CEntryPoint(name = "zip4j_release_progress_monitor")
+    public static int releaseProgressMonitor(IsolateThread thread, long monitorHandle) {
+        try {
+            ProgressMonitor progressMonitor = progressMonitors.remove(monitorHandle);
+            if (progressMonitor == null) {
+                return ERROR_INVALID_HANDLE;
+            }
 
+            lastErrors.remove(monitorHandle);
             return SUCCESS;
         } catch (Throwable e) {
-            return handleException(zipHandle, e);
+            return handleException(monitorHandle, e);
         }
     }
 
-    // ========== ZipEntry Management ==========
+    // ========== Error Handling ==========
 
     /**
-     * Gets the number of entries in the zip file.
+     * Gets the last error message for a handle.
      */
-    @CEntryPoint(name = "zip4j_get_entry_count")
-    public static int getEntryCount(IsolateThread thread, long zipHandle, CLongPointer entryCount) {
+    @CEntryPoint(name = "zip4j_get_last_error")
+    public static int getLastError(IsolateThread thread, long handle, CCharPointer buffer, int bufferSize, CIntPointer errorLength) {
         try {
-            if (entryCount.equal(WordFactory.nullPointer())) {
+            if (buffer.equal(WordFactory.nullPointer()) || errorLength.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
             }
 
-            ZipFile zipFile = zipFiles.get(zipHandle);
-            if (zipFile == null) {
-                return ERROR_INVALID_HANDLE;
+            Map<Long, String> handleErrors = lastErrors.get(handle);
+            String errorMessage = handleErrors == null ? null : handleErrors.get(Thread.currentThread().getId());
+            if (errorMessage == null) {
+                errorMessage = "No error";
             }
 
-            List<FileHeader> fileHeaders = zipFile.getFileHeaders();
-            entryCount.write(fileHeaders != null ? fileHeaders.size() : 0);
-            return SUCCESS;
+            return copyStringToBuffer(errorMessage, buffer, bufferSize, errorLength);
         } catch (Throwable e) {
-            return handleException(zipHandle, e);
+            return ERROR_UNKNOWN;
         }
     }
 
     /**
-     * Gets a ZipEntry handle by index.
+     * Describes the last exception raised on the calling thread, provided it was reported as
+     * {@code code}: its class and message, followed by those of its causes. An empty string is
+     * written when there is no such exception. The exception is forgotten once it has been read,
+     * so a later error that didn't come from an exception isn't described by a stale one.
      */
-    @CEntryPoint(name = "zip4j_get_entry_by_index")
-    public static int getEntryByIndex(IsolateThread thread, long zipHandle, long index, CLongPointer entryHandle) {
+    @CEntryPoint(name = "zip4j_take_last_exception")
+    public static int takeLastException(IsolateThread thread, int code, CCharPointer buffer, int bufferSize, CIntPointer detailsLength) {
         try {
-            if (entryHandle.equal(WordFactory.nullPointer())) {
+            if (buffer.equal(WordFactory.nullPointer()) || detailsLength.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
             }
 
-            ZipFile zipFile = zipFiles.get(zipHandle);
-            if (zipFile == null) {
-                return ERROR_INVALID_HANDLE;
+            Throwable exception = lastException.get();
+            Integer reportedCode = lastExceptionCode.get();
+            String details = "";
+            if (exception != null && reportedCode != null && reportedCode == code) {
+                StringBuilder description = new StringBuilder(exception.toString());
+                Throwable cause = exception.getCause();
+                for (int depth = 0; cause != null && depth < 8; depth++) {
+                    description.append("; caused by ").append(cause);
+                    cause = cause.getCause();
+                }
+                details = description.toString();
             }
 
-            List<FileHeader> fileHeaders = zipFile.getFileHeaders();
-            if (fileHeaders == null || index < 0 || index >= fileHeaders.size()) {
-                return ERROR_INVALID_PARAMETER;
+            int result = copyStringToBuffer(details, buffer, bufferSize, detailsLength);
+            if (result == SUCCESS) {
+                lastException.remove();
+                lastExceptionCode.remove();
             }
+            return result;
+        } catch (Throwable e) {
+            return ERROR_UNKNOWN;
+        }
+    }
 
-            FileHeader fileHeader = fileHeaders.get((int) index);
-            long handle = handleCounter.getAndIncrement();
-            zipEntries.put(handle, fileHeader);
+    // ========== Version Information ==========
 
-            entryHandle.write(handle);
+    /**
+     * Reports the ABI version of this library, and writes the zip4j version and the version of
+     * the GraalVM it was built with as a list of null-terminated strings.
+     */
+    @CEntryPoint(name = "zip4j_get_version_info")
+    public static int getVersionInfo(IsolateThread thread, CIntPointer abiVersion, CCharPointer buffer, int bufferSize,
+                                     CIntPointer length) {
+        forgetLastException();
+        try {
+            if (abiVersion.equal(WordFactory.nullPointer()) || buffer.equal(WordFactory.nullPointer())
+                    || length.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            abiVersion.write(ABI_VERSION);
+
+            String graalVersion = System.getProperty("org.graalvm.version");
+            if (graalVersion == null) {
+                graalVersion = System.getProperty("java.vm.version", "");
+            }
+            byte[] zip4j = BuildInfo.ZIP4J_VERSION.getBytes(StandardCharsets.UTF_8);
+            byte[] graal = graalVersion.getBytes(StandardCharsets.UTF_8);
+
+            int total = zip4j.length + graal.length + 2;
+            length.write(total);
+            if (total > bufferSize) {
+                return ERROR_BUFFER_TOO_SMALL;
+            }
+
+            int offset = 0;
+            for (byte[] version : new byte[][] { zip4j, graal }) {
+                for (byte b : version) {
+                    buffer.write(offset++, b);
+                }
+                buffer.write(offset++, (byte) 0);
+            }
             return SUCCESS;
         } catch (Throwable e) {
-            return handleException(zipHandle, e);
+            return handleException(0, e);
         }
     }
 
+    // ========== Diagnostics ==========
+
     /**
-     * Gets a ZipEntry handle by name.
+     * Writes up to {@code count} diagnostic values, in order: heap used, heap committed and
+     * maximum heap size in bytes, then the number of open archives, entries, streams and
+     * parameter sets. Handles that keep growing point to archives or entries that are never
+     * closed.
      */
-    @CEntryPoint(name = "zip4j_get_entry_by_name")
-    public static int getEntryByName(IsolateThread thread, long zipHandle, CCharPointer entryName, CLongPointer entryHandle) {
+    @CEntryPoint(name = "zip4j_get_diagnostics")
+    public static int getDiagnostics(IsolateThread thread, CLongPointer values, int count) {
+        forgetLastException();
         try {
-            if (entryName.equal(WordFactory.nullPointer()) || entryHandle.equal(WordFactory.nullPointer())) {
+            if (values.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
             }
 
+            Runtime runtime = Runtime.getRuntime();
+            long[] diagnostics = {
+                    runtime.totalMemory() - runtime.freeMemory(),
+                    runtime.totalMemory(),
+                    runtime.maxMemory(),
+                    zipFiles.size(),
+                    zipEntries.size(),
+                    inputStreams.size() + outputStreams.size() + dataWriters.size(),
+                    parameterSets.size(),
+            };
+
+            for (int i = 0; i < Math.min(count, diagnostics.length); i++) {
+                values.write(i, diagnostics[i]);
+            }
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(0, e);
+        }
+    }
+
+    // ========== Advanced Features ==========
+
+    /**
+     * Rewrites the archive so the data of every stored entry starts on an alignment-byte boundary,
+     * as Android tooling requires for mmap-able resources. Entries are copied without being
+     * recompressed. Split and Zip64 archives are not supported.
+     */
+    @CEntryPoint(name = "zip4j_align_archive")
+    public static int alignArchive(IsolateThread thread, long zipHandle, int alignment) {
+        forgetLastException();
+        try {
             ZipFile zipFile = zipFiles.get(zipHandle);
             if (zipFile == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            String name = CTypeConversion.toJavaString(entryName);
-            if (name == null || name.trim().isEmpty()) {
+            if (alignment < 1 || alignment > 0x8000) {
                 return ERROR_INVALID_PARAMETER;
             }
 
-            FileHeader fileHeader = zipFile.getFileHeader(name);
-            if (fileHeader == null) {
-                return ERROR_ENTRY_NOT_FOUND;
+            if (zipFile.isSplitArchive()) {
+                return ERROR_UNSUPPORTED_OPERATION;
             }
 
-            long handle = handleCounter.getAndIncrement();
-            zipEntries.put(handle, fileHeader);
+            File file = zipFile.getFile();
+            File temp = File.createTempFile(file.getName(), ".tmp", file.getAbsoluteFile().getParentFile());
+            try {
+                writeAlignedArchive(file, temp, alignment);
+                Files.move(temp.toPath(), file.toPath(), StandardCopyOption.REPLACE_EXISTING);
+            } finally {
+                temp.delete();
+            }
 
-            entryHandle.write(handle);
+            zipFiles.put(zipHandle, openZipFile(file, zipPasswords.get(zipHandle)));
             return SUCCESS;
         } catch (Throwable e) {
             return handleException(zipHandle, e);
@@ -571,200 +3762,383 @@ public class Zip4JC {
     }
 
     /**
-     * Releases a ZipEntry handle.
+     * Rewrites a zip file keeping only its entries and central directory, and writes the number
+     * of bytes that were dropped.
      */
-    @CEntryPoint(name = "zip4j_release_entry")
-    public static int releaseEntry(IsolateThread thread, long entryHandle) {
+    @CEntryPoint(name = "zip4j_compact")
+    public static int compact(IsolateThread thread, long zipHandle, CLongPointer reclaimed) {
+        forgetLastException();
         try {
-            FileHeader fileHeader = zipEntries.remove(entryHandle);
-            if (fileHeader == null) {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
+            if (reclaimed.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            File file = zipFile.getFile();
+            if (!file.exists()) {
+                reclaimed.write(0);
+                return SUCCESS;
+            }
+
+            if (zipFile.isSplitArchive()) {
+                return ERROR_UNSUPPORTED_OPERATION;
+            }
+
+            File temp = File.createTempFile(file.getName(), ".tmp", file.getAbsoluteFile().getParentFile());
+            long saved;
+            try {
+                writeAlignedArchive(file, temp, 0);
+                saved = file.length() - temp.length();
+                Files.move(temp.toPath(), file.toPath(), StandardCopyOption.REPLACE_EXISTING);
+            } finally {
+                temp.delete();
+            }
+
+            zipFiles.put(zipHandle, openZipFile(file, zipPasswords.get(zipHandle)));
+            reclaimed.write(saved);
             return SUCCESS;
         } catch (Throwable e) {
-            return handleException(entryHandle, e);
+            return handleException(zipHandle, e);
         }
     }
 
     /**
-     * Gets the name of a ZipEntry.
+     * Redirects modifications of a zip file to a copy next to it, so the
+     * original stays untouched until the staging is finished.
      */
-    @CEntryPoint(name = "zip4j_entry_get_name")
-    public static int getEntryName(IsolateThread thread, long entryHandle, CCharPointer buffer,
-                                  int bufferSize, CIntPointer nameLength) {
+    @CEntryPoint(name = "zip4j_begin_staging")
+    public static int beginStaging(IsolateThread thread, long zipHandle) {
+        forgetLastException();
         try {
-            if (buffer.equal(WordFactory.nullPointer()) || nameLength.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
             }
 
-            FileHeader fileHeader = zipEntries.get(entryHandle);
-            if (fileHeader == null) {
-                return ERROR_INVALID_HANDLE;
+            if (stagedTargets.containsKey(zipHandle)) {
+                return ERROR_INVALID_PARAMETER;
             }
 
-            String fileName = fileHeader.getFileName();
-            return copyStringToBuffer(fileName, buffer, bufferSize, nameLength);
+            File file = zipFile.getFile();
+            if (file.exists() && zipFile.isSplitArchive()) {
+                return ERROR_UNSUPPORTED_OPERATION;
+            }
+
+            File staged = File.createTempFile(file.getName(), ".tmp", file.getAbsoluteFile().getParentFile());
+            try {
+                if (file.exists()) {
+                    Files.copy(file.toPath(), staged.toPath(), StandardCopyOption.REPLACE_EXISTING);
+                } else {
+                    Files.delete(staged.toPath());
+                }
+                zipFile.close();
+                zipFiles.put(zipHandle, openZipFile(staged, zipPasswords.get(zipHandle)));
+                stagedTargets.put(zipHandle, file);
+            } catch (Throwable e) {
+                Files.deleteIfExists(staged.toPath());
+                throw e;
+            }
+            return SUCCESS;
         } catch (Throwable e) {
-            return handleException(entryHandle, e);
+            return handleException(zipHandle, e);
         }
     }
 
     /**
-     * Gets the uncompressed size of a ZipEntry.
+     * Ends the staging started by zip4j_begin_staging. When commit is non-zero
+     * the copy atomically replaces the original, otherwise it is discarded.
+     * Either way the zip file is reopened at its original location.
      */
-    @CEntryPoint(name = "zip4j_entry_get_size")
-    public static int getEntrySize(IsolateThread thread, long entryHandle, CLongPointer size) {
+    @CEntryPoint(name = "zip4j_end_staging")
+    public static int endStaging(IsolateThread thread, long zipHandle, int commit) {
+        forgetLastException();
         try {
-            if (size.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
             }
 
-            FileHeader fileHeader = zipEntries.get(entryHandle);
-            if (fileHeader == null) {
-                return ERROR_INVALID_HANDLE;
+            File target = stagedTargets.remove(zipHandle);
+            if (target == null) {
+                return ERROR_INVALID_PARAMETER;
             }
 
-            size.write(fileHeader.getUncompressedSize());
+            Path staged = zipFile.getFile().toPath();
+            zipFile.close();
+            try {
+                if (commit != 0 && Files.exists(staged)) {
+                    Files.move(staged, target.toPath(), StandardCopyOption.ATOMIC_MOVE, StandardCopyOption.REPLACE_EXISTING);
+                }
+            } finally {
+                Files.deleteIfExists(staged);
+                zipFiles.put(zipHandle, openZipFile(target, zipPasswords.get(zipHandle)));
+            }
             return SUCCESS;
         } catch (Throwable e) {
-            return handleException(entryHandle, e);
+            return handleException(zipHandle, e);
         }
     }
 
     /**
-     * Gets the compressed size of a ZipEntry.
+     * Takes an exclusive advisory lock on the lock file next to a zip file (its name with ".lock"
+     * appended), held until zip4j_unlock or zip4j_close. When wait is zero, acquired is set to 0
+     * instead of blocking if another process or handle holds the lock.
      */
-    @CEntryPoint(name = "zip4j_entry_get_compressed_size")
-    public static int getEntryCompressedSize(IsolateThread thread, long entryHandle, CLongPointer compressedSize) {
+    @CEntryPoint(name = "zip4j_lock")
+    public static int lock(IsolateThread thread, long zipHandle, int wait, CIntPointer acquired) {
+        forgetLastException();
         try {
-            if (compressedSize.equal(WordFactory.nullPointer())) {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (acquired.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
             }
 
-            FileHeader fileHeader = zipEntries.get(entryHandle);
-            if (fileHeader == null) {
+            if (archiveLocks.containsKey(zipHandle)) {
+                acquired.write(1);
+                return SUCCESS;
+            }
+
+            File target = stagedTargets.getOrDefault(zipHandle, zipFile.getFile());
+            Path lockPath = Paths.get(target.getAbsolutePath() + ".lock");
+            FileChannel channel = FileChannel.open(lockPath, StandardOpenOption.CREATE, StandardOpenOption.WRITE);
+            FileLock lock = null;
+            try {
+                while (true) {
+                    try {
+                        lock = wait != 0 ? channel.lock() : channel.tryLock();
+                        break;
+                    } catch (OverlappingFileLockException e) {
+                        // Held through another handle of this process
+                        if (wait == 0) {
+                            break;
+                        }
+                        Thread.sleep(50);
+                    }
+                }
+            } finally {
+                if (lock == null) {
+                    channel.close();
+                }
+            }
+
+            if (lock != null) {
+                archiveLocks.put(zipHandle, lock);
+            }
+            acquired.write(lock != null ? 1 : 0);
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Releases the lock taken by zip4j_lock, if any.
+     */
+    @CEntryPoint(name = "zip4j_unlock")
+    public static int unlock(IsolateThread thread, long zipHandle) {
+        forgetLastException();
+        try {
+            if (!zipFiles.containsKey(zipHandle)) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            compressedSize.write(fileHeader.getCompressedSize());
+            releaseLock(archiveLocks.remove(zipHandle));
             return SUCCESS;
         } catch (Throwable e) {
-            return handleException(entryHandle, e);
+            return handleException(zipHandle, e);
         }
     }
 
     /**
-     * Checks if a ZipEntry is a directory.
+     * Copies a zip file to another path, leaving the original open. When compact is non-zero
+     * the copy only keeps the entries and central directory, dropping unreferenced bytes.
      */
-    @CEntryPoint(name = "zip4j_entry_is_directory")
-    public static int isEntryDirectory(IsolateThread thread, long entryHandle, CIntPointer isDirectory) {
+    @CEntryPoint(name = "zip4j_save_as")
+    public static int saveAs(IsolateThread thread, long zipHandle, CCharPointer outputPath, int compact) {
+        forgetLastException();
         try {
-            if (isDirectory.equal(WordFactory.nullPointer())) {
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
+            }
+
+            if (outputPath.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
             }
 
-            FileHeader fileHeader = zipEntries.get(entryHandle);
-            if (fileHeader == null) {
-                return ERROR_INVALID_HANDLE;
+            String path = CTypeConversion.toJavaString(outputPath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
             }
 
-            isDirectory.write(fileHeader.isDirectory() ? 1 : 0);
+            File file = zipFile.getFile();
+            File target = new File(path);
+            if (target.exists() && Files.isSameFile(file.toPath(), target.toPath())) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            if (zipFile.isSplitArchive()) {
+                return ERROR_UNSUPPORTED_OPERATION;
+            }
+
+            if (compact != 0) {
+                writeAlignedArchive(file, target, 0);
+            } else {
+                Files.copy(file.toPath(), target.toPath(), StandardCopyOption.REPLACE_EXISTING);
+            }
             return SUCCESS;
         } catch (Throwable e) {
-            return handleException(entryHandle, e);
+            return handleException(zipHandle, e);
         }
     }
 
     /**
-     * Checks if a ZipEntry is encrypted.
+     * Creates a split zip archive with specified split size.
      */
-    @CEntryPoint(name = "zip4j_entry_is_encrypted")
-    public static int isEntryEncrypted(IsolateThread thread, long entryHandle, CIntPointer isEncrypted) {
+    @CEntryPoint(name = "zip4j_create_split_zip")
+    public static int createSplitZip(IsolateThread thread, CCharPointer filePath, long splitSize, CLongPointer zipHandle) {
+        forgetLastException();
         try {
-            if (isEncrypted.equal(WordFactory.nullPointer())) {
+            if (filePath.equal(WordFactory.nullPointer()) || zipHandle.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
             }
 
-            FileHeader fileHeader = zipEntries.get(entryHandle);
-            if (fileHeader == null) {
-                return ERROR_INVALID_HANDLE;
+            String path = CTypeConversion.toJavaString(filePath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
             }
 
-            isEncrypted.write(fileHeader.isEncrypted() ? 1 : 0);
+            ZipFile zipFile = openZipFile(path, null);
+            zipFile.createSplitZipFile(null, new ZipParameters(), true, splitSize);
+
+            long handle = handleCounter.getAndIncrement();
+            zipFiles.put(handle, zipFile);
+            lastErrors.remove(handle);
+
+            zipHandle.write(handle);
             return SUCCESS;
         } catch (Throwable e) {
-            return handleException(entryHandle, e);
+            return handleException(0, e);
         }
     }
 
     /**
-     * Gets the CRC32 checksum of a ZipEntry.
+     * Creates a split zip file containing the given files.
      */
-    @CEntryPoint(name = "zip4j_entry_get_crc")
-    public static int getEntryCrc(IsolateThread thread, long entryHandle, CLongPointer crc) {
+    @CEntryPoint(name = "zip4j_create_split_zip_from_files")
+    public static int createSplitZipFromFiles(IsolateThread thread, CCharPointer filePath, CCharPointer filePaths,
+                                              int filePathsLength, long paramsHandle, long splitSize,
+                                              CLongPointer zipHandle) {
+        forgetLastException();
         try {
-            if (crc.equal(WordFactory.nullPointer())) {
+            if (filePath.equal(WordFactory.nullPointer()) || filePaths.equal(WordFactory.nullPointer())
+                    || zipHandle.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
             }
 
-            FileHeader fileHeader = zipEntries.get(entryHandle);
-            if (fileHeader == null) {
+            String path = CTypeConversion.toJavaString(filePath);
+            if (path == null || path.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            List<File> files = new ArrayList<>();
+            for (String file : readStringList(filePaths, filePathsLength)) {
+                files.add(new File(file));
+            }
+
+            ZipFile zipFile = openZipFile(path, null);
+            ZipParameters zipParameters = prepareParameters(zipFile, paramsHandle);
+            if (zipParameters == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            crc.write(fileHeader.getCrc());
+            zipFile.createSplitZipFile(files, zipParameters, true, splitSize);
+
+            long handle = handleCounter.getAndIncrement();
+            zipFiles.put(handle, zipFile);
+            lastErrors.remove(handle);
+
+            zipHandle.write(handle);
             return SUCCESS;
         } catch (Throwable e) {
-            return handleException(entryHandle, e);
+            return handleException(0, e);
         }
     }
 
     /**
-     * Gets the last modified time of a ZipEntry (DOS time format).
+     * Creates a split zip file containing a folder and everything below it.
      */
-    @CEntryPoint(name = "zip4j_entry_get_last_modified_time")
-    public static int getEntryLastModifiedTime(IsolateThread thread, long entryHandle, CLongPointer lastModifiedTime) {
+    @CEntryPoint(name = "zip4j_create_split_zip_from_folder")
+    public static int createSplitZipFromFolder(IsolateThread thread, CCharPointer filePath, CCharPointer folderPath,
+                                               long paramsHandle, long splitSize, CLongPointer zipHandle) {
+        forgetLastException();
         try {
-            if (lastModifiedTime.equal(WordFactory.nullPointer())) {
+            if (filePath.equal(WordFactory.nullPointer()) || folderPath.equal(WordFactory.nullPointer())
+                    || zipHandle.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
             }
 
-            FileHeader fileHeader = zipEntries.get(entryHandle);
-            if (fileHeader == null) {
+            String path = CTypeConversion.toJavaString(filePath);
+            String folder = CTypeConversion.toJavaString(folderPath);
+            if (path == null || path.trim().isEmpty() || folder == null || folder.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
+
+            ZipFile zipFile = openZipFile(path, null);
+            ZipParameters zipParameters = prepareParameters(zipFile, paramsHandle);
+            if (zipParameters == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            lastModifiedTime.write(fileHeader.getLastModifiedTime());
+            zipFile.createSplitZipFileFromFolder(new File(folder), zipParameters, true, splitSize);
+
+            long handle = handleCounter.getAndIncrement();
+            zipFiles.put(handle, zipFile);
+            lastErrors.remove(handle);
+
+            zipHandle.write(handle);
             return SUCCESS;
         } catch (Throwable e) {
-            return handleException(entryHandle, e);
+            return handleException(0, e);
         }
     }
 
-    // ========== File Addition Operations ==========
-
     /**
-     * Adds a file to the zip archive with default parameters.
+     * Rewrites an archive as a new split archive. Entries are re-compressed and, if encrypted,
+     * re-encrypted with the archive password.
      */
-    @CEntryPoint(name = "zip4j_add_file")
-    public static int addFile(IsolateThread thread, long zipHandle, CCharPointer filePath) {
+    @CEntryPoint(name = "zip4j_split_archive")
+    public static int splitArchive(IsolateThread thread, long zipHandle, CCharPointer outputPath, long splitSize,
+                                   CLongPointer splitHandle) {
+        forgetLastException();
         try {
+            if (outputPath.equal(WordFactory.nullPointer()) || splitHandle.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
             ZipFile zipFile = zipFiles.get(zipHandle);
             if (zipFile == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            if (filePath.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
-            }
-
-            String path = CTypeConversion.toJavaString(filePath);
+            String path = CTypeConversion.toJavaString(outputPath);
             if (path == null || path.trim().isEmpty()) {
                 return ERROR_INVALID_PARAMETER;
             }
 
-            zipFile.addFile(path);
+            File output = new File(path);
+            char[] password = zipPasswords.get(zipHandle);
+            writeSplitArchive(zipFile, output, splitSize, password, new ProgressMonitor());
+
+            splitHandle.write(registerZipFile(openZipFile(output, null), password));
             return SUCCESS;
         } catch (Throwable e) {
             return handleException(zipHandle, e);
@@ -772,45 +4146,53 @@ public class Zip4JC {
     }
 
     /**
-     * Adds a file to the zip archive with custom parameters.
+     * Starts rewriting an archive as a new split archive on a worker thread. The split archive
+     * handle is usable once the operation behind the returned progress monitor has succeeded.
      */
-    @CEntryPoint(name = "zip4j_add_file_with_params")
-    public static int addFileWithParams(IsolateThread thread, long zipHandle, CCharPointer filePath,
-                                       int compressionLevel, int compressionMethod, int encryptionMethod,
-                                       int aesKeyStrength, CCharPointer password) {
+    @CEntryPoint(name = "zip4j_split_archive_async")
+    public static int splitArchiveAsync(IsolateThread thread, long zipHandle, CCharPointer outputPath, long splitSize,
+                                        CLongPointer splitHandle, CLongPointer monitorHandle) {
+        forgetLastException();
         try {
+            if (outputPath.equal(WordFactory.nullPointer()) || splitHandle.equal(WordFactory.nullPointer())
+                    || monitorHandle.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
             ZipFile zipFile = zipFiles.get(zipHandle);
             if (zipFile == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            if (filePath.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
-            }
-
-            String path = CTypeConversion.toJavaString(filePath);
+            String path = CTypeConversion.toJavaString(outputPath);
             if (path == null || path.trim().isEmpty()) {
                 return ERROR_INVALID_PARAMETER;
             }
 
-            ZipParameters zipParameters = new ZipParameters();
-            zipParameters.setCompressionLevel(getCompressionLevel(compressionLevel));
-            zipParameters.setCompressionMethod(getCompressionMethod(compressionMethod));
-            zipParameters.setEncryptionMethod(getEncryptionMethod(encryptionMethod));
-
-            if (encryptionMethod == ENCRYPTION_AES_128 || encryptionMethod == ENCRYPTION_AES_256) {
-                zipParameters.setAesKeyStrength(getAesKeyStrength(aesKeyStrength));
-            }
-
-            if (!password.equal(WordFactory.nullPointer())) {
-                String pass = CTypeConversion.toJavaString(password);
-                if (pass != null && !pass.isEmpty()) {
-                    zipParameters.setEncryptFiles(true);
-                    zipFile.setPassword(pass.toCharArray());
+            File output = new File(path);
+            char[] tracked = zipPasswords.get(zipHandle);
+            char[] password = tracked != null ? tracked.clone() : null;
+            ProgressMonitor progressMonitor = new ProgressMonitor();
+            progressMonitor.setState(ProgressMonitor.State.BUSY);
+            long splitZipHandle = registerZipFile(openZipFile(output, null), password);
+
+            Thread worker = new Thread(() -> {
+                try {
+                    writeSplitArchive(zipFile, output, splitSize, password, progressMonitor);
+                    progressMonitor.endProgressMonitor();
+                } catch (Exception e) {
+                    if (progressMonitor.getResult() != ProgressMonitor.Result.CANCELLED) {
+                        progressMonitor.endProgressMonitor(e);
+                    }
+                } finally {
+                    wipe(password);
                 }
-            }
+            }, "zip4j-split-archive");
+            worker.setDaemon(true);
+            worker.start();
 
-            zipFile.addFile(path, zipParameters);
+            splitHandle.write(splitZipHandle);
+            monitorHandle.write(registerProgressMonitor(progressMonitor));
             return SUCCESS;
         } catch (Throwable e) {
             return handleException(zipHandle, e);
@@ -818,26 +4200,27 @@ public class Zip4JC {
     }
 
     /**
-     * Adds a directory to the zip archive.
+     * Merges split zip files into a single zip file.
      */
-    @CEntryPoint(name = "zip4j_add_directory")
-    public static int addDirectory(IsolateThread thread, long zipHandle, CCharPointer dirPath) {
+    @CEntryPoint(name = "zip4j_merge_split_files")
+    public static int mergeSplitFiles(IsolateThread thread, long zipHandle, CCharPointer outputPath) {
+        forgetLastException();
         try {
             ZipFile zipFile = zipFiles.get(zipHandle);
             if (zipFile == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            if (dirPath.equal(WordFactory.nullPointer())) {
+            if (outputPath.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
             }
 
-            String path = CTypeConversion.toJavaString(dirPath);
+            String path = CTypeConversion.toJavaString(outputPath);
             if (path == null || path.trim().isEmpty()) {
                 return ERROR_INVALID_PARAMETER;
             }
 
-            zipFile.addFolder(Paths.get(path).toFile());
+            zipFile.mergeSplitFiles(new File(path));
             return SUCCESS;
         } catch (Throwable e) {
             return handleException(zipHandle, e);
@@ -845,74 +4228,118 @@ public class Zip4JC {
     }
 
     /**
-     * Adds a directory to the zip archive with custom parameters.
+     * Starts merging split zip files into a single zip file on a worker thread.
      */
-    @CEntryPoint(name = "zip4j_add_directory_with_params")
-    public static int addDirectoryWithParams(IsolateThread thread, long zipHandle, CCharPointer dirPath,
-                                            int compressionLevel, int compressionMethod, int encryptionMethod,
-                                            int aesKeyStrength, CCharPointer password) {
+    @CEntryPoint(name = "zip4j_merge_split_files_async")
+    public static int mergeSplitFilesAsync(IsolateThread thread, long zipHandle, CCharPointer outputPath,
+                                           CLongPointer monitorHandle) {
+        forgetLastException();
         try {
+            if (outputPath.equal(WordFactory.nullPointer()) || monitorHandle.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
             ZipFile zipFile = zipFiles.get(zipHandle);
             if (zipFile == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            if (dirPath.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
-            }
-
-            String path = CTypeConversion.toJavaString(dirPath);
+            String path = CTypeConversion.toJavaString(outputPath);
             if (path == null || path.trim().isEmpty()) {
                 return ERROR_INVALID_PARAMETER;
             }
 
-            ZipParameters zipParameters = new ZipParameters();
-            zipParameters.setCompressionLevel(getCompressionLevel(compressionLevel));
-            zipParameters.setCompressionMethod(getCompressionMethod(compressionMethod));
-            zipParameters.setEncryptionMethod(getEncryptionMethod(encryptionMethod));
+            zipFile.setRunInThread(true);
+            try {
+                zipFile.mergeSplitFiles(new File(path));
+            } finally {
+                zipFile.setRunInThread(false);
+            }
 
-            if (encryptionMethod == ENCRYPTION_AES_128 || encryptionMethod == ENCRYPTION_AES_256) {
-                zipParameters.setAesKeyStrength(getAesKeyStrength(aesKeyStrength));
+            monitorHandle.write(registerProgressMonitor(zipFile.getProgressMonitor()));
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(zipHandle, e);
+        }
+    }
+
+    /**
+     * Checks a password against the smallest encrypted entry without extracting the archive.
+     * Writes 1 if the password is correct or the archive has no encrypted entries.
+     */
+    @CEntryPoint(name = "zip4j_verify_password")
+    public static int verifyPassword(IsolateThread thread, long zipHandle, CCharPointer password, CIntPointer isValid) {
+        forgetLastException();
+        try {
+            if (password.equal(WordFactory.nullPointer()) || isValid.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
             }
 
-            if (!password.equal(WordFactory.nullPointer())) {
-                String pass = CTypeConversion.toJavaString(password);
-                if (pass != null && !pass.isEmpty()) {
-                    zipParameters.setEncryptFiles(true);
-                    zipFile.setPassword(pass.toCharArray());
-                }
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
             }
 
-            zipFile.addFolder(Paths.get(path).toFile(), zipParameters);
+            FileHeader fileHeader = smallestEncryptedEntry(zipFile);
+            char[] pass = readPassword(password);
+            try {
+                isValid.write(fileHeader == null
+                        || checkPassword(openZipFile(zipFile.getFile(), null), fileHeader, pass) ? 1 : 0);
+            } finally {
+                wipe(pass);
+            }
             return SUCCESS;
         } catch (Throwable e) {
             return handleException(zipHandle, e);
         }
     }
 
-    // ========== Extraction Operations ==========
-
     /**
-     * Extracts all files from the zip archive to a destination directory.
+     * Checks a list of candidate passwords (consecutive null-terminated strings) against the
+     * archive and writes the index of the first one that opens it, or -1 if none do. Every
+     * candidate is checked against the same entry through one reader, so the central directory
+     * is only read once. Writes 0 if the archive has no encrypted entries.
      */
-    @CEntryPoint(name = "zip4j_extract_all")
-    public static int extractAll(IsolateThread thread, long zipHandle, CCharPointer destPath) {
+    @CEntryPoint(name = "zip4j_find_password")
+    public static int findPassword(IsolateThread thread, long zipHandle, CCharPointer candidates,
+                                   int candidatesLength, CIntPointer index) {
+        forgetLastException();
         try {
+            if (candidates.equal(WordFactory.nullPointer()) || index.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
             ZipFile zipFile = zipFiles.get(zipHandle);
             if (zipFile == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            if (destPath.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
+            FileHeader fileHeader = smallestEncryptedEntry(zipFile);
+            if (fileHeader == null) {
+                index.write(candidatesLength > 0 ? 0 : -1);
+                return SUCCESS;
             }
 
-            String path = CTypeConversion.toJavaString(destPath);
-            if (path == null || path.trim().isEmpty()) {
-                return ERROR_INVALID_PARAMETER;
+            ZipFile reader = openZipFile(zipFile.getFile(), null);
+            int found = -1;
+            int start = 0;
+            for (int i = 0, candidate = 0; i < candidatesLength && found < 0; i++) {
+                if (candidates.read(i) != 0) {
+                    continue;
+                }
+                char[] pass = readPassword(candidates.addressOf(start));
+                try {
+                    if (checkPassword(reader, fileHeader, pass)) {
+                        found = candidate;
+                    }
+                } finally {
+                    wipe(pass);
+                }
+                start = i + 1;
+                candidate++;
             }
 
-            zipFile.extractAll(path);
+            index.write(found);
             return SUCCESS;
         } catch (Throwable e) {
             return handleException(zipHandle, e);
@@ -920,28 +4347,55 @@ public class Zip4JC {
     }
 
     /**
-     * Extracts a specific file by name from the zip archive.
+     * Re-encrypts every encrypted entry with a new password by rewriting the archive. Entries keep
+     * their encryption method unless encryptionMethod is one of the ENCRYPTION_* constants
+     * (pass -1 to keep it); ENCRYPTION_NONE stores them decrypted.
      */
-    @CEntryPoint(name = "zip4j_extract_file")
-    public static int extractFile(IsolateThread thread, long zipHandle, CCharPointer fileName, CCharPointer destPath) {
+    @CEntryPoint(name = "zip4j_change_password")
+    public static int changePassword(IsolateThread thread, long zipHandle, CCharPointer oldPassword,
+                                     CCharPointer newPassword, int encryptionMethod) {
+        forgetLastException();
         try {
+            if (oldPassword.equal(WordFactory.nullPointer()) || newPassword.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
             ZipFile zipFile = zipFiles.get(zipHandle);
             if (zipFile == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            if (fileName.equal(WordFactory.nullPointer()) || destPath.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
+            if (zipFile.isSplitArchive()) {
+                return ERROR_UNSUPPORTED_OPERATION;
             }
 
-            String name = CTypeConversion.toJavaString(fileName);
-            String path = CTypeConversion.toJavaString(destPath);
-
-            if (name == null || name.trim().isEmpty() || path == null || path.trim().isEmpty()) {
-                return ERROR_INVALID_PARAMETER;
+            char[] oldPass = readPassword(oldPassword);
+            char[] newPass = readPassword(newPassword);
+
+            File file = zipFile.getFile();
+            ZipFile source = openZipFile(file, oldPass);
+            File temp = File.createTempFile(file.getName(), ".tmp", file.getAbsoluteFile().getParentFile());
+            try {
+                try (ZipOutputStream outputStream = openZipOutputStream(new FileOutputStream(temp), newPass)) {
+                    copyEntries(source, source.getFileHeaders(), outputStream, fileHeader -> {
+                        ZipParameters zipParameters = parametersFromHeader(fileHeader);
+                        if (fileHeader.isEncrypted() && encryptionMethod >= 0) {
+                            zipParameters.setEncryptFiles(encryptionMethod != ENCRYPTION_NONE);
+                            zipParameters.setEncryptionMethod(getEncryptionMethod(encryptionMethod));
+                            zipParameters.setAesKeyStrength(encryptionMethod == ENCRYPTION_AES_128
+                                    ? AesKeyStrength.KEY_STRENGTH_128 : AesKeyStrength.KEY_STRENGTH_256);
+                        }
+                        return zipParameters;
+                    }, new ProgressMonitor());
+                }
+                Files.move(temp.toPath(), file.toPath(), StandardCopyOption.REPLACE_EXISTING);
+            } finally {
+                temp.delete();
+                wipe(oldPass);
             }
 
-            zipFile.extractFile(name, path);
+            zipFiles.put(zipHandle, openZipFile(file, newPass));
+            wipe(zipPasswords.put(zipHandle, newPass));
             return SUCCESS;
         } catch (Throwable e) {
             return handleException(zipHandle, e);
@@ -949,10 +4403,11 @@ public class Zip4JC {
     }
 
     /**
-     * Extracts a specific entry using a ZipEntry handle.
+     * Renames an entry in the zip archive. Renaming a directory moves the entries below it along.
      */
-    @CEntryPoint(name = "zip4j_extract_entry")
-    public static int extractEntry(IsolateThread thread, long zipHandle, long entryHandle, CCharPointer destPath) {
+    @CEntryPoint(name = "zip4j_rename_entry")
+    public static int renameEntry(IsolateThread thread, long zipHandle, long entryHandle, CCharPointer newName) {
+        forgetLastException();
         try {
             ZipFile zipFile = zipFiles.get(zipHandle);
             if (zipFile == null) {
@@ -964,45 +4419,56 @@ public class Zip4JC {
                 return ERROR_INVALID_HANDLE;
             }
 
-            if (destPath.equal(WordFactory.nullPointer())) {
+            if (newName.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
             }
 
-            String path = CTypeConversion.toJavaString(destPath);
-            if (path == null || path.trim().isEmpty()) {
+            String name = CTypeConversion.toJavaString(newName);
+            if (name == null || name.trim().isEmpty()) {
                 return ERROR_INVALID_PARAMETER;
             }
 
-            zipFile.extractFile(fileHeader, path);
+            applyRenamePlan(zipHandle, zipFile, renamePlan(zipFile, Collections.singletonMap(fileHeader.getFileName(), name)));
             return SUCCESS;
         } catch (Throwable e) {
             return handleException(zipHandle, e);
         }
     }
 
-    // ========== Modification Operations ==========
-
     /**
-     * Removes a file from the zip archive by name.
+     * Renames multiple entries in a single operation.
+     * The buffer holds null-terminated names as consecutive (old name, new name) pairs. Names
+     * match entries exactly; the entries below a renamed directory ending in '/' move along with
+     * it unless they are renamed themselves.
      */
-    @CEntryPoint(name = "zip4j_remove_file")
-    public static int removeFile(IsolateThread thread, long zipHandle, CCharPointer fileName) {
+    @CEntryPoint(name = "zip4j_rename_entries")
+    public static int renameEntries(IsolateThread thread, long zipHandle, CCharPointer names, int namesLength) {
+        forgetLastException();
         try {
             ZipFile zipFile = zipFiles.get(zipHandle);
             if (zipFile == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            if (fileName.equal(WordFactory.nullPointer())) {
+            if (names.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
             }
 
-            String name = CTypeConversion.toJavaString(fileName);
-            if (name == null || name.trim().isEmpty()) {
+            List<String> nameList = readStringList(names, namesLength);
+            if (nameList.size() % 2 != 0) {
                 return ERROR_INVALID_PARAMETER;
             }
 
-            zipFile.removeFile(name);
+            Map<String, String> renames = new LinkedHashMap<>();
+            for (int i = 0; i < nameList.size(); i += 2) {
+                String oldName = nameList.get(i);
+                String newName = nameList.get(i + 1);
+                if (oldName.isEmpty() || newName.trim().isEmpty() || renames.put(oldName, newName) != null) {
+                    return ERROR_INVALID_PARAMETER;
+                }
+            }
+
+            applyRenamePlan(zipHandle, zipFile, renamePlan(zipFile, renames));
             return SUCCESS;
         } catch (Throwable e) {
             return handleException(zipHandle, e);
@@ -1010,55 +4476,65 @@ public class Zip4JC {
     }
 
     /**
-     * Removes an entry from the zip archive using a ZipEntry handle.
+     * Renames and removes entries with a single rewrite of the archive. Names come in pairs of
+     * old and new name; an empty new name removes the entry. Names match entries exactly, so a
+     * directory's contents stay where they are. Fails with ERROR_INVALID_PARAMETER without changing
+     * anything if an entry is named twice or a new name is taken by an entry that stays. Zip64
+     * archives fall back to zip4j's own removal and renaming where that changes the same entries.
      */
-    @CEntryPoint(name = "zip4j_remove_entry")
-    public static int removeEntry(IsolateThread thread, long zipHandle, long entryHandle) {
+    @CEntryPoint(name = "zip4j_rewrite_entries")
+    public static int rewriteEntries(IsolateThread thread, long zipHandle, CCharPointer names, int namesLength) {
+        forgetLastException();
         try {
             ZipFile zipFile = zipFiles.get(zipHandle);
             if (zipFile == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            FileHeader fileHeader = zipEntries.get(entryHandle);
-            if (fileHeader == null) {
-                return ERROR_INVALID_HANDLE;
+            if (names.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
             }
 
-            zipFile.removeFile(fileHeader);
-            return SUCCESS;
-        } catch (Throwable e) {
-            return handleException(zipHandle, e);
-        }
-    }
-
-    // ========== Streaming Operations ==========
-
-    /**
-     * Creates a ZipInputStream for reading entries from a zip file.
-     */
-    @CEntryPoint(name = "zip4j_create_input_stream")
-    public static int createInputStream(IsolateThread thread, long zipHandle, long entryHandle, CLongPointer streamHandle) {
-        try {
-            if (streamHandle.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
+            List<String> nameList = readStringList(names, namesLength);
+            if (nameList.size() % 2 != 0) {
+                return ERROR_INVALID_PARAMETER;
             }
 
-            ZipFile zipFile = zipFiles.get(zipHandle);
-            if (zipFile == null) {
-                return ERROR_INVALID_HANDLE;
+            // Each entry is changed at most once, and no two entries end up with the same name
+            Map<String, String> changes = new LinkedHashMap<>();
+            for (int i = 0; i < nameList.size(); i += 2) {
+                String oldName = nameList.get(i);
+                String newName = nameList.get(i + 1);
+                if (oldName.isEmpty() || changes.containsKey(oldName)) {
+                    return ERROR_INVALID_PARAMETER;
+                }
+                changes.put(oldName, newName.isEmpty() ? null : newName);
             }
 
-            FileHeader fileHeader = zipEntries.get(entryHandle);
-            if (fileHeader == null) {
-                return ERROR_INVALID_HANDLE;
+            Set<String> existing = new HashSet<>();
+            for (FileHeader fileHeader : zipFile.getFileHeaders()) {
+                existing.add(fileHeader.getFileName());
+            }
+            Set<String> targets = new HashSet<>();
+            for (String newName : changes.values()) {
+                if (newName != null && (!targets.add(newName) || (existing.contains(newName) && !changes.containsKey(newName)))) {
+                    return ERROR_INVALID_PARAMETER;
+                }
             }
 
-            ZipInputStream zipInputStream = zipFile.getInputStream(fileHeader);
-            long handle = handleCounter.getAndIncrement();
-            inputStreams.put(handle, zipInputStream);
+            Map<String, String> plan = new LinkedHashMap<>();
+            for (FileHeader fileHeader : zipFile.getFileHeaders()) {
+                String name = fileHeader.getFileName();
+                if (changes.containsKey(name)) {
+                    String newName = changes.get(name);
+                    if (newName != null && fileHeader.isDirectory() && !newName.endsWith("/")) {
+                        newName += "/";
+                    }
+                    plan.put(name, newName);
+                }
+            }
 
-            streamHandle.write(handle);
+            applyRenamePlan(zipHandle, zipFile, plan);
             return SUCCESS;
         } catch (Throwable e) {
             return handleException(zipHandle, e);
@@ -1066,63 +4542,70 @@ public class Zip4JC {
     }
 
     /**
-     * Reads data from a ZipInputStream.
+     * Adds data from a byte array to the zip archive.
      */
-    @CEntryPoint(name = "zip4j_stream_read")
-    public static int streamRead(IsolateThread thread, long streamHandle, CCharPointer buffer, int bufferSize, CIntPointer bytesRead) {
+    @CEntryPoint(name = "zip4j_add_data")
+    public static int addData(IsolateThread thread, long zipHandle, CCharPointer entryName,
+                             CCharPointer data, int dataLength, int compressionLevel,
+                             int compressionMethod, int encryptionMethod, int aesKeyStrength,
+                             CCharPointer password) {
+        forgetLastException();
         try {
-            if (buffer.equal(WordFactory.nullPointer()) || bytesRead.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
+            ZipFile zipFile = zipFiles.get(zipHandle);
+            if (zipFile == null) {
+                return ERROR_INVALID_HANDLE;
             }
 
-            ZipInputStream zipInputStream = inputStreams.get(streamHandle);
-            if (zipInputStream == null) {
-                return ERROR_INVALID_HANDLE;
+            if (entryName.equal(WordFactory.nullPointer()) || data.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
             }
 
-            byte[] readBuffer = new byte[bufferSize];
-            int read = zipInputStream.read(readBuffer);
+            String name = CTypeConversion.toJavaString(entryName);
+            if (name == null || name.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
+            }
 
-            if (read > 0) {
-                for (int i = 0; i < read; i++) {
-                    buffer.write(i, readBuffer[i]);
-                }
+            // Convert C data to byte array
+            byte[] dataBytes = new byte[dataLength];
+            for (int i = 0; i < dataLength; i++) {
+                dataBytes[i] = data.read(i);
             }
 
-            bytesRead.write(read);
-            return SUCCESS;
-        } catch (Throwable e) {
-            return handleException(streamHandle, e);
-        }
-    }
+            ZipParameters zipParameters = new ZipParameters();
+            zipParameters.setCompressionLevel(getCompressionLevel(compressionLevel));
+            zipParameters.setCompressionMethod(getCompressionMethod(compressionMethod));
+            zipParameters.setEncryptionMethod(getEncryptionMethod(encryptionMethod));
+            zipParameters.setFileNameInZip(name);
 
-    /**
-     * Closes a ZipInputStream.
-     */
-    @CEntryPoint(name = "zip4j_close_input_stream")
-    public static int closeInputStream(IsolateThread thread, long streamHandle) {
-        try {
-            ZipInputStream zipInputStream = inputStreams.remove(streamHandle);
-            if (zipInputStream == null) {
-                return ERROR_INVALID_HANDLE;
+            if (encryptionMethod == ENCRYPTION_AES_128 || encryptionMethod == ENCRYPTION_AES_256) {
+                zipParameters.setAesKeyStrength(getAesKeyStrength(aesKeyStrength));
             }
 
-            zipInputStream.close();
+            char[] pass = readPassword(password);
+            if (pass != null && pass.length > 0) {
+                zipParameters.setEncryptFiles(true);
+                zipFile.setPassword(pass);
+            }
+
+            ByteArrayInputStream inputStream = new ByteArrayInputStream(dataBytes);
+            zipFile.addStream(inputStream, zipParameters);
+
             return SUCCESS;
         } catch (Throwable e) {
-            return handleException(streamHandle, e);
+            return handleException(zipHandle, e);
         }
     }
 
-    // ========== Progress Monitoring ==========
-
     /**
-     * Gets the progress monitor for a zip file operation.
+     * Starts adding an entry whose data is written in chunks. zip4j reads the chunks on a
+     * worker thread as they are written, so the data is never buffered as a whole.
      */
-    @CEntryPoint(name = "zip4j_get_progress_monitor")
-    public static int getProgressMonitor(IsolateThread thread, long zipHandle, CLongPointer monitorHandle) {
+    @CEntryPoint(name = "zip4j_begin_data")
+    public static int beginData(IsolateThread thread, long zipHandle, CCharPointer entryName,
+                               long paramsHandle, CLongPointer writerHandle) {
+        forgetLastException();
         try {
-            if (monitorHandle.equal(WordFactory.nullPointer())) {
+            if (entryName.equal(WordFactory.nullPointer()) || writerHandle.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
             }
 
@@ -1131,154 +4614,141 @@ public class Zip4JC {
                 return ERROR_INVALID_HANDLE;
             }
 
-            ProgressMonitor progressMonitor = zipFile.getProgressMonitor();
-            long handle = handleCounter.getAndIncrement();
-            progressMonitors.put(handle, progressMonitor);
-
-            monitorHandle.write(handle);
-            return SUCCESS;
-        } catch (Throwable e) {
-            return handleException(zipHandle, e);
-        }
-    }
-
-    /**
-     * Gets the current progress percentage.
-     */
-    @CEntryPoint(name = "zip4j_get_progress_percentage")
-    public static int getProgressPercentage(IsolateThread thread, long monitorHandle, CIntPointer percentage) {
-        try {
-            if (percentage.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
+            String name = CTypeConversion.toJavaString(entryName);
+            if (name == null || name.trim().isEmpty()) {
+                return ERROR_INVALID_PARAMETER;
             }
 
-            ProgressMonitor progressMonitor = progressMonitors.get(monitorHandle);
-            if (progressMonitor == null) {
+            ZipParameters zipParameters = prepareParameters(zipFile, paramsHandle);
+            if (zipParameters == null) {
                 return ERROR_INVALID_HANDLE;
             }
+            zipParameters.setFileNameInZip(name);
 
-            percentage.write(progressMonitor.getPercentDone());
+            ChunkInputStream stream = new ChunkInputStream();
+            stream.worker = new Thread(() -> {
+                try {
+                    zipFile.addStream(stream, zipParameters);
+                } catch (Throwable e) {
+                    stream.failure = e;
+                }
+            }, "zip4j-add-data");
+            stream.worker.setDaemon(true);
+            stream.worker.start();
+
+            long handle = handleCounter.getAndIncrement();
+            dataWriters.put(handle, stream);
+
+            writerHandle.write(handle);
             return SUCCESS;
         } catch (Throwable e) {
-            return handleException(monitorHandle, e);
+            return handleException(zipHandle, e);
         }
     }
 
     /**
-     * Checks if the operation is finished.
+     * Writes a chunk of data to an entry started with zip4j_begin_data.
      */
-    @CEntryPoint(name = "zip4j_is_operation_finished")
-    public static int isOperationFinished(IsolateThread thread, long monitorHandle, CIntPointer isFinished) {
+    @CEntryPoint(name = "zip4j_write_data")
+    public static int writeData(IsolateThread thread, long writerHandle, CCharPointer data, int dataLength) {
+        forgetLastException();
         try {
-            if (isFinished.equal(WordFactory.nullPointer())) {
+            if (data.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
             }
 
-            ProgressMonitor progressMonitor = progressMonitors.get(monitorHandle);
-            if (progressMonitor == null) {
+            ChunkInputStream stream = dataWriters.get(writerHandle);
+            if (stream == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            isFinished.write(progressMonitor.getState() == ProgressMonitor.State.READY ? 1 : 0);
-            return SUCCESS;
-        } catch (Throwable e) {
-            return handleException(monitorHandle, e);
-        }
-    }
+            byte[] chunk = new byte[dataLength];
+            for (int i = 0; i < dataLength; i++) {
+                chunk[i] = data.read(i);
+            }
 
-    /**
-     * Cancels the current operation.
-     */
-    @CEntryPoint(name = "zip4j_cancel_operation")
-    public static int cancelOperation(IsolateThread thread, long monitorHandle) {
-        try {
-            ProgressMonitor progressMonitor = progressMonitors.get(monitorHandle);
-            if (progressMonitor == null) {
-                return ERROR_INVALID_HANDLE;
+            stream.push(chunk);
+            if (stream.failure != null) {
+                return handleException(writerHandle, stream.failure);
             }
 
-            progressMonitor.setCancelAllTasks(true);
             return SUCCESS;
         } catch (Throwable e) {
-            return handleException(monitorHandle, e);
+            return handleException(writerHandle, e);
         }
     }
 
-    // ========== Error Handling ==========
-
     /**
-     * Gets the last error message for a handle.
+     * Finishes an entry started with zip4j_begin_data and waits until it is written.
      */
-    @CEntryPoint(name = "zip4j_get_last_error")
-    public static int getLastError(IsolateThread thread, long handle, CCharPointer buffer, int bufferSize, CIntPointer errorLength) {
+    @CEntryPoint(name = "zip4j_finish_data")
+    public static int finishData(IsolateThread thread, long writerHandle) {
+        forgetLastException();
         try {
-            if (buffer.equal(WordFactory.nullPointer()) || errorLength.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
+            ChunkInputStream stream = dataWriters.remove(writerHandle);
+            if (stream == null) {
+                return ERROR_INVALID_HANDLE;
             }
 
-            String errorMessage = lastErrors.get(handle);
-            if (errorMessage == null) {
-                errorMessage = "No error";
+            stream.push(ChunkInputStream.END);
+            stream.worker.join();
+            if (stream.failure != null) {
+                return handleException(writerHandle, stream.failure);
             }
 
-            return copyStringToBuffer(errorMessage, buffer, bufferSize, errorLength);
+            return SUCCESS;
         } catch (Throwable e) {
-            return ERROR_UNKNOWN;
+            return handleException(writerHandle, e);
         }
     }
 
-    // ========== Advanced Features ==========
-
     /**
-     * Creates a split zip archive with specified split size.
+     * Abandons an entry started with zip4j_begin_data.
      */
-    @CEntryPoint(name = "zip4j_create_split_zip")
-    public static int createSplitZip(IsolateThread thread, CCharPointer filePath, long splitSize, CLongPointer zipHandle) {
+    @CEntryPoint(name = "zip4j_abort_data")
+    public static int abortData(IsolateThread thread, long writerHandle) {
         try {
-            if (filePath.equal(WordFactory.nullPointer()) || zipHandle.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
-            }
-
-            String path = CTypeConversion.toJavaString(filePath);
-            if (path == null || path.trim().isEmpty()) {
-                return ERROR_INVALID_PARAMETER;
+            ChunkInputStream stream = dataWriters.remove(writerHandle);
+            if (stream == null) {
+                return ERROR_INVALID_HANDLE;
             }
 
-            ZipFile zipFile = new ZipFile(path);
-            zipFile.createSplitZipFile(null, new ZipParameters(), true, splitSize);
-
-            long handle = handleCounter.getAndIncrement();
-            zipFiles.put(handle, zipFile);
-            lastErrors.remove(handle);
-
-            zipHandle.write(handle);
+            stream.abort();
+            stream.worker.join();
             return SUCCESS;
         } catch (Throwable e) {
-            return handleException(0, e);
+            return handleException(writerHandle, e);
         }
     }
 
     /**
-     * Merges split zip files into a single zip file.
+     * Tests an entry by decompressing it without keeping the data; zip4j checks the CRC once the
+     * end of the entry is reached. Directories always pass.
      */
-    @CEntryPoint(name = "zip4j_merge_split_files")
-    public static int mergeSplitFiles(IsolateThread thread, long zipHandle, CCharPointer outputPath) {
+    @CEntryPoint(name = "zip4j_test_entry")
+    public static int testEntry(IsolateThread thread, long zipHandle, long entryHandle) {
+        forgetLastException();
         try {
             ZipFile zipFile = zipFiles.get(zipHandle);
             if (zipFile == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            if (outputPath.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
             }
 
-            String path = CTypeConversion.toJavaString(outputPath);
-            if (path == null || path.trim().isEmpty()) {
-                return ERROR_INVALID_PARAMETER;
+            if (fileHeader.isDirectory()) {
+                return SUCCESS;
             }
 
-            zipFile.mergeSplitFiles(new File(path));
+            try (ZipInputStream zipInputStream = zipFile.getInputStream(fileHeader)) {
+                byte[] readBuffer = new byte[8192];
+                while (zipInputStream.read(readBuffer) != -1) {
+                    // Discard the data; only the CRC check matters
+                }
+            }
             return SUCCESS;
         } catch (Throwable e) {
             return handleException(zipHandle, e);
@@ -1286,11 +4756,18 @@ public class Zip4JC {
     }
 
     /**
-     * Renames an entry in the zip archive.
+     * Reads an entry's local header and data and classifies its condition as one of the HEALTH_*
+     * constants. Problems with the entry itself are reported through the health value, with the
+     * details stored as the zip handle's last error, rather than as an error code.
      */
-    @CEntryPoint(name = "zip4j_rename_entry")
-    public static int renameEntry(IsolateThread thread, long zipHandle, long entryHandle, CCharPointer newName) {
+    @CEntryPoint(name = "zip4j_scan_entry")
+    public static int scanEntry(IsolateThread thread, long zipHandle, long entryHandle, CIntPointer health) {
+        forgetLastException();
         try {
+            if (health.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
             ZipFile zipFile = zipFiles.get(zipHandle);
             if (zipFile == null) {
                 return ERROR_INVALID_HANDLE;
@@ -1301,16 +4778,35 @@ public class Zip4JC {
                 return ERROR_INVALID_HANDLE;
             }
 
-            if (newName.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
+            if (!zipFile.isSplitArchive()
+                    && fileHeader.getOffsetLocalHeader() + fileHeader.getCompressedSize() > zipFile.getFile().length()) {
+                setLastError(zipHandle, "entry data extends past the end of the archive: " + fileHeader.getFileName());
+                health.write(HEALTH_TRUNCATED);
+                return SUCCESS;
             }
 
-            String name = CTypeConversion.toJavaString(newName);
-            if (name == null || name.trim().isEmpty()) {
-                return ERROR_INVALID_PARAMETER;
+            try (ZipInputStream zipInputStream = zipFile.getInputStream(fileHeader)) {
+                byte[] readBuffer = new byte[8192];
+                while (zipInputStream.read(readBuffer) != -1) {
+                    // Discard the data; only the CRC check matters
+                }
+                health.write(HEALTH_INTACT);
+            } catch (EOFException e) {
+                handleException(zipHandle, e);
+                health.write(HEALTH_TRUNCATED);
+            } catch (ZipException e) {
+                handleException(zipHandle, e);
+                if (e.getType() == ZipException.Type.CHECKSUM_MISMATCH) {
+                    health.write(HEALTH_CRC_MISMATCH);
+                } else if (e.getType() == ZipException.Type.WRONG_PASSWORD) {
+                    health.write(HEALTH_WRONG_PASSWORD);
+                } else {
+                    health.write(HEALTH_CORRUPT);
+                }
+            } catch (IOException e) {
+                handleException(zipHandle, e);
+                health.write(HEALTH_CORRUPT);
             }
-
-            zipFile.renameFile(fileHeader, name);
             return SUCCESS;
         } catch (Throwable e) {
             return handleException(zipHandle, e);
@@ -1318,55 +4814,62 @@ public class Zip4JC {
     }
 
     /**
-     * Adds data from a byte array to the zip archive.
+     * Reads up to bufferSize bytes of an entry's uncompressed data starting at offset. Unencrypted
+     * stored entries are read straight from the archive file; anything else is decoded from the
+     * start of the entry and the bytes before offset discarded. Fewer bytes are returned at the
+     * end of the entry.
      */
-    @CEntryPoint(name = "zip4j_add_data")
-    public static int addData(IsolateThread thread, long zipHandle, CCharPointer entryName,
-                             CCharPointer data, int dataLength, int compressionLevel,
-                             int compressionMethod, int encryptionMethod, int aesKeyStrength,
-                             CCharPointer password) {
+    @CEntryPoint(name = "zip4j_extract_range")
+    public static int extractRange(IsolateThread thread, long zipHandle, long entryHandle, long offset,
+                                   CCharPointer buffer, int bufferSize, CIntPointer dataLength) {
+        forgetLastException();
         try {
+            if (buffer.equal(WordFactory.nullPointer()) || dataLength.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
             ZipFile zipFile = zipFiles.get(zipHandle);
             if (zipFile == null) {
                 return ERROR_INVALID_HANDLE;
             }
 
-            if (entryName.equal(WordFactory.nullPointer()) || data.equal(WordFactory.nullPointer())) {
-                return ERROR_NULL_POINTER;
+            FileHeader fileHeader = zipEntries.get(entryHandle);
+            if (fileHeader == null) {
+                return ERROR_INVALID_HANDLE;
             }
 
-            String name = CTypeConversion.toJavaString(entryName);
-            if (name == null || name.trim().isEmpty()) {
+            if (offset < 0 || bufferSize < 0) {
                 return ERROR_INVALID_PARAMETER;
             }
 
-            // Convert C data to byte array
-            byte[] dataBytes = new byte[dataLength];
-            for (int i = 0; i < dataLength; i++) {
-                dataBytes[i] = data.read(i);
-            }
-
-            ZipParameters zipParameters = new ZipParameters();
-            zipParameters.setCompressionLevel(getCompressionLevel(compressionLevel));
-            zipParameters.setCompressionMethod(getCompressionMethod(compressionMethod));
-            zipParameters.setEncryptionMethod(getEncryptionMethod(encryptionMethod));
-            zipParameters.setFileNameInZip(name);
-
-            if (encryptionMethod == ENCRYPTION_AES_128 || encryptionMethod == ENCRYPTION_AES_256) {
-                zipParameters.setAesKeyStrength(getAesKeyStrength(aesKeyStrength));
-            }
-
-            if (!password.equal(WordFactory.nullPointer())) {
-                String pass = CTypeConversion.toJavaString(password);
-                if (pass != null && !pass.isEmpty()) {
-                    zipParameters.setEncryptFiles(true);
-                    zipFile.setPassword(pass.toCharArray());
+            byte[] data = new byte[(int) Math.max(0, Math.min(bufferSize, fileHeader.getUncompressedSize() - offset))];
+            int total = 0;
+            if (!fileHeader.isEncrypted() && !zipFile.isSplitArchive()
+                    && fileHeader.getCompressionMethod() == CompressionMethod.STORE) {
+                try (RandomAccessFile file = new RandomAccessFile(zipFile.getFile(), "r")) {
+                    file.seek(localDataOffset(zipFile.getFile(), fileHeader) + offset);
+                    file.readFully(data);
+                }
+                total = data.length;
+            } else {
+                try (ZipInputStream zipInputStream = zipFile.getInputStream(fileHeader)) {
+                    byte[] skipBuffer = new byte[8192];
+                    long remaining = offset;
+                    int bytesRead = 0;
+                    while (remaining > 0 && bytesRead != -1) {
+                        bytesRead = zipInputStream.read(skipBuffer, 0, (int) Math.min(skipBuffer.length, remaining));
+                        remaining -= Math.max(bytesRead, 0);
+                    }
+                    while (total < data.length && (bytesRead = zipInputStream.read(data, total, data.length - total)) != -1) {
+                        total += bytesRead;
+                    }
                 }
             }
 
-            ByteArrayInputStream inputStream = new ByteArrayInputStream(dataBytes);
-            zipFile.addStream(inputStream, zipParameters);
-
+            for (int i = 0; i < total; i++) {
+                buffer.write(i, data[i]);
+            }
+            dataLength.write(total);
             return SUCCESS;
         } catch (Throwable e) {
             return handleException(zipHandle, e);
@@ -1379,6 +4882,7 @@ public class Zip4JC {
     @CEntryPoint(name = "zip4j_extract_data")
     public static int extractData(IsolateThread thread, long zipHandle, long entryHandle,
                                  CCharPointer buffer, int bufferSize, CIntPointer dataLength) {
+        forgetLastException();
         try {
             if (buffer.equal(WordFactory.nullPointer()) || dataLength.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
@@ -1426,6 +4930,7 @@ public class Zip4JC {
      */
     @CEntryPoint(name = "zip4j_entry_get_compression_method")
     public static int getEntryCompressionMethod(IsolateThread thread, long entryHandle, CIntPointer compressionMethod) {
+        forgetLastException();
         try {
             if (compressionMethod.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
@@ -1436,9 +4941,7 @@ public class Zip4JC {
                 return ERROR_INVALID_HANDLE;
             }
 
-            CompressionMethod method = fileHeader.getCompressionMethod();
-            int methodValue = (method == CompressionMethod.STORE) ? COMPRESSION_STORE : COMPRESSION_DEFLATE;
-            compressionMethod.write(methodValue);
+            compressionMethod.write(compressionMethodCode(fileHeader));
 
             return SUCCESS;
         } catch (Throwable e) {
@@ -1451,6 +4954,7 @@ public class Zip4JC {
      */
     @CEntryPoint(name = "zip4j_entry_get_encryption_method")
     public static int getEntryEncryptionMethod(IsolateThread thread, long entryHandle, CIntPointer encryptionMethod) {
+        forgetLastException();
         try {
             if (encryptionMethod.equal(WordFactory.nullPointer())) {
                 return ERROR_NULL_POINTER;
@@ -1461,22 +4965,198 @@ public class Zip4JC {
                 return ERROR_INVALID_HANDLE;
             }
 
-            EncryptionMethod method = fileHeader.getEncryptionMethod();
-            int methodValue;
-            switch (method) {
-                case NONE: methodValue = ENCRYPTION_NONE; break;
-                case ZIP_STANDARD: methodValue = ENCRYPTION_STANDARD; break;
-                case AES:
-                    AesKeyStrength keyStrength = fileHeader.getAesExtraDataRecord().getAesKeyStrength();
-                    methodValue = (keyStrength == AesKeyStrength.KEY_STRENGTH_128) ? ENCRYPTION_AES_128 : ENCRYPTION_AES_256;
-                    break;
-                default: methodValue = ENCRYPTION_NONE; break;
-            }
-            encryptionMethod.write(methodValue);
+            encryptionMethod.write(encryptionMethodCode(fileHeader));
 
             return SUCCESS;
         } catch (Throwable e) {
             return handleException(entryHandle, e);
         }
     }
+
+    // ========== Output Capture ==========
+
+    /**
+     * Starts (enabled != 0) or stops capturing what is printed to System.out and System.err.
+     * Captured lines are queued for zip4j_next_output_line instead of reaching the process's
+     * stdout and stderr.
+     */
+    @CEntryPoint(name = "zip4j_capture_output")
+    public static int captureOutput(IsolateThread thread, int enabled) {
+        forgetLastException();
+        try {
+            synchronized (capturedOutput) {
+                if (enabled != 0 && originalOut == null) {
+                    originalOut = System.out;
+                    originalErr = System.err;
+                    System.setOut(new PrintStream(new LineCapture(OUTPUT_STDOUT), true, "UTF-8"));
+                    System.setErr(new PrintStream(new LineCapture(OUTPUT_STDERR), true, "UTF-8"));
+                } else if (enabled == 0 && originalOut != null) {
+                    System.setOut(originalOut);
+                    System.setErr(originalErr);
+                    originalOut = null;
+                    originalErr = null;
+                }
+            }
+            return SUCCESS;
+        } catch (Throwable e) {
+            return handleException(0, e);
+        }
+    }
+
+    /**
+     * Takes the oldest captured line, writing the stream it was printed to (OUTPUT_STDOUT or
+     * OUTPUT_STDERR), or OUTPUT_NONE when no line is waiting.
+     */
+    @CEntryPoint(name = "zip4j_next_output_line")
+    public static int nextOutputLine(IsolateThread thread, CIntPointer stream, CCharPointer buffer, int bufferSize, CIntPointer lineLength) {
+        try {
+            if (stream.equal(WordFactory.nullPointer()) || buffer.equal(WordFactory.nullPointer())
+                    || lineLength.equal(WordFactory.nullPointer())) {
+                return ERROR_NULL_POINTER;
+            }
+
+            synchronized (capturedOutput) {
+                OutputLine line = capturedOutput.peek();
+                if (line == null) {
+                    stream.write(OUTPUT_NONE);
+                    lineLength.write(0);
+                    return SUCCESS;
+                }
+
+                int result = copyStringToBuffer(line.text, buffer, bufferSize, lineLength);
+                if (result == SUCCESS) {
+                    capturedOutput.poll();
+                    stream.write(line.stream);
+                }
+                return result;
+            }
+        } catch (Throwable e) {
+            return ERROR_UNKNOWN;
+        }
+    }
+
+    /**
+     * A line printed while output is captured.
+     */
+    private static final class OutputLine {
+        final int stream;
+        final String text;
+
+        OutputLine(int stream, String text) {
+            this.stream = stream;
+            this.text = text;
+        }
+    }
+
+    /**
+     * Splits what is written to System.out or System.err into lines. When nobody takes the
+     * captured lines, the oldest are dropped so the queue can't grow without bound.
+     */
+    private static final class LineCapture extends OutputStream {
+        private final int stream;
+        private final ByteArrayOutputStream line = new ByteArrayOutputStream();
+
+        LineCapture(int stream) {
+            this.stream = stream;
+        }
+
+        @Override
+        public synchronized void write(int b) {
+            if (b != '\n') {
+                line.write(b);
+                return;
+            }
+
+            String text = new String(line.toByteArray(), StandardCharsets.UTF_8);
+            line.reset();
+            if (text.endsWith("\r")) {
+                text = text.substring(0, text.length() - 1);
+            }
+
+            OutputLine captured = new OutputLine(stream, text);
+            synchronized (capturedOutput) {
+                while (!capturedOutput.offer(captured)) {
+                    capturedOutput.poll();
+                }
+            }
+        }
+    }
+
+    // ========== Chunked Data ==========
+
+    /**
+     * Input stream fed with chunks written from native code, read by zip4j on a worker thread.
+     */
+    private static final class ChunkInputStream extends InputStream {
+        static final byte[] END = new byte[0];
+        static final byte[] ABORT = new byte[0];
+
+        private final BlockingQueue<byte[]> chunks = new ArrayBlockingQueue<>(16);
+        private byte[] current = new byte[0];
+        private int position;
+        private boolean finished;
+
+        volatile Thread worker;
+        volatile Throwable failure;
+
+        /**
+         * Queues a chunk, giving up if the worker has stopped reading.
+         */
+        void push(byte[] chunk) throws InterruptedException {
+            while (!chunks.offer(chunk, 100, TimeUnit.MILLISECONDS)) {
+                if (!worker.isAlive()) {
+                    return;
+                }
+            }
+        }
+
+        /**
+         * Drops queued chunks and makes the next read fail.
+         */
+        void abort() {
+            chunks.clear();
+            chunks.offer(ABORT);
+        }
+
+        @Override
+        public int read() throws IOException {
+            byte[] single = new byte[1];
+            int read = read(single, 0, 1);
+            return read < 0 ? -1 : single[0] & 0xff;
+        }
+
+        @Override
+        public int read(byte[] buffer, int offset, int length) throws IOException {
+            if (length == 0) {
+                return 0;
+            }
+
+            while (position >= current.length) {
+                if (finished) {
+                    return -1;
+                }
+
+                try {
+                    current = chunks.take();
+                } catch (InterruptedException e) {
+                    Thread.currentThread().interrupt();
+                    throw new InterruptedIOException();
+                }
+                position = 0;
+
+                if (current == ABORT) {
+                    throw new IOException("Adding data was aborted");
+                }
+                if (current == END) {
+                    finished = true;
+                    return -1;
+                }
+            }
+
+            int count = Math.min(length, current.length - position);
+            System.arraycopy(current, position, buffer, offset, count);
+            position += count;
+            return count;
+        }
+    }
 }